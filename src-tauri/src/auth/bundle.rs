@@ -0,0 +1,213 @@
+//! Portable Credential Bundles
+//!
+//! Passphrase-protected export format for moving credentials between machines
+//! (the store's master key is machine-bound). The payload is encrypted with
+//! AES-256-GCM under a key derived from the passphrase with Argon2id; only the
+//! header (format, version, KDF parameters, entry count) is readable without it.
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use rand::RngCore;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use zeroize::Zeroizing;
+
+use super::crypto;
+
+/// Identifies a bundle file
+const FORMAT: &str = "tairseach-credential-bundle";
+/// Current bundle format version
+pub const BUNDLE_VERSION: u32 = 1;
+/// Shortest passphrase accepted for new bundles
+const MIN_PASSPHRASE_LEN: usize = 12;
+/// Upper bound on KDF memory accepted from a bundle header (1 GiB)
+const MAX_MEMORY_KIB: u32 = 1024 * 1024;
+/// Upper bound on KDF passes accepted from a bundle header
+const MAX_ITERATIONS: u32 = 16;
+/// Upper bound on KDF lanes accepted from a bundle header
+const MAX_PARALLELISM: u32 = 16;
+
+/// Argon2id parameters, recorded in the header so they can change later
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KdfParams {
+    pub algorithm: String,
+    /// Base64 salt
+    pub salt: String,
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+impl KdfParams {
+    /// OWASP-recommended Argon2id cost with a fresh random salt
+    fn generate() -> Self {
+        Self::with_cost(64 * 1024, 3, 1)
+    }
+
+    fn with_cost(memory_kib: u32, iterations: u32, parallelism: u32) -> Self {
+        let mut salt = [0u8; 16];
+        rand::rngs::OsRng.fill_bytes(&mut salt);
+        Self {
+            algorithm: "argon2id".to_string(),
+            salt: BASE64.encode(salt),
+            memory_kib,
+            iterations,
+            parallelism,
+        }
+    }
+
+    fn derive_key(&self, passphrase: &str) -> Result<Zeroizing<[u8; 32]>, String> {
+        if self.algorithm != "argon2id" {
+            return Err(format!("Unsupported bundle KDF: {}", self.algorithm));
+        }
+        if self.memory_kib > MAX_MEMORY_KIB {
+            return Err("Bundle KDF memory cost is too high".to_string());
+        }
+        if self.iterations > MAX_ITERATIONS {
+            return Err("Bundle KDF iteration count is too high".to_string());
+        }
+        if self.parallelism > MAX_PARALLELISM {
+            return Err("Bundle KDF parallelism is too high".to_string());
+        }
+        let salt = BASE64
+            .decode(&self.salt)
+            .map_err(|e| format!("Invalid bundle salt: {}", e))?;
+        let params = Params::new(self.memory_kib, self.iterations, self.parallelism, Some(32))
+            .map_err(|e| format!("Invalid bundle KDF parameters: {}", e))?;
+        let mut key = Zeroizing::new([0u8; 32]);
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(passphrase.as_bytes(), &salt, key.as_mut())
+            .map_err(|e| format!("Key derivation failed: {}", e))?;
+        Ok(key)
+    }
+}
+
+/// Bundle file contents
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Bundle {
+    pub format: String,
+    pub version: u32,
+    pub created_at: String,
+    /// Number of entries (informational; the payload is authoritative)
+    pub count: usize,
+    pub kdf: KdfParams,
+    pub cipher: String,
+    /// Base64 of nonce || ciphertext+tag
+    pub data: String,
+}
+
+/// Encrypt `payload` (`count` entries) into bundle file bytes.
+pub fn seal<T: Serialize>(payload: &T, count: usize, passphrase: &str) -> Result<Vec<u8>, String> {
+    seal_with(payload, count, passphrase, KdfParams::generate())
+}
+
+fn seal_with<T: Serialize>(
+    payload: &T,
+    count: usize,
+    passphrase: &str,
+    kdf: KdfParams,
+) -> Result<Vec<u8>, String> {
+    if passphrase.chars().count() < MIN_PASSPHRASE_LEN {
+        return Err(format!(
+            "Bundle passphrase must be at least {} characters",
+            MIN_PASSPHRASE_LEN
+        ));
+    }
+    let key = kdf.derive_key(passphrase)?;
+    let plaintext = Zeroizing::new(
+        serde_json::to_vec(payload).map_err(|e| format!("Failed to serialize bundle: {}", e))?,
+    );
+    let ciphertext = crypto::encrypt(&key, &plaintext)?;
+
+    let bundle = Bundle {
+        format: FORMAT.to_string(),
+        version: BUNDLE_VERSION,
+        created_at: chrono::Utc::now().to_rfc3339(),
+        count,
+        kdf,
+        cipher: "aes-256-gcm".to_string(),
+        data: BASE64.encode(ciphertext),
+    };
+    serde_json::to_vec_pretty(&bundle).map_err(|e| format!("Failed to serialize bundle: {}", e))
+}
+
+/// Decrypt bundle file bytes. A wrong passphrase and a tampered file both
+/// fail authentication and give the same error.
+pub fn open<T: DeserializeOwned>(bytes: &[u8], passphrase: &str) -> Result<T, String> {
+    let bundle: Bundle =
+        serde_json::from_slice(bytes).map_err(|e| format!("Not a credential bundle: {}", e))?;
+    if bundle.format != FORMAT {
+        return Err(format!("Not a credential bundle (format '{}')", bundle.format));
+    }
+    if bundle.version > BUNDLE_VERSION {
+        return Err(format!(
+            "Bundle version {} is newer than supported ({})",
+            bundle.version, BUNDLE_VERSION
+        ));
+    }
+    if bundle.cipher != "aes-256-gcm" {
+        return Err(format!("Unsupported bundle cipher: {}", bundle.cipher));
+    }
+
+    let key = bundle.kdf.derive_key(passphrase)?;
+    let ciphertext = BASE64
+        .decode(&bundle.data)
+        .map_err(|e| format!("Invalid bundle data: {}", e))?;
+    let plaintext = Zeroizing::new(
+        crypto::decrypt(&key, &ciphertext)
+            .map_err(|_| "Wrong passphrase or corrupted bundle".to_string())?,
+    );
+    serde_json::from_slice(&plaintext).map_err(|e| format!("Invalid bundle payload: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn cheap_kdf() -> KdfParams {
+        KdfParams::with_cost(256, 1, 1)
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let payload = json!([{"key": "jira:default", "secret": "abc"}]);
+        let bytes = seal_with(&payload, 1, "correct horse battery", cheap_kdf()).unwrap();
+
+        let header: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(header["format"], FORMAT);
+        assert_eq!(header["count"], 1);
+        assert!(!String::from_utf8_lossy(&bytes).contains("abc"));
+
+        let opened: serde_json::Value = open(&bytes, "correct horse battery").unwrap();
+        assert_eq!(opened, payload);
+    }
+
+    #[test]
+    fn test_wrong_passphrase_and_short_passphrase() {
+        let bytes = seal_with(&json!([]), 0, "correct horse battery", cheap_kdf()).unwrap();
+        let err = open::<serde_json::Value>(&bytes, "incorrect horse battery").unwrap_err();
+        assert_eq!(err, "Wrong passphrase or corrupted bundle");
+
+        assert!(seal_with(&json!([]), 0, "short", cheap_kdf()).is_err());
+    }
+
+    #[test]
+    fn test_rejects_unknown_format() {
+        let err = open::<serde_json::Value>(br#"{"hello": 1}"#, "whatever passphrase").unwrap_err();
+        assert!(err.starts_with("Not a credential bundle"));
+    }
+
+    #[test]
+    fn test_rejects_excessive_kdf_cost() {
+        let bytes = seal_with(&json!([]), 0, "correct horse battery", cheap_kdf()).unwrap();
+        for (field, value) in [("iterations", 1_000_000), ("parallelism", 1_000)] {
+            let mut header: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+            header["kdf"][field] = json!(value);
+            let tampered = serde_json::to_vec(&header).unwrap();
+            let err = open::<serde_json::Value>(&tampered, "correct horse battery").unwrap_err();
+            assert!(err.ends_with("is too high"), "{}", err);
+        }
+    }
+}
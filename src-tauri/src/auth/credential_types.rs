@@ -72,6 +72,7 @@ impl CredentialTypeRegistry {
         registry.register_built_in(Self::notion_schema());
         registry.register_built_in(Self::slack_schema());
         registry.register_built_in(Self::godaddy_schema());
+        registry.register_built_in(Self::oauth_client_schema());
         
         registry
     }
@@ -293,6 +294,32 @@ impl CredentialTypeRegistry {
             built_in: false,
         }
     }
+
+    fn oauth_client_schema() -> CredentialTypeSchema {
+        CredentialTypeSchema {
+            provider_type: "oauth_client".to_string(),
+            display_name: "OAuth App".to_string(),
+            description: "Client credentials of an OAuth app, labelled with the provider name".to_string(),
+            fields: vec![
+                CredentialField {
+                    name: "client_id".to_string(),
+                    display_name: "Client ID".to_string(),
+                    field_type: FieldType::String,
+                    required: true,
+                    description: Some("Client ID from the provider's developer console".to_string()),
+                },
+                CredentialField {
+                    name: "client_secret".to_string(),
+                    display_name: "Client Secret".to_string(),
+                    field_type: FieldType::Secret,
+                    required: false,
+                    description: Some("Client secret (omit for public PKCE clients)".to_string()),
+                },
+            ],
+            supports_multiple: true,
+            built_in: false,
+        }
+    }
 }
 
 impl Default for CredentialTypeRegistry {
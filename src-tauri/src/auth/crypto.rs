@@ -1,7 +1,8 @@
 //! Cryptographic utilities for the Auth Broker
 //!
 //! - AES-256-GCM for token encryption at rest
-//! - Master key derivation via HKDF from machine identity
+//! - Master key derivation via HKDF from machine identity (see `master_key`
+//!   for the other key sources)
 //! - Passphrase generation
 
 use aes_gcm::{
@@ -90,20 +91,27 @@ pub fn derive_master_key() -> Result<[u8; KEY_SIZE], String> {
     let username = std::env::var("USER")
         .map_err(|_| "Could not determine username from $USER")?;
 
-    let ikm = format!("{}:{}:{}", hw_uuid, username, STATIC_SALT);
-    
+    let key = derive_identity_key(&hw_uuid, &username)?;
+
+    info!("Derived master key from machine identity");
+    Ok(key)
+}
+
+/// HKDF-SHA256 over `machine_id:username:STATIC_SALT`. Shared by the macOS
+/// hardware UUID and Linux machine-id key sources.
+pub fn derive_identity_key(machine_id: &str, username: &str) -> Result<[u8; KEY_SIZE], String> {
+    let ikm = format!("{}:{}:{}", machine_id, username, STATIC_SALT);
+
     let hkdf = Hkdf::<Sha256>::new(Some(HKDF_SALT), ikm.as_bytes());
     let mut key = [0u8; KEY_SIZE];
     hkdf.expand(HKDF_INFO, &mut key)
         .map_err(|e| format!("HKDF expand failed: {}", e))?;
-
-    info!("Derived master key from machine identity");
     Ok(key)
 }
 
 #[cfg(not(target_os = "macos"))]
 pub fn derive_master_key() -> Result<[u8; KEY_SIZE], String> {
-    Err("Master key derivation requires macOS hardware UUID".to_string())
+    Err("The hardware master key source requires macOS (use machineId or keyFile)".to_string())
 }
 
 /// Cached hardware UUID (fetched once per process lifetime).
@@ -0,0 +1,326 @@
+//! Master Key Sources
+//!
+//! Where the token store's AES-256 master key comes from:
+//! - `hardware` — HKDF of the macOS hardware UUID and `$USER` (macOS default)
+//! - `machineId` — HKDF of `/etc/machine-id` and `$USER` (Linux default)
+//! - `keyFile` — 32 random bytes in a file readable only by the owner
+//! - `env` — `TAIRSEACH_MASTER_KEY` (hex) or `TAIRSEACH_MASTER_PASSPHRASE`,
+//!   for tests and CI
+//!
+//! The selected source is recorded in `~/.tairseach/master_key.json`;
+//! `TAIRSEACH_MASTER_KEY_SOURCE` overrides it for one process. Switching sources
+//! goes through `TokenStore::change_master_key` so every entry is re-encrypted.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tracing::info;
+use zeroize::Zeroizing;
+
+use super::crypto;
+
+/// Master key bytes, wiped on drop
+pub type MasterKey = Zeroizing<[u8; 32]>;
+
+/// Env var selecting the key source for this process
+pub const SOURCE_ENV: &str = "TAIRSEACH_MASTER_KEY_SOURCE";
+/// Env var with a hex master key (`env` source)
+pub const KEY_ENV: &str = "TAIRSEACH_MASTER_KEY";
+/// Env var with a passphrase to derive the master key from (`env` source)
+pub const PASSPHRASE_ENV: &str = "TAIRSEACH_MASTER_PASSPHRASE";
+/// Env var overriding the key file path (`keyFile` source)
+pub const KEY_FILE_ENV: &str = "TAIRSEACH_MASTER_KEY_FILE";
+
+/// Salt for passphrase-derived keys. Fixed so the same passphrase always
+/// yields the same key; this source is meant for tests, not real secrets.
+const PASSPHRASE_SALT: &[u8] = b"tairseach-master-passphrase-v1";
+
+/// A way of obtaining the master key
+pub trait MasterKeyProvider: Send + Sync {
+    /// Source name as used in `master_key.json`
+    fn name(&self) -> &'static str;
+
+    /// Produce the key. Sources that hold state (the key file) create it on
+    /// first use.
+    fn load_key(&self) -> Result<MasterKey, String>;
+}
+
+/// Selected key source, as stored in `master_key.json`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "source", rename_all = "camelCase")]
+pub enum MasterKeySource {
+    Hardware,
+    MachineId,
+    KeyFile {
+        /// Default: `~/.tairseach/master.key`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        path: Option<PathBuf>,
+    },
+    Env,
+}
+
+impl MasterKeySource {
+    /// The platform default: hardware UUID on macOS, machine-id on Linux,
+    /// otherwise a key file.
+    pub fn platform_default() -> Self {
+        if cfg!(target_os = "macos") {
+            Self::Hardware
+        } else if cfg!(target_os = "linux") {
+            Self::MachineId
+        } else {
+            Self::KeyFile { path: None }
+        }
+    }
+
+    /// Parse a source name (`hardware`, `machineId`, `keyFile`, `env`).
+    pub fn from_name(name: &str, path: Option<PathBuf>) -> Result<Self, String> {
+        match name {
+            "hardware" => Ok(Self::Hardware),
+            "machineId" | "machine-id" => Ok(Self::MachineId),
+            "keyFile" | "key-file" => Ok(Self::KeyFile { path }),
+            "env" => Ok(Self::Env),
+            other => Err(format!(
+                "Unknown master key source '{}' (expected hardware, machineId, keyFile or env)",
+                other
+            )),
+        }
+    }
+
+    pub fn provider(&self) -> Result<Box<dyn MasterKeyProvider>, String> {
+        Ok(match self {
+            Self::Hardware => Box::new(HardwareKey),
+            Self::MachineId => Box::new(MachineIdKey),
+            Self::KeyFile { path } => Box::new(KeyFile {
+                path: match path {
+                    Some(p) => p.clone(),
+                    None => default_key_file()?,
+                },
+            }),
+            Self::Env => Box::new(EnvKey),
+        })
+    }
+}
+
+/// The source to use: `TAIRSEACH_MASTER_KEY_SOURCE`, else `master_key.json`,
+/// else the platform default.
+pub fn configured_source() -> Result<MasterKeySource, String> {
+    if let Ok(name) = std::env::var(SOURCE_ENV) {
+        let path = std::env::var_os(KEY_FILE_ENV).map(PathBuf::from);
+        return MasterKeySource::from_name(&name, path);
+    }
+    let path = selection_path()?;
+    match std::fs::read_to_string(&path) {
+        Ok(content) => serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse {:?}: {}", path, e)),
+        Err(_) => Ok(MasterKeySource::platform_default()),
+    }
+}
+
+/// Record the selected source in `master_key.json`.
+pub fn save_source(source: &MasterKeySource) -> Result<(), String> {
+    let path = selection_path()?;
+    let json = serde_json::to_string_pretty(source)
+        .map_err(|e| format!("Failed to serialize key source: {}", e))?;
+    crate::common::write_atomic(&path, json.as_bytes(), Some(0o600))
+}
+
+fn selection_path() -> Result<PathBuf, String> {
+    crate::common::tairseach_path("master_key.json")
+}
+
+fn default_key_file() -> Result<PathBuf, String> {
+    crate::common::tairseach_path("master.key")
+}
+
+fn current_username() -> Result<String, String> {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("LOGNAME"))
+        .map_err(|_| "Could not determine username from $USER".to_string())
+}
+
+// ── Sources ─────────────────────────────────────────────────────────────────
+
+/// macOS hardware UUID + `$USER` (the original derivation)
+struct HardwareKey;
+
+impl MasterKeyProvider for HardwareKey {
+    fn name(&self) -> &'static str {
+        "hardware"
+    }
+
+    fn load_key(&self) -> Result<MasterKey, String> {
+        crypto::derive_master_key().map(Zeroizing::new)
+    }
+}
+
+/// systemd/dbus machine-id + `$USER`
+struct MachineIdKey;
+
+impl MasterKeyProvider for MachineIdKey {
+    fn name(&self) -> &'static str {
+        "machineId"
+    }
+
+    fn load_key(&self) -> Result<MasterKey, String> {
+        let machine_id = ["/etc/machine-id", "/var/lib/dbus/machine-id"]
+            .iter()
+            .find_map(|p| std::fs::read_to_string(p).ok())
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .ok_or("No machine-id found in /etc/machine-id or /var/lib/dbus/machine-id")?;
+        let key = crypto::derive_identity_key(&machine_id, &current_username()?)?;
+        info!("Derived master key from machine-id");
+        Ok(Zeroizing::new(key))
+    }
+}
+
+/// Random key stored hex-encoded in a 0600 file owned by the current user
+struct KeyFile {
+    path: PathBuf,
+}
+
+impl MasterKeyProvider for KeyFile {
+    fn name(&self) -> &'static str {
+        "keyFile"
+    }
+
+    fn load_key(&self) -> Result<MasterKey, String> {
+        if !self.path.exists() {
+            create_key_file(&self.path)?;
+        }
+        check_key_file_permissions(&self.path)?;
+        let content = Zeroizing::new(
+            std::fs::read_to_string(&self.path)
+                .map_err(|e| format!("Failed to read key file {:?}: {}", self.path, e))?,
+        );
+        parse_hex_key(content.trim()).map_err(|e| format!("Key file {:?}: {}", self.path, e))
+    }
+}
+
+fn create_key_file(path: &Path) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {:?}: {}", parent, e))?;
+    }
+    let mut key = Zeroizing::new([0u8; 32]);
+    rand::RngCore::fill_bytes(&mut rand::rngs::OsRng, key.as_mut());
+    let hex_key = Zeroizing::new(hex::encode(key.as_ref()));
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    use std::io::Write;
+    options
+        .open(path)
+        .and_then(|mut f| f.write_all(hex_key.as_bytes()))
+        .map_err(|e| format!("Failed to create key file {:?}: {}", path, e))?;
+    info!("Generated new master key file at {:?}", path);
+    Ok(())
+}
+
+/// Refuse key files that other users could read or that someone else owns.
+#[cfg(unix)]
+fn check_key_file_permissions(path: &Path) -> Result<(), String> {
+    use std::os::unix::fs::MetadataExt;
+    let meta = std::fs::metadata(path)
+        .map_err(|e| format!("Failed to stat key file {:?}: {}", path, e))?;
+    if meta.mode() & 0o077 != 0 {
+        return Err(format!(
+            "Key file {:?} is accessible by other users (mode {:o}); run chmod 600",
+            path,
+            meta.mode() & 0o777
+        ));
+    }
+    // SAFETY: getuid has no preconditions and cannot fail
+    let uid = unsafe { libc::getuid() };
+    if meta.uid() != uid {
+        return Err(format!("Key file {:?} is not owned by the current user", path));
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn check_key_file_permissions(_path: &Path) -> Result<(), String> {
+    Ok(())
+}
+
+/// `TAIRSEACH_MASTER_KEY` (64 hex chars) or `TAIRSEACH_MASTER_PASSPHRASE`
+struct EnvKey;
+
+impl MasterKeyProvider for EnvKey {
+    fn name(&self) -> &'static str {
+        "env"
+    }
+
+    fn load_key(&self) -> Result<MasterKey, String> {
+        if let Ok(hex_key) = std::env::var(KEY_ENV) {
+            let hex_key = Zeroizing::new(hex_key);
+            return parse_hex_key(hex_key.trim()).map_err(|e| format!("{}: {}", KEY_ENV, e));
+        }
+        if let Ok(passphrase) = std::env::var(PASSPHRASE_ENV) {
+            let passphrase = Zeroizing::new(passphrase);
+            let mut key = Zeroizing::new([0u8; 32]);
+            argon2::Argon2::default()
+                .hash_password_into(passphrase.as_bytes(), PASSPHRASE_SALT, key.as_mut())
+                .map_err(|e| format!("Key derivation failed: {}", e))?;
+            return Ok(key);
+        }
+        Err(format!("Neither {} nor {} is set", KEY_ENV, PASSPHRASE_ENV))
+    }
+}
+
+fn parse_hex_key(hex_key: &str) -> Result<MasterKey, String> {
+    let bytes = Zeroizing::new(hex::decode(hex_key).map_err(|e| format!("invalid hex: {}", e))?);
+    if bytes.len() != 32 {
+        return Err(format!("expected 32 bytes, got {}", bytes.len()));
+    }
+    let mut key = Zeroizing::new([0u8; 32]);
+    key.copy_from_slice(&bytes);
+    Ok(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_serde() {
+        let source: MasterKeySource =
+            serde_json::from_str(r#"{"source":"keyFile","path":"/tmp/k"}"#).unwrap();
+        assert_eq!(source, MasterKeySource::KeyFile { path: Some("/tmp/k".into()) });
+        assert_eq!(
+            serde_json::to_string(&MasterKeySource::MachineId).unwrap(),
+            r#"{"source":"machineId"}"#
+        );
+        assert!(MasterKeySource::from_name("keychain", None).is_err());
+    }
+
+    #[test]
+    fn test_parse_hex_key() {
+        let key = parse_hex_key(&"ab".repeat(32)).unwrap();
+        assert_eq!(key[0], 0xab);
+        assert!(parse_hex_key("abcd").is_err());
+        assert!(parse_hex_key("zz").is_err());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_key_file_created_private_and_stable() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = std::env::temp_dir().join(format!("tairseach-key-test-{}", std::process::id()));
+        let provider = KeyFile { path: dir.join("master.key") };
+
+        let first = provider.load_key().unwrap();
+        let second = provider.load_key().unwrap();
+        assert_eq!(*first, *second);
+
+        std::fs::set_permissions(&provider.path, std::fs::Permissions::from_mode(0o644)).unwrap();
+        assert!(provider.load_key().unwrap_err().contains("chmod 600"));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -7,8 +7,10 @@
 //!
 //! See ADR-001 for the full design rationale.
 
+pub mod bundle;
 pub mod credential_types;
 pub mod crypto;
+pub mod master_key;
 pub mod provider;
 pub mod store;
 
@@ -20,7 +22,9 @@ use tokio::sync::RwLock;
 use tracing::{error, info, warn};
 use zeroize::{Zeroize, ZeroizeOnDrop};
 
+use self::provider::device::{DeviceAuthorization, DeviceClient, DevicePoll};
 use self::provider::google::GoogleProvider;
+use self::provider::oauth2::{GenericOAuth2Provider, OAuth2ProviderConfig, CLIENT_CREDENTIAL_TYPE};
 use self::provider::OAuthProvider;
 use self::store::TokenStore;
 
@@ -101,10 +105,30 @@ impl From<&TokenRecord> for AccountInfo {
 pub struct AuthStatus {
     pub initialized: bool,
     pub master_key_available: bool,
+    /// `hardware`, `machineId`, `keyFile` or `env`
+    pub master_key_source: String,
     pub account_count: usize,
     pub gog_passphrase_set: bool,
 }
 
+/// Health of the background token refresh daemon
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RefreshDaemonHealth {
+    pub running: bool,
+    /// Completion time of the last sweep (RFC 3339)
+    pub last_run: Option<String>,
+    pub runs: u64,
+    pub refreshed: u64,
+    pub failures: u64,
+    pub last_error: Option<String>,
+    /// Static credentials expired, expiring or due for rotation at the last sweep
+    pub expiring_credentials: usize,
+}
+
+/// How far ahead the refresh daemon warns about expiring static credentials
+const CREDENTIAL_EXPIRY_WARNING_DAYS: i64 = 7;
+
 /// Auth broker error codes (JSON-RPC custom range)
 pub mod error_codes {
     pub const TOKEN_NOT_FOUND: i32 = -32010;
@@ -118,12 +142,33 @@ pub mod error_codes {
 
 // ── Auth Broker ─────────────────────────────────────────────────────────────
 
+/// A device-flow authorization waiting for the user.
+struct DeviceSession {
+    provider: String,
+    client: DeviceClient,
+    device_code: String,
+    scopes: Vec<String>,
+    account: Option<String>,
+    interval: std::time::Duration,
+    next_poll: std::time::Instant,
+    expires_at: std::time::Instant,
+}
+
 /// The central auth broker instance, shared across the application.
 pub struct AuthBroker {
     store: RwLock<TokenStore>,
     google: GoogleProvider,
+    /// Config-driven OAuth2 providers keyed by name
+    oauth2: std::collections::HashMap<String, OAuth2ProviderConfig>,
+    /// Last status notified per flagged static credential
+    flagged_credentials: std::sync::Mutex<std::collections::HashMap<String, &'static str>>,
+    /// Pending device-flow authorizations keyed by session ID
+    device_sessions: std::sync::Mutex<std::collections::HashMap<String, DeviceSession>>,
     /// gog file-backend passphrase (generated once, stored encrypted)
     gog_passphrase: RwLock<Option<String>>,
+    /// Stops the background refresh daemon
+    daemon_stop: tokio::sync::Notify,
+    daemon_health: std::sync::Mutex<RefreshDaemonHealth>,
 }
 
 impl AuthBroker {
@@ -137,7 +182,12 @@ impl AuthBroker {
         let broker = Arc::new(Self {
             store: RwLock::new(store),
             google: GoogleProvider::new(),
+            oauth2: provider::oauth2::load_providers(),
+            flagged_credentials: std::sync::Mutex::new(std::collections::HashMap::new()),
+            device_sessions: std::sync::Mutex::new(std::collections::HashMap::new()),
             gog_passphrase: RwLock::new(gog_passphrase),
+            daemon_stop: tokio::sync::Notify::new(),
+            daemon_health: std::sync::Mutex::new(RefreshDaemonHealth::default()),
         });
 
         info!("Auth broker initialized");
@@ -152,11 +202,21 @@ impl AuthBroker {
         AuthStatus {
             initialized: true,
             master_key_available: true, // if we got this far, we have it
+            master_key_source: store
+                .key_source()
+                .provider()
+                .map(|p| p.name().to_string())
+                .unwrap_or_default(),
             account_count: accounts.len(),
             gog_passphrase_set: gog.is_some(),
         }
     }
 
+    /// Snapshot of the refresh daemon's health.
+    pub fn daemon_health(&self) -> RefreshDaemonHealth {
+        self.daemon_health.lock().map(|h| h.clone()).unwrap_or_default()
+    }
+
     /// List all accounts (no secrets).
     pub async fn list_accounts(&self, provider_filter: Option<&str>) -> Vec<AccountInfo> {
         let store = self.store.read().await;
@@ -169,7 +229,52 @@ impl AuthBroker {
 
     /// List supported providers.
     pub fn list_providers(&self) -> Vec<String> {
-        vec!["google".to_string()]
+        let mut generic: Vec<String> = self.oauth2.keys().cloned().collect();
+        generic.sort();
+        std::iter::once("google".to_string()).chain(generic).collect()
+    }
+
+    /// Whether tokens for `provider` can be refreshed and revoked.
+    pub fn supports_provider(&self, provider: &str) -> bool {
+        provider == "google" || self.oauth2.contains_key(provider)
+    }
+
+    /// Build a generic OAuth2 provider. Client credentials come from the
+    /// token record when it has them, else from the `oauth_client` credential.
+    pub async fn oauth2_provider(
+        &self,
+        name: &str,
+        record: Option<&TokenRecord>,
+    ) -> Result<GenericOAuth2Provider, String> {
+        let config = self
+            .oauth2
+            .get(name)
+            .cloned()
+            .ok_or_else(|| format!("Unsupported provider: {}", name))?;
+
+        if let Some(rec) = record.filter(|r| !r.client_id.is_empty()) {
+            return Ok(GenericOAuth2Provider::new(
+                config,
+                rec.client_id.clone(),
+                rec.client_secret.clone(),
+            ));
+        }
+
+        let label = config.client_credential_label().to_string();
+        let store = self.store.read().await;
+        let mut fields = store
+            .get_credential(CLIENT_CREDENTIAL_TYPE, &label)?
+            .ok_or_else(|| {
+                format!(
+                    "No OAuth client configured for {} (store an '{}' credential labelled '{}')",
+                    name, CLIENT_CREDENTIAL_TYPE, label
+                )
+            })?;
+        let client_id = fields
+            .remove("client_id")
+            .ok_or_else(|| format!("OAuth client for {} has no client_id", name))?;
+        let client_secret = fields.remove("client_secret").unwrap_or_default();
+        Ok(GenericOAuth2Provider::new(config, client_id, client_secret))
     }
 
     /// Retrieve a valid access token, refreshing if necessary.
@@ -179,6 +284,8 @@ impl AuthBroker {
         account: &str,
         required_scopes: Option<&[String]>,
     ) -> Result<serde_json::Value, (i32, String)> {
+        authorize_credential(provider, account)?;
+
         let store = self.store.read().await;
         let mut record = store
             .get_token(provider, account)
@@ -190,6 +297,7 @@ impl AuthBroker {
                 )
             })?;
         drop(store);
+        self.reencrypt_stale_entries().await;
 
         // Check scope coverage (with superset recognition)
         if let Some(required) = required_scopes {
@@ -211,7 +319,7 @@ impl AuthBroker {
             match self.refresh_token_internal(&mut record).await {
                 Ok(()) => {
                     let mut store = self.store.write().await;
-                    store.save_token(&record).map_err(|e| {
+                    store.save_token(&record).await.map_err(|e| {
                         (error_codes::TOKEN_REFRESH_FAILED, e)
                     })?;
                 }
@@ -239,7 +347,8 @@ impl AuthBroker {
         provider: &str,
         account: &str,
     ) -> Result<serde_json::Value, (i32, String)> {
-        if provider != "google" {
+        authorize_credential(provider, account)?;
+        if !self.supports_provider(provider) {
             return Err((
                 error_codes::PROVIDER_NOT_SUPPORTED,
                 format!("Unsupported provider: {}", provider),
@@ -265,6 +374,7 @@ impl AuthBroker {
         let mut store = self.store.write().await;
         store
             .save_token(&record)
+            .await
             .map_err(|e| (error_codes::TOKEN_REFRESH_FAILED, e))?;
 
         Ok(serde_json::json!({
@@ -280,7 +390,8 @@ impl AuthBroker {
         provider: &str,
         account: &str,
     ) -> Result<(), (i32, String)> {
-        if provider != "google" {
+        authorize_credential(provider, account)?;
+        if !self.supports_provider(provider) {
             return Err((
                 error_codes::PROVIDER_NOT_SUPPORTED,
                 format!("Unsupported provider: {}", provider),
@@ -295,7 +406,15 @@ impl AuthBroker {
 
         // Try to revoke at provider (best-effort)
         if let Some(rec) = &record {
-            if let Err(e) = self.google.revoke_token(&rec.access_token).await {
+            let result = if provider == "google" {
+                self.google.revoke_token(&rec.access_token).await
+            } else {
+                match self.oauth2_provider(provider, Some(rec)).await {
+                    Ok(p) => p.revoke_token(&rec.access_token).await,
+                    Err(e) => Err(e),
+                }
+            };
+            if let Err(e) = result {
                 warn!("Provider-side revocation failed (continuing): {}", e);
             }
         }
@@ -303,17 +422,201 @@ impl AuthBroker {
         let mut store = self.store.write().await;
         store
             .delete_token(provider, account)
+            .await
             .map_err(|e| (error_codes::TOKEN_NOT_FOUND, e))?;
 
         info!("Revoked token for {}:{}", provider, account);
         Ok(())
     }
 
+    /// Begin an RFC 8628 device authorization. Returns a session ID to pass
+    /// to [`device_poll`](Self::device_poll) and the code the user enters at
+    /// the verification URI.
+    pub async fn device_start(
+        &self,
+        provider: &str,
+        scopes: Option<Vec<String>>,
+        account: Option<String>,
+    ) -> Result<serde_json::Value, (i32, String)> {
+        let unsupported = || {
+            (
+                error_codes::PROVIDER_NOT_SUPPORTED,
+                format!("Provider {} does not support the device flow", provider),
+            )
+        };
+
+        let (client, scopes, separator) = if provider == "google" {
+            let google = configured_google_provider().await;
+            let scopes = scopes.unwrap_or_else(|| {
+                vec!["openid".to_string(), "email".to_string()]
+                    .into_iter()
+                    .chain(google.default_scopes())
+                    .collect()
+            });
+            let client = DeviceClient {
+                device_authorization_url: provider::google::DEVICE_ENDPOINT.to_string(),
+                token_url: provider::google::TOKEN_ENDPOINT.to_string(),
+                client_id: google.client_id,
+                client_secret: google.client_secret,
+            };
+            (client, scopes, " ".to_string())
+        } else {
+            if !self.oauth2.contains_key(provider) {
+                return Err(unsupported());
+            }
+            let oauth = self
+                .oauth2_provider(provider, None)
+                .await
+                .map_err(|e| (error_codes::PROVIDER_NOT_SUPPORTED, e))?;
+            let url = oauth.config.device_authorization_url.clone().ok_or_else(unsupported)?;
+            let scopes = scopes.unwrap_or_else(|| oauth.default_scopes());
+            let client = DeviceClient {
+                device_authorization_url: url,
+                token_url: oauth.config.token_url.clone(),
+                client_id: oauth.client_id,
+                client_secret: oauth.client_secret,
+            };
+            (client, scopes, oauth.config.scope_separator)
+        };
+
+        let auth: DeviceAuthorization = client
+            .start(&scopes.join(&separator))
+            .await
+            .map_err(|e| (crate::common::ErrorCode::GenericError.code(), e))?;
+
+        let now = std::time::Instant::now();
+        let interval = std::time::Duration::from_secs(auth.interval);
+        let session_id = generate_state();
+        let session = DeviceSession {
+            provider: provider.to_string(),
+            client,
+            device_code: auth.device_code,
+            scopes,
+            account,
+            interval,
+            next_poll: now + interval,
+            expires_at: now + std::time::Duration::from_secs(auth.expires_in),
+        };
+        if let Ok(mut sessions) = self.device_sessions.lock() {
+            sessions.retain(|_, s| s.expires_at > now);
+            sessions.insert(session_id.clone(), session);
+        }
+
+        info!("Started device authorization for {}", provider);
+        Ok(serde_json::json!({
+            "session": session_id,
+            "userCode": auth.user_code,
+            "verificationUri": auth.verification_uri,
+            "verificationUriComplete": auth.verification_uri_complete,
+            "expiresIn": auth.expires_in,
+            "interval": auth.interval,
+        }))
+    }
+
+    /// Poll a device authorization once. `status` is `pending`, `slow_down`,
+    /// `complete` (token stored), `denied` or `expired`; polls sooner than the
+    /// provider's interval report `pending` without contacting it.
+    pub async fn device_poll(&self, session_id: &str) -> Result<serde_json::Value, (i32, String)> {
+        let unknown = || {
+            (
+                crate::common::ErrorCode::InvalidParams.code(),
+                format!("Unknown or finished device session: {}", session_id),
+            )
+        };
+        let now = std::time::Instant::now();
+        let (provider, client, device_code) = {
+            let mut sessions = self.device_sessions.lock().map_err(|_| unknown())?;
+            let session = sessions.get(session_id).ok_or_else(unknown)?;
+            if session.expires_at <= now {
+                sessions.remove(session_id);
+                return Ok(serde_json::json!({ "status": "expired" }));
+            }
+            if session.next_poll > now {
+                return Ok(serde_json::json!({
+                    "status": "pending",
+                    "retryIn": (session.next_poll - now).as_secs().max(1),
+                }));
+            }
+            (session.provider.clone(), session.client.clone(), session.device_code.clone())
+        };
+
+        let result = client.poll(&device_code).await;
+
+        // Scoped so the std guard isn't held across the awaits below
+        let (session, tokens) = {
+            let mut sessions = self.device_sessions.lock().map_err(|_| unknown())?;
+            let tokens = match result {
+                Ok(DevicePoll::Complete(tokens)) => tokens,
+                Ok(poll @ (DevicePoll::Pending | DevicePoll::SlowDown)) => {
+                    let slow_down = matches!(poll, DevicePoll::SlowDown);
+                    let session = sessions.get_mut(session_id).ok_or_else(unknown)?;
+                    if slow_down {
+                        session.interval += std::time::Duration::from_secs(5);
+                    }
+                    session.next_poll = std::time::Instant::now() + session.interval;
+                    return Ok(serde_json::json!({
+                        "status": if slow_down { "slow_down" } else { "pending" },
+                        "retryIn": session.interval.as_secs(),
+                    }));
+                }
+                Ok(DevicePoll::Denied) => {
+                    sessions.remove(session_id);
+                    return Ok(serde_json::json!({ "status": "denied" }));
+                }
+                Ok(DevicePoll::Expired) => {
+                    sessions.remove(session_id);
+                    return Ok(serde_json::json!({ "status": "expired" }));
+                }
+                Err(e) => {
+                    sessions.remove(session_id);
+                    return Err((crate::common::ErrorCode::GenericError.code(), e));
+                }
+            };
+            (sessions.remove(session_id).ok_or_else(unknown)?, tokens)
+        };
+
+        let account = match session.account {
+            Some(a) => a,
+            None if provider == "google" => fetch_google_email(&tokens.access_token)
+                .await
+                .map_err(|e| (crate::common::ErrorCode::GenericError.code(), e))?,
+            None => match self.oauth2_provider(&provider, None).await {
+                Ok(p) => p.fetch_account(&tokens.access_token).await.ok().flatten(),
+                Err(_) => None,
+            }
+            .unwrap_or_else(|| "default".to_string()),
+        };
+
+        let record = TokenRecord {
+            provider: provider.clone(),
+            account: account.clone(),
+            client_id: client.client_id,
+            client_secret: client.client_secret,
+            token_type: tokens.token_type,
+            access_token: tokens.access_token,
+            refresh_token: tokens.refresh_token.unwrap_or_default(),
+            expiry: tokens.expiry,
+            scopes: if tokens.scopes.is_empty() { session.scopes } else { tokens.scopes },
+            issued_at: chrono::Utc::now().to_rfc3339(),
+            last_refreshed: String::new(),
+        };
+        self.store_token(record).await?;
+
+        info!("Completed device authorization for {}:{}", provider, account);
+        Ok(serde_json::json!({
+            "status": "complete",
+            "provider": provider,
+            "account": account,
+        }))
+    }
+
     /// Store/import a token directly.
     pub async fn store_token(&self, record: TokenRecord) -> Result<(), (i32, String)> {
+        authorize_credential(&record.provider, &record.account)?;
         let mut store = self.store.write().await;
         store
             .save_token(&record)
+            .await
             .map_err(|e| (error_codes::MASTER_KEY_NOT_INITIALIZED, e))?;
         info!(
             "Stored token for {}:{} ({} scopes)",
@@ -342,6 +645,7 @@ impl AuthBroker {
             let mut store = self.store.write().await;
             store
                 .save_gog_passphrase(&passphrase)
+                .await
                 .map_err(|e| (error_codes::MASTER_KEY_NOT_INITIALIZED, e))?;
         }
 
@@ -388,8 +692,9 @@ impl AuthBroker {
         fields: std::collections::HashMap<String, String>,
         label: Option<&str>,
     ) -> Result<(), String> {
+        authorize_credential(provider, account).map_err(|(_, e)| e)?;
         let mut store = self.store.write().await;
-        store.store_credential(provider, account, cred_type, fields, label)
+        store.store_credential(provider, account, cred_type, fields, label).await
     }
 
     /// Get a credential (local store only for now)
@@ -400,10 +705,16 @@ impl AuthBroker {
     ) -> Result<std::collections::HashMap<String, String>, String> {
         let store = self.store.read().await;
         let account = label.unwrap_or("default");
-        store
+        let credential_id = format!("{}:{}", provider, account);
+        crate::proxy::policy::check_credential(&credential_id).map_err(|v| v.to_string())?;
+        crate::proxy::audit::note_credential(credential_id);
+        let fields = store
             .get_credential(provider, account)
             .transpose()
-            .ok_or_else(|| format!("No credential found for {}:{}", provider, account))?
+            .ok_or_else(|| format!("No credential found for {}:{}", provider, account))?;
+        drop(store);
+        self.reencrypt_stale_entries().await;
+        fields
     }
 
     /// List all credentials (metadata only)
@@ -414,34 +725,293 @@ impl AuthBroker {
 
     /// Delete a credential
     pub async fn delete_credential(&self, provider: &str, account: &str) -> Result<(), String> {
+        authorize_credential(provider, account).map_err(|(_, e)| e)?;
+        let mut store = self.store.write().await;
+        store.delete_credential(provider, account).await
+    }
+
+    /// Set or clear a credential's expiry and rotation deadline (RFC 3339).
+    pub async fn set_credential_lifecycle(
+        &self,
+        provider: &str,
+        account: &str,
+        expires_at: Option<String>,
+        rotate_after: Option<String>,
+    ) -> Result<(), String> {
+        authorize_credential(provider, account).map_err(|(_, e)| e)?;
+        let mut store = self.store.write().await;
+        store.set_credential_lifecycle(provider, account, expires_at, rotate_after).await
+    }
+
+    /// Swap in a new secret, keeping the old one readable for `grace`.
+    pub async fn rotate_credential(
+        &self,
+        provider: &str,
+        account: &str,
+        fields: std::collections::HashMap<String, String>,
+        grace: chrono::Duration,
+        expires_at: Option<String>,
+        rotate_after: Option<String>,
+    ) -> Result<store::CredentialMetadata, String> {
+        authorize_credential(provider, account).map_err(|(_, e)| e)?;
+        let mut store = self.store.write().await;
+        let metadata = store
+            .rotate_credential(provider, account, fields, grace, expires_at, rotate_after)
+            .await?;
+        drop(store);
+        if let Ok(mut flagged) = self.flagged_credentials.lock() {
+            flagged.remove(&format!("{}:{}", provider, account));
+        }
+        Ok(metadata)
+    }
+
+    /// Get the pre-rotation secret of a credential during its grace window.
+    pub async fn get_previous_credential(
+        &self,
+        provider: &str,
+        label: Option<&str>,
+    ) -> Result<std::collections::HashMap<String, String>, String> {
+        let store = self.store.read().await;
+        let account = label.unwrap_or("default");
+        let credential_id = format!("{}:{}", provider, account);
+        crate::proxy::policy::check_credential(&credential_id).map_err(|v| v.to_string())?;
+        crate::proxy::audit::note_credential(credential_id);
+        store
+            .get_previous_credential(provider, account)
+            .transpose()
+            .ok_or_else(|| {
+                format!("No previous secret within its grace window for {}:{}", provider, account)
+            })?
+    }
+
+    /// Static credentials expired, expiring within `within`, or due for rotation.
+    pub async fn expiring_credentials(&self, within: chrono::Duration) -> Vec<store::ExpiringCredential> {
+        let store = self.store.read().await;
+        store.expiring_credentials(within)
+    }
+
+    /// Check the token store for damage and optionally repair it from backups.
+    pub async fn verify_store(&self, repair: bool) -> Result<store::VerifyReport, String> {
+        let mut store = self.store.write().await;
+        store.verify(repair).await
+    }
+
+    /// The master key source in use, whether the environment overrides it,
+    /// and the data keyring.
+    pub async fn master_key_status(&self) -> serde_json::Value {
+        let store = self.store.read().await;
+        serde_json::json!({
+            "current": store.key_source(),
+            "envOverride": std::env::var_os(master_key::SOURCE_ENV).is_some(),
+            "platformDefault": master_key::MasterKeySource::platform_default(),
+            "keyring": store.keyring_status(),
+        })
+    }
+
+    /// Start a new data key; with `reencrypt_all`, move every entry to it now.
+    pub async fn rekey(&self, reencrypt_all: bool) -> Result<store::KeyringStatus, String> {
+        let mut store = self.store.write().await;
+        store.rekey(reencrypt_all).await
+    }
+
+    /// Move entries that reads found under an old data key to the current one.
+    async fn reencrypt_stale_entries(&self) {
+        if !self.store.read().await.has_stale_entries() {
+            return;
+        }
+        let mut store = self.store.write().await;
+        if let Err(e) = store.reencrypt_stale().await {
+            warn!("Lazy re-encryption failed: {}", e);
+        }
+    }
+
+    /// Re-encrypt the store under a different master key source.
+    pub async fn switch_master_key(
+        &self,
+        source: master_key::MasterKeySource,
+    ) -> Result<serde_json::Value, String> {
+        let mut store = self.store.write().await;
+        if *store.key_source() == source {
+            return Err("That master key source is already in use".to_string());
+        }
+        let count = store.change_master_key(source).await?;
+        Ok(serde_json::json!({
+            "current": store.key_source(),
+            "reencrypted": count,
+        }))
+    }
+
+    /// Export credentials and tokens to a passphrase-protected bundle file.
+    /// `selectors` are `provider:account` keys or bare providers; empty
+    /// exports all. Keys the client's policy denies fail an explicit
+    /// selection and are left out of a full export.
+    pub async fn export_bundle(
+        &self,
+        path: &std::path::Path,
+        passphrase: &str,
+        selectors: &[String],
+    ) -> Result<serde_json::Value, String> {
+        let store = self.store.read().await;
+        let keys: Vec<String> = if selectors.is_empty() {
+            store
+                .all_keys()
+                .into_iter()
+                .filter(|key| crate::proxy::policy::check_credential(key).is_ok())
+                .collect()
+        } else {
+            let mut keys = Vec::new();
+            for selector in selectors {
+                for key in store.select_keys(selector) {
+                    crate::proxy::policy::check_credential(&key).map_err(|v| v.to_string())?;
+                    if !keys.contains(&key) {
+                        keys.push(key);
+                    }
+                }
+            }
+            keys.sort();
+            keys
+        };
+        let entries = store.export_entries(&keys)?;
+        drop(store);
+        if entries.is_empty() {
+            return Err("No credentials matched the selection".to_string());
+        }
+        for key in keys {
+            crate::proxy::audit::note_credential(key);
+        }
+
+        let exported: Vec<serde_json::Value> = store::PortableEntry::summaries(&entries);
+        let count = entries.len();
+        let passphrase = zeroize::Zeroizing::new(passphrase.to_string());
+        let bytes = tokio::task::spawn_blocking(move || bundle::seal(&entries, count, &passphrase))
+            .await
+            .map_err(|e| format!("Bundle encryption task failed: {}", e))??;
+        crate::common::write_atomic(path, &bytes, Some(0o600))?;
+
+        info!("Exported {} credential(s) to {:?}", count, path);
+        Ok(serde_json::json!({
+            "path": path,
+            "count": count,
+            "entries": exported,
+        }))
+    }
+
+    /// Import a bundle written by [`export_bundle`](Self::export_bundle),
+    /// or with `dry_run` just report what would happen.
+    pub async fn import_bundle(
+        &self,
+        path: &std::path::Path,
+        passphrase: &str,
+        conflict: store::ConflictPolicy,
+        dry_run: bool,
+    ) -> Result<serde_json::Value, String> {
+        let bytes = std::fs::read(path)
+            .map_err(|e| format!("Failed to read bundle {:?}: {}", path, e))?;
+        let passphrase = zeroize::Zeroizing::new(passphrase.to_string());
+        let entries: Vec<store::PortableEntry> =
+            tokio::task::spawn_blocking(move || bundle::open(&bytes, &passphrase))
+                .await
+                .map_err(|e| format!("Bundle decryption task failed: {}", e))??;
+
         let mut store = self.store.write().await;
-        store.delete_credential(provider, account)
+        let outcomes = store
+            .import_entries(entries, conflict, dry_run, |key| {
+                crate::proxy::policy::check_credential(key).map_err(|v| v.to_string())
+            })
+            .await?;
+        drop(store);
+
+        let count = |action: &str| outcomes.iter().filter(|o| o.action == action).count();
+        let summary = serde_json::json!({
+            "add": count("add"),
+            "unchanged": count("unchanged"),
+            "skip": count("skip"),
+            "overwrite": count("overwrite"),
+            "rename": count("rename"),
+        });
+        if !dry_run {
+            info!("Imported credential bundle {:?}: {}", path, summary);
+        }
+        Ok(serde_json::json!({
+            "dryRun": dry_run,
+            "entries": outcomes,
+            "summary": summary,
+        }))
     }
 
     /// Start the background token refresh daemon.
     pub fn spawn_refresh_daemon(self: &Arc<Self>) {
         let broker = Arc::clone(self);
+        broker.update_daemon_health(|h| h.running = true);
         tokio::spawn(async move {
             let mut interval = tokio::time::interval(std::time::Duration::from_secs(60));
             loop {
-                interval.tick().await;
-                broker.refresh_expiring_tokens().await;
+                tokio::select! {
+                    _ = interval.tick() => {
+                        broker.refresh_expiring_tokens().await;
+                        broker.check_credential_lifecycle().await;
+                        broker.reencrypt_stale_entries().await;
+                    }
+                    _ = broker.daemon_stop.notified() => break,
+                }
             }
+            broker.update_daemon_health(|h| h.running = false);
+            info!("Background token refresh daemon stopped");
         });
         info!("Background token refresh daemon started");
     }
 
+    /// Stop the refresh daemon and write the store to disk. Waits for any
+    /// refresh that is mid-save to finish first.
+    pub async fn shutdown(&self) {
+        self.daemon_stop.notify_one();
+        let store = self.store.write().await;
+        if let Err(e) = store.flush().await {
+            error!("Failed to flush credential store on shutdown: {}", e);
+        }
+    }
+
     // ── Internal ────────────────────────────────────────────────────────────
 
+    fn update_daemon_health(&self, f: impl FnOnce(&mut RefreshDaemonHealth)) {
+        if let Ok(mut health) = self.daemon_health.lock() {
+            f(&mut health);
+        }
+    }
+
     async fn refresh_token_internal(&self, record: &mut TokenRecord) -> Result<(), String> {
-        let tokens = self
-            .google
-            .refresh_token(
-                &record.client_id,
-                &record.client_secret,
-                &record.refresh_token,
-            )
-            .await?;
+        let result = if record.refresh_token.is_empty() {
+            Err(format!(
+                "No refresh token for {}:{}; re-authorization is required",
+                record.provider, record.account
+            ))
+        } else if record.provider == "google" {
+            self.google
+                .refresh_token(&record.client_id, &record.client_secret, &record.refresh_token)
+                .await
+        } else {
+            match self.oauth2_provider(&record.provider, Some(record)).await {
+                Ok(p) => {
+                    p.refresh_token(&record.client_id, &record.client_secret, &record.refresh_token)
+                        .await
+                }
+                Err(e) => Err(e),
+            }
+        };
+
+        crate::proxy::notifications::publish_for_credential(
+            crate::proxy::notifications::topics::AUTH_TOKEN_REFRESHED,
+            &format!("{}:{}", record.provider, record.account),
+            serde_json::json!({
+                "provider": record.provider,
+                "account": record.account,
+                "success": result.is_ok(),
+                "expiry": result.as_ref().map(|t| t.expiry.clone()).ok(),
+                "error": result.as_ref().err(),
+            }),
+        );
+
+        let tokens = result?;
 
         record.access_token = tokens.access_token;
         if let Some(rt) = tokens.refresh_token {
@@ -453,43 +1023,100 @@ impl AuthBroker {
         Ok(())
     }
 
+    /// Drop pre-rotation secrets past their grace window and flag static
+    /// credentials that are expired, expiring or due for rotation. Each
+    /// credential is notified once per status change.
+    async fn check_credential_lifecycle(&self) {
+        let mut store = self.store.write().await;
+        match store.purge_expired_previous().await {
+            Ok(0) => {}
+            Ok(n) => info!("Dropped {} pre-rotation secret(s) past their grace window", n),
+            Err(e) => warn!("Failed to drop expired pre-rotation secrets: {}", e),
+        }
+        let expiring =
+            store.expiring_credentials(chrono::Duration::days(CREDENTIAL_EXPIRY_WARNING_DAYS));
+        drop(store);
+
+        let Ok(mut flagged) = self.flagged_credentials.lock() else {
+            return;
+        };
+        let mut current = std::collections::HashMap::new();
+        for cred in &expiring {
+            let key = format!("{}:{}", cred.credential.provider, cred.credential.account);
+            if flagged.get(&key) != Some(&cred.status) {
+                warn!("Credential {} is {}", key, cred.status.replace('_', " "));
+                crate::proxy::notifications::publish_for_credential(
+                    crate::proxy::notifications::topics::AUTH_CREDENTIAL_EXPIRING,
+                    &key,
+                    serde_json::to_value(cred).unwrap_or_default(),
+                );
+            }
+            current.insert(key, cred.status);
+        }
+        *flagged = current;
+        drop(flagged);
+
+        self.update_daemon_health(|h| h.expiring_credentials = expiring.len());
+    }
+
     async fn refresh_expiring_tokens(&self) {
         let store = self.store.read().await;
         let accounts = store.list_accounts();
         drop(store);
 
         for acct in accounts {
-            if is_token_expiring(&acct.expiry, 300) {
+            // Account listings don't carry the expiry; read it from the record
+            let store = self.store.read().await;
+            let record = match store.get_token(&acct.provider, &acct.account) {
+                Ok(Some(r)) => r,
+                _ => continue,
+            };
+            drop(store);
+
+            // Nothing to refresh with; callers get the re-authorization error
+            // from `get_token` instead of a failure notice every cycle
+            if record.refresh_token.is_empty() {
+                continue;
+            }
+
+            if is_token_expiring(&record.expiry, 300) {
                 // Expiring within 5 minutes
                 info!(
                     "Proactively refreshing token for {}:{}",
                     acct.provider, acct.account
                 );
 
-                let store = self.store.read().await;
-                let record = match store.get_token(&acct.provider, &acct.account) {
-                    Ok(Some(r)) => r,
-                    _ => continue,
-                };
-                drop(store);
-
                 let mut record = record;
-                match self.refresh_token_internal(&mut record).await {
+                let result = match self.refresh_token_internal(&mut record).await {
                     Ok(()) => {
                         let mut store = self.store.write().await;
-                        if let Err(e) = store.save_token(&record) {
+                        store.save_token(&record).await.map_err(|e| {
                             error!("Failed to save refreshed token: {}", e);
-                        }
+                            e
+                        })
                     }
                     Err(e) => {
                         warn!(
                             "Background refresh failed for {}:{}: {}",
                             acct.provider, acct.account, e
                         );
+                        Err(e)
                     }
-                }
+                };
+                self.update_daemon_health(|h| match result {
+                    Ok(()) => h.refreshed += 1,
+                    Err(e) => {
+                        h.failures += 1;
+                        h.last_error = Some(format!("{}:{}: {}", acct.provider, acct.account, e));
+                    }
+                });
             }
         }
+
+        self.update_daemon_health(|h| {
+            h.runs += 1;
+            h.last_run = Some(chrono::Utc::now().to_rfc3339());
+        });
     }
 }
 
@@ -608,11 +1235,9 @@ pub async fn auth_store_token(record: TokenRecord) -> Result<(), String> {
 
 #[tauri::command]
 pub async fn auth_start_google_oauth(_app: tauri::AppHandle) -> Result<serde_json::Value, String> {
-    use provider::google::{generate_code_verifier, generate_code_challenge, GoogleProvider};
+    use provider::google::{generate_code_verifier, generate_code_challenge};
     use provider::OAuthProvider;
-    use tokio::io::{AsyncBufReadExt, BufReader};
     use tokio::net::TcpListener;
-    use tokio::time::{timeout, Duration};
     
     // 1. Generate PKCE pair
     let code_verifier = generate_code_verifier();
@@ -642,17 +1267,7 @@ pub async fn auth_start_google_oauth(_app: tauri::AppHandle) -> Result<serde_jso
     
     let state = generate_state();
     
-    // Load credentials from saved Google OAuth config, fall back to defaults
-    let provider = match crate::config::config_google_oauth_get().await {
-        Ok(Some(config)) if !config.client_id.is_empty() => {
-            info!("Using saved Google OAuth credentials");
-            GoogleProvider::with_credentials(config.client_id, config.client_secret)
-        }
-        _ => {
-            warn!("No saved Google OAuth credentials found, using defaults");
-            GoogleProvider::new()
-        }
-    };
+    let provider = configured_google_provider().await;
     let auth_url = provider.authorize_url(&scopes, &state, &code_challenge, &redirect_uri);
     
     info!("Opening browser for OAuth authorization");
@@ -667,7 +1282,160 @@ pub async fn auth_start_google_oauth(_app: tauri::AppHandle) -> Result<serde_jso
     }
     
     // 5. Wait for callback (with timeout)
-    let callback_result = timeout(Duration::from_secs(120), async {
+    let code = wait_for_oauth_callback(listener, &state).await?;
+    
+    // 7. Exchange code for tokens
+    info!("Exchanging authorization code for tokens");
+    let tokens = provider
+        .exchange_code(&code, &code_verifier, &redirect_uri)
+        .await?;
+    
+    // 8. Fetch user email from Google userinfo endpoint
+    let email = fetch_google_email(&tokens.access_token).await?;
+    
+    // 9. Store token in broker
+    let record = TokenRecord {
+        provider: "google".to_string(),
+        account: email.clone(),
+        client_id: provider.client_id.clone(),
+        client_secret: provider.client_secret.clone(),
+        token_type: tokens.token_type,
+        access_token: tokens.access_token,
+        refresh_token: tokens.refresh_token.unwrap_or_default(),
+        expiry: tokens.expiry,
+        scopes: tokens.scopes,
+        issued_at: chrono::Utc::now().to_rfc3339(),
+        last_refreshed: String::new(),
+    };
+    
+    let broker = get_or_init_broker().await?;
+    broker.store_token(record).await.map_err(|(_, msg)| msg)?;
+    
+    info!("Successfully completed OAuth flow for {}", email);
+    
+    // 10. Return success with account email
+    Ok(serde_json::json!({
+        "success": true,
+        "email": email,
+        "message": "Authentication successful"
+    }))
+}
+
+/// Start the Authorization Code flow for a config-driven OAuth2 provider
+/// (GitHub, Microsoft 365, Slack, ...). The app's client credentials must be
+/// stored as an `oauth_client` credential first.
+#[tauri::command]
+pub async fn auth_start_oauth(
+    provider: String,
+    scopes: Option<Vec<String>>,
+    account: Option<String>,
+) -> Result<serde_json::Value, String> {
+    use provider::google::{generate_code_challenge, generate_code_verifier};
+    use provider::OAuthProvider;
+    use tokio::net::TcpListener;
+
+    if provider == "google" {
+        return Err("Use auth_start_google_oauth for Google".to_string());
+    }
+
+    let broker = get_or_init_broker().await?;
+    let oauth = broker.oauth2_provider(&provider, None).await?;
+
+    let code_verifier = generate_code_verifier();
+    let code_challenge = generate_code_challenge(&code_verifier);
+
+    let listener = TcpListener::bind("127.0.0.1:0")
+        .await
+        .map_err(|e| format!("Failed to bind local server: {}", e))?;
+    let local_addr = listener
+        .local_addr()
+        .map_err(|e| format!("Failed to get local address: {}", e))?;
+    let redirect_uri = format!("http://127.0.0.1:{}", local_addr.port());
+    info!("OAuth callback server listening on {}", redirect_uri);
+
+    let scopes = scopes.unwrap_or_else(|| oauth.default_scopes());
+    let state = generate_state();
+    let auth_url = oauth.authorize_url(&scopes, &state, &code_challenge, &redirect_uri);
+
+    info!("Opening browser for {} authorization", provider);
+    if let Err(e) = open::that(&auth_url) {
+        warn!("Failed to open browser automatically: {}. URL: {}", e, auth_url);
+        return Err(format!(
+            "Could not open browser. Please manually visit: {}",
+            auth_url
+        ));
+    }
+
+    let code = wait_for_oauth_callback(listener, &state).await?;
+    let tokens = oauth
+        .exchange_code(&code, &code_verifier, &redirect_uri)
+        .await?;
+
+    // Name the account from the provider's userinfo endpoint unless given
+    let account = match account {
+        Some(a) => a,
+        None => match oauth.fetch_account(&tokens.access_token).await {
+            Ok(Some(a)) => a,
+            Ok(None) => "default".to_string(),
+            Err(e) => {
+                warn!("Could not determine {} account name: {}", provider, e);
+                "default".to_string()
+            }
+        },
+    };
+
+    let record = TokenRecord {
+        provider: provider.clone(),
+        account: account.clone(),
+        client_id: oauth.client_id.clone(),
+        client_secret: oauth.client_secret.clone(),
+        token_type: tokens.token_type,
+        access_token: tokens.access_token,
+        refresh_token: tokens.refresh_token.unwrap_or_default(),
+        expiry: tokens.expiry,
+        scopes: if tokens.scopes.is_empty() { scopes } else { tokens.scopes },
+        issued_at: chrono::Utc::now().to_rfc3339(),
+        last_refreshed: String::new(),
+    };
+    broker.store_token(record).await.map_err(|(_, msg)| msg)?;
+
+    info!("Successfully completed OAuth flow for {}:{}", provider, account);
+
+    Ok(serde_json::json!({
+        "success": true,
+        "provider": provider,
+        "account": account,
+        "message": "Authentication successful"
+    }))
+}
+
+// ── OAuth Flow Helpers ──────────────────────────────────────────────────────
+
+/// Google provider with the saved OAuth client credentials, falling back to
+/// the defaults.
+async fn configured_google_provider() -> GoogleProvider {
+    match crate::config::config_google_oauth_get().await {
+        Ok(Some(config)) if !config.client_id.is_empty() => {
+            info!("Using saved Google OAuth credentials");
+            GoogleProvider::with_credentials(config.client_id, config.client_secret)
+        }
+        _ => {
+            warn!("No saved Google OAuth credentials found, using defaults");
+            GoogleProvider::new()
+        }
+    }
+}
+
+/// Accept loopback requests until the provider redirects back with an
+/// authorization code (or an error), checking `state`. Times out after 120s.
+async fn wait_for_oauth_callback(
+    listener: tokio::net::TcpListener,
+    state: &str,
+) -> Result<String, String> {
+    use tokio::io::{AsyncBufReadExt, BufReader};
+    use tokio::time::{timeout, Duration};
+
+    timeout(Duration::from_secs(120), async {
         loop {
             let (mut socket, _) = listener.accept().await?;
             
@@ -695,11 +1463,11 @@ pub async fn auth_start_google_oauth(_app: tauri::AppHandle) -> Result<serde_jso
             if let (Some(received_code), Some(received_state)) =
                 (params.get("code"), params.get("state"))
             {
-                // 6. Validate state
-                if received_state != &state {
+                // Validate state
+                if received_state != state {
                     let error_html = success_html("Error: Invalid state parameter. Please try again.");
                     send_response(&mut socket, "400 Bad Request", error_html).await?;
-                    return Err::<(String, String), std::io::Error>(std::io::Error::new(
+                    return Err::<String, std::io::Error>(std::io::Error::new(
                         std::io::ErrorKind::InvalidData,
                         "State mismatch",
                     ));
@@ -709,7 +1477,7 @@ pub async fn auth_start_google_oauth(_app: tauri::AppHandle) -> Result<serde_jso
                 let success_html = success_html("Authentication successful! You can close this tab.");
                 send_response(&mut socket, "200 OK", success_html).await?;
                 
-                return Ok((received_code.clone(), code_verifier.clone()));
+                return Ok(received_code.clone());
             } else if let Some(error) = params.get("error") {
                 let error_desc = params
                     .get("error_description")
@@ -726,49 +1494,9 @@ pub async fn auth_start_google_oauth(_app: tauri::AppHandle) -> Result<serde_jso
     })
     .await
     .map_err(|_| "OAuth flow timed out after 120 seconds".to_string())?
-    .map_err(|e| format!("Callback server error: {}", e))?;
-    
-    let (code, verifier) = callback_result;
-    
-    // 7. Exchange code for tokens
-    info!("Exchanging authorization code for tokens");
-    let tokens = provider
-        .exchange_code(&code, &verifier, &redirect_uri)
-        .await?;
-    
-    // 8. Fetch user email from Google userinfo endpoint
-    let email = fetch_google_email(&tokens.access_token).await?;
-    
-    // 9. Store token in broker
-    let record = TokenRecord {
-        provider: "google".to_string(),
-        account: email.clone(),
-        client_id: provider.client_id.clone(),
-        client_secret: provider.client_secret.clone(),
-        token_type: tokens.token_type,
-        access_token: tokens.access_token,
-        refresh_token: tokens.refresh_token.unwrap_or_default(),
-        expiry: tokens.expiry,
-        scopes: tokens.scopes,
-        issued_at: chrono::Utc::now().to_rfc3339(),
-        last_refreshed: String::new(),
-    };
-    
-    let broker = get_or_init_broker().await?;
-    broker.store_token(record).await.map_err(|(_, msg)| msg)?;
-    
-    info!("Successfully completed OAuth flow for {}", email);
-    
-    // 10. Return success with account email
-    Ok(serde_json::json!({
-        "success": true,
-        "email": email,
-        "message": "Authentication successful"
-    }))
+    .map_err(|e| format!("Callback server error: {}", e))
 }
 
-// ── OAuth Flow Helpers ──────────────────────────────────────────────────────
-
 /// Generate a random state string for CSRF protection
 fn generate_state() -> String {
     use rand::Rng;
@@ -1046,6 +1774,16 @@ pub async fn op_config_default_vault_set(vault_id: String) -> Result<(), String>
 
 // ── Helpers ─────────────────────────────────────────────────────────────────
 
+/// Refuse `provider:account` unless the current client's policy allows it
+/// (always allowed outside a socket request), and note it for the audit log.
+fn authorize_credential(provider: &str, account: &str) -> Result<(), (i32, String)> {
+    let credential_id = format!("{}:{}", provider, account);
+    crate::proxy::policy::check_credential(&credential_id)
+        .map_err(|v| (crate::common::ErrorCode::PolicyDenied.code(), v.to_string()))?;
+    crate::proxy::audit::note_credential(credential_id);
+    Ok(())
+}
+
 /// Check whether `existing` scope is a known superset that covers `required`.
 ///
 /// Google uses full-access scopes (e.g. `https://mail.google.com/`) that
@@ -1101,6 +1839,10 @@ fn scope_covers(existing: &str, required: &str) -> bool {
 
 /// Check whether a token's expiry (RFC 3339) is within `margin_secs` of now.
 fn is_token_expiring(expiry: &str, margin_secs: i64) -> bool {
+    // Providers such as GitHub issue tokens that never expire
+    if expiry.is_empty() {
+        return false;
+    }
     match chrono::DateTime::parse_from_rfc3339(expiry) {
         Ok(exp) => {
             let now = chrono::Utc::now();
@@ -1110,3 +1852,29 @@ fn is_token_expiring(expiry: &str, margin_secs: i64) -> bool {
         Err(_) => true, // unparseable ⇒ treat as expired
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proxy::policy::{self, ClientPolicy, ResolvedPolicy};
+
+    #[tokio::test]
+    async fn test_authorize_credential_follows_policy() {
+        assert!(authorize_credential("google", "me@example.com").is_ok());
+
+        let restricted = Arc::new(ResolvedPolicy {
+            client: "mail-agent".into(),
+            policy: ClientPolicy {
+                credentials: Some(vec!["google:work@example.com".into()]),
+                ..ClientPolicy::deny_all()
+            },
+        });
+        policy::scope(Some(restricted), async {
+            assert!(authorize_credential("google", "work@example.com").is_ok());
+            let (code, message) = authorize_credential("google", "me@example.com").unwrap_err();
+            assert_eq!(code, crate::common::ErrorCode::PolicyDenied.code());
+            assert!(message.contains("google:me@example.com"));
+        })
+        .await;
+    }
+}
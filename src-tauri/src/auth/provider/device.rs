@@ -0,0 +1,223 @@
+//! OAuth Device Authorization Grant (RFC 8628)
+//!
+//! For authorizing accounts from machines no browser redirect can reach: the
+//! user enters a short code at the provider's verification page on any device
+//! while we poll the token endpoint.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::{parse_token_response, OAuthTokens};
+
+/// `grant_type` for the polling token request
+const DEVICE_CODE_GRANT: &str = "urn:ietf:params:oauth:grant-type:device_code";
+
+/// Polling interval when the provider doesn't send one (RFC 8628 §3.2)
+const DEFAULT_INTERVAL: u64 = 5;
+
+/// Endpoints and client credentials for one device flow
+#[derive(Debug, Clone)]
+pub struct DeviceClient {
+    pub device_authorization_url: String,
+    pub token_url: String,
+    pub client_id: String,
+    pub client_secret: String,
+}
+
+/// Device authorization response (RFC 8628 §3.2)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviceAuthorization {
+    pub device_code: String,
+    pub user_code: String,
+    /// Google calls this `verification_url`
+    #[serde(alias = "verification_url")]
+    pub verification_uri: String,
+    #[serde(default)]
+    pub verification_uri_complete: Option<String>,
+    pub expires_in: u64,
+    #[serde(default = "default_interval")]
+    pub interval: u64,
+}
+
+fn default_interval() -> u64 {
+    DEFAULT_INTERVAL
+}
+
+/// Outcome of one token poll (RFC 8628 §3.5)
+#[derive(Debug)]
+pub enum DevicePoll {
+    /// The user hasn't finished yet
+    Pending,
+    /// Polling too fast; add 5 seconds to the interval
+    SlowDown,
+    Complete(OAuthTokens),
+    Denied,
+    Expired,
+}
+
+impl DeviceClient {
+    /// Client ID/secret form fields, leaving out an empty secret
+    fn client_params(&self) -> HashMap<&str, &str> {
+        let mut params = HashMap::new();
+        params.insert("client_id", self.client_id.as_str());
+        if !self.client_secret.is_empty() {
+            params.insert("client_secret", self.client_secret.as_str());
+        }
+        params
+    }
+
+    /// Request a device and user code.
+    pub async fn start(&self, scope: &str) -> Result<DeviceAuthorization, String> {
+        let mut params = self.client_params();
+        if !scope.is_empty() {
+            params.insert("scope", scope);
+        }
+
+        let (status, body) = post_form_any_status(&self.device_authorization_url, &params).await?;
+        if !(200..300).contains(&status) {
+            return Err(oauth_error(&body)
+                .map(|(err, desc)| format!("{}: {}", err, desc))
+                .unwrap_or_else(|| format!("HTTP {} from device authorization endpoint", status)));
+        }
+        serde_json::from_str(&body)
+            .map_err(|e| format!("Invalid device authorization response: {}", e))
+    }
+
+    /// Poll the token endpoint once.
+    pub async fn poll(&self, device_code: &str) -> Result<DevicePoll, String> {
+        let mut params = self.client_params();
+        params.insert("device_code", device_code);
+        params.insert("grant_type", DEVICE_CODE_GRANT);
+
+        // Pending/denied/expired arrive as 400s with an `error` body
+        let (status, body) = post_form_any_status(&self.token_url, &params).await?;
+        match oauth_error(&body) {
+            Some((err, desc)) => match err.as_str() {
+                "authorization_pending" => Ok(DevicePoll::Pending),
+                "slow_down" => Ok(DevicePoll::SlowDown),
+                "access_denied" => Ok(DevicePoll::Denied),
+                "expired_token" => Ok(DevicePoll::Expired),
+                _ => Err(format!("{}: {}", err, desc)),
+            },
+            None if (200..300).contains(&status) => {
+                parse_token_response(&body, None).map(DevicePoll::Complete)
+            }
+            None => Err(format!("HTTP {} from token endpoint", status)),
+        }
+    }
+}
+
+/// `(error, error_description)` from an OAuth error body
+fn oauth_error(body: &str) -> Option<(String, String)> {
+    let parsed: serde_json::Value = serde_json::from_str(body).ok()?;
+    let err = parsed.get("error")?.as_str()?.to_string();
+    let desc = parsed
+        .get("error_description")
+        .and_then(|v| v.as_str())
+        .unwrap_or("")
+        .to_string();
+    Some((err, desc))
+}
+
+/// POST a form and return the status and body without treating 4xx as failure.
+async fn post_form_any_status(
+    url: &str,
+    params: &HashMap<&str, &str>,
+) -> Result<(u16, String), String> {
+    let client = crate::common::create_http_client()?;
+    let response = client
+        .post(url)
+        .header("Accept", "application/json")
+        .form(params)
+        .send()
+        .await
+        .map_err(|e| format!("HTTP request failed: {}", e))?;
+    let status = response.status().as_u16();
+    let body = response
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read response body: {}", e))?;
+    Ok((status, body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Stand-in OAuth server answering each request with the next canned response.
+    async fn stand_in(responses: Vec<(u16, &'static str)>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            for (status, body) in responses {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut buf = [0u8; 4096];
+                let _ = socket.read(&mut buf).await;
+                let reply = format!(
+                    "HTTP/1.1 {} X\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                let _ = socket.write_all(reply.as_bytes()).await;
+            }
+        });
+        base
+    }
+
+    #[tokio::test]
+    async fn test_device_flow_against_stand_in() {
+        let base = stand_in(vec![
+            (200, r#"{"device_code":"dc","user_code":"ABCD-EFGH","verification_url":"https://example.com/device","expires_in":900}"#),
+            (400, r#"{"error":"authorization_pending"}"#),
+            (400, r#"{"error":"slow_down"}"#),
+            (200, r#"{"access_token":"at","refresh_token":"rt","expires_in":3600,"scope":"a b"}"#),
+        ])
+        .await;
+        let client = DeviceClient {
+            device_authorization_url: format!("{}/device/code", base),
+            token_url: format!("{}/token", base),
+            client_id: "cid".into(),
+            client_secret: String::new(),
+        };
+
+        let auth = client.start("a b").await.unwrap();
+        assert_eq!(auth.user_code, "ABCD-EFGH");
+        assert_eq!(auth.verification_uri, "https://example.com/device");
+        assert_eq!(auth.interval, DEFAULT_INTERVAL);
+
+        assert!(matches!(client.poll(&auth.device_code).await, Ok(DevicePoll::Pending)));
+        assert!(matches!(client.poll(&auth.device_code).await, Ok(DevicePoll::SlowDown)));
+        match client.poll(&auth.device_code).await {
+            Ok(DevicePoll::Complete(tokens)) => {
+                assert_eq!(tokens.access_token, "at");
+                assert_eq!(tokens.refresh_token.as_deref(), Some("rt"));
+                assert_eq!(tokens.scopes, ["a", "b"]);
+            }
+            other => panic!("unexpected poll result: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_device_flow_denied_and_errors() {
+        let base = stand_in(vec![
+            (400, r#"{"error":"access_denied"}"#),
+            (400, r#"{"error":"expired_token"}"#),
+            (400, r#"{"error":"invalid_client","error_description":"bad client"}"#),
+        ])
+        .await;
+        let client = DeviceClient {
+            device_authorization_url: format!("{}/device/code", base),
+            token_url: format!("{}/token", base),
+            client_id: "cid".into(),
+            client_secret: "secret".into(),
+        };
+
+        assert!(matches!(client.poll("dc").await, Ok(DevicePoll::Denied)));
+        assert!(matches!(client.poll("dc").await, Ok(DevicePoll::Expired)));
+        assert_eq!(client.poll("dc").await.unwrap_err(), "invalid_client: bad client");
+    }
+}
@@ -4,22 +4,27 @@
 //! Client credentials are placeholder — Geilt will provide real ones.
 
 use std::collections::HashMap;
-use tracing::{error, info};
+use tracing::info;
 
-use super::{OAuthProvider, OAuthTokens};
+use super::{parse_token_response, post_form, OAuthProvider, OAuthTokens};
 
 // ── Google OAuth endpoints ──────────────────────────────────────────────────
 
 #[allow(dead_code)]
 const AUTH_ENDPOINT: &str = "https://accounts.google.com/o/oauth2/v2/auth";
-const TOKEN_ENDPOINT: &str = "https://oauth2.googleapis.com/token";
+pub(crate) const TOKEN_ENDPOINT: &str = "https://oauth2.googleapis.com/token";
 const REVOKE_ENDPOINT: &str = "https://oauth2.googleapis.com/revoke";
+/// RFC 8628 device authorization endpoint
+pub(crate) const DEVICE_ENDPOINT: &str = "https://oauth2.googleapis.com/device/code";
 
 // TODO: Replace with real credentials — Geilt will provide these.
 // These must be registered in Google Cloud Console as a "Desktop app" OAuth client.
 const DEFAULT_CLIENT_ID: &str = "PLACEHOLDER_CLIENT_ID.apps.googleusercontent.com";
 const DEFAULT_CLIENT_SECRET: &str = "PLACEHOLDER_CLIENT_SECRET";
 
+/// Google always sends `expires_in`; assume an hour if it doesn't
+const DEFAULT_EXPIRES_IN: u64 = 3600;
+
 /// Google OAuth2 provider.
 pub struct GoogleProvider {
     pub client_id: String,
@@ -84,7 +89,7 @@ impl OAuthProvider for GoogleProvider {
         params.insert("redirect_uri", redirect_uri);
 
         let response = post_form(TOKEN_ENDPOINT, &params).await?;
-        parse_token_response(&response)
+        parse_token_response(&response, Some(DEFAULT_EXPIRES_IN))
     }
 
     async fn refresh_token(
@@ -115,7 +120,7 @@ impl OAuthProvider for GoogleProvider {
         params.insert("grant_type", "refresh_token");
 
         let response = post_form(TOKEN_ENDPOINT, &params).await?;
-        parse_token_response(&response)
+        parse_token_response(&response, Some(DEFAULT_EXPIRES_IN))
     }
 
     async fn revoke_token(&self, token: &str) -> Result<(), String> {
@@ -156,102 +161,6 @@ impl OAuthProvider for GoogleProvider {
     }
 }
 
-// ── HTTP utilities ──────────────────────────────────────────────────────────
-
-/// POST a form-encoded request and return the response body.
-///
-/// **SECURITY:** Uses reqwest HTTP client. Secrets are passed in request body,
-/// never in process arguments. Safe from ps/pgrep exposure.
-async fn post_form(
-    url: &str,
-    params: &HashMap<&str, &str>,
-) -> Result<String, String> {
-    let client = crate::common::create_http_client()?;
-
-    let response = client
-        .post(url)
-        .form(params)
-        .send()
-        .await
-        .map_err(|e| format!("HTTP request failed: {}", e))?;
-
-    if !response.status().is_success() {
-        let status = response.status();
-        let body = response
-            .text()
-            .await
-            .unwrap_or_else(|_| "Failed to read error response".to_string());
-        
-        // Redact potentially sensitive error details (max 200 chars)
-        let safe_body = if body.len() > 200 {
-            format!("{}... [truncated]", &body[..200])
-        } else {
-            body.clone()
-        };
-        
-        error!("HTTP error {}: {}", status, safe_body);
-        return Err(format!("HTTP {} error: {}", status, body));
-    }
-
-    response
-        .text()
-        .await
-        .map_err(|e| format!("Failed to read response body: {}", e))
-}
-
-/// Parse a Google OAuth2 token response.
-fn parse_token_response(body: &str) -> Result<OAuthTokens, String> {
-    let parsed: serde_json::Value =
-        serde_json::from_str(body).map_err(|e| format!("Invalid JSON response: {}", e))?;
-
-    // Check for error
-    if let Some(err) = parsed.get("error").and_then(|v| v.as_str()) {
-        let desc = parsed
-            .get("error_description")
-            .and_then(|v| v.as_str())
-            .unwrap_or("Unknown error");
-        return Err(format!("{}: {}", err, desc));
-    }
-
-    let access_token = parsed
-        .get("access_token")
-        .and_then(|v| v.as_str())
-        .ok_or("Missing access_token in response")?
-        .to_string();
-
-    let refresh_token = parsed
-        .get("refresh_token")
-        .and_then(|v| v.as_str())
-        .map(String::from);
-
-    let token_type = parsed
-        .get("token_type")
-        .and_then(|v| v.as_str())
-        .unwrap_or("Bearer")
-        .to_string();
-
-    let expires_in = parsed
-        .get("expires_in")
-        .and_then(|v| v.as_u64())
-        .unwrap_or(3600);
-
-    let expiry = (chrono::Utc::now() + chrono::Duration::seconds(expires_in as i64)).to_rfc3339();
-
-    let scopes = parsed
-        .get("scope")
-        .and_then(|v| v.as_str())
-        .map(|s| s.split(' ').map(String::from).collect())
-        .unwrap_or_default();
-
-    Ok(OAuthTokens {
-        access_token,
-        refresh_token,
-        token_type,
-        expiry,
-        scopes,
-    })
-}
-
 // ── PKCE Utilities ──────────────────────────────────────────────────────────
 
 /// Generate a PKCE code verifier (43-128 characters of unreserved URI characters).
@@ -307,7 +216,7 @@ mod tests {
             "scope": "https://www.googleapis.com/auth/gmail.modify"
         }"#;
 
-        let tokens = parse_token_response(body).unwrap();
+        let tokens = parse_token_response(body, Some(DEFAULT_EXPIRES_IN)).unwrap();
         assert_eq!(tokens.access_token, "ya29.test");
         assert_eq!(tokens.refresh_token.as_deref(), Some("1//0e.test"));
         assert_eq!(tokens.scopes.len(), 1);
@@ -316,6 +225,6 @@ mod tests {
     #[test]
     fn test_parse_token_response_error() {
         let body = r#"{"error": "invalid_grant", "error_description": "Token has been revoked"}"#;
-        assert!(parse_token_response(body).is_err());
+        assert!(parse_token_response(body, None).is_err());
     }
 }
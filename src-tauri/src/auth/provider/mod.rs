@@ -2,10 +2,15 @@
 //!
 //! Trait-based provider system allowing multiple OAuth providers (Google, Microsoft, etc.)
 
+pub mod device;
 pub mod google;
+pub mod oauth2;
 pub mod onepassword;
 
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
+use tracing::error;
 
 /// Tokens returned from an OAuth token exchange or refresh.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -58,3 +63,111 @@ pub trait OAuthProvider {
     /// Default scopes for this provider.
     fn default_scopes(&self) -> Vec<String>;
 }
+
+// ── HTTP utilities ──────────────────────────────────────────────────────────
+
+/// POST a form-encoded request and return the response body.
+///
+/// **SECURITY:** Uses reqwest HTTP client. Secrets are passed in request body,
+/// never in process arguments. Safe from ps/pgrep exposure.
+pub(crate) async fn post_form(
+    url: &str,
+    params: &HashMap<&str, &str>,
+) -> Result<String, String> {
+    let client = crate::common::create_http_client()?;
+
+    let response = client
+        .post(url)
+        .header("Accept", "application/json")
+        .form(params)
+        .send()
+        .await
+        .map_err(|e| format!("HTTP request failed: {}", e))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "Failed to read error response".to_string());
+        
+        // Redact potentially sensitive error details (max 200 chars)
+        let safe_body = if body.len() > 200 {
+            format!("{}... [truncated]", &body[..200])
+        } else {
+            body.clone()
+        };
+        
+        error!("HTTP error {}: {}", status, safe_body);
+        return Err(format!("HTTP {} error: {}", status, body));
+    }
+
+    response
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read response body: {}", e))
+}
+
+/// Parse an OAuth2 token response. Without `expires_in` the token gets
+/// `default_expires_in` seconds, or no expiry at all (empty `expiry`) if that
+/// is `None`.
+pub(crate) fn parse_token_response(
+    body: &str,
+    default_expires_in: Option<u64>,
+) -> Result<OAuthTokens, String> {
+    let parsed: serde_json::Value =
+        serde_json::from_str(body).map_err(|e| format!("Invalid JSON response: {}", e))?;
+
+    // Check for error
+    if let Some(err) = parsed.get("error").and_then(|v| v.as_str()) {
+        let desc = parsed
+            .get("error_description")
+            .and_then(|v| v.as_str())
+            .unwrap_or("Unknown error");
+        return Err(format!("{}: {}", err, desc));
+    }
+
+    let access_token = parsed
+        .get("access_token")
+        .and_then(|v| v.as_str())
+        .ok_or("Missing access_token in response")?
+        .to_string();
+
+    let refresh_token = parsed
+        .get("refresh_token")
+        .and_then(|v| v.as_str())
+        .map(String::from);
+
+    let token_type = parsed
+        .get("token_type")
+        .and_then(|v| v.as_str())
+        .unwrap_or("Bearer")
+        .to_string();
+
+    let expiry = parsed
+        .get("expires_in")
+        .and_then(|v| v.as_u64())
+        .or(default_expires_in)
+        .map(|secs| (chrono::Utc::now() + chrono::Duration::seconds(secs as i64)).to_rfc3339())
+        .unwrap_or_default();
+
+    // Space-separated per RFC 6749; GitHub and Slack use commas
+    let scopes = parsed
+        .get("scope")
+        .and_then(|v| v.as_str())
+        .map(|s| {
+            s.split([' ', ','])
+                .filter(|s| !s.is_empty())
+                .map(String::from)
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Ok(OAuthTokens {
+        access_token,
+        refresh_token,
+        token_type,
+        expiry,
+        scopes,
+    })
+}
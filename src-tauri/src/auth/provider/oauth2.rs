@@ -0,0 +1,433 @@
+//! Generic OAuth2 Provider
+//!
+//! Config-driven Authorization Code flow (PKCE optional) for services that
+//! follow RFC 6749. Presets cover GitHub, Microsoft 365, Slack, Linear and
+//! Atlassian; more can be declared, or presets overridden by name, in
+//! `~/.tairseach/oauth_providers.json`:
+//!
+//! ```json
+//! [{
+//!   "name": "gitlab",
+//!   "authorizeUrl": "https://gitlab.com/oauth/authorize",
+//!   "tokenUrl": "https://gitlab.com/oauth/token",
+//!   "revokeUrl": "https://gitlab.com/oauth/revoke",
+//!   "scopes": ["read_api"],
+//!   "userinfoUrl": "https://gitlab.com/api/v4/user",
+//!   "accountField": "username"
+//! }]
+//! ```
+//!
+//! The OAuth app's client ID and secret are kept in the credential store as an
+//! `oauth_client` credential whose label is the provider name (or
+//! `clientCredential`).
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::{info, warn};
+
+use super::{parse_token_response, post_form, OAuthProvider, OAuthTokens};
+
+/// Credential type holding OAuth app client credentials
+pub const CLIENT_CREDENTIAL_TYPE: &str = "oauth_client";
+
+/// Endpoints and behaviour of one OAuth2 provider
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OAuth2ProviderConfig {
+    /// Provider name used in `auth.token` etc. (e.g. "github")
+    pub name: String,
+    #[serde(default)]
+    pub display_name: String,
+    pub authorize_url: String,
+    pub token_url: String,
+    #[serde(default)]
+    pub revoke_url: Option<String>,
+    /// RFC 8628 device authorization endpoint, for `auth.deviceStart`
+    #[serde(default)]
+    pub device_authorization_url: Option<String>,
+    /// Send an S256 code challenge with the authorization request
+    #[serde(default = "default_true")]
+    pub pkce: bool,
+    /// Scopes requested when the caller doesn't name any
+    #[serde(default)]
+    pub scopes: Vec<String>,
+    #[serde(default = "default_scope_separator")]
+    pub scope_separator: String,
+    /// Extra query params for the authorization URL
+    #[serde(default)]
+    pub authorize_params: BTreeMap<String, String>,
+    /// Endpoint returning the signed-in user, to name the account
+    #[serde(default)]
+    pub userinfo_url: Option<String>,
+    /// Path into the userinfo response holding the account name
+    #[serde(default)]
+    pub account_field: Option<String>,
+    /// Label of the `oauth_client` credential (default: `name`)
+    #[serde(default)]
+    pub client_credential: Option<String>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_scope_separator() -> String {
+    " ".to_string()
+}
+
+impl OAuth2ProviderConfig {
+    fn preset(name: &str, display_name: &str, authorize_url: &str, token_url: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            display_name: display_name.to_string(),
+            authorize_url: authorize_url.to_string(),
+            token_url: token_url.to_string(),
+            revoke_url: None,
+            device_authorization_url: None,
+            pkce: true,
+            scopes: Vec::new(),
+            scope_separator: default_scope_separator(),
+            authorize_params: BTreeMap::new(),
+            userinfo_url: None,
+            account_field: None,
+            client_credential: None,
+        }
+    }
+
+    /// Label of the `oauth_client` credential holding this app's client ID
+    pub fn client_credential_label(&self) -> &str {
+        self.client_credential.as_deref().unwrap_or(&self.name)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.is_empty() || self.name == "google" {
+            return Err(format!("Invalid OAuth provider name '{}'", self.name));
+        }
+        let mut urls = vec![("authorizeUrl", &self.authorize_url), ("tokenUrl", &self.token_url)];
+        for (field, url) in [
+            ("deviceAuthorizationUrl", &self.device_authorization_url),
+            ("revokeUrl", &self.revoke_url),
+            ("userinfoUrl", &self.userinfo_url),
+        ] {
+            if let Some(url) = url {
+                urls.push((field, url));
+            }
+        }
+        for (field, url) in urls {
+            let parsed = reqwest::Url::parse(url)
+                .map_err(|e| format!("{}: {} is not a valid URL: {}", self.name, field, e))?;
+            if parsed.scheme() != "https" {
+                return Err(format!("{}: {} must use https", self.name, field));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Providers available without any configuration
+pub fn builtin_providers() -> Vec<OAuth2ProviderConfig> {
+    let scopes = |s: &[&str]| s.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+    let mut github = OAuth2ProviderConfig::preset(
+        "github",
+        "GitHub",
+        "https://github.com/login/oauth/authorize",
+        "https://github.com/login/oauth/access_token",
+    );
+    github.pkce = false;
+    github.device_authorization_url = Some("https://github.com/login/device/code".to_string());
+    github.scopes = scopes(&["repo", "read:user"]);
+    github.userinfo_url = Some("https://api.github.com/user".to_string());
+    github.account_field = Some("login".to_string());
+
+    let mut microsoft = OAuth2ProviderConfig::preset(
+        "microsoft",
+        "Microsoft 365",
+        "https://login.microsoftonline.com/common/oauth2/v2.0/authorize",
+        "https://login.microsoftonline.com/common/oauth2/v2.0/token",
+    );
+    microsoft.device_authorization_url =
+        Some("https://login.microsoftonline.com/common/oauth2/v2.0/devicecode".to_string());
+    microsoft.scopes = scopes(&["offline_access", "User.Read", "Mail.ReadWrite", "Calendars.ReadWrite"]);
+    microsoft.userinfo_url = Some("https://graph.microsoft.com/v1.0/me".to_string());
+    microsoft.account_field = Some("userPrincipalName".to_string());
+
+    let mut slack = OAuth2ProviderConfig::preset(
+        "slack",
+        "Slack",
+        "https://slack.com/oauth/v2/authorize",
+        "https://slack.com/api/oauth.v2.access",
+    );
+    slack.pkce = false;
+    slack.revoke_url = Some("https://slack.com/api/auth.revoke".to_string());
+    slack.scopes = scopes(&["channels:read", "chat:write"]);
+    slack.scope_separator = ",".to_string();
+    slack.userinfo_url = Some("https://slack.com/api/auth.test".to_string());
+    slack.account_field = Some("user".to_string());
+
+    let mut linear = OAuth2ProviderConfig::preset(
+        "linear",
+        "Linear",
+        "https://linear.app/oauth/authorize",
+        "https://api.linear.app/oauth/token",
+    );
+    linear.revoke_url = Some("https://api.linear.app/oauth/revoke".to_string());
+    linear.scopes = scopes(&["read", "write"]);
+    linear.scope_separator = ",".to_string();
+
+    let mut atlassian = OAuth2ProviderConfig::preset(
+        "atlassian",
+        "Atlassian",
+        "https://auth.atlassian.com/authorize",
+        "https://auth.atlassian.com/oauth/token",
+    );
+    atlassian.pkce = false;
+    atlassian.scopes = scopes(&["read:jira-work", "write:jira-work", "read:me", "offline_access"]);
+    atlassian.authorize_params = BTreeMap::from([
+        ("audience".to_string(), "api.atlassian.com".to_string()),
+        ("prompt".to_string(), "consent".to_string()),
+    ]);
+    atlassian.userinfo_url = Some("https://api.atlassian.com/me".to_string());
+    atlassian.account_field = Some("email".to_string());
+
+    vec![github, microsoft, slack, linear, atlassian]
+}
+
+/// Presets merged with `~/.tairseach/oauth_providers.json` (same name replaces)
+pub fn load_providers() -> HashMap<String, OAuth2ProviderConfig> {
+    let mut providers: HashMap<String, OAuth2ProviderConfig> = builtin_providers()
+        .into_iter()
+        .map(|p| (p.name.clone(), p))
+        .collect();
+
+    let Ok(path) = crate::common::tairseach_path("oauth_providers.json") else {
+        return providers;
+    };
+    let Ok(content) = std::fs::read_to_string(&path) else {
+        return providers;
+    };
+    match serde_json::from_str::<Vec<OAuth2ProviderConfig>>(&content) {
+        Ok(configured) => {
+            for provider in configured {
+                match provider.validate() {
+                    Ok(()) => {
+                        providers.insert(provider.name.clone(), provider);
+                    }
+                    Err(e) => warn!("Ignoring OAuth provider in {:?}: {}", path, e),
+                }
+            }
+        }
+        Err(e) => warn!("Failed to parse {:?}: {}", path, e),
+    }
+    providers
+}
+
+/// An OAuth2 provider with its app's client credentials
+pub struct GenericOAuth2Provider {
+    pub config: OAuth2ProviderConfig,
+    pub client_id: String,
+    pub client_secret: String,
+}
+
+impl GenericOAuth2Provider {
+    pub fn new(config: OAuth2ProviderConfig, client_id: String, client_secret: String) -> Self {
+        Self {
+            config,
+            client_id,
+            client_secret,
+        }
+    }
+
+    /// Client ID/secret (and token) form fields, leaving out an empty secret
+    /// for public clients
+    fn client_params(&self) -> HashMap<&str, &str> {
+        let mut params = HashMap::new();
+        params.insert("client_id", self.client_id.as_str());
+        if !self.client_secret.is_empty() {
+            params.insert("client_secret", self.client_secret.as_str());
+        }
+        params
+    }
+
+    /// Name the account a token belongs to from the userinfo endpoint.
+    /// `Ok(None)` if the provider doesn't declare one.
+    pub async fn fetch_account(&self, access_token: &str) -> Result<Option<String>, String> {
+        let (Some(url), Some(field)) = (&self.config.userinfo_url, &self.config.account_field) else {
+            return Ok(None);
+        };
+        let client = crate::common::create_http_client_with_timeout(10)?;
+        let response = client
+            .get(url)
+            .bearer_auth(access_token)
+            .header("Accept", "application/json")
+            .header("User-Agent", "Tairseach")
+            .send()
+            .await
+            .map_err(|e| format!("Failed to fetch user info: {}", e))?;
+        if !response.status().is_success() {
+            return Err(format!("Failed to fetch user info: HTTP {}", response.status()));
+        }
+        let body: Value = response
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse user info: {}", e))?;
+        crate::common::template::extract(&body, field)
+            .and_then(|v| v.as_str().map(str::to_string))
+            .map(Some)
+            .ok_or_else(|| format!("'{}' not found in user info", field))
+    }
+}
+
+impl OAuthProvider for GenericOAuth2Provider {
+    fn name(&self) -> &str {
+        &self.config.name
+    }
+
+    fn authorize_url(
+        &self,
+        scopes: &[String],
+        state: &str,
+        code_challenge: &str,
+        redirect_uri: &str,
+    ) -> String {
+        let scope_str = scopes.join(&self.config.scope_separator);
+        let mut params: Vec<(&str, &str)> = vec![
+            ("client_id", &self.client_id),
+            ("redirect_uri", redirect_uri),
+            ("response_type", "code"),
+            ("state", state),
+        ];
+        if !scope_str.is_empty() {
+            params.push(("scope", &scope_str));
+        }
+        if self.config.pkce {
+            params.push(("code_challenge", code_challenge));
+            params.push(("code_challenge_method", "S256"));
+        }
+        params.extend(
+            self.config
+                .authorize_params
+                .iter()
+                .map(|(k, v)| (k.as_str(), v.as_str())),
+        );
+
+        match reqwest::Url::parse_with_params(&self.config.authorize_url, &params) {
+            Ok(url) => url.to_string(),
+            Err(_) => self.config.authorize_url.clone(),
+        }
+    }
+
+    async fn exchange_code(
+        &self,
+        code: &str,
+        code_verifier: &str,
+        redirect_uri: &str,
+    ) -> Result<OAuthTokens, String> {
+        info!("Exchanging authorization code for {} tokens", self.config.name);
+
+        let mut params = self.client_params();
+        params.insert("code", code);
+        params.insert("grant_type", "authorization_code");
+        params.insert("redirect_uri", redirect_uri);
+        if self.config.pkce {
+            params.insert("code_verifier", code_verifier);
+        }
+
+        let response = post_form(&self.config.token_url, &params).await?;
+        parse_token_response(&response, None)
+    }
+
+    async fn refresh_token(
+        &self,
+        client_id: &str,
+        client_secret: &str,
+        refresh_token: &str,
+    ) -> Result<OAuthTokens, String> {
+        info!("Refreshing {} access token", self.config.name);
+
+        let mut params = self.client_params();
+        if !client_id.is_empty() {
+            params.insert("client_id", client_id);
+        }
+        if !client_secret.is_empty() {
+            params.insert("client_secret", client_secret);
+        }
+        params.insert("refresh_token", refresh_token);
+        params.insert("grant_type", "refresh_token");
+
+        let response = post_form(&self.config.token_url, &params).await?;
+        parse_token_response(&response, None)
+    }
+
+    async fn revoke_token(&self, token: &str) -> Result<(), String> {
+        let Some(url) = &self.config.revoke_url else {
+            return Ok(());
+        };
+        info!("Revoking token at {}", self.config.name);
+
+        let mut params = self.client_params();
+        params.insert("token", token);
+        post_form(url, &params).await.map(|_| ())
+    }
+
+    fn default_scopes(&self) -> Vec<String> {
+        self.config.scopes.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_providers_are_valid() {
+        let providers = builtin_providers();
+        let names: Vec<&str> = providers.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["github", "microsoft", "slack", "linear", "atlassian"]);
+        for provider in &providers {
+            provider.validate().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_authorize_url() {
+        let slack = builtin_providers().into_iter().find(|p| p.name == "slack").unwrap();
+        let provider = GenericOAuth2Provider::new(slack, "cid".into(), "secret".into());
+        let url = provider.authorize_url(&provider.default_scopes(), "st", "chal", "http://127.0.0.1:9/");
+        assert!(url.starts_with("https://slack.com/oauth/v2/authorize?client_id=cid&"));
+        assert!(url.contains("scope=channels%3Aread%2Cchat%3Awrite"));
+        assert!(!url.contains("code_challenge"));
+
+        let mut config = OAuth2ProviderConfig::preset("x", "X", "https://x.example/auth", "https://x.example/token");
+        config.authorize_params.insert("audience".into(), "api".into());
+        let provider = GenericOAuth2Provider::new(config, "cid".into(), String::new());
+        let url = provider.authorize_url(&[], "st", "chal", "http://127.0.0.1:9/");
+        assert!(url.contains("code_challenge=chal&code_challenge_method=S256"));
+        assert!(url.contains("audience=api"));
+        assert!(!url.contains("scope="));
+    }
+
+    #[test]
+    fn test_config_from_json() {
+        let config: OAuth2ProviderConfig = serde_json::from_value(serde_json::json!({
+            "name": "gitlab",
+            "authorizeUrl": "https://gitlab.com/oauth/authorize",
+            "tokenUrl": "https://gitlab.com/oauth/token"
+        }))
+        .unwrap();
+        assert!(config.pkce);
+        assert_eq!(config.client_credential_label(), "gitlab");
+        assert!(config.validate().is_ok());
+
+        let mut bad = config.clone();
+        bad.userinfo_url = Some("http://gitlab.com/api/v4/user".into());
+        assert!(bad.validate().is_err());
+        bad.userinfo_url = None;
+        bad.token_url = "http://gitlab.com/oauth/token".into();
+        assert!(bad.validate().is_err());
+        bad.name = "google".into();
+        assert!(bad.validate().is_err());
+    }
+}
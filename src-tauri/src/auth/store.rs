@@ -2,19 +2,34 @@
 //!
 //! Unified encrypted credential store at `~/.tairseach/credentials.enc.json`.
 //! Schema metadata (no secrets) at `~/.tairseach/credentials.schema.json`.
-//! Master key derived from machine identity (no Keychain prompts).
+//! Writes are atomic (temp file + fsync + rename), and the last few versions
+//! of the credentials file are kept in `backups/`. Every change is a
+//! read-modify-write under an advisory lock: if another process wrote the
+//! files since this one last saw them, they are reloaded before the change
+//! is applied, so neither side's writes are lost.
+//!
+//! Entries are encrypted with data keys from a keyring stored in the
+//! credentials file, each wrapped by the master key. Every entry records the
+//! ID of its data key, so `rekey` can start a new key and move entries over
+//! lazily as they are read.
+//! Master key from a pluggable source (see `master_key`), machine identity by
+//! default (no Keychain prompts).
 
 use async_trait::async_trait;
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 use tracing::{info, warn};
 use zeroize::Zeroizing;
 
 use super::credential_types::CredentialTypeRegistry;
+use crate::common::{write_atomic, FileLock};
 use super::crypto;
+use super::master_key::{self, MasterKeySource};
 use super::{AccountInfo, TokenRecord};
 
 // ── Public Types ────────────────────────────────────────────────────────────
@@ -29,6 +44,26 @@ pub struct CredentialMetadata {
     pub provider: String,
     pub added: String,
     pub last_refreshed: Option<String>,
+    /// When the secret stops working (RFC 3339)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<String>,
+    /// When the secret is due for rotation (RFC 3339)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rotate_after: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rotated_at: Option<String>,
+    /// The pre-rotation secret stays readable until then (RFC 3339)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_valid_until: Option<String>,
+}
+
+/// A credential past or near its expiry or rotation deadline
+#[derive(Debug, Clone, Serialize)]
+pub struct ExpiringCredential {
+    #[serde(flatten)]
+    pub credential: CredentialMetadata,
+    /// `expired`, `expiring` or `rotation_due`
+    pub status: &'static str,
 }
 
 /// Trait for resolving credentials from 1Password
@@ -41,15 +76,121 @@ pub trait OnePasswordResolver: Send + Sync {
     ) -> Result<HashMap<String, String>, String>;
 }
 
+/// A decrypted entry with its metadata, as carried in export bundles
+#[derive(Serialize, Deserialize)]
+pub struct PortableEntry {
+    key: String,
+    schema: SchemaEntry,
+    /// Decrypted entry JSON (a `TokenRecord` or a field map)
+    secret: String,
+}
+
+impl PortableEntry {
+    /// `{type, label}` of each entry, for reporting what was exported
+    pub fn summaries(entries: &[PortableEntry]) -> Vec<serde_json::Value> {
+        entries
+            .iter()
+            .map(|e| serde_json::json!({ "type": e.schema.cred_type, "label": e.schema.account }))
+            .collect()
+    }
+}
+
+impl Drop for PortableEntry {
+    fn drop(&mut self) {
+        use zeroize::Zeroize;
+        self.secret.zeroize();
+    }
+}
+
+/// What to do when an imported entry already exists with a different secret
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConflictPolicy {
+    #[default]
+    Skip,
+    Overwrite,
+    /// Import under a new label (`<label>-imported`, `-imported-2`, ...)
+    Rename,
+}
+
+/// Keyring summary (no key material)
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyringStatus {
+    pub current_key: Option<String>,
+    pub keys: Vec<KeyInfo>,
+    /// Entries not yet under the current key (re-encrypted as they are read)
+    pub pending: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyInfo {
+    pub id: String,
+    pub created: String,
+    /// Entries encrypted with this key
+    pub entries: usize,
+}
+
+/// Result of [`TokenStore::verify`]
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifyReport {
+    /// Nothing is wrong (or everything wrong was repaired)
+    pub healthy: bool,
+    pub entries: usize,
+    /// The credentials file on disk parses and matches the in-memory store
+    pub file_ok: bool,
+    /// Entries that don't decrypt under the master key or aren't valid JSON
+    pub corrupt: Vec<String>,
+    /// Schema entries whose credential is gone
+    pub orphan_schema: Vec<String>,
+    /// Readable backups available for recovery
+    pub backups: usize,
+    /// Changes made by a repair
+    pub repaired: Vec<String>,
+    /// Corrupt entries no backup could restore
+    pub unrecoverable: Vec<String>,
+}
+
+/// Planned or applied outcome for one imported entry
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportOutcome {
+    #[serde(rename = "type")]
+    pub cred_type: String,
+    pub label: String,
+    /// `add`, `unchanged`, `skip`, `overwrite` or `rename`
+    pub action: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_label: Option<String>,
+}
+
 /// Credential file format version
 const SCHEMA_VERSION: u32 = 2;
 
+const CREDENTIALS_FILE: &str = "credentials.enc.json";
+const SCHEMA_FILE: &str = "credentials.schema.json";
+/// Advisory lock taken around every read-modify-write of the store files
+const LOCK_FILE: &str = "credentials.lock";
+/// Rolling copies of the credentials file, newest first (`backups/<file>.1`)
+const BACKUP_COUNT: usize = 5;
+/// How long to wait for another process holding the store lock
+const LOCK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Namespace for pre-rotation secrets kept during a grace window
+const PREVIOUS_PREFIX: &str = "_previous";
+
 // ── File Formats ────────────────────────────────────────────────────────────
 
 /// Encrypted credential entry
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 struct CredentialEntry {
     encrypted: bool,
+    /// Data key this entry is encrypted with. Absent on entries written before
+    /// key versioning, which are encrypted directly with the master key.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    key_id: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     algorithm: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -59,10 +200,25 @@ struct CredentialEntry {
     data: String,
 }
 
+/// A data key, encrypted with the master key
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct WrappedKey {
+    id: String,
+    created: String,
+    /// Base64 of nonce || ciphertext+tag
+    data: String,
+}
+
 /// Credentials file (encrypted)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct CredentialsFile {
     version: u32,
+    /// Data key used for new writes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    current_key: Option<String>,
+    /// Current and older data keys still used by some entry
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    keyring: Vec<WrappedKey>,
     credentials: HashMap<String, CredentialEntry>,
 }
 
@@ -70,6 +226,8 @@ impl Default for CredentialsFile {
     fn default() -> Self {
         Self {
             version: SCHEMA_VERSION,
+            current_key: None,
+            keyring: Vec::new(),
             credentials: HashMap::new(),
         }
     }
@@ -86,6 +244,30 @@ struct SchemaEntry {
     added: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     last_refreshed: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    expires_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    rotate_after: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    rotated_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    previous_valid_until: Option<String>,
+}
+
+impl SchemaEntry {
+    fn metadata(&self) -> CredentialMetadata {
+        CredentialMetadata {
+            provider: self.provider.clone(),
+            account: self.account.clone(),
+            cred_type: self.cred_type.clone(),
+            added: self.added.clone(),
+            last_refreshed: self.last_refreshed.clone(),
+            expires_at: self.expires_at.clone(),
+            rotate_after: self.rotate_after.clone(),
+            rotated_at: self.rotated_at.clone(),
+            previous_valid_until: self.previous_valid_until.clone(),
+        }
+    }
 }
 
 /// Schema file (unencrypted metadata)
@@ -104,6 +286,23 @@ impl Default for SchemaFile {
     }
 }
 
+/// SHA-256 of each store file (`None` if absent), to notice writes made by
+/// another process
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct DiskState {
+    credentials: Option<[u8; 32]>,
+    schema: Option<[u8; 32]>,
+}
+
+impl DiskState {
+    fn read(base_dir: &Path) -> Self {
+        Self {
+            credentials: file_digest(&base_dir.join(CREDENTIALS_FILE)),
+            schema: file_digest(&base_dir.join(SCHEMA_FILE)),
+        }
+    }
+}
+
 // ── TokenStore ──────────────────────────────────────────────────────────────
 
 pub struct TokenStore {
@@ -111,6 +310,14 @@ pub struct TokenStore {
     base_dir: PathBuf,
     /// Master encryption key (zeroized on drop)
     master_key: Zeroizing<[u8; 32]>,
+    /// Where the master key came from
+    key_source: MasterKeySource,
+    /// Unwrapped data keys by ID (zeroized on drop)
+    data_keys: HashMap<String, Zeroizing<[u8; 32]>>,
+    /// Entries read under an old key, waiting for `reencrypt_stale`
+    stale: std::sync::Mutex<HashSet<String>>,
+    /// The files as this process last read or wrote them
+    on_disk: std::sync::Mutex<DiskState>,
     /// Credentials file
     credentials: CredentialsFile,
     /// Schema file
@@ -138,47 +345,64 @@ impl TokenStore {
                 .map_err(|e| format!("Failed to set base dir permissions: {}", e))?;
         }
 
-        // Derive master key (v2)
-        let master_key = crypto::derive_master_key()?;
+        // Load master key from the configured source
+        let key_source = master_key::configured_source()?;
+        let master_key = key_source.provider()?.load_key()?;
+
+        // Load both files under the lock so a concurrent writer can't be
+        // caught between them
+        let lock = FileLock::exclusive(&base_dir.join(LOCK_FILE), LOCK_TIMEOUT).await?;
 
-        // Load or initialize credentials file
-        let cred_path = base_dir.join("credentials.enc.json");
+        // Load or initialize credentials file, falling back to the newest
+        // readable backup if it is damaged
+        let cred_path = base_dir.join(CREDENTIALS_FILE);
+        let mut restored_from_backup = false;
         let credentials = if cred_path.exists() {
-            let data = fs::read_to_string(&cred_path)
-                .map_err(|e| format!("Failed to read credentials file: {}", e))?;
-            serde_json::from_str(&data)
-                .map_err(|e| format!("Failed to parse credentials file: {}", e))?
+            match read_credentials_file(&cred_path) {
+                Ok(credentials) => credentials,
+                Err(e) => {
+                    let (backup, credentials) = read_backups(&base_dir)
+                        .into_iter()
+                        .find(|(_, file)| backup_is_readable(&master_key, file))
+                        .ok_or_else(|| format!("{} (and no readable backup)", e))?;
+                    warn!("{}; restoring from backup {:?}", e, backup);
+                    quarantine(&base_dir, &cred_path)?;
+                    restored_from_backup = true;
+                    credentials
+                }
+            }
         } else {
             CredentialsFile::default()
         };
 
-        // Load or initialize schema file
-        let schema_path = base_dir.join("credentials.schema.json");
-        let schema = if schema_path.exists() {
-            let data = fs::read_to_string(&schema_path)
-                .map_err(|e| format!("Failed to read schema file: {}", e))?;
-            serde_json::from_str(&data)
-                .map_err(|e| format!("Failed to parse schema file: {}", e))?
-        } else {
-            SchemaFile::default()
-        };
+        let schema = read_schema_file(&base_dir.join(SCHEMA_FILE))?;
+        let on_disk = DiskState::read(&base_dir);
+        drop(lock);
 
         let mut store = Self {
             base_dir,
-            master_key: Zeroizing::new(master_key),
+            master_key,
+            key_source,
+            data_keys: HashMap::new(),
+            stale: std::sync::Mutex::new(HashSet::new()),
+            on_disk: std::sync::Mutex::new(on_disk),
             credentials,
             schema,
             credential_types: CredentialTypeRegistry::new(),
             onepassword_cache: HashMap::new(),
         };
 
+        store.unlock()?;
+        let key_created = store.ensure_current_key()?;
+
         // Migrate from v1 if old auth/ directory exists
         store.migrate_from_v1().await?;
 
         // Auto-encrypt any plaintext entries
         let needs_flush = store.auto_encrypt_plaintext()?;
-        if needs_flush {
-            store.flush_credentials()?;
+        if needs_flush || restored_from_backup || key_created {
+            let lock = store.begin().await?;
+            store.flush_credentials(&lock)?;
         }
 
         info!(
@@ -222,22 +446,9 @@ impl TokenStore {
             return Err("Credential is not encrypted (should not happen after auto-encrypt)".to_string());
         }
 
-        // Decode base64 fields
-        let iv = BASE64.decode(entry.iv.as_ref().ok_or("Missing IV")?)
-            .map_err(|e| format!("Invalid IV base64: {}", e))?;
-        let tag = BASE64.decode(entry.tag.as_ref().ok_or("Missing tag")?)
-            .map_err(|e| format!("Invalid tag base64: {}", e))?;
-        let ciphertext = BASE64.decode(&entry.data)
-            .map_err(|e| format!("Invalid ciphertext base64: {}", e))?;
-
-        // Reconstruct the format expected by decrypt(): nonce || ciphertext+tag
-        let mut encrypted_blob = Vec::with_capacity(iv.len() + ciphertext.len() + tag.len());
-        encrypted_blob.extend_from_slice(&iv);
-        encrypted_blob.extend_from_slice(&ciphertext);
-        encrypted_blob.extend_from_slice(&tag);
-
         // Decrypt
-        let decrypted = Zeroizing::new(crypto::decrypt(&self.master_key, &encrypted_blob)?);
+        let decrypted = self.decrypt_entry(entry)?;
+        self.note_if_stale(&key, entry);
 
         // Parse JSON
         let record: TokenRecord = serde_json::from_slice(&*decrypted)
@@ -247,7 +458,8 @@ impl TokenStore {
     }
 
     /// Save (create or update) an encrypted token record.
-    pub fn save_token(&mut self, record: &TokenRecord) -> Result<(), String> {
+    pub async fn save_token(&mut self, record: &TokenRecord) -> Result<(), String> {
+        let lock = self.begin().await?;
         let key = credential_key(&record.provider, &record.account);
 
         // Serialize token to JSON
@@ -255,7 +467,7 @@ impl TokenStore {
             .map_err(|e| format!("Failed to serialize token: {}", e))?;
 
         // Encrypt and store
-        let entry = encrypt_to_entry(&self.master_key, &json)?;
+        let entry = self.encrypt(&json)?;
         self.credentials.credentials.insert(key.clone(), entry);
 
         // Update schema
@@ -272,19 +484,20 @@ impl TokenStore {
                 .map(|e| e.added.clone())
                 .unwrap_or_else(|| now.clone()),
             last_refreshed: Some(now),
+            expires_at: None,
+            rotate_after: None,
+            rotated_at: None,
+            previous_valid_until: None,
         };
 
         self.schema.entries.insert(key, schema_entry);
 
-        // Flush both files
-        self.flush_credentials()?;
-        self.flush_schema()?;
-
-        Ok(())
+        self.write_files(&lock)
     }
 
     /// Delete a token record.
-    pub fn delete_token(&mut self, provider: &str, account: &str) -> Result<(), String> {
+    pub async fn delete_token(&mut self, provider: &str, account: &str) -> Result<(), String> {
+        let lock = self.begin().await?;
         let key = credential_key(provider, account);
 
         if self.credentials.credentials.remove(&key).is_none() {
@@ -293,10 +506,7 @@ impl TokenStore {
 
         self.schema.entries.remove(&key);
 
-        self.flush_credentials()?;
-        self.flush_schema()?;
-
-        Ok(())
+        self.write_files(&lock)
     }
 
     /// Load the gog passphrase from encrypted storage.
@@ -310,7 +520,7 @@ impl TokenStore {
     }
 
     /// Save the gog passphrase as an encrypted credential entry.
-    pub fn save_gog_passphrase(&mut self, passphrase: &str) -> Result<(), String> {
+    pub async fn save_gog_passphrase(&mut self, passphrase: &str) -> Result<(), String> {
         // Store as a synthetic TokenRecord under _internal:gog_passphrase
         let record = super::TokenRecord {
             provider: "_internal".to_string(),
@@ -326,7 +536,7 @@ impl TokenStore {
             last_refreshed: String::new(),
         };
 
-        self.save_token(&record)?;
+        self.save_token(&record).await?;
         info!("Saved gog passphrase to encrypted credential store");
         Ok(())
     }
@@ -334,7 +544,7 @@ impl TokenStore {
     // ── Generic Credential Methods ─────────────────────────────────────────
 
     /// Store a generic credential (non-OAuth)
-    pub fn store_credential(
+    pub async fn store_credential(
         &mut self,
         provider: &str,
         account: &str,
@@ -347,6 +557,7 @@ impl TokenStore {
             schema.validate(&fields)?;
         }
 
+        let lock = self.begin().await?;
         let key = credential_key(provider, account);
 
         // Serialize fields to JSON
@@ -354,30 +565,29 @@ impl TokenStore {
             .map_err(|e| format!("Failed to serialize credential: {}", e))?;
 
         // Encrypt and store
-        let entry = encrypt_to_entry(&self.master_key, &json)?;
+        let entry = self.encrypt(&json)?;
         self.credentials.credentials.insert(key.clone(), entry);
 
-        // Update schema
+        // Update schema, keeping any expiry/rotation metadata
         let now = chrono::Utc::now().to_rfc3339();
+        let existing = self.schema.entries.get(&key);
         let schema_entry = SchemaEntry {
             provider: provider.to_string(),
             account: label.unwrap_or(account).to_string(),
             cred_type: cred_type.to_string(),
             scopes: vec![], // Not applicable for generic credentials
-            added: self
-                .schema
-                .entries
-                .get(&key)
+            added: existing
                 .map(|e| e.added.clone())
                 .unwrap_or_else(|| now.clone()),
             last_refreshed: Some(now),
+            expires_at: existing.and_then(|e| e.expires_at.clone()),
+            rotate_after: existing.and_then(|e| e.rotate_after.clone()),
+            rotated_at: existing.and_then(|e| e.rotated_at.clone()),
+            previous_valid_until: existing.and_then(|e| e.previous_valid_until.clone()),
         };
 
         self.schema.entries.insert(key, schema_entry);
-
-        // Flush both files
-        self.flush_credentials()?;
-        self.flush_schema()?;
+        self.write_files(&lock)?;
 
         info!("Stored credential {}:{} (type: {})", provider, account, cred_type);
         Ok(())
@@ -400,20 +610,8 @@ impl TokenStore {
             return Err("Credential is not encrypted".to_string());
         }
 
-        // Decode and decrypt (same as get_token)
-        let iv = BASE64.decode(entry.iv.as_ref().ok_or("Missing IV")?)
-            .map_err(|e| format!("Invalid IV base64: {}", e))?;
-        let tag = BASE64.decode(entry.tag.as_ref().ok_or("Missing tag")?)
-            .map_err(|e| format!("Invalid tag base64: {}", e))?;
-        let ciphertext = BASE64.decode(&entry.data)
-            .map_err(|e| format!("Invalid ciphertext base64: {}", e))?;
-
-        let mut encrypted_blob = Vec::with_capacity(iv.len() + ciphertext.len() + tag.len());
-        encrypted_blob.extend_from_slice(&iv);
-        encrypted_blob.extend_from_slice(&ciphertext);
-        encrypted_blob.extend_from_slice(&tag);
-
-        let decrypted = Zeroizing::new(crypto::decrypt(&self.master_key, &encrypted_blob)?);
+        let decrypted = self.decrypt_entry(entry)?;
+        self.note_if_stale(&key, entry);
 
         // Try parsing as TokenRecord first (backward compat), then as generic credential
         if let Ok(token) = serde_json::from_slice::<TokenRecord>(&*decrypted) {
@@ -437,21 +635,353 @@ impl TokenStore {
     pub fn list_credentials(&self) -> Vec<CredentialMetadata> {
         self.schema
             .entries
-            .iter()
-            .map(|(_key, entry)| CredentialMetadata {
-                provider: entry.provider.clone(),
-                account: entry.account.clone(),
-                cred_type: entry.cred_type.clone(),
-                added: entry.added.clone(),
-                last_refreshed: entry.last_refreshed.clone(),
+            .values()
+            .map(SchemaEntry::metadata)
+            .collect()
+    }
+
+    /// Delete a credential (and any pre-rotation secret)
+    pub async fn delete_credential(&mut self, provider: &str, account: &str) -> Result<(), String> {
+        let lock = self.begin().await?;
+        let key = credential_key(provider, account);
+
+        if self.credentials.credentials.remove(&key).is_none() {
+            return Err(format!("No credential found for {}:{}", provider, account));
+        }
+        self.credentials.credentials.remove(&previous_key(&key));
+        self.schema.entries.remove(&key);
+
+        self.write_files(&lock)
+    }
+
+    // ── Export & Import ────────────────────────────────────────────────────
+
+    /// Sorted entry keys matching `selector`: a `provider:account` key or a
+    /// bare provider (every account of that provider). Internal entries are
+    /// never selected.
+    pub fn select_keys(&self, selector: &str) -> Vec<String> {
+        let mut keys: Vec<String> = self
+            .schema
+            .entries
+            .keys()
+            .filter(|key| !is_internal_key(key))
+            .filter(|key| {
+                *key == selector
+                    || key.strip_prefix(selector).is_some_and(|rest| rest.starts_with(':'))
+            })
+            .cloned()
+            .collect();
+        keys.sort();
+        keys
+    }
+
+    /// Sorted keys of every stored entry except internal ones
+    pub fn all_keys(&self) -> Vec<String> {
+        let mut keys: Vec<String> = self
+            .schema
+            .entries
+            .keys()
+            .filter(|key| !is_internal_key(key))
+            .cloned()
+            .collect();
+        keys.sort();
+        keys
+    }
+
+    /// Decrypt the entries under `keys` for export
+    pub fn export_entries(&self, keys: &[String]) -> Result<Vec<PortableEntry>, String> {
+        keys.iter()
+            .map(|key| {
+                let entry = self
+                    .credentials
+                    .credentials
+                    .get(key)
+                    .ok_or_else(|| format!("Credential {} has metadata but no secret", key))?;
+                let plaintext = self.decrypt_entry(entry)?;
+                let secret = String::from_utf8(plaintext.to_vec())
+                    .map_err(|_| format!("Credential {} is not valid UTF-8", key))?;
+                Ok(PortableEntry {
+                    key: key.clone(),
+                    schema: self.schema.entries[key].clone(),
+                    secret,
+                })
             })
             .collect()
     }
 
-    /// Delete a credential
-    pub fn delete_credential(&mut self, provider: &str, account: &str) -> Result<(), String> {
-        // Alias to delete_token for now
-        self.delete_token(provider, account)
+    /// Plan (`dry_run`) or apply an import. Entries whose secret matches the
+    /// stored one are left alone; other existing entries follow `conflict`.
+    /// Every key the import would write (including renamed ones) must pass
+    /// `authorize`, and internal keys are refused, before anything changes.
+    /// Applied imports are re-encrypted under this store's master key and
+    /// written in one flush.
+    pub async fn import_entries(
+        &mut self,
+        entries: Vec<PortableEntry>,
+        conflict: ConflictPolicy,
+        dry_run: bool,
+        authorize: impl Fn(&str) -> Result<(), String>,
+    ) -> Result<Vec<ImportOutcome>, String> {
+        // Plan against the files as they are now, so the plan still holds
+        // when it is applied under the same lock
+        let lock = self.begin().await?;
+        let mut outcomes = Vec::with_capacity(entries.len());
+        let mut writes = Vec::new();
+
+        for entry in entries {
+            if is_internal_key(&entry.key) {
+                return Err(format!("Bundle entry {} is internal and can't be imported", entry.key));
+            }
+            let (prefix, label) = entry
+                .key
+                .split_once(':')
+                .map(|(p, l)| (p.to_string(), l.to_string()))
+                .ok_or_else(|| format!("Invalid credential key in bundle: {}", entry.key))?;
+            authorize(&entry.key)?;
+
+            let existing = match self.credentials.credentials.get(&entry.key) {
+                Some(e) => Some(self.decrypt_entry(e)?),
+                None => None,
+            };
+            let (action, target_label) = match existing {
+                None => ("add", label.clone()),
+                Some(current) if current.as_slice() == entry.secret.as_bytes() => ("unchanged", label.clone()),
+                Some(_) => match conflict {
+                    ConflictPolicy::Skip => ("skip", label.clone()),
+                    ConflictPolicy::Overwrite => ("overwrite", label.clone()),
+                    ConflictPolicy::Rename => ("rename", self.free_label(&prefix, &label)),
+                },
+            };
+            let key = credential_key(&prefix, &target_label);
+            if action == "rename" {
+                authorize(&key)?;
+            }
+            outcomes.push(ImportOutcome {
+                cred_type: entry.schema.cred_type.clone(),
+                label: entry.schema.account.clone(),
+                action,
+                new_label: (action == "rename").then(|| target_label.clone()),
+            });
+            if !matches!(action, "skip" | "unchanged") {
+                writes.push((entry, key, target_label, action == "rename"));
+            }
+        }
+        if dry_run || writes.is_empty() {
+            return Ok(outcomes);
+        }
+
+        for (mut entry, key, target_label, renamed) in writes {
+            if renamed {
+                entry.schema.account = target_label.clone();
+                // OAuth tokens carry their account name inside the record
+                if let Ok(mut record) = serde_json::from_str::<TokenRecord>(&entry.secret) {
+                    record.account = target_label;
+                    let renamed = serde_json::to_string(&record)
+                        .map_err(|e| format!("Failed to serialize token: {}", e))?;
+                    zeroize::Zeroize::zeroize(&mut std::mem::replace(&mut entry.secret, renamed));
+                }
+            }
+            let encrypted = self.encrypt(entry.secret.as_bytes())?;
+            self.credentials.credentials.insert(key.clone(), encrypted);
+            self.schema.entries.insert(key, entry.schema.clone());
+        }
+        self.write_files(&lock)?;
+        Ok(outcomes)
+    }
+
+    /// First unused `<label>-imported[-N]` for a credential prefix.
+    fn free_label(&self, prefix: &str, label: &str) -> String {
+        let mut candidate = format!("{}-imported", label);
+        let mut n = 2;
+        while self
+            .credentials
+            .credentials
+            .contains_key(&credential_key(prefix, &candidate))
+        {
+            candidate = format!("{}-imported-{}", label, n);
+            n += 1;
+        }
+        candidate
+    }
+
+    // ── Expiry & Rotation ──────────────────────────────────────────────────
+
+    /// Set or clear a credential's expiry and rotation deadline (RFC 3339).
+    pub async fn set_credential_lifecycle(
+        &mut self,
+        provider: &str,
+        account: &str,
+        expires_at: Option<String>,
+        rotate_after: Option<String>,
+    ) -> Result<(), String> {
+        validate_timestamp("expires_at", expires_at.as_deref())?;
+        validate_timestamp("rotate_after", rotate_after.as_deref())?;
+
+        let lock = self.begin().await?;
+        let key = credential_key(provider, account);
+        let entry = self
+            .schema
+            .entries
+            .get_mut(&key)
+            .ok_or_else(|| format!("No credential found for {}:{}", provider, account))?;
+        entry.expires_at = expires_at;
+        entry.rotate_after = rotate_after;
+        self.flush_schema(&lock)
+    }
+
+    /// Swap in a new secret for a static credential. `fields` are merged over
+    /// the current ones (so rotating only `api_token` keeps `host`/`email`).
+    /// The old secret stays readable via [`get_previous_credential`] for
+    /// `grace`; a zero grace drops it immediately. Both files are written
+    /// together and the in-memory state is restored if either write fails.
+    ///
+    /// [`get_previous_credential`]: Self::get_previous_credential
+    pub async fn rotate_credential(
+        &mut self,
+        provider: &str,
+        account: &str,
+        fields: HashMap<String, String>,
+        grace: chrono::Duration,
+        expires_at: Option<String>,
+        rotate_after: Option<String>,
+    ) -> Result<CredentialMetadata, String> {
+        validate_timestamp("expires_at", expires_at.as_deref())?;
+        validate_timestamp("rotate_after", rotate_after.as_deref())?;
+
+        let lock = self.begin().await?;
+        let key = credential_key(provider, account);
+        let old_schema = self
+            .schema
+            .entries
+            .get(&key)
+            .cloned()
+            .ok_or_else(|| format!("No credential found for {}:{}", provider, account))?;
+        if old_schema.cred_type == "oauth2" {
+            return Err("OAuth tokens are rotated by refreshing, not auth.credentials.rotate".to_string());
+        }
+
+        let mut merged = self
+            .get_credential(provider, account)?
+            .ok_or_else(|| format!("No credential found for {}:{}", provider, account))?;
+        merged.extend(fields);
+        if let Some(schema) = self.credential_types.get(&old_schema.cred_type) {
+            schema.validate(&merged)?;
+        }
+
+        let json = Zeroizing::new(
+            serde_json::to_vec(&merged)
+                .map_err(|e| format!("Failed to serialize credential: {}", e))?,
+        );
+        let new_entry = self.encrypt(&json)?;
+
+        let prev_key = previous_key(&key);
+        let old_entry = self.credentials.credentials.get(&key).cloned();
+        let old_previous = self.credentials.credentials.get(&prev_key).cloned();
+
+        let now = chrono::Utc::now();
+        let keep_previous = grace > chrono::Duration::zero();
+        match (&old_entry, keep_previous) {
+            (Some(entry), true) => {
+                self.credentials.credentials.insert(prev_key.clone(), entry.clone());
+            }
+            _ => {
+                self.credentials.credentials.remove(&prev_key);
+            }
+        }
+        self.credentials.credentials.insert(key.clone(), new_entry);
+
+        let mut schema_entry = old_schema.clone();
+        schema_entry.last_refreshed = Some(now.to_rfc3339());
+        schema_entry.rotated_at = Some(now.to_rfc3339());
+        schema_entry.expires_at = expires_at;
+        schema_entry.rotate_after = rotate_after;
+        schema_entry.previous_valid_until = keep_previous.then(|| (now + grace).to_rfc3339());
+        self.schema.entries.insert(key.clone(), schema_entry.clone());
+
+        if let Err(e) = self.write_files(&lock) {
+            // Put back what was there so memory matches the files we kept
+            match old_entry {
+                Some(entry) => self.credentials.credentials.insert(key.clone(), entry),
+                None => self.credentials.credentials.remove(&key),
+            };
+            match old_previous {
+                Some(entry) => self.credentials.credentials.insert(prev_key, entry),
+                None => self.credentials.credentials.remove(&prev_key),
+            };
+            self.schema.entries.insert(key, old_schema);
+            let _ = self.write_files(&lock);
+            return Err(e);
+        }
+
+        info!("Rotated credential {}:{}", provider, account);
+        Ok(schema_entry.metadata())
+    }
+
+    /// The pre-rotation secret, while its grace window lasts.
+    pub fn get_previous_credential(
+        &self,
+        provider: &str,
+        account: &str,
+    ) -> Result<Option<HashMap<String, String>>, String> {
+        let key = credential_key(provider, account);
+        let valid = self
+            .schema
+            .entries
+            .get(&key)
+            .and_then(|e| e.previous_valid_until.as_deref())
+            .is_some_and(|until| !is_past(until, chrono::Utc::now()));
+        if !valid {
+            return Ok(None);
+        }
+        // previous_key(key) == credential_key(PREVIOUS_PREFIX, key)
+        self.get_credential(PREVIOUS_PREFIX, &key)
+    }
+
+    /// Drop pre-rotation secrets whose grace window has ended. Returns how
+    /// many were removed.
+    pub async fn purge_expired_previous(&mut self) -> Result<usize, String> {
+        let lock = self.begin().await?;
+        let now = chrono::Utc::now();
+        let expired: Vec<String> = self
+            .schema
+            .entries
+            .iter()
+            .filter(|(_, e)| e.previous_valid_until.as_deref().is_some_and(|u| is_past(u, now)))
+            .map(|(k, _)| k.clone())
+            .collect();
+        for key in &expired {
+            self.credentials.credentials.remove(&previous_key(key));
+            if let Some(entry) = self.schema.entries.get_mut(key) {
+                entry.previous_valid_until = None;
+            }
+        }
+        if !expired.is_empty() {
+            self.write_files(&lock)?;
+        }
+        Ok(expired.len())
+    }
+
+    /// Credentials that have expired, expire within `within`, or are past
+    /// their rotation deadline.
+    pub fn expiring_credentials(&self, within: chrono::Duration) -> Vec<ExpiringCredential> {
+        let now = chrono::Utc::now();
+        let mut expiring: Vec<ExpiringCredential> = self
+            .schema
+            .entries
+            .values()
+            .filter_map(|e| {
+                let status = credential_status(e, now, within)?;
+                Some(ExpiringCredential {
+                    credential: e.metadata(),
+                    status,
+                })
+            })
+            .collect();
+        expiring.sort_by(|a, b| {
+            (&a.credential.cred_type, &a.credential.account)
+                .cmp(&(&b.credential.cred_type, &b.credential.account))
+        });
+        expiring
     }
 
     /// Resolve credential with fallback chain:
@@ -488,7 +1018,8 @@ impl TokenStore {
                     info!("Resolved credential {}:{} from 1Password", provider, account_key);
                     
                     // Cache it locally
-                    self.store_credential(provider, account_key, provider, fields.clone(), None)?;
+                    self.store_credential(provider, account_key, provider, fields.clone(), None)
+                        .await?;
                     self.onepassword_cache.insert(cache_key, fields.clone());
                     
                     return Ok(fields);
@@ -508,6 +1039,261 @@ impl TokenStore {
         ))
     }
 
+    /// Write the credential and schema files from the in-memory state.
+    /// Refuses if another process changed them since this one last read or
+    /// wrote them, rather than overwriting its changes.
+    pub async fn flush(&self) -> Result<(), String> {
+        let lock = self.lock().await?;
+        if DiskState::read(&self.base_dir) != self.disk_state() {
+            return Err("Token store files were changed by another process; not overwriting them".to_string());
+        }
+        self.write_files(&lock)
+    }
+
+    /// Check the store for damage: the on-disk credentials file must parse and
+    /// match memory, and every entry must decrypt to JSON. With `repair`,
+    /// corrupt entries are restored from the newest backup holding a good
+    /// copy, orphaned schema entries are dropped and the files are rewritten.
+    pub async fn verify(&mut self, repair: bool) -> Result<VerifyReport, String> {
+        // Pick up another process's writes; a file that no longer loads is
+        // what this reports on, so memory is kept as it is then
+        let lock = self.lock().await?;
+        if let Err(e) = self.reload_if_changed() {
+            warn!("{}", e);
+        }
+        let mut report = VerifyReport {
+            entries: self.credentials.credentials.len(),
+            ..Default::default()
+        };
+
+        let cred_path = self.base_dir.join(CREDENTIALS_FILE);
+        report.file_ok = match read_credentials_file(&cred_path) {
+            Ok(on_disk) => on_disk.credentials == self.credentials.credentials,
+            Err(_) => !cred_path.exists() && self.credentials.credentials.is_empty(),
+        };
+
+        let mut corrupt: Vec<String> = self
+            .credentials
+            .credentials
+            .iter()
+            .filter(|(_, entry)| entry.encrypted && !self.entry_is_readable(entry))
+            .map(|(key, _)| key.clone())
+            .collect();
+        corrupt.sort();
+        let mut orphans: Vec<String> = self
+            .schema
+            .entries
+            .keys()
+            .filter(|key| !self.credentials.credentials.contains_key(*key))
+            .cloned()
+            .collect();
+        orphans.sort();
+
+        let backups = read_backups(&self.base_dir);
+        report.backups = backups.len();
+
+        if repair {
+            // Each backup is read with its own keyring, since the data keys
+            // its entries use may have been retired since; restored entries
+            // are re-encrypted under the current key
+            let keyrings: Vec<_> = backups
+                .iter()
+                .map(|(_, file)| unwrap_keyring(&self.master_key, file))
+                .collect();
+            for key in &corrupt {
+                let good = backups.iter().zip(&keyrings).find_map(|((path, file), keys)| {
+                    let entry = file.credentials.get(key)?;
+                    let plaintext = decrypt_with(keys, &self.master_key, entry).ok()?;
+                    serde_json::from_slice::<serde_json::Value>(&plaintext).ok()?;
+                    Some((path, plaintext))
+                });
+                match good {
+                    Some((path, plaintext)) => {
+                        let entry = self.encrypt(&plaintext)?;
+                        self.credentials.credentials.insert(key.clone(), entry);
+                        report.repaired.push(format!(
+                            "restored {} from {}",
+                            key,
+                            path.file_name().unwrap_or_default().to_string_lossy()
+                        ));
+                    }
+                    None => report.unrecoverable.push(key.clone()),
+                }
+            }
+            for key in &orphans {
+                self.schema.entries.remove(key);
+                report.repaired.push(format!("removed orphaned schema entry {}", key));
+            }
+            if !report.file_ok || !report.repaired.is_empty() {
+                self.write_files(&lock)?;
+                report.repaired.push("rewrote store files".to_string());
+            }
+            report.healthy = report.unrecoverable.is_empty();
+        } else {
+            report.healthy = report.file_ok && corrupt.is_empty() && orphans.is_empty();
+        }
+
+        report.corrupt = corrupt;
+        report.orphan_schema = orphans;
+        if !report.healthy {
+            warn!(
+                "Token store verification: {} corrupt, {} orphaned, file ok: {}",
+                report.corrupt.len(),
+                report.orphan_schema.len(),
+                report.file_ok
+            );
+        }
+        Ok(report)
+    }
+
+    /// The master key source in use
+    pub fn key_source(&self) -> &MasterKeySource {
+        &self.key_source
+    }
+
+    /// Switch to the master key from `source`: every entry is re-encrypted
+    /// under a fresh data key wrapped by the new master key, and older data
+    /// keys are dropped, since the old master key could unwrap them. Nothing
+    /// changes unless every entry decrypts. Returns the number of entries
+    /// re-encrypted.
+    pub async fn change_master_key(&mut self, source: MasterKeySource) -> Result<usize, String> {
+        let lock = self.begin().await?;
+        let new_master = source.provider()?.load_key()?;
+        let (wrapped, data_key) = generate_data_key(&new_master)?;
+        let reencrypted = self.reencrypt_all(&wrapped.id, &data_key)?;
+        let count = reencrypted.len();
+
+        let old_file = self.credentials.clone();
+        self.credentials.credentials = reencrypted;
+        self.credentials.current_key = Some(wrapped.id.clone());
+        self.credentials.keyring = vec![wrapped.clone()];
+        if let Err(e) = self.flush_credentials(&lock) {
+            self.credentials = old_file;
+            return Err(e);
+        }
+        if let Err(e) = master_key::save_source(&source) {
+            // Put the file back under the old key so the next start can read it
+            self.credentials = old_file;
+            self.flush_credentials(&lock)?;
+            return Err(e);
+        }
+
+        self.master_key = new_master;
+        self.data_keys = HashMap::from([(wrapped.id, data_key)]);
+        self.reset_backups(&lock);
+        info!(
+            "Re-keyed {} credentials to master key source '{}'",
+            count,
+            source.provider()?.name()
+        );
+        self.key_source = source;
+        Ok(count)
+    }
+
+    /// Start a new data key for all future writes. With `reencrypt_all`,
+    /// every entry moves to it now and the old keys are dropped; otherwise
+    /// entries move as they are read and an old key is dropped once no entry
+    /// uses it.
+    pub async fn rekey(&mut self, reencrypt_all: bool) -> Result<KeyringStatus, String> {
+        let lock = self.begin().await?;
+        let (wrapped, data_key) = generate_data_key(&self.master_key)?;
+        let reencrypted = if reencrypt_all {
+            Some(self.reencrypt_all(&wrapped.id, &data_key)?)
+        } else {
+            None
+        };
+
+        let old_file = self.credentials.clone();
+        let previous = self.credentials.current_key.replace(wrapped.id.clone());
+        self.credentials.keyring.push(wrapped.clone());
+        if let Some(entries) = reencrypted {
+            self.credentials.credentials = entries;
+        }
+        self.data_keys.insert(wrapped.id.clone(), data_key);
+        self.prune_unused_keys();
+        if let Err(e) = self.flush_credentials(&lock) {
+            self.credentials = old_file;
+            self.data_keys.remove(&wrapped.id);
+            return Err(e);
+        }
+        if reencrypt_all {
+            self.reset_backups(&lock);
+        }
+
+        info!(
+            "Rotated data key {} -> {}{}",
+            previous.as_deref().unwrap_or("(master key)"),
+            wrapped.id,
+            if reencrypt_all { " (all entries re-encrypted)" } else { "" }
+        );
+        Ok(self.keyring_status())
+    }
+
+    /// Keyring summary: the current data key, each key's entry count and how
+    /// many entries still wait for re-encryption.
+    pub fn keyring_status(&self) -> KeyringStatus {
+        let current = self.credentials.current_key.clone();
+        let count = |id: &str| {
+            self.credentials
+                .credentials
+                .values()
+                .filter(|e| e.key_id.as_deref() == Some(id))
+                .count()
+        };
+        KeyringStatus {
+            keys: self
+                .credentials
+                .keyring
+                .iter()
+                .map(|k| KeyInfo { id: k.id.clone(), created: k.created.clone(), entries: count(&k.id) })
+                .collect(),
+            pending: self
+                .credentials
+                .credentials
+                .values()
+                .filter(|e| e.key_id != current)
+                .count(),
+            current_key: current,
+        }
+    }
+
+    /// Whether reads have found entries under an old key.
+    pub fn has_stale_entries(&self) -> bool {
+        self.stale.lock().map(|s| !s.is_empty()).unwrap_or(false)
+    }
+
+    /// Re-encrypt entries found under an old key on read, then drop keys no
+    /// entry uses any more. Returns how many entries moved.
+    pub async fn reencrypt_stale(&mut self) -> Result<usize, String> {
+        let keys: Vec<String> = match self.stale.lock() {
+            Ok(mut stale) => stale.drain().collect(),
+            Err(_) => return Ok(0),
+        };
+        if keys.is_empty() {
+            return Ok(0);
+        }
+        let lock = self.begin().await?;
+        let mut moved = 0;
+        for key in keys {
+            let Some(entry) = self.credentials.credentials.get(&key) else {
+                continue;
+            };
+            if !entry.encrypted || entry.key_id == self.credentials.current_key {
+                continue;
+            }
+            let plaintext = self.decrypt_entry(entry)?;
+            let new_entry = self.encrypt(&plaintext)?;
+            self.credentials.credentials.insert(key, new_entry);
+            moved += 1;
+        }
+        if moved > 0 {
+            self.prune_unused_keys();
+            self.flush_credentials(&lock)?;
+            info!("Re-encrypted {} credential(s) under the current data key", moved);
+        }
+        Ok(moved)
+    }
+
     /// Access the credential type registry
     pub fn credential_types(&self) -> &CredentialTypeRegistry {
         &self.credential_types
@@ -520,39 +1306,272 @@ impl TokenStore {
 
     // ── Internal ────────────────────────────────────────────────────────────
 
-    fn flush_credentials(&self) -> Result<(), String> {
-        let path = self.base_dir.join("credentials.enc.json");
-        let json = serde_json::to_string_pretty(&self.credentials)
-            .map_err(|e| format!("Failed to serialize credentials: {}", e))?;
-        fs::write(&path, &json)
-            .map_err(|e| format!("Failed to write credentials file: {}", e))?;
+    /// Unwrap the keyring with the master key. Fails early, with a pointer to
+    /// the key source, when the master key can't open the existing store
+    /// (e.g. the source changed outside `change_master_key`).
+    fn unlock(&mut self) -> Result<(), String> {
+        let wrong_key = |what: &str| {
+            format!(
+                "Master key from source '{}' cannot decrypt the token store ({}). \
+                 Restore the previous source in ~/.tairseach/master_key.json or {}",
+                self.key_source.provider().map(|p| p.name()).unwrap_or("unknown"),
+                what,
+                master_key::SOURCE_ENV
+            )
+        };
 
-        // Set restrictive file permissions (0600)
-        #[cfg(unix)]
+        for wrapped in &self.credentials.keyring {
+            let key = unwrap_data_key(&self.master_key, wrapped)
+                .map_err(|_| wrong_key(&format!("data key '{}'", wrapped.id)))?;
+            self.data_keys.insert(wrapped.id.clone(), key);
+        }
+
+        // Entries from before key versioning use the master key directly
+        if let Some((key, entry)) = self
+            .credentials
+            .credentials
+            .iter()
+            .find(|(_, e)| e.encrypted && e.key_id.is_none())
         {
-            use std::os::unix::fs::PermissionsExt;
-            fs::set_permissions(&path, fs::Permissions::from_mode(0o600))
-                .map_err(|e| format!("Failed to set credentials file permissions: {}", e))?;
+            self.decrypt_entry(entry)
+                .map_err(|_| wrong_key(&format!("entry '{}'", key)))?;
         }
+        Ok(())
+    }
+
+    /// Make sure there is a current data key, creating the first one for a
+    /// new or pre-versioning store. Returns true if one was created.
+    fn ensure_current_key(&mut self) -> Result<bool, String> {
+        let current = self.credentials.current_key.as_ref();
+        if current.is_some_and(|id| self.data_keys.contains_key(id)) {
+            return Ok(false);
+        }
+        let (wrapped, data_key) = generate_data_key(&self.master_key)?;
+        info!("Created data key {}", wrapped.id);
+        self.credentials.current_key = Some(wrapped.id.clone());
+        self.data_keys.insert(wrapped.id.clone(), data_key);
+        self.credentials.keyring.push(wrapped);
+        Ok(true)
+    }
+
+    /// Encrypt under the current data key.
+    fn encrypt(&self, plaintext: &[u8]) -> Result<CredentialEntry, String> {
+        let id = self
+            .credentials
+            .current_key
+            .as_ref()
+            .ok_or("Token store has no current data key")?;
+        let key = self
+            .data_keys
+            .get(id)
+            .ok_or_else(|| format!("Data key '{}' is not loaded", id))?;
+        let mut entry = encrypt_to_entry(key, plaintext)?;
+        entry.key_id = Some(id.clone());
+        Ok(entry)
+    }
+
+    /// Remember an entry read under an old key for `reencrypt_stale`.
+    fn note_if_stale(&self, key: &str, entry: &CredentialEntry) {
+        if entry.key_id != self.credentials.current_key {
+            if let Ok(mut stale) = self.stale.lock() {
+                stale.insert(key.to_string());
+            }
+        }
+    }
+
+    /// Decrypt every entry and re-encrypt it under `data_key`, without
+    /// touching the store. Fails if any entry doesn't decrypt.
+    fn reencrypt_all(
+        &self,
+        key_id: &str,
+        data_key: &[u8; 32],
+    ) -> Result<HashMap<String, CredentialEntry>, String> {
+        let mut reencrypted = HashMap::with_capacity(self.credentials.credentials.len());
+        for (key, entry) in &self.credentials.credentials {
+            let plaintext = if entry.encrypted {
+                self.decrypt_entry(entry)
+                    .map_err(|e| format!("Cannot re-key, failed to decrypt '{}': {}", key, e))?
+            } else {
+                Zeroizing::new(entry.data.as_bytes().to_vec())
+            };
+            let mut new_entry = encrypt_to_entry(data_key, &plaintext)?;
+            new_entry.key_id = Some(key_id.to_string());
+            reencrypted.insert(key.clone(), new_entry);
+        }
+        Ok(reencrypted)
+    }
+
+    /// Drop keyring entries that are neither current nor used by any entry.
+    fn prune_unused_keys(&mut self) {
+        let used: HashSet<&str> = self
+            .credentials
+            .credentials
+            .values()
+            .filter_map(|e| e.key_id.as_deref())
+            .collect();
+        let current = self.credentials.current_key.as_deref();
+        let (keep, drop): (Vec<_>, Vec<_>) = std::mem::take(&mut self.credentials.keyring)
+            .into_iter()
+            .partition(|k| Some(k.id.as_str()) == current || used.contains(k.id.as_str()));
+        for retired in drop {
+            info!("Retired data key {}", retired.id);
+            self.data_keys.remove(&retired.id);
+        }
+        self.credentials.keyring = keep;
+    }
+
+    /// Decrypt an entry's raw JSON with its data key (or the master key for
+    /// entries from before key versioning).
+    fn decrypt_entry(&self, entry: &CredentialEntry) -> Result<Zeroizing<Vec<u8>>, String> {
+        decrypt_with(&self.data_keys, &self.master_key, entry)
+    }
 
+    /// Whether an encrypted entry decrypts to valid JSON.
+    fn entry_is_readable(&self, entry: &CredentialEntry) -> bool {
+        self.decrypt_entry(entry)
+            .is_ok_and(|plaintext| serde_json::from_slice::<serde_json::Value>(&plaintext).is_ok())
+    }
+
+    async fn lock(&self) -> Result<FileLock, String> {
+        FileLock::exclusive(&self.base_dir.join(LOCK_FILE), LOCK_TIMEOUT).await
+    }
+
+    /// Take the store lock for a read-modify-write, first reloading the files
+    /// if another process wrote them since this one last did. Changes made
+    /// while the returned lock is held apply on top of what is on disk.
+    async fn begin(&mut self) -> Result<FileLock, String> {
+        let lock = self.lock().await?;
+        self.reload_if_changed()?;
+        Ok(lock)
+    }
+
+    /// Reload both files (call with the lock held) if they differ from what
+    /// this process last read or wrote.
+    fn reload_if_changed(&mut self) -> Result<(), String> {
+        let current = DiskState::read(&self.base_dir);
+        if current == self.disk_state() {
+            return Ok(());
+        }
+        info!("Token store files were changed by another process; reloading");
+
+        let cred_path = self.base_dir.join(CREDENTIALS_FILE);
+        let credentials = if cred_path.exists() {
+            read_credentials_file(&cred_path)?
+        } else {
+            CredentialsFile::default()
+        };
+        let schema = read_schema_file(&self.base_dir.join(SCHEMA_FILE))?;
+
+        let previous = (
+            std::mem::replace(&mut self.credentials, credentials),
+            std::mem::replace(&mut self.schema, schema),
+        );
+        let ready = self
+            .unlock()
+            .and_then(|_| self.ensure_current_key())
+            .and_then(|_| self.auto_encrypt_plaintext());
+        if let Err(e) = ready {
+            (self.credentials, self.schema) = previous;
+            return Err(e);
+        }
+        self.set_disk_state(|state| *state = current);
         Ok(())
     }
 
-    fn flush_schema(&self) -> Result<(), String> {
-        let path = self.base_dir.join("credentials.schema.json");
+    fn disk_state(&self) -> DiskState {
+        self.on_disk.lock().map(|s| *s).unwrap_or_default()
+    }
+
+    fn set_disk_state(&self, update: impl FnOnce(&mut DiskState)) {
+        if let Ok(mut state) = self.on_disk.lock() {
+            update(&mut state);
+        }
+    }
+
+    /// Write both files (call with the lock held).
+    fn write_files(&self, lock: &FileLock) -> Result<(), String> {
+        self.flush_credentials(lock)?;
+        self.flush_schema(lock)
+    }
+
+    fn flush_credentials(&self, _lock: &FileLock) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(&self.credentials)
+            .map_err(|e| format!("Failed to serialize credentials: {}", e))?;
+
+        if let Err(e) = self.rotate_backups() {
+            warn!("Failed to back up credentials file: {}", e);
+        }
+        // Owner-only (0600)
+        write_atomic(&self.base_dir.join(CREDENTIALS_FILE), json.as_bytes(), Some(0o600))?;
+        self.set_disk_state(|state| state.credentials = Some(Sha256::digest(json.as_bytes()).into()));
+        Ok(())
+    }
+
+    fn flush_schema(&self, _lock: &FileLock) -> Result<(), String> {
         let json = serde_json::to_string_pretty(&self.schema)
             .map_err(|e| format!("Failed to serialize schema: {}", e))?;
-        fs::write(&path, &json)
-            .map_err(|e| format!("Failed to write schema file: {}", e))?;
 
-        // Set readable permissions (0644)
+        // Readable metadata (0644)
+        write_atomic(&self.base_dir.join(SCHEMA_FILE), json.as_bytes(), Some(0o644))?;
+        self.set_disk_state(|state| state.schema = Some(Sha256::digest(json.as_bytes()).into()));
+        Ok(())
+    }
+
+    /// Replace every backup (and quarantined file) with a copy of the current
+    /// file, after a master key change or full rekey, so no backup still
+    /// holds data keys that were retired or wrapped by an old master key.
+    fn reset_backups(&self, _lock: &FileLock) {
+        if let Err(e) = self.remove_backups().and_then(|_| self.rotate_backups()) {
+            warn!("Failed to replace credentials backups after key change: {}", e);
+        }
+    }
+
+    /// Delete every backup and quarantined copy of the credentials file.
+    fn remove_backups(&self) -> Result<(), String> {
+        let dir = self.base_dir.join("backups");
+        if let Ok(entries) = fs::read_dir(&dir) {
+            for entry in entries.flatten() {
+                if entry.file_name().to_string_lossy().starts_with(CREDENTIALS_FILE) {
+                    fs::remove_file(entry.path())
+                        .map_err(|e| format!("Failed to remove backup {:?}: {}", entry.path(), e))?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Shift `backups/credentials.enc.json.N` up by one and copy the current
+    /// file to `.1`. A current file that doesn't parse is not backed up, so
+    /// damage can't push the good copies out.
+    fn rotate_backups(&self) -> Result<(), String> {
+        let current = self.base_dir.join(CREDENTIALS_FILE);
+        if !current.exists() {
+            return Ok(());
+        }
+        if let Err(e) = read_credentials_file(&current) {
+            warn!("Not backing up unreadable credentials file: {}", e);
+            return Ok(());
+        }
+
+        let dir = self.base_dir.join("backups");
+        fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {:?}: {}", dir, e))?;
         #[cfg(unix)]
         {
             use std::os::unix::fs::PermissionsExt;
-            fs::set_permissions(&path, fs::Permissions::from_mode(0o644))
-                .map_err(|e| format!("Failed to set schema file permissions: {}", e))?;
+            fs::set_permissions(&dir, fs::Permissions::from_mode(0o700))
+                .map_err(|e| format!("Failed to set backup dir permissions: {}", e))?;
         }
 
+        for n in (1..BACKUP_COUNT).rev() {
+            let from = backup_path(&self.base_dir, n);
+            if from.exists() {
+                fs::rename(&from, backup_path(&self.base_dir, n + 1))
+                    .map_err(|e| format!("Failed to rotate backup {:?}: {}", from, e))?;
+            }
+        }
+        // fs::copy carries over the 0600 mode
+        fs::copy(&current, backup_path(&self.base_dir, 1))
+            .map_err(|e| format!("Failed to copy credentials backup: {}", e))?;
         Ok(())
     }
 
@@ -568,7 +1587,7 @@ impl TokenStore {
             
             if !entry.encrypted {
                 info!("Auto-encrypting plaintext credential: {}", key);
-                let new_entry = encrypt_to_entry(&self.master_key, entry.data.as_bytes())?;
+                let new_entry = self.encrypt(entry.data.as_bytes())?;
                 self.credentials.credentials.insert(key, new_entry);
                 modified = true;
             }
@@ -590,7 +1609,7 @@ impl TokenStore {
 
         #[cfg(feature = "keychain-migration")]
         {
-            self.do_v1_migration(&old_auth_dir)
+            self.do_v1_migration(&old_auth_dir).await
         }
 
         #[cfg(not(feature = "keychain-migration"))]
@@ -601,7 +1620,7 @@ impl TokenStore {
     }
 
     #[cfg(feature = "keychain-migration")]
-    fn do_v1_migration(&mut self, old_auth_dir: &std::path::Path) -> Result<(), String> {
+    async fn do_v1_migration(&mut self, old_auth_dir: &std::path::Path) -> Result<(), String> {
         // Try to read old master key from Keychain
             let old_key = match crypto::read_keychain_master_key() {
                 Ok(key) => {
@@ -659,7 +1678,7 @@ impl TokenStore {
                                 );
                                 
                                 // Re-encrypt with new key and save
-                                self.save_token(&record)?;
+                                self.save_token(&record).await?;
                                 migrated_count += 1;
                             }
                             Err(e) => {
@@ -687,10 +1706,184 @@ impl TokenStore {
 // ── Helpers ─────────────────────────────────────────────────────────────────
 
 /// Build the credential key for a provider:account pair.
+/// A fresh random data key and its wrapped form under `master`.
+fn generate_data_key(master: &[u8; 32]) -> Result<(WrappedKey, Zeroizing<[u8; 32]>), String> {
+    use rand::RngCore;
+    let mut key = Zeroizing::new([0u8; 32]);
+    rand::rngs::OsRng.fill_bytes(key.as_mut());
+    let mut id = [0u8; 4];
+    rand::rngs::OsRng.fill_bytes(&mut id);
+
+    let wrapped = WrappedKey {
+        id: format!("k-{}", hex::encode(id)),
+        created: chrono::Utc::now().to_rfc3339(),
+        data: BASE64.encode(crypto::encrypt(master, key.as_ref())?),
+    };
+    Ok((wrapped, key))
+}
+
+fn unwrap_data_key(master: &[u8; 32], wrapped: &WrappedKey) -> Result<Zeroizing<[u8; 32]>, String> {
+    let blob = BASE64
+        .decode(&wrapped.data)
+        .map_err(|e| format!("Invalid data key base64: {}", e))?;
+    let bytes = Zeroizing::new(crypto::decrypt(master, &blob)?);
+    if bytes.len() != 32 {
+        return Err(format!("Data key '{}' has the wrong length", wrapped.id));
+    }
+    let mut key = Zeroizing::new([0u8; 32]);
+    key.copy_from_slice(&bytes);
+    Ok(key)
+}
+
+fn read_credentials_file(path: &Path) -> Result<CredentialsFile, String> {
+    let data = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read credentials file: {}", e))?;
+    serde_json::from_str(&data).map_err(|e| format!("Failed to parse credentials file: {}", e))
+}
+
+/// The schema file, or an empty one if it doesn't exist yet
+fn read_schema_file(path: &Path) -> Result<SchemaFile, String> {
+    if !path.exists() {
+        return Ok(SchemaFile::default());
+    }
+    let data = fs::read_to_string(path).map_err(|e| format!("Failed to read schema file: {}", e))?;
+    serde_json::from_str(&data).map_err(|e| format!("Failed to parse schema file: {}", e))
+}
+
+/// SHA-256 of a file's contents, `None` if it can't be read
+fn file_digest(path: &Path) -> Option<[u8; 32]> {
+    fs::read(path).ok().map(|data| Sha256::digest(&data).into())
+}
+
+fn backup_path(base_dir: &Path, n: usize) -> PathBuf {
+    base_dir.join("backups").join(format!("{}.{}", CREDENTIALS_FILE, n))
+}
+
+/// Backups that parse, newest first.
+fn read_backups(base_dir: &Path) -> Vec<(PathBuf, CredentialsFile)> {
+    (1..=BACKUP_COUNT)
+        .map(|n| backup_path(base_dir, n))
+        .filter_map(|path| read_credentials_file(&path).ok().map(|file| (path, file)))
+        .collect()
+}
+
+/// Unwrap the data keys in `file`'s keyring that `master` can open
+fn unwrap_keyring(master: &[u8; 32], file: &CredentialsFile) -> HashMap<String, Zeroizing<[u8; 32]>> {
+    file.keyring
+        .iter()
+        .filter_map(|wrapped| Some((wrapped.id.clone(), unwrap_data_key(master, wrapped).ok()?)))
+        .collect()
+}
+
+/// Whether `master` opens a backup's whole keyring and every encrypted entry
+/// in it decrypts to JSON
+fn backup_is_readable(master: &[u8; 32], file: &CredentialsFile) -> bool {
+    let keys = unwrap_keyring(master, file);
+    keys.len() == file.keyring.len()
+        && file.credentials.values().filter(|e| e.encrypted).all(|entry| {
+            decrypt_with(&keys, master, entry).is_ok_and(|plaintext| {
+                serde_json::from_slice::<serde_json::Value>(&plaintext).is_ok()
+            })
+        })
+}
+
+/// Decrypt an entry's raw JSON with its data key from `keys` (or `master`
+/// for entries from before key versioning).
+fn decrypt_with(
+    keys: &HashMap<String, Zeroizing<[u8; 32]>>,
+    master: &[u8; 32],
+    entry: &CredentialEntry,
+) -> Result<Zeroizing<Vec<u8>>, String> {
+    if !entry.encrypted {
+        return Err("Credential is not encrypted".to_string());
+    }
+    let key: &[u8; 32] = match &entry.key_id {
+        Some(id) => keys.get(id).ok_or_else(|| format!("Unknown data key '{}'", id))?,
+        None => master,
+    };
+    let iv = BASE64.decode(entry.iv.as_ref().ok_or("Missing IV")?)
+        .map_err(|e| format!("Invalid IV base64: {}", e))?;
+    let tag = BASE64.decode(entry.tag.as_ref().ok_or("Missing tag")?)
+        .map_err(|e| format!("Invalid tag base64: {}", e))?;
+    let ciphertext = BASE64.decode(&entry.data)
+        .map_err(|e| format!("Invalid ciphertext base64: {}", e))?;
+
+    let mut encrypted_blob = Vec::with_capacity(iv.len() + ciphertext.len() + tag.len());
+    encrypted_blob.extend_from_slice(&iv);
+    encrypted_blob.extend_from_slice(&ciphertext);
+    encrypted_blob.extend_from_slice(&tag);
+
+    Ok(Zeroizing::new(crypto::decrypt(key, &encrypted_blob)?))
+}
+
+/// Move a damaged credentials file aside (`backups/credentials.enc.json.corrupt-<ts>`)
+/// so it can be inspected and isn't backed up over good copies.
+fn quarantine(base_dir: &Path, path: &Path) -> Result<(), String> {
+    let dir = base_dir.join("backups");
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {:?}: {}", dir, e))?;
+    let target = dir.join(format!(
+        "{}.corrupt-{}",
+        CREDENTIALS_FILE,
+        chrono::Utc::now().format("%Y%m%d-%H%M%S")
+    ));
+    fs::rename(path, &target)
+        .map_err(|e| format!("Failed to move damaged credentials file aside: {}", e))?;
+    warn!("Moved damaged credentials file to {:?}", target);
+    Ok(())
+}
+
 fn credential_key(provider: &str, account: &str) -> String {
     format!("{}:{}", provider, account)
 }
 
+/// Key holding a credential's pre-rotation secret (internal namespace, like
+/// `_internal`, so it can't collide with a real credential type).
+fn previous_key(key: &str) -> String {
+    credential_key(PREVIOUS_PREFIX, key)
+}
+
+/// Whether a key is in an internal namespace (`_internal:*`, `_previous:*`),
+/// which export and import never touch
+fn is_internal_key(key: &str) -> bool {
+    key.starts_with('_')
+}
+
+fn validate_timestamp(field: &str, value: Option<&str>) -> Result<(), String> {
+    match value {
+        Some(v) => chrono::DateTime::parse_from_rfc3339(v)
+            .map(|_| ())
+            .map_err(|e| format!("Invalid {} '{}': {}", field, v, e)),
+        None => Ok(()),
+    }
+}
+
+/// Whether an RFC 3339 timestamp is at or before `now`. Unparseable counts as past.
+fn is_past(timestamp: &str, now: chrono::DateTime<chrono::Utc>) -> bool {
+    chrono::DateTime::parse_from_rfc3339(timestamp)
+        .map(|t| t <= now)
+        .unwrap_or(true)
+}
+
+/// `expired`, `expiring` (within `within`) or `rotation_due`, if any applies.
+fn credential_status(
+    entry: &SchemaEntry,
+    now: chrono::DateTime<chrono::Utc>,
+    within: chrono::Duration,
+) -> Option<&'static str> {
+    if let Some(expires_at) = &entry.expires_at {
+        if is_past(expires_at, now) {
+            return Some("expired");
+        }
+        if is_past(expires_at, now + within) {
+            return Some("expiring");
+        }
+    }
+    match &entry.rotate_after {
+        Some(rotate_after) if is_past(rotate_after, now) => Some("rotation_due"),
+        _ => None,
+    }
+}
+
 /// Encrypt plaintext bytes and return a CredentialEntry.
 /// Splits AES-256-GCM output (nonce || ciphertext || tag) into base64 components.
 fn encrypt_to_entry(key: &[u8; 32], plaintext: &[u8]) -> Result<CredentialEntry, String> {
@@ -706,6 +1899,7 @@ fn encrypt_to_entry(key: &[u8; 32], plaintext: &[u8]) -> Result<CredentialEntry,
 
     Ok(CredentialEntry {
         encrypted: true,
+        key_id: None,
         algorithm: Some("aes-256-gcm".to_string()),
         iv: Some(BASE64.encode(iv)),
         tag: Some(BASE64.encode(tag)),
@@ -729,4 +1923,97 @@ mod tests {
         let k2 = credential_key("google", "alex@example.com");
         assert_eq!(k1, k2);
     }
+
+    #[test]
+    fn test_credential_status() {
+        let now = chrono::Utc::now();
+        let at = |days: i64| Some((now + chrono::Duration::days(days)).to_rfc3339());
+        let week = chrono::Duration::days(7);
+        let mut entry = SchemaEntry {
+            provider: "jira".into(),
+            account: "default".into(),
+            cred_type: "jira".into(),
+            scopes: vec![],
+            added: now.to_rfc3339(),
+            last_refreshed: None,
+            expires_at: None,
+            rotate_after: None,
+            rotated_at: None,
+            previous_valid_until: None,
+        };
+        assert_eq!(credential_status(&entry, now, week), None);
+
+        entry.rotate_after = at(-1);
+        assert_eq!(credential_status(&entry, now, week), Some("rotation_due"));
+        entry.expires_at = at(30);
+        assert_eq!(credential_status(&entry, now, week), Some("rotation_due"));
+        entry.expires_at = at(3);
+        assert_eq!(credential_status(&entry, now, week), Some("expiring"));
+        entry.expires_at = at(-1);
+        assert_eq!(credential_status(&entry, now, week), Some("expired"));
+    }
+
+    #[test]
+    fn test_previous_key_is_internal() {
+        assert_eq!(previous_key("jira:default"), "_previous:jira:default");
+        assert!(is_internal_key(&previous_key("jira:default")));
+        assert!(is_internal_key("_internal:gog_passphrase"));
+        assert!(!is_internal_key("jira:default"));
+        assert!(validate_timestamp("expires_at", Some("2026-01-01T00:00:00Z")).is_ok());
+        assert!(validate_timestamp("expires_at", Some("next week")).is_err());
+    }
+
+    #[test]
+    fn test_data_key_wrapping() {
+        let master = [7u8; 32];
+        let (wrapped, key) = generate_data_key(&master).unwrap();
+        assert!(wrapped.id.starts_with("k-"));
+        assert_eq!(*unwrap_data_key(&master, &wrapped).unwrap(), *key);
+        assert!(unwrap_data_key(&[8u8; 32], &wrapped).is_err());
+
+        // Entries written before key versioning have no key_id
+        let legacy: CredentialEntry =
+            serde_json::from_str(r#"{"encrypted": true, "iv": "AA==", "tag": "AA==", "data": ""}"#)
+                .unwrap();
+        assert_eq!(legacy.key_id, None);
+    }
+
+    #[test]
+    fn test_read_backups_skips_damaged_copies() {
+        let base = std::env::temp_dir().join(format!("tairseach-backups-{}", std::process::id()));
+        fs::create_dir_all(base.join("backups")).unwrap();
+        let good = serde_json::to_string(&CredentialsFile::default()).unwrap();
+        fs::write(backup_path(&base, 1), "{\"version\": 2, \"credenti").unwrap();
+        fs::write(backup_path(&base, 2), &good).unwrap();
+        fs::write(backup_path(&base, 3), &good).unwrap();
+
+        let backups = read_backups(&base);
+        assert_eq!(backups.len(), 2);
+        assert_eq!(backups[0].0, backup_path(&base, 2));
+
+        let _ = fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn test_backup_read_with_own_keyring() {
+        let master = [9u8; 32];
+        let (wrapped, data_key) = generate_data_key(&master).unwrap();
+        let mut entry = encrypt_to_entry(&data_key, br#"{"token": "abc"}"#).unwrap();
+        entry.key_id = Some(wrapped.id.clone());
+        let mut backup = CredentialsFile {
+            current_key: Some(wrapped.id.clone()),
+            keyring: vec![wrapped],
+            ..Default::default()
+        };
+        backup.credentials.insert("jira:default".to_string(), entry.clone());
+
+        // The live store has moved on to other keys; the backup still opens
+        let keys = unwrap_keyring(&master, &backup);
+        assert_eq!(&*decrypt_with(&keys, &master, &entry).unwrap(), br#"{"token": "abc"}"#);
+        assert!(decrypt_with(&HashMap::new(), &master, &entry).is_err());
+        assert!(backup_is_readable(&master, &backup));
+
+        // A backup whose keyring was wrapped by another master key is skipped
+        assert!(!backup_is_readable(&[1u8; 32], &backup));
+    }
 }
@@ -0,0 +1,191 @@
+//! Crash-Safe File Writes
+//!
+//! `write_atomic` writes to a temp file in the target's directory, fsyncs it
+//! and renames it over the target, so readers see either the old or the new
+//! contents — never a truncated file. `FileLock` is an advisory lock that
+//! keeps the app and command-line tools from writing the same files at once.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// Distinguishes temp files of concurrent writers in one process
+static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Atomically replace `path` with `bytes`.
+///
+/// With `mode`, the file gets exactly those Unix permissions; without it an
+/// existing file keeps its permissions.
+pub fn write_atomic(path: &Path, bytes: &[u8], mode: Option<u32>) -> Result<(), String> {
+    let parent = match path.parent() {
+        Some(p) if !p.as_os_str().is_empty() => p,
+        _ => Path::new("."),
+    };
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| format!("Not a file path: {:?}", path))?
+        .to_string_lossy();
+    fs::create_dir_all(parent).map_err(|e| format!("Failed to create {:?}: {}", parent, e))?;
+
+    let tmp = parent.join(format!(
+        ".{}.{}-{}.tmp",
+        file_name,
+        std::process::id(),
+        TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    let result = (|| -> std::io::Result<()> {
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(mode.unwrap_or(0o600));
+        }
+        let mut file = options.open(&tmp)?;
+
+        // `mode` is filtered by the umask; set the final permissions explicitly
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            match mode {
+                Some(m) => file.set_permissions(fs::Permissions::from_mode(m))?,
+                None => {
+                    let perms = fs::metadata(path)
+                        .map(|m| m.permissions())
+                        .unwrap_or_else(|_| fs::Permissions::from_mode(0o644));
+                    file.set_permissions(perms)?;
+                }
+            }
+        }
+
+        file.write_all(bytes)?;
+        file.sync_all()?;
+        drop(file);
+
+        fs::rename(&tmp, path)?;
+        sync_dir(parent);
+        Ok(())
+    })();
+
+    result.map_err(|e| {
+        let _ = fs::remove_file(&tmp);
+        format!("Failed to write {:?}: {}", path, e)
+    })
+}
+
+/// Make a rename durable by syncing its directory (best effort).
+fn sync_dir(dir: &Path) {
+    #[cfg(unix)]
+    if let Ok(d) = File::open(dir) {
+        let _ = d.sync_all();
+    }
+    #[cfg(not(unix))]
+    let _ = dir;
+}
+
+/// Exclusive advisory lock on a lock file, released on drop.
+///
+/// Advisory only: it coordinates processes that take it, not arbitrary
+/// writers.
+pub struct FileLock {
+    _file: File,
+}
+
+impl FileLock {
+    /// Lock `path` (created if missing), waiting up to `timeout`. While
+    /// another holder has it, polls on the async timer rather than blocking
+    /// the runtime thread.
+    pub async fn exclusive(path: &Path, timeout: Duration) -> Result<Self, String> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)
+            .map_err(|e| format!("Failed to open lock file {:?}: {}", path, e))?;
+
+        let deadline = Instant::now() + timeout;
+        loop {
+            match try_lock(&file) {
+                Ok(true) => return Ok(Self { _file: file }),
+                Ok(false) if Instant::now() < deadline => {
+                    tokio::time::sleep(Duration::from_millis(25)).await
+                }
+                Ok(false) => {
+                    return Err(format!(
+                        "Timed out waiting for lock {:?} (held by another process)",
+                        path
+                    ))
+                }
+                Err(e) => return Err(format!("Failed to lock {:?}: {}", path, e)),
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+fn try_lock(file: &File) -> std::io::Result<bool> {
+    use std::os::unix::io::AsRawFd;
+    // SAFETY: the descriptor is owned by `file` and stays open for the call
+    if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) } == 0 {
+        return Ok(true);
+    }
+    let err = std::io::Error::last_os_error();
+    if err.kind() == std::io::ErrorKind::WouldBlock {
+        Ok(false)
+    } else {
+        Err(err)
+    }
+}
+
+#[cfg(not(unix))]
+fn try_lock(_file: &File) -> std::io::Result<bool> {
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("tairseach-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_write_atomic_replaces_and_cleans_up() {
+        let dir = temp_dir("atomic");
+        let path = dir.join("file.json");
+        write_atomic(&path, b"one", Some(0o600)).unwrap();
+        write_atomic(&path, b"two", None).unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"two");
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        let leftovers = fs::read_dir(&dir).unwrap().count();
+        assert_eq!(leftovers, 1);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    #[cfg(unix)]
+    async fn test_lock_is_exclusive() {
+        let dir = temp_dir("lock");
+        let path = dir.join("test.lock");
+        let held = FileLock::exclusive(&path, Duration::from_secs(1)).await.unwrap();
+        // flock locks belong to the open file description, so a second open
+        // in the same process contends like another process would
+        assert!(FileLock::exclusive(&path, Duration::from_millis(50)).await.is_err());
+        drop(held);
+        assert!(FileLock::exclusive(&path, Duration::from_millis(50)).await.is_ok());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
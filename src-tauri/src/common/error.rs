@@ -28,6 +28,11 @@ pub enum ErrorCode {
     // Permission errors
     PermissionDenied = -32001,
     
+    // Access control (client policy)
+    PolicyDenied = -32020,
+    ApprovalDenied = -32021,
+    RateLimited = -32022,
+    
     // Generic application error
     GenericError = -32000,
 }
@@ -1,8 +1,32 @@
 //! HTTP Client Utilities
 //!
-//! Shared HTTP client creation with consistent configuration.
+//! Shared HTTP client creation with consistent configuration, and the
+//! outbound layer integrations call through ([`HttpClient`]):
+//!
+//! - one pooled `reqwest::Client` shared by every call
+//! - per-manifest timeouts and retry settings (`http` in the manifest)
+//! - exponential backoff with jitter, honoring `Retry-After`
+//! - retries only for idempotent methods (GET/HEAD/PUT/DELETE/OPTIONS) unless
+//!   the caller declares the request safe to repeat
+//! - a circuit breaker per base URL, reported by [`circuit_statuses`]
+//!
+//! ```json
+//! "http": { "timeoutSecs": 60, "maxRetries": 3, "failureThreshold": 5, "cooldownSecs": 30 }
+//! ```
+
+use std::collections::{BTreeSet, HashMap};
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, Instant};
 
-use std::time::Duration;
+use once_cell::sync::Lazy;
+use rand::Rng;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::{Method, RequestBuilder, Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+const CONNECT_TIMEOUT_SECS: u64 = 10;
+const POOL_IDLE_TIMEOUT_SECS: u64 = 90;
 
 /// Create a reqwest HTTP client with standard configuration
 ///
@@ -22,3 +46,418 @@ pub fn create_http_client_with_timeout(timeout_secs: u64) -> Result<reqwest::Cli
         .build()
         .map_err(|e| format!("Failed to build HTTP client: {}", e))
 }
+
+// ── Policy ──────────────────────────────────────────────────────────────────
+
+/// Timeout, retry and circuit breaker settings for one manifest's calls
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HttpPolicy {
+    /// Whole-request timeout
+    pub timeout_secs: u64,
+    /// Retries after the first attempt
+    pub max_retries: u32,
+    /// Backoff before the first retry; doubles on each one
+    pub backoff_ms: u64,
+    /// Longest single wait. A `Retry-After` beyond this isn't waited for.
+    pub max_backoff_ms: u64,
+    /// Consecutive failures (transport errors, 5xx) that open the circuit
+    pub failure_threshold: u32,
+    /// How long an open circuit rejects calls before letting one through
+    pub cooldown_secs: u64,
+}
+
+impl Default for HttpPolicy {
+    fn default() -> Self {
+        Self {
+            timeout_secs: 30,
+            max_retries: 2,
+            backoff_ms: 500,
+            max_backoff_ms: 30_000,
+            failure_threshold: 5,
+            cooldown_secs: 30,
+        }
+    }
+}
+
+impl HttpPolicy {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.timeout_secs == 0 {
+            return Err("http.timeoutSecs must be at least 1".to_string());
+        }
+        if self.failure_threshold == 0 {
+            return Err("http.failureThreshold must be at least 1".to_string());
+        }
+        Ok(())
+    }
+
+    /// Wait before retry number `attempt` (0-based), with jitter
+    fn backoff(&self, attempt: u32) -> Duration {
+        let ms = self
+            .backoff_ms
+            .saturating_mul(1u64 << attempt.min(16))
+            .min(self.max_backoff_ms);
+        let jittered = rand::thread_rng().gen_range(ms / 2..=ms.max(1));
+        Duration::from_millis(jittered)
+    }
+}
+
+/// Policies by manifest ID, replaced whenever manifests are (re)loaded
+static POLICIES: Lazy<RwLock<HashMap<String, HttpPolicy>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// Install the `http` blocks of the loaded manifests
+pub fn set_policies(policies: HashMap<String, HttpPolicy>) {
+    if let Ok(mut current) = POLICIES.write() {
+        *current = policies;
+    }
+}
+
+/// The policy for a manifest, or the defaults
+pub fn policy_for(namespace: &str) -> HttpPolicy {
+    POLICIES
+        .read()
+        .ok()
+        .and_then(|policies| policies.get(namespace).cloned())
+        .unwrap_or_default()
+}
+
+// ── Client ──────────────────────────────────────────────────────────────────
+
+/// Pooled client shared by all outbound calls; timeouts are set per request
+static CLIENT: Lazy<reqwest::Client> = Lazy::new(|| {
+    reqwest::Client::builder()
+        .connect_timeout(Duration::from_secs(CONNECT_TIMEOUT_SECS))
+        .pool_idle_timeout(Duration::from_secs(POOL_IDLE_TIMEOUT_SECS))
+        .build()
+        .unwrap_or_else(|e| {
+            warn!("Failed to build pooled HTTP client, using defaults: {}", e);
+            reqwest::Client::new()
+        })
+});
+
+/// Why an outbound call produced no response
+#[derive(Debug)]
+pub enum HttpError {
+    Transport(reqwest::Error),
+    /// The base URL's circuit is open; nothing was sent
+    CircuitOpen { base_url: String, retry_in: Duration },
+}
+
+impl std::fmt::Display for HttpError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HttpError::Transport(e) => write!(f, "HTTP request failed: {}", e),
+            HttpError::CircuitOpen { base_url, retry_in } => write!(
+                f,
+                "{} is failing; calls paused for {}s",
+                base_url,
+                retry_in.as_secs().max(1)
+            ),
+        }
+    }
+}
+
+impl std::error::Error for HttpError {}
+
+/// Outbound HTTP for one namespace (manifest ID)
+#[derive(Debug, Clone)]
+pub struct HttpClient {
+    namespace: String,
+    policy: HttpPolicy,
+}
+
+impl HttpClient {
+    /// Client using the policy the manifest `namespace` declares
+    pub fn for_namespace(namespace: &str) -> Self {
+        Self {
+            namespace: namespace.to_string(),
+            policy: policy_for(namespace),
+        }
+    }
+
+    /// Start a request on the shared pool
+    pub fn request(&self, method: Method, url: &str) -> RequestBuilder {
+        CLIENT.request(method, url).timeout(Duration::from_secs(self.policy.timeout_secs))
+    }
+
+    /// Send with retries and the circuit breaker. `retry_safe` allows retrying
+    /// methods that aren't idempotent (e.g. a POST search).
+    ///
+    /// Non-success responses are returned as-is once retries run out, so
+    /// callers keep their own error handling.
+    pub async fn send(&self, request: RequestBuilder, retry_safe: bool) -> Result<Response, HttpError> {
+        let request = request.build().map_err(HttpError::Transport)?;
+        let idempotent = retry_safe
+            || matches!(
+                *request.method(),
+                Method::GET | Method::HEAD | Method::PUT | Method::DELETE | Method::OPTIONS
+            );
+        let base_url = request.url().origin().ascii_serialization();
+
+        let mut attempt = 0;
+        loop {
+            admit(&base_url, &self.namespace)?;
+
+            // Bodies that can't be replayed get a single attempt
+            let Some(this_try) = request.try_clone() else {
+                let outcome = CLIENT.execute(request).await;
+                record(&base_url, is_healthy(&outcome), &self.policy);
+                return outcome.map_err(HttpError::Transport);
+            };
+
+            let outcome = CLIENT.execute(this_try).await;
+            record(&base_url, is_healthy(&outcome), &self.policy);
+
+            let retry = match &outcome {
+                // Rejected before being processed, so safe for any method
+                Ok(response) if response.status() == StatusCode::TOO_MANY_REQUESTS => {
+                    Some(retry_after(response.headers()))
+                }
+                Ok(response) if response.status().is_server_error() && idempotent => {
+                    Some(retry_after(response.headers()))
+                }
+                Ok(_) => None,
+                Err(e) if idempotent || e.is_connect() => Some(None),
+                Err(_) => None,
+            };
+
+            let Some(hint) = retry.filter(|_| attempt < self.policy.max_retries) else {
+                return outcome.map_err(HttpError::Transport);
+            };
+            let delay = hint.unwrap_or_else(|| self.policy.backoff(attempt));
+            if delay > Duration::from_millis(self.policy.max_backoff_ms) {
+                return outcome.map_err(HttpError::Transport);
+            }
+
+            attempt += 1;
+            warn!(
+                "{} {} failed ({}), retry {}/{} in {}ms",
+                request.method(),
+                base_url,
+                describe(&outcome),
+                attempt,
+                self.policy.max_retries,
+                delay.as_millis()
+            );
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+fn is_healthy(outcome: &Result<Response, reqwest::Error>) -> bool {
+    matches!(outcome, Ok(response) if !response.status().is_server_error())
+}
+
+fn describe(outcome: &Result<Response, reqwest::Error>) -> String {
+    match outcome {
+        Ok(response) => response.status().to_string(),
+        Err(e) => e.to_string(),
+    }
+}
+
+/// `Retry-After` as delay-seconds or an HTTP date
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let at = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    let wait = at.with_timezone(&chrono::Utc) - chrono::Utc::now();
+    Some(wait.to_std().unwrap_or(Duration::ZERO))
+}
+
+// ── Circuit Breaker ─────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CircuitState {
+    Closed,
+    /// Calls are rejected until the cooldown passes
+    Open,
+    /// Cooldown passed; the next result closes or reopens the circuit
+    HalfOpen,
+}
+
+#[derive(Debug)]
+struct Breaker {
+    state: CircuitState,
+    failures: u32,
+    opened_at: Option<Instant>,
+    cooldown: Duration,
+    /// Manifests that called this base URL
+    namespaces: BTreeSet<String>,
+}
+
+impl Breaker {
+    fn new() -> Self {
+        Self {
+            state: CircuitState::Closed,
+            failures: 0,
+            opened_at: None,
+            cooldown: Duration::ZERO,
+            namespaces: BTreeSet::new(),
+        }
+    }
+
+    /// `Err(remaining cooldown)` while open
+    fn admit(&mut self, now: Instant) -> Result<(), Duration> {
+        if self.state != CircuitState::Open {
+            return Ok(());
+        }
+        let elapsed = self.opened_at.map_or(self.cooldown, |at| now.duration_since(at));
+        if elapsed >= self.cooldown {
+            self.state = CircuitState::HalfOpen;
+            Ok(())
+        } else {
+            Err(self.cooldown - elapsed)
+        }
+    }
+
+    /// Returns true if this result opened the circuit
+    fn record(&mut self, healthy: bool, policy: &HttpPolicy, now: Instant) -> bool {
+        if healthy {
+            self.state = CircuitState::Closed;
+            self.failures = 0;
+            self.opened_at = None;
+            return false;
+        }
+        self.failures = self.failures.saturating_add(1);
+        let trip = self.state == CircuitState::HalfOpen
+            || (self.state == CircuitState::Closed && self.failures >= policy.failure_threshold);
+        if trip {
+            self.state = CircuitState::Open;
+            self.opened_at = Some(now);
+            self.cooldown = Duration::from_secs(policy.cooldown_secs);
+        }
+        trip
+    }
+
+    fn retry_in(&self, now: Instant) -> Option<Duration> {
+        match (self.state, self.opened_at) {
+            (CircuitState::Open, Some(at)) => Some(self.cooldown.saturating_sub(now.duration_since(at))),
+            _ => None,
+        }
+    }
+}
+
+/// Circuit breakers by base URL (scheme://host:port)
+static BREAKERS: Lazy<Mutex<HashMap<String, Breaker>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn admit(base_url: &str, namespace: &str) -> Result<(), HttpError> {
+    let Ok(mut breakers) = BREAKERS.lock() else {
+        return Ok(());
+    };
+    let breaker = breakers.entry(base_url.to_string()).or_insert_with(Breaker::new);
+    if !breaker.namespaces.contains(namespace) {
+        breaker.namespaces.insert(namespace.to_string());
+    }
+    breaker.admit(Instant::now()).map_err(|retry_in| HttpError::CircuitOpen {
+        base_url: base_url.to_string(),
+        retry_in,
+    })
+}
+
+fn record(base_url: &str, healthy: bool, policy: &HttpPolicy) {
+    let Ok(mut breakers) = BREAKERS.lock() else {
+        return;
+    };
+    if let Some(breaker) = breakers.get_mut(base_url) {
+        if breaker.record(healthy, policy, Instant::now()) {
+            warn!(
+                "Circuit opened for {} after {} consecutive failures",
+                base_url, breaker.failures
+            );
+        }
+    }
+}
+
+/// Breaker state for one base URL
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CircuitStatus {
+    pub base_url: String,
+    pub state: CircuitState,
+    pub consecutive_failures: u32,
+    /// Seconds until an open circuit lets a call through
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_in_secs: Option<u64>,
+    pub namespaces: Vec<String>,
+}
+
+/// Every base URL called since startup
+pub fn circuit_statuses() -> Vec<CircuitStatus> {
+    let Ok(breakers) = BREAKERS.lock() else {
+        return Vec::new();
+    };
+    let now = Instant::now();
+    let mut statuses: Vec<CircuitStatus> = breakers
+        .iter()
+        .map(|(base_url, breaker)| CircuitStatus {
+            base_url: base_url.clone(),
+            state: breaker.state,
+            consecutive_failures: breaker.failures,
+            retry_in_secs: breaker.retry_in(now).map(|d| d.as_secs()),
+            namespaces: breaker.namespaces.iter().cloned().collect(),
+        })
+        .collect();
+    statuses.sort_by(|a, b| a.base_url.cmp(&b.base_url));
+    statuses
+}
+
+/// Origin (`scheme://host:port`) of a base URL, as circuits are keyed
+pub fn origin_of(url: &str) -> Option<String> {
+    reqwest::Url::parse(url).ok().map(|u| u.origin().ascii_serialization())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_breaker_opens_and_half_opens() {
+        let policy = HttpPolicy { failure_threshold: 2, cooldown_secs: 30, ..Default::default() };
+        let mut breaker = Breaker::new();
+        let start = Instant::now();
+
+        assert!(!breaker.record(false, &policy, start));
+        assert!(breaker.record(false, &policy, start));
+        assert_eq!(breaker.state, CircuitState::Open);
+        assert!(breaker.admit(start + Duration::from_secs(10)).is_err());
+
+        // After the cooldown one call goes through; a failure reopens at once
+        assert!(breaker.admit(start + Duration::from_secs(31)).is_ok());
+        assert_eq!(breaker.state, CircuitState::HalfOpen);
+        assert!(breaker.record(false, &policy, start + Duration::from_secs(31)));
+        assert!(breaker.admit(start + Duration::from_secs(32)).is_err());
+
+        assert!(breaker.admit(start + Duration::from_secs(62)).is_ok());
+        breaker.record(true, &policy, start + Duration::from_secs(62));
+        assert_eq!((breaker.state, breaker.failures), (CircuitState::Closed, 0));
+    }
+
+    #[test]
+    fn test_retry_after_and_backoff() {
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, "7".parse().unwrap());
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(7)));
+        headers.insert(RETRY_AFTER, "Wed, 21 Oct 2015 07:28:00 GMT".parse().unwrap());
+        assert_eq!(retry_after(&headers), Some(Duration::ZERO));
+        headers.insert(RETRY_AFTER, "soon".parse().unwrap());
+        assert_eq!(retry_after(&headers), None);
+
+        let policy = HttpPolicy { backoff_ms: 100, max_backoff_ms: 1000, ..Default::default() };
+        let first = policy.backoff(0).as_millis();
+        assert!((50..=100).contains(&first));
+        assert!(policy.backoff(10).as_millis() <= 1000);
+    }
+
+    #[test]
+    fn test_origin_of() {
+        assert_eq!(
+            origin_of("https://api.example.com/v2/items").as_deref(),
+            Some("https://api.example.com")
+        );
+        assert_eq!(origin_of("http://localhost:8080").as_deref(), Some("http://localhost:8080"));
+    }
+}
@@ -7,7 +7,9 @@ use std::collections::HashMap;
 
 /// Interpolate parameters in a template string
 ///
-/// Replaces placeholders like `{field_name}` with values from params object.
+/// Replaces placeholders like `{field_name}` or `{user.id}` with values from
+/// the params object; see [`super::template`] for the full syntax. Missing
+/// values render as empty.
 ///
 /// # Example
 /// ```ignore
@@ -17,22 +19,7 @@ use std::collections::HashMap;
 /// // result == "/users/123/posts/456"
 /// ```
 pub fn interpolate_params(template: &str, params: &Value) -> String {
-    let mut result = template.to_string();
-
-    if let Some(obj) = params.as_object() {
-        for (key, value) in obj {
-            let placeholder = format!("{{{}}}", key);
-            let replacement = match value {
-                Value::String(s) => s.clone(),
-                Value::Number(n) => n.to_string(),
-                Value::Bool(b) => b.to_string(),
-                _ => continue,
-            };
-            result = result.replace(&placeholder, &replacement);
-        }
-    }
-
-    result
+    super::template::render(template, params, super::template::Escape::None).text
 }
 
 /// Interpolate credential placeholders in environment variable values
@@ -97,6 +84,14 @@ mod tests {
         assert_eq!(result, "page=1&limit=100");
     }
 
+    #[test]
+    fn test_interpolate_params_nested() {
+        let template = "{user.name} <{emails[0]}>";
+        let params = json!({"user": {"name": "Ana"}, "emails": ["ana@example.com"]});
+        let result = interpolate_params(template, &params);
+        assert_eq!(result, "Ana <ana@example.com>");
+    }
+
     #[test]
     fn test_interpolate_credentials() {
         let template = "Bearer {credential:google-oauth:access_token}";
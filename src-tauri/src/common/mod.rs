@@ -2,12 +2,18 @@
 //!
 //! Shared types, error handling, and utility functions used across the application.
 
+pub mod atomic;
 pub mod error;
 pub mod http;
 pub mod interpolation;
+pub mod pagination;
 pub mod paths;
+pub mod pattern;
 pub mod result;
+pub mod template;
+pub mod timezone;
 
+pub use atomic::{write_atomic, FileLock};
 #[allow(unused_imports)]
 pub use error::{AppError, ErrorCode};
 pub use http::{create_http_client, create_http_client_with_timeout};
@@ -15,4 +21,6 @@ pub use interpolation::{interpolate_credentials, interpolate_params};
 #[allow(unused_imports)]
 pub use paths::{logs_dir, manifest_dir, scripts_dir, socket_path, tairseach_dir, tairseach_path};
 #[allow(unused_imports)]
+pub use pattern::{any_match, glob_match};
+#[allow(unused_imports)]
 pub use result::AppResult;
@@ -0,0 +1,356 @@
+//! Pagination
+//!
+//! Declarative description of how an HTTP API pages its results, and a
+//! [`Paginator`] that walks pages and collects items. Used by proxy tool
+//! bindings (`pagination` in the manifest) and the Google API client.
+//!
+//! ```json
+//! "pagination": { "style": "cursor", "items": "$.messages", "cursorPath": "nextPageToken", "maxItems": 500 }
+//! ```
+//!
+//! Styles:
+//! - `cursor` — the response carries a token for the next page
+//! - `offset` — `offset`/`limit` query params
+//! - `link` — RFC 8288 `Link: <…>; rel="next"` response header
+//! - `startAt` — `startAt`/`maxResults` with a `total` in the response (Jira)
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::template;
+
+/// Hard stop for APIs that never report a last page
+pub const DEFAULT_MAX_PAGES: usize = 100;
+
+/// How results are paged
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Pagination {
+    #[serde(flatten)]
+    pub style: PaginationStyle,
+    /// Path to the item array in each page (e.g. `$.values`)
+    pub items: String,
+    /// Stop after collecting this many items
+    #[serde(default, alias = "max_items")]
+    pub max_items: Option<usize>,
+    /// Stop after this many requests (default [`DEFAULT_MAX_PAGES`])
+    #[serde(default)]
+    pub max_pages: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "style", rename_all = "camelCase")]
+pub enum PaginationStyle {
+    #[serde(rename_all = "camelCase")]
+    Cursor {
+        /// Path to the next-page token in the response
+        #[serde(default = "default_cursor_path")]
+        cursor_path: String,
+        /// Query param the token is sent back in
+        #[serde(default = "default_cursor_param")]
+        cursor_param: String,
+        #[serde(default)]
+        page_size_param: Option<String>,
+        #[serde(default)]
+        page_size: Option<usize>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Offset {
+        #[serde(default = "default_offset_param")]
+        offset_param: String,
+        #[serde(default = "default_limit_param")]
+        limit_param: String,
+        #[serde(default = "default_page_size")]
+        page_size: usize,
+    },
+    Link,
+    #[serde(rename_all = "camelCase")]
+    StartAt {
+        #[serde(default = "default_start_param")]
+        start_param: String,
+        #[serde(default = "default_max_results_param")]
+        max_param: String,
+        #[serde(default = "default_total_path")]
+        total_path: String,
+        #[serde(default = "default_page_size")]
+        page_size: usize,
+    },
+}
+
+fn default_cursor_path() -> String {
+    "nextPageToken".to_string()
+}
+fn default_cursor_param() -> String {
+    "pageToken".to_string()
+}
+fn default_offset_param() -> String {
+    "offset".to_string()
+}
+fn default_limit_param() -> String {
+    "limit".to_string()
+}
+fn default_start_param() -> String {
+    "startAt".to_string()
+}
+fn default_max_results_param() -> String {
+    "maxResults".to_string()
+}
+fn default_total_path() -> String {
+    "total".to_string()
+}
+fn default_page_size() -> usize {
+    50
+}
+
+impl Pagination {
+    /// Cursor pagination with Google's `pageToken`/`nextPageToken` names
+    pub fn google(items: &str, page_size: Option<usize>, max_items: Option<usize>) -> Self {
+        Self {
+            style: PaginationStyle::Cursor {
+                cursor_path: default_cursor_path(),
+                cursor_param: default_cursor_param(),
+                page_size_param: Some(default_max_results_param()),
+                page_size,
+            },
+            items: items.to_string(),
+            max_items,
+            max_pages: None,
+        }
+    }
+
+    /// Like [`google`](Self::google) for APIs that take `pageSize` rather
+    /// than `maxResults` (People, Drive)
+    pub fn google_page_size(items: &str, page_size: Option<usize>, max_items: Option<usize>) -> Self {
+        let mut spec = Self::google(items, page_size, max_items);
+        if let PaginationStyle::Cursor { page_size_param, .. } = &mut spec.style {
+            *page_size_param = Some("pageSize".to_string());
+        }
+        spec
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        template::parse_path(&self.items).map_err(|e| format!("pagination.items: {}", e))?;
+        match &self.style {
+            PaginationStyle::Cursor { cursor_path, .. } => {
+                template::parse_path(cursor_path).map_err(|e| format!("pagination.cursorPath: {}", e))?;
+            }
+            PaginationStyle::StartAt { total_path, .. } => {
+                template::parse_path(total_path).map_err(|e| format!("pagination.totalPath: {}", e))?;
+            }
+            PaginationStyle::Offset { page_size, .. } if *page_size == 0 => {
+                return Err("pagination.pageSize must be at least 1".to_string());
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// Where the next page comes from
+#[derive(Debug, Clone, PartialEq)]
+pub enum NextPage {
+    /// Same URL, with these query params set (replacing earlier values)
+    Query(Vec<(String, String)>),
+    /// A different URL (already carrying its query)
+    Url(String),
+}
+
+/// Walks pages for one call
+#[derive(Debug)]
+pub struct Paginator<'a> {
+    spec: &'a Pagination,
+    items: Vec<Value>,
+    pages: usize,
+    /// Items seen so far across pages (offset / startAt position)
+    position: usize,
+    truncated: bool,
+}
+
+impl<'a> Paginator<'a> {
+    pub fn new(spec: &'a Pagination) -> Self {
+        Self {
+            spec,
+            items: Vec::new(),
+            pages: 0,
+            position: 0,
+            truncated: false,
+        }
+    }
+
+    /// Query params for the first request
+    pub fn first_page(&self) -> Vec<(String, String)> {
+        match &self.spec.style {
+            PaginationStyle::Cursor { page_size_param: Some(param), page_size: Some(size), .. } => {
+                vec![(param.clone(), self.page_size(*size).to_string())]
+            }
+            PaginationStyle::Offset { offset_param, limit_param, page_size } => vec![
+                (offset_param.clone(), "0".to_string()),
+                (limit_param.clone(), self.page_size(*page_size).to_string()),
+            ],
+            PaginationStyle::StartAt { start_param, max_param, page_size, .. } => vec![
+                (start_param.clone(), "0".to_string()),
+                (max_param.clone(), self.page_size(*page_size).to_string()),
+            ],
+            _ => Vec::new(),
+        }
+    }
+
+    /// Take one page's body (and `Link` header). Returns where to fetch the
+    /// next page, or `None` once done.
+    pub fn absorb(&mut self, body: &Value, link_header: Option<&str>) -> Option<NextPage> {
+        self.pages += 1;
+        let page: Vec<Value> = template::extract(body, &self.spec.items)
+            .and_then(|v| v.as_array().cloned())
+            .unwrap_or_default();
+        let page_len = page.len();
+        self.position += page_len;
+        self.items.extend(page);
+
+        let next = match &self.spec.style {
+            PaginationStyle::Cursor { cursor_path, cursor_param, page_size_param, page_size } => {
+                template::extract(body, cursor_path)
+                    .and_then(|v| v.as_str().map(str::to_string))
+                    .filter(|token| !token.is_empty())
+                    .map(|token| {
+                        let mut query = vec![(cursor_param.clone(), token)];
+                        if let (Some(param), Some(size)) = (page_size_param, page_size) {
+                            query.push((param.clone(), self.page_size(*size).to_string()));
+                        }
+                        NextPage::Query(query)
+                    })
+            }
+            PaginationStyle::Offset { offset_param, limit_param, page_size } => {
+                (page_len >= *page_size).then(|| {
+                    NextPage::Query(vec![
+                        (offset_param.clone(), self.position.to_string()),
+                        (limit_param.clone(), self.page_size(*page_size).to_string()),
+                    ])
+                })
+            }
+            PaginationStyle::Link => link_header.and_then(next_link).map(NextPage::Url),
+            PaginationStyle::StartAt { start_param, max_param, total_path, page_size } => {
+                let total = template::extract(body, total_path).and_then(|v| v.as_u64());
+                let more = match total {
+                    Some(total) => (self.position as u64) < total,
+                    None => page_len >= *page_size,
+                };
+                (more && page_len > 0).then(|| {
+                    NextPage::Query(vec![
+                        (start_param.clone(), self.position.to_string()),
+                        (max_param.clone(), self.page_size(*page_size).to_string()),
+                    ])
+                })
+            }
+        };
+
+        let full = self.spec.max_items.is_some_and(|max| self.items.len() >= max);
+        let out_of_pages = self.pages >= self.spec.max_pages.unwrap_or(DEFAULT_MAX_PAGES);
+        if next.is_some() && (full || out_of_pages) {
+            self.truncated = true;
+            return None;
+        }
+        if let Some(max) = self.spec.max_items {
+            if self.items.len() > max {
+                self.items.truncate(max);
+                self.truncated = true;
+            }
+        }
+        next
+    }
+
+    /// Requests made so far
+    pub fn pages(&self) -> usize {
+        self.pages
+    }
+
+    /// Whether items were left behind because of `maxItems`/`maxPages`
+    pub fn truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// The collected items (capped at `maxItems`)
+    pub fn into_items(mut self) -> Vec<Value> {
+        if let Some(max) = self.spec.max_items {
+            self.items.truncate(max);
+        }
+        self.items
+    }
+
+    /// Don't ask for more than `maxItems` still needs
+    fn page_size(&self, size: usize) -> usize {
+        match self.spec.max_items {
+            Some(max) => size.min(max.saturating_sub(self.items.len()).max(1)),
+            None => size,
+        }
+    }
+}
+
+/// The `rel="next"` target of an RFC 8288 `Link` header
+pub fn next_link(header: &str) -> Option<String> {
+    header.split(',').find_map(|link| {
+        let mut parts = link.split(';');
+        let target = parts.next()?.trim().strip_prefix('<')?.strip_suffix('>')?;
+        parts
+            .any(|param| {
+                let param = param.trim().replace(' ', "");
+                param == "rel=\"next\"" || param == "rel=next"
+            })
+            .then(|| target.to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_cursor_pages_until_cap() {
+        let spec: Pagination = serde_json::from_value(json!({
+            "style": "cursor", "items": "$.values", "maxItems": 3
+        }))
+        .unwrap();
+        let mut pager = Paginator::new(&spec);
+        assert!(pager.first_page().is_empty());
+
+        let next = pager.absorb(&json!({"values": [1, 2], "nextPageToken": "t2"}), None);
+        assert_eq!(next, Some(NextPage::Query(vec![("pageToken".into(), "t2".into())])));
+        assert_eq!(pager.absorb(&json!({"values": [3, 4], "nextPageToken": "t3"}), None), None);
+        assert!(pager.truncated());
+        assert_eq!(pager.into_items(), vec![json!(1), json!(2), json!(3)]);
+    }
+
+    #[test]
+    fn test_start_at_uses_total() {
+        let spec: Pagination = serde_json::from_value(json!({
+            "style": "startAt", "items": "issues", "pageSize": 2
+        }))
+        .unwrap();
+        let mut pager = Paginator::new(&spec);
+        assert_eq!(
+            pager.first_page(),
+            vec![("startAt".into(), "0".into()), ("maxResults".into(), "2".into())]
+        );
+        let next = pager.absorb(&json!({"issues": [1, 2], "total": 3}), None);
+        assert_eq!(
+            next,
+            Some(NextPage::Query(vec![("startAt".into(), "2".into()), ("maxResults".into(), "2".into())]))
+        );
+        assert_eq!(pager.absorb(&json!({"issues": [3], "total": 3}), None), None);
+        assert!(!pager.truncated());
+        assert_eq!(pager.pages(), 2);
+    }
+
+    #[test]
+    fn test_offset_and_link() {
+        let spec: Pagination =
+            serde_json::from_value(json!({"style": "offset", "items": "$", "pageSize": 2})).unwrap();
+        let mut pager = Paginator::new(&spec);
+        assert!(pager.absorb(&json!([1, 2]), None).is_some());
+        assert_eq!(pager.absorb(&json!([3]), None), None);
+
+        let header = r#"<https://api.example.com/items?page=1>; rel="prev", <https://api.example.com/items?page=3>; rel="next""#;
+        assert_eq!(next_link(header).as_deref(), Some("https://api.example.com/items?page=3"));
+        assert_eq!(next_link(r#"<https://x/1>; rel="last""#), None);
+    }
+}
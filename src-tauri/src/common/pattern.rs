@@ -0,0 +1,58 @@
+//! Glob Patterns
+//!
+//! Minimal glob matching for policy and config rules: `*` matches any run of
+//! characters (including `.` and `/`), `?` matches exactly one character.
+//! Everything else matches literally.
+
+/// Match `text` against a glob `pattern`
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let p: Vec<char> = pattern.chars().collect();
+    let t: Vec<char> = text.chars().collect();
+
+    let (mut pi, mut ti) = (0, 0);
+    // Position of the last `*` in the pattern and the text index it was tried at
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while ti < t.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == t[ti]) {
+            pi += 1;
+            ti += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            backtrack = Some((pi, ti));
+            pi += 1;
+        } else if let Some((star, matched)) = backtrack {
+            // Let the last `*` swallow one more character
+            pi = star + 1;
+            ti = matched + 1;
+            backtrack = Some((star, ti));
+        } else {
+            return false;
+        }
+    }
+
+    p[pi..].iter().all(|&c| c == '*')
+}
+
+/// Whether any pattern in `patterns` matches `text`
+pub fn any_match<S: AsRef<str>>(patterns: &[S], text: &str) -> bool {
+    patterns.iter().any(|p| glob_match(p.as_ref(), text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("*", "gmail.send"));
+        assert!(glob_match("gmail.*", "gmail.send"));
+        assert!(!glob_match("gmail.*", "gcalendar.list"));
+        assert!(glob_match("*.delete*", "contacts.deleteAll"));
+        assert!(glob_match("/Applications/*.app/Contents/MacOS/*", "/Applications/Agent.app/Contents/MacOS/agent"));
+        assert!(glob_match("google:?e", "google:me"));
+        assert!(!glob_match("google:me", "google:me2"));
+        assert!(glob_match("a*b*c", "aXbYbZc"));
+        assert!(!glob_match("", "x"));
+        assert!(glob_match("", ""));
+    }
+}
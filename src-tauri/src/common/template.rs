@@ -0,0 +1,387 @@
+//! Binding Templates
+//!
+//! Expression language used by proxy tool bindings to build requests from
+//! params and to pick results out of responses.
+//!
+//! **Paths** address into JSON: `user.id`, `items[0].name`, `items[-1]`,
+//! `items[*].id` (wildcard, collects an array). A leading `$` or `$.` is
+//! accepted and ignored, so `$.data.items` and `data.items` are the same.
+//!
+//! **Placeholders** embed a path in a template: `{user.id}`. Filters follow
+//! a `|`:
+//!
+//! - `default:<value>` — used when the path is missing or null (`<value>` is
+//!   parsed as JSON when it can be, e.g. `default:50`, else taken as text)
+//! - `json` — insert the value as JSON text
+//! - `join:<sep>` — join an array of scalars
+//! - `url` — percent-encode the value
+//!
+//! In URL paths every value is percent-encoded as a path segment unless the
+//! placeholder starts with `+` (`{+name}` keeps `/` in resource names).
+//! Braces whose contents aren't a valid expression are left as text.
+
+use serde_json::{Map, Value};
+
+/// One step of a path expression
+#[derive(Debug, Clone, PartialEq)]
+pub enum Segment {
+    Key(String),
+    /// Array index; negative counts from the end
+    Index(i64),
+    /// Every element of an array (or value of an object)
+    Wildcard,
+}
+
+/// Parse a path such as `$.data.items[0].id`
+pub fn parse_path(path: &str) -> Result<Vec<Segment>, String> {
+    let mut rest = path.trim();
+    rest = rest.strip_prefix('$').unwrap_or(rest);
+    rest = rest.strip_prefix('.').unwrap_or(rest);
+
+    let mut segments = Vec::new();
+    let mut key = String::new();
+    let mut chars = rest.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '.' => {
+                push_key(&mut segments, &mut key, path)?;
+            }
+            '[' => {
+                if !key.is_empty() {
+                    push_key(&mut segments, &mut key, path)?;
+                }
+                let mut inner = String::new();
+                loop {
+                    match chars.next() {
+                        Some(']') => break,
+                        Some(c) => inner.push(c),
+                        None => return Err(format!("Unclosed '[' in path '{}'", path)),
+                    }
+                }
+                let inner = inner.trim();
+                if inner == "*" {
+                    segments.push(Segment::Wildcard);
+                } else {
+                    let index = inner
+                        .parse::<i64>()
+                        .map_err(|_| format!("Invalid index '[{}]' in path '{}'", inner, path))?;
+                    segments.push(Segment::Index(index));
+                }
+                // A '.' directly after ']' just separates the next key
+                if chars.peek() == Some(&'.') {
+                    chars.next();
+                }
+            }
+            c if c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '*' => key.push(c),
+            c => return Err(format!("Unexpected '{}' in path '{}'", c, path)),
+        }
+    }
+    if !key.is_empty() {
+        push_key(&mut segments, &mut key, path)?;
+    }
+    Ok(segments)
+}
+
+fn push_key(segments: &mut Vec<Segment>, key: &mut String, path: &str) -> Result<(), String> {
+    match key.as_str() {
+        "" => return Err(format!("Empty segment in path '{}'", path)),
+        "*" => segments.push(Segment::Wildcard),
+        k if k.contains('*') => return Err(format!("Invalid key '{}' in path '{}'", k, path)),
+        k => segments.push(Segment::Key(k.to_string())),
+    }
+    key.clear();
+    Ok(())
+}
+
+/// Resolve parsed segments against a value. Wildcards yield an array of
+/// whatever the remaining segments select from each element (misses are
+/// skipped).
+pub fn select(value: &Value, segments: &[Segment]) -> Option<Value> {
+    let Some((first, rest)) = segments.split_first() else {
+        return Some(value.clone());
+    };
+
+    match first {
+        Segment::Key(key) => select(value.get(key)?, rest),
+        Segment::Index(index) => {
+            let items = value.as_array()?;
+            let i = if *index < 0 { items.len() as i64 + index } else { *index };
+            select(items.get(usize::try_from(i).ok()?)?, rest)
+        }
+        Segment::Wildcard => {
+            let children: Vec<&Value> = match value {
+                Value::Array(items) => items.iter().collect(),
+                Value::Object(obj) => obj.values().collect(),
+                _ => return None,
+            };
+            Some(Value::Array(
+                children.into_iter().filter_map(|child| select(child, rest)).collect(),
+            ))
+        }
+    }
+}
+
+/// Evaluate a path expression against a value (`None` if it misses or
+/// doesn't parse)
+pub fn extract(value: &Value, path: &str) -> Option<Value> {
+    select(value, &parse_path(path).ok()?)
+}
+
+// ── Placeholders ────────────────────────────────────────────────────────────
+
+/// How substituted values are escaped in string templates
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Escape {
+    None,
+    /// Percent-encode as a URL path segment (unless `{+...}`)
+    PathSegment,
+}
+
+#[derive(Debug, Clone)]
+enum Filter {
+    Default(Value),
+    Json,
+    Join(String),
+    Url,
+}
+
+/// A parsed `{...}` placeholder
+#[derive(Debug, Clone)]
+struct Placeholder {
+    source: String,
+    raw: bool,
+    path: Vec<Segment>,
+    filters: Vec<Filter>,
+}
+
+impl Placeholder {
+    fn parse(inner: &str) -> Option<Self> {
+        let mut parts = inner.split('|');
+        let expr = parts.next()?.trim();
+        let (raw, expr) = match expr.strip_prefix('+') {
+            Some(rest) => (true, rest),
+            None => (false, expr),
+        };
+        if expr.is_empty() {
+            return None;
+        }
+        let path = parse_path(expr).ok()?;
+
+        let mut filters = Vec::new();
+        for filter in parts {
+            let filter = filter.trim();
+            let (name, arg) = match filter.split_once(':') {
+                Some((name, arg)) => (name.trim(), Some(arg)),
+                None => (filter, None),
+            };
+            filters.push(match (name, arg) {
+                ("default", Some(arg)) => Filter::Default(
+                    serde_json::from_str(arg.trim()).unwrap_or_else(|_| Value::String(arg.to_string())),
+                ),
+                ("json", None) => Filter::Json,
+                ("join", arg) => Filter::Join(arg.unwrap_or(",").to_string()),
+                ("url", None) => Filter::Url,
+                _ => return None,
+            });
+        }
+
+        Some(Self {
+            source: format!("{{{}}}", inner),
+            raw,
+            path,
+            filters,
+        })
+    }
+
+    /// The value the placeholder stands for (`None` = missing)
+    fn resolve(&self, params: &Value) -> Option<Value> {
+        let mut value = select(params, &self.path).filter(|v| !v.is_null());
+        for filter in &self.filters {
+            value = match (filter, value) {
+                (Filter::Default(default), None) => Some(default.clone()),
+                (Filter::Json, Some(v)) => Some(Value::String(v.to_string())),
+                (Filter::Join(sep), Some(Value::Array(items))) => Some(Value::String(
+                    items.iter().map(scalar_text).collect::<Vec<_>>().join(sep),
+                )),
+                (Filter::Url, Some(v)) => Some(Value::String(urlencoding::encode(&scalar_text(&v)).into_owned())),
+                (_, v) => v,
+            };
+        }
+        value
+    }
+}
+
+/// Text form of a value inside a string template
+fn scalar_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+/// Split a template into literal text and placeholders
+fn tokens(template: &str) -> Vec<Result<&str, Placeholder>> {
+    let mut out = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let Some(len) = rest[start..].find('}') else {
+            break;
+        };
+        let inner = &rest[start + 1..start + len];
+        match Placeholder::parse(inner) {
+            Some(placeholder) => {
+                if start > 0 {
+                    out.push(Ok(&rest[..start]));
+                }
+                out.push(Err(placeholder));
+            }
+            None => out.push(Ok(&rest[..=start + len])),
+        }
+        rest = &rest[start + len + 1..];
+    }
+    if !rest.is_empty() {
+        out.push(Ok(rest));
+    }
+    out
+}
+
+/// Result of rendering a string template
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rendered {
+    pub text: String,
+    /// Placeholders (as written) that had no value and no default
+    pub missing: Vec<String>,
+}
+
+/// Substitute placeholders in a string. Missing values render as empty and
+/// are reported in [`Rendered::missing`].
+pub fn render(template: &str, params: &Value, escape: Escape) -> Rendered {
+    let mut rendered = Rendered {
+        text: String::new(),
+        missing: Vec::new(),
+    };
+    for token in tokens(template) {
+        match token {
+            Ok(literal) => rendered.text.push_str(literal),
+            Err(placeholder) => match placeholder.resolve(params) {
+                Some(value) => {
+                    let text = scalar_text(&value);
+                    match escape {
+                        Escape::PathSegment if !placeholder.raw => {
+                            rendered.text.push_str(&urlencoding::encode(&text));
+                        }
+                        _ => rendered.text.push_str(&text),
+                    }
+                }
+                None => rendered.missing.push(placeholder.source),
+            },
+        }
+    }
+    rendered
+}
+
+/// Render a JSON body template. A string that is exactly one placeholder is
+/// replaced by the value itself (numbers stay numbers, objects stay
+/// objects); other strings are interpolated as text. Object fields and
+/// array items whose placeholder is missing, null or empty are omitted.
+/// Returns `None` if the whole template rendered empty.
+pub fn render_json(template: &Value, params: &Value) -> Option<Value> {
+    match template {
+        Value::String(s) => {
+            let mut parts = tokens(s);
+            if parts.len() == 1 {
+                if let Some(Err(placeholder)) = parts.pop() {
+                    return placeholder.resolve(params).filter(|v| !is_empty(v));
+                }
+            }
+            Some(Value::String(render(s, params, Escape::None).text))
+        }
+        Value::Array(items) if !items.is_empty() => {
+            let rendered: Vec<Value> = items.iter().filter_map(|item| render_json(item, params)).collect();
+            (!rendered.is_empty()).then_some(Value::Array(rendered))
+        }
+        Value::Object(obj) if !obj.is_empty() => {
+            let rendered: Map<String, Value> = obj
+                .iter()
+                .filter_map(|(key, value)| render_json(value, params).map(|v| (key.clone(), v)))
+                .collect();
+            (!rendered.is_empty()).then_some(Value::Object(rendered))
+        }
+        other => Some(other.clone()),
+    }
+}
+
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::String(s) => s.is_empty(),
+        Value::Array(items) => items.is_empty(),
+        Value::Object(obj) => obj.is_empty(),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_extract_paths() {
+        let body = json!({"data": {"items": [{"id": 1, "tags": ["a"]}, {"id": 2}]}});
+        assert_eq!(extract(&body, "$.data.items[1].id"), Some(json!(2)));
+        assert_eq!(extract(&body, "data.items[-1].id"), Some(json!(2)));
+        assert_eq!(extract(&body, "data.items[*].id"), Some(json!([1, 2])));
+        assert_eq!(extract(&body, "data.items.*.tags[0]"), Some(json!(["a"])));
+        assert_eq!(extract(&body, "$"), Some(body.clone()));
+        assert_eq!(extract(&body, "data.missing"), None);
+        assert!(parse_path("items[x]").is_err());
+    }
+
+    #[test]
+    fn test_render_path_segments() {
+        let params = json!({"user": {"id": "a b/c"}, "name": "projects/p1", "page": 2});
+        let r = render("/users/{user.id}/{+name}?p={page}", &params, Escape::PathSegment);
+        assert_eq!(r.text, "/users/a%20b%2Fc/projects/p1?p=2");
+        assert!(r.missing.is_empty());
+
+        let r = render("/users/{userId}/x", &params, Escape::PathSegment);
+        assert_eq!(r.missing, vec!["{userId}"]);
+
+        // Not an expression: left alone
+        let r = render("{credential:api:key} {limit|default:50}", &params, Escape::None);
+        assert_eq!(r.text, "{credential:api:key} 50");
+    }
+
+    #[test]
+    fn test_render_json_preserves_types_and_omits_empty() {
+        let template = json!({
+            "summary": "{title}",
+            "attendees": "{emails}",
+            "count": "{count|default:10}",
+            "note": "Created by {user.name}",
+            "optional": "{missing}",
+            "nested": {"only": "{missing}"},
+            "labels": ["{first}", "{missing}"],
+            "csv": "{emails|join:;}"
+        });
+        let params = json!({
+            "title": "Standup",
+            "emails": ["a@x", "b@x"],
+            "user": {"name": "Ana"},
+            "first": "inbox"
+        });
+        assert_eq!(
+            render_json(&template, &params),
+            Some(json!({
+                "summary": "Standup",
+                "attendees": ["a@x", "b@x"],
+                "count": 10,
+                "note": "Created by Ana",
+                "labels": ["inbox"],
+                "csv": "a@x;b@x"
+            }))
+        );
+    }
+}
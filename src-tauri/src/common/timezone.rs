@@ -0,0 +1,402 @@
+//! IANA Time Zones
+//!
+//! Resolves zone names like `Europe/Dublin` against the system tz database
+//! (`/usr/share/zoneinfo`, or `$TZDIR`) by reading its compiled TZif files:
+//! the explicit transitions first, then the POSIX TZ rule in the file's
+//! footer for times past the last one.
+
+use chrono::{DateTime, Datelike, FixedOffset, NaiveDate, NaiveDateTime};
+use std::path::PathBuf;
+
+const ZONEINFO_DIR: &str = "/usr/share/zoneinfo";
+/// TZif header: magic, version, reserved, then six big-endian counts
+const HEADER_LEN: usize = 44;
+
+/// A time zone's UTC offsets over time
+#[derive(Debug, Clone)]
+pub struct Zone {
+    /// (UTC seconds, offset in seconds from then on), ascending
+    transitions: Vec<(i64, i32)>,
+    /// Offset before the first transition
+    initial: i32,
+    /// Rule for times after the last transition
+    rule: Option<PosixRule>,
+}
+
+impl Zone {
+    /// Load an IANA zone by name
+    pub fn load(name: &str) -> Result<Self, String> {
+        let valid = !name.is_empty()
+            && name.split('/').all(|part| !part.is_empty() && part != "." && part != "..")
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '/' | '_' | '-' | '+'));
+        if !valid {
+            return Err(format!("Invalid time zone '{}'", name));
+        }
+        let dir = std::env::var_os("TZDIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from(ZONEINFO_DIR));
+        let data = std::fs::read(dir.join(name)).map_err(|_| format!("Unknown time zone '{}'", name))?;
+        parse_tzif(&data).ok_or_else(|| format!("Unreadable time zone data for '{}'", name))
+    }
+
+    /// A zone that is always at `offset`
+    pub fn fixed(offset: FixedOffset) -> Self {
+        Self {
+            transitions: Vec::new(),
+            initial: offset.local_minus_utc(),
+            rule: None,
+        }
+    }
+
+    /// `t` with this zone's offset at that instant
+    pub fn localize<Tz: chrono::TimeZone>(&self, t: &DateTime<Tz>) -> DateTime<FixedOffset> {
+        t.with_timezone(&self.offset_at(t.timestamp()))
+    }
+
+    /// The instant a wall-clock time in this zone names. An ambiguous time
+    /// (clocks going back) resolves to the earlier instant, a skipped one
+    /// (clocks going forward) to the same distance past the jump.
+    pub fn resolve(&self, local: NaiveDateTime) -> DateTime<FixedOffset> {
+        let wall = local.and_utc().timestamp();
+        // Zones never change offset twice within a day
+        let before = self.offset_secs(wall - 86_400);
+        let after = self.offset_secs(wall + 86_400);
+        let utc = [before, after]
+            .into_iter()
+            .map(|offset| wall - i64::from(offset))
+            .filter(|&utc| i64::from(self.offset_secs(utc)) == wall - utc)
+            .min()
+            .unwrap_or(wall - i64::from(before));
+        DateTime::from_timestamp(utc, local.and_utc().timestamp_subsec_nanos())
+            .unwrap_or_else(|| local.and_utc())
+            .with_timezone(&self.offset_at(utc))
+    }
+
+    fn offset_at(&self, t: i64) -> FixedOffset {
+        FixedOffset::east_opt(self.offset_secs(t)).unwrap_or_else(|| FixedOffset::east_opt(0).unwrap())
+    }
+
+    fn offset_secs(&self, t: i64) -> i32 {
+        let n = self.transitions.partition_point(|&(at, _)| at <= t);
+        if n == self.transitions.len() {
+            if let Some(rule) = &self.rule {
+                return rule.offset_at(t);
+            }
+        }
+        match n {
+            0 => self.initial,
+            n => self.transitions[n - 1].1,
+        }
+    }
+}
+
+// ── POSIX TZ rules ──────────────────────────────────────────────────────────
+
+/// A POSIX TZ string such as `CET-1CEST,M3.5.0,M10.5.0/3`
+#[derive(Debug, Clone)]
+struct PosixRule {
+    std_offset: i32,
+    dst: Option<DstRule>,
+}
+
+#[derive(Debug, Clone)]
+struct DstRule {
+    offset: i32,
+    /// Switch to DST at this wall time (standard time)
+    start: (RuleDate, i32),
+    /// Switch back at this wall time (daylight time)
+    end: (RuleDate, i32),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum RuleDate {
+    /// `Mm.w.d`: weekday `d` (0 = Sunday) of week `w` (5 = last) of month `m`
+    Month(u32, u32, u32),
+    /// `Jn`: day 1-365, February 29 never counted
+    Julian(u32),
+    /// `n`: zero-based day of the year
+    Day(u32),
+}
+
+impl RuleDate {
+    fn date(self, year: i32) -> Option<NaiveDate> {
+        match self {
+            Self::Month(month, week, weekday) => {
+                let first = NaiveDate::from_ymd_opt(year, month, 1)?;
+                let first_match = 1 + (weekday + 7 - first.weekday().num_days_from_sunday()) % 7;
+                let mut day = first_match + (week.clamp(1, 5) - 1) * 7;
+                while NaiveDate::from_ymd_opt(year, month, day).is_none() {
+                    day -= 7;
+                }
+                NaiveDate::from_ymd_opt(year, month, day)
+            }
+            Self::Julian(n) => {
+                let leap = NaiveDate::from_ymd_opt(year, 2, 29).is_some();
+                let ordinal = if leap && n >= 60 { n + 1 } else { n };
+                NaiveDate::from_yo_opt(year, ordinal)
+            }
+            Self::Day(n) => NaiveDate::from_yo_opt(year, n + 1),
+        }
+    }
+
+    /// UTC seconds of this date's wall time `time`, in a zone at `offset`
+    fn instant(self, year: i32, time: i32, offset: i32) -> Option<i64> {
+        let midnight = self.date(year)?.and_hms_opt(0, 0, 0)?.and_utc().timestamp();
+        Some(midnight + i64::from(time) - i64::from(offset))
+    }
+}
+
+impl PosixRule {
+    fn offset_at(&self, t: i64) -> i32 {
+        let Some(dst) = &self.dst else {
+            return self.std_offset;
+        };
+        let Some(year) = DateTime::from_timestamp(t + i64::from(self.std_offset), 0).map(|d| d.year()) else {
+            return self.std_offset;
+        };
+        let (Some(start), Some(end)) = (
+            dst.start.0.instant(year, dst.start.1, self.std_offset),
+            dst.end.0.instant(year, dst.end.1, dst.offset),
+        ) else {
+            return self.std_offset;
+        };
+        // Southern-hemisphere (and negative-DST) rules start after they end
+        let in_dst = if start < end {
+            start <= t && t < end
+        } else {
+            !(end <= t && t < start)
+        };
+        if in_dst {
+            dst.offset
+        } else {
+            self.std_offset
+        }
+    }
+}
+
+fn parse_posix(mut s: &str) -> Option<PosixRule> {
+    let s = &mut s;
+    zone_name(s)?;
+    // POSIX offsets count west of UTC
+    let std_offset = -clock(s)?;
+    if s.is_empty() {
+        return Some(PosixRule { std_offset, dst: None });
+    }
+
+    zone_name(s)?;
+    let offset = if s.starts_with(',') || s.is_empty() {
+        std_offset + 3600
+    } else {
+        -clock(s)?
+    };
+    let change = |s: &mut &str| -> Option<(RuleDate, i32)> {
+        *s = s.strip_prefix(',')?;
+        let date = rule_date(s)?;
+        let time = match s.strip_prefix('/') {
+            Some(rest) => {
+                *s = rest;
+                clock(s)?
+            }
+            None => 2 * 3600,
+        };
+        Some((date, time))
+    };
+    let start = change(s)?;
+    let end = change(s)?;
+    if !s.is_empty() {
+        return None;
+    }
+    Some(PosixRule {
+        std_offset,
+        dst: Some(DstRule { offset, start, end }),
+    })
+}
+
+/// `CET` or `<+0330>`
+fn zone_name(s: &mut &str) -> Option<()> {
+    let len = match s.strip_prefix('<') {
+        Some(rest) => rest.find('>')? + 2,
+        None => s.find(|c: char| !c.is_ascii_alphabetic()).unwrap_or(s.len()),
+    };
+    if len < 3 {
+        return None;
+    }
+    *s = &s[len..];
+    Some(())
+}
+
+/// `[+-]h[h][:mm[:ss]]` in seconds
+fn clock(s: &mut &str) -> Option<i32> {
+    let sign = match s.chars().next()? {
+        '-' => -1,
+        _ => 1,
+    };
+    *s = s.strip_prefix(['+', '-']).unwrap_or(s);
+    let mut secs = number(s)? as i32 * 3600;
+    for unit in [60, 1] {
+        match s.strip_prefix(':') {
+            Some(rest) => {
+                *s = rest;
+                secs += number(s)? as i32 * unit;
+            }
+            None => break,
+        }
+    }
+    Some(sign * secs)
+}
+
+fn rule_date(s: &mut &str) -> Option<RuleDate> {
+    if let Some(rest) = s.strip_prefix('M') {
+        *s = rest;
+        let month = number(s)?;
+        *s = s.strip_prefix('.')?;
+        let week = number(s)?;
+        *s = s.strip_prefix('.')?;
+        let weekday = number(s)?;
+        return (1..=12).contains(&month).then_some(RuleDate::Month(month, week, weekday % 7));
+    }
+    if let Some(rest) = s.strip_prefix('J') {
+        *s = rest;
+        return number(s).filter(|n| (1..=365).contains(n)).map(RuleDate::Julian);
+    }
+    number(s).filter(|n| *n <= 365).map(RuleDate::Day)
+}
+
+fn number(s: &mut &str) -> Option<u32> {
+    let len = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let value = s[..len].parse().ok()?;
+    *s = &s[len..];
+    Some(value)
+}
+
+// ── TZif files ──────────────────────────────────────────────────────────────
+
+/// Parse a TZif file (RFC 8536), preferring the 64-bit block of version 2+
+fn parse_tzif(data: &[u8]) -> Option<Zone> {
+    let counts = tzif_counts(data)?;
+    let (data, counts, time_size) = if data[4] >= b'2' {
+        let v2 = data.get(HEADER_LEN + block_len(&counts, 4)..)?;
+        (v2, tzif_counts(v2)?, 8)
+    } else {
+        (data, counts, 4)
+    };
+    let (times, types) = (counts[3], counts[4]);
+    let body = data.get(HEADER_LEN..)?;
+
+    let be = |at: usize, len: usize| -> Option<i64> {
+        let bytes = body.get(at..at + len)?;
+        Some(match len {
+            8 => i64::from_be_bytes(bytes.try_into().ok()?),
+            _ => i64::from(i32::from_be_bytes(bytes.try_into().ok()?)),
+        })
+    };
+    let indexes_at = times * time_size;
+    let types_at = indexes_at + times;
+    let offsets = (0..types)
+        .map(|i| be(types_at + i * 6, 4).map(|offset| offset as i32))
+        .collect::<Option<Vec<i32>>>()?;
+    let transitions = (0..times)
+        .map(|i| {
+            let at = be(i * time_size, time_size)?;
+            let offset = *offsets.get(usize::from(*body.get(indexes_at + i)?))?;
+            Some((at, offset))
+        })
+        .collect::<Option<Vec<_>>>()?;
+
+    let rule = match time_size {
+        8 => body
+            .get(block_len(&counts, 8)..)
+            .and_then(|footer| std::str::from_utf8(footer).ok())
+            .and_then(|footer| footer.strip_prefix('\n')?.split('\n').next())
+            .filter(|tz| !tz.is_empty())
+            .and_then(parse_posix),
+        _ => None,
+    };
+    Some(Zone {
+        transitions,
+        initial: *offsets.first()?,
+        rule,
+    })
+}
+
+/// isutcnt, isstdcnt, leapcnt, timecnt, typecnt, charcnt
+fn tzif_counts(data: &[u8]) -> Option<[usize; 6]> {
+    if data.get(..4)? != b"TZif" {
+        return None;
+    }
+    let mut counts = [0; 6];
+    for (i, count) in counts.iter_mut().enumerate() {
+        let at = 20 + i * 4;
+        *count = u32::from_be_bytes(data.get(at..at + 4)?.try_into().ok()?) as usize;
+    }
+    Some(counts)
+}
+
+/// Length of the data block following a header
+fn block_len(counts: &[usize; 6], time_size: usize) -> usize {
+    let [isut, isstd, leap, times, types, chars] = *counts;
+    times * time_size + times + types * 6 + chars + leap * (time_size + 4) + isstd + isut
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn zone(rule: &str) -> Zone {
+        Zone {
+            transitions: Vec::new(),
+            initial: 0,
+            rule: Some(parse_posix(rule).unwrap()),
+        }
+    }
+
+    fn at(zone: &Zone, local: &str) -> String {
+        let local = NaiveDateTime::parse_from_str(local, "%Y-%m-%d %H:%M").unwrap();
+        zone.resolve(local).to_rfc3339()
+    }
+
+    #[test]
+    fn test_posix_rule_follows_dst() {
+        let berlin = zone("CET-1CEST,M3.5.0,M10.5.0/3");
+        assert_eq!(at(&berlin, "2026-03-27 09:00"), "2026-03-27T09:00:00+01:00");
+        assert_eq!(at(&berlin, "2026-03-30 09:00"), "2026-03-30T09:00:00+02:00");
+        // Skipped hour lands past the jump; the repeated one resolves early
+        assert_eq!(at(&berlin, "2026-03-29 02:30"), "2026-03-29T03:30:00+02:00");
+        assert_eq!(at(&berlin, "2026-10-25 02:30"), "2026-10-25T02:30:00+02:00");
+
+        // Negative DST (winter time is the "daylight" one) and the south
+        let dublin = zone("IST-1GMT0,M10.5.0,M3.5.0/1");
+        assert_eq!(at(&dublin, "2026-01-15 09:00"), "2026-01-15T09:00:00+00:00");
+        assert_eq!(at(&dublin, "2026-07-15 09:00"), "2026-07-15T09:00:00+01:00");
+        let sydney = zone("AEST-10AEDT,M10.1.0,M4.1.0/3");
+        assert_eq!(at(&sydney, "2026-01-15 09:00"), "2026-01-15T09:00:00+11:00");
+
+        let kolkata = zone("<+0530>-5:30");
+        assert_eq!(at(&kolkata, "2026-07-15 09:00"), "2026-07-15T09:00:00+05:30");
+    }
+
+    #[test]
+    fn test_tzif_transitions_then_footer() {
+        let mut data = b"TZif2".to_vec();
+        data.resize(HEADER_LEN, 0);
+        // v1 block left empty; v2 block: to type 1 (+01:00), then back to type 0
+        let mut v2 = b"TZif2".to_vec();
+        v2.resize(HEADER_LEN, 0);
+        v2[32..36].copy_from_slice(&2u32.to_be_bytes()); // timecnt
+        v2[36..40].copy_from_slice(&2u32.to_be_bytes()); // typecnt
+        v2.extend_from_slice(&1_000_000_000i64.to_be_bytes());
+        v2.extend_from_slice(&1_500_000_000i64.to_be_bytes());
+        v2.extend_from_slice(&[1, 0]);
+        v2.extend_from_slice(&[0, 0, 0, 0, 0, 0]);
+        v2.extend_from_slice(&3600i32.to_be_bytes());
+        v2.extend_from_slice(&[0, 0]);
+        v2.extend_from_slice(b"\nCET-1CEST,M3.5.0,M10.5.0/3\n");
+        data.extend_from_slice(&v2);
+
+        let zone = parse_tzif(&data).unwrap();
+        assert_eq!(zone.offset_secs(0), 0);
+        assert_eq!(zone.offset_secs(1_200_000_000), 3600);
+        assert_eq!(at(&zone, "2026-07-15 09:00"), "2026-07-15T09:00:00+02:00");
+        assert!(Zone::load("../etc/passwd").is_err());
+    }
+}
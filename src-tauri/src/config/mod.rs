@@ -22,7 +22,7 @@ fn read_json_file<T: serde::de::DeserializeOwned>(path: &PathBuf, label: &str) -
 }
 
 /// Write a JSON value to a file, creating a `.bak` backup if the file already exists.
-/// Ensures parent directories exist.
+/// The write is atomic, so a crash leaves either the old or the new file.
 fn write_json_file_with_backup(path: &PathBuf, value: &impl Serialize, label: &str) -> Result<(), String> {
     if path.exists() {
         let backup_path = path.with_extension("json.bak");
@@ -30,15 +30,9 @@ fn write_json_file_with_backup(path: &PathBuf, value: &impl Serialize, label: &s
             .map_err(|e| format!("Failed to create {} backup: {}", label, e))?;
     }
 
-    if let Some(parent) = path.parent() {
-        std::fs::create_dir_all(parent)
-            .map_err(|e| format!("Failed to create {} directory: {}", label, e))?;
-    }
-
     let content = serde_json::to_string_pretty(value)
         .map_err(|e| format!("Failed to serialize {}: {}", label, e))?;
-    std::fs::write(path, content)
-        .map_err(|e| format!("Failed to write {}: {}", label, e))
+    crate::common::write_atomic(path, content.as_bytes(), None)
 }
 
 fn get_openclaw_config_path() -> PathBuf {
@@ -2,18 +2,150 @@
 //!
 //! Provides methods for interacting with Google Calendar API:
 //! - List calendars
-//! - List/get events
-//! - Create/update/delete events
+//! - List/get events, and expand recurring events into instances
+//! - Create/update/delete events with recurrence, attendees, reminders
+//!   and Google Meet conferences
+//! - RSVP to invitations
+//! - Query free/busy across calendars and find open slots
 //!
 //! All methods use the authenticated GoogleClient with Tier 1 proxy mode.
 
 use super::common::{extract_array, google_api_wrapper};
+use crate::common::timezone::Zone;
+use chrono::{DateTime, Datelike, Duration, FixedOffset, NaiveTime, Weekday};
 use serde_json::{json, Value};
 use tracing::{debug, info};
 
 const CALENDAR_API_BASE: &str = "https://www.googleapis.com/calendar/v3";
 
-google_api_wrapper!(CalendarApi);
+/// Accepted values of `sendUpdates` (who Google emails about a change)
+pub const SEND_UPDATES: &[&str] = &["all", "externalOnly", "none"];
+/// Accepted RSVP responses
+pub const RESPONSES: &[&str] = &["accepted", "declined", "tentative"];
+/// Google's limit on reminder overrides per event
+pub const MAX_REMINDERS: usize = 5;
+
+/// Event fields for create and update; `None` leaves a field unchanged
+#[derive(Debug, Default)]
+pub struct EventFields<'a> {
+    pub summary: Option<&'a str>,
+    pub start: Option<&'a str>,
+    pub end: Option<&'a str>,
+    /// IANA zone for timed start/end (required by Google for recurring events)
+    pub time_zone: Option<&'a str>,
+    pub location: Option<&'a str>,
+    pub description: Option<&'a str>,
+    /// Replaces the recurrence (see [`recurrence_lines`])
+    pub recurrence: Option<Vec<String>>,
+    /// EXDATE lines appended to the (new or existing) recurrence
+    pub exdates: Vec<String>,
+    /// Replaces the attendee list; people already invited keep their response
+    pub attendees: Option<Vec<Attendee>>,
+    pub reminders: Option<Reminders>,
+    /// Attach a new Google Meet conference
+    pub add_meet: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Attendee {
+    pub email: String,
+    pub display_name: Option<String>,
+    pub optional: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Reminders {
+    /// The calendar's default reminders
+    Default,
+    /// `(method, minutes before)` pairs; an empty list means no reminders
+    Overrides(Vec<(String, u32)>),
+}
+
+impl EventFields<'_> {
+    /// Write the given fields into an event resource
+    fn apply(&self, event: &mut Value) {
+        if let Some(summary) = self.summary {
+            event["summary"] = json!(summary);
+        }
+        if let Some(start) = self.start {
+            event["start"] = parse_datetime(start);
+        }
+        if let Some(end) = self.end {
+            event["end"] = parse_datetime(end);
+        }
+        if let Some(tz) = self.time_zone {
+            for field in ["start", "end"] {
+                if event[field].get("dateTime").is_some() {
+                    event[field]["timeZone"] = json!(tz);
+                }
+            }
+        }
+        if let Some(location) = self.location {
+            event["location"] = json!(location);
+        }
+        if let Some(description) = self.description {
+            event["description"] = json!(description);
+        }
+
+        if let Some(lines) = &self.recurrence {
+            event["recurrence"] = json!(lines);
+        }
+        if !self.exdates.is_empty() {
+            let mut lines: Vec<Value> = extract_array(event, "recurrence");
+            lines.extend(self.exdates.iter().map(|l| json!(l)));
+            event["recurrence"] = Value::Array(lines);
+        }
+
+        if let Some(attendees) = &self.attendees {
+            let existing = extract_array(event, "attendees");
+            let list: Vec<Value> = attendees
+                .iter()
+                .map(|a| {
+                    let mut entry = existing
+                        .iter()
+                        .find(|e| {
+                            e["email"]
+                                .as_str()
+                                .is_some_and(|m| m.eq_ignore_ascii_case(&a.email))
+                        })
+                        .cloned()
+                        .unwrap_or_else(|| json!({ "email": a.email }));
+                    if let Some(name) = &a.display_name {
+                        entry["displayName"] = json!(name);
+                    }
+                    if a.optional {
+                        entry["optional"] = json!(true);
+                    }
+                    entry
+                })
+                .collect();
+            event["attendees"] = Value::Array(list);
+        }
+
+        match &self.reminders {
+            Some(Reminders::Default) => event["reminders"] = json!({ "useDefault": true }),
+            Some(Reminders::Overrides(list)) => {
+                let overrides: Vec<Value> = list
+                    .iter()
+                    .map(|(method, minutes)| json!({ "method": method, "minutes": minutes }))
+                    .collect();
+                event["reminders"] = json!({ "useDefault": false, "overrides": overrides });
+            }
+            None => {}
+        }
+
+        if self.add_meet {
+            event["conferenceData"] = json!({
+                "createRequest": {
+                    "requestId": request_id(),
+                    "conferenceSolutionKey": { "type": "hangoutsMeet" },
+                }
+            });
+        }
+    }
+}
+
+google_api_wrapper!(CalendarApi, "google-calendar");
 
 impl CalendarApi {
     /// List all calendars for the authenticated user
@@ -39,6 +171,8 @@ impl CalendarApi {
     /// * `time_min` - Lower bound (RFC3339 timestamp, e.g., "2026-02-08T00:00:00Z")
     /// * `time_max` - Upper bound (RFC3339 timestamp)
     /// * `max_results` - Maximum number of events to return
+    /// * `single_events` - Expand recurring events into their instances
+    ///   (ordered by start time); otherwise return the recurring events themselves
     ///
     /// # Returns
     /// Array of event objects
@@ -48,6 +182,7 @@ impl CalendarApi {
         time_min: Option<&str>,
         time_max: Option<&str>,
         max_results: Option<usize>,
+        single_events: bool,
     ) -> Result<Vec<Value>, String> {
         info!("Listing events for calendar: {}", calendar_id);
 
@@ -61,12 +196,14 @@ impl CalendarApi {
             query_params.push(("timeMax", max.to_string()));
         }
 
-        // Order by start time
-        query_params.push(("orderBy", "startTime".to_string()));
-        query_params.push(("singleEvents", "true".to_string()));
+        // Order by start time (only possible once recurrences are expanded)
+        if single_events {
+            query_params.push(("orderBy", "startTime".to_string()));
+        }
+        query_params.push(("singleEvents", single_events.to_string()));
 
         let url = format!("{}/calendars/{}/events", CALENDAR_API_BASE, calendar_id);
-        let events = self.client.get_paginated(&url, &query_params, max_results).await?;
+        let events = self.client.get_paginated(&url, &query_params, "items", max_results).await?;
 
         debug!("Retrieved {} events", events.len());
         Ok(events)
@@ -94,86 +231,95 @@ impl CalendarApi {
         self.client.get(&url, &[]).await
     }
 
+    /// Instances of a recurring event
+    ///
+    /// # Arguments
+    /// * `event_id` - ID of the recurring event
+    /// * `time_min`/`time_max` - RFC3339 bounds
+    pub async fn list_instances(
+        &self,
+        calendar_id: &str,
+        event_id: &str,
+        time_min: Option<&str>,
+        time_max: Option<&str>,
+        max_results: Option<usize>,
+    ) -> Result<Vec<Value>, String> {
+        info!("Listing instances of event: {} in calendar: {}", event_id, calendar_id);
+
+        let mut query_params = vec![];
+        if let Some(min) = time_min {
+            query_params.push(("timeMin", min.to_string()));
+        }
+        if let Some(max) = time_max {
+            query_params.push(("timeMax", max.to_string()));
+        }
+
+        let url = format!(
+            "{}/calendars/{}/events/{}/instances",
+            CALENDAR_API_BASE, calendar_id, event_id
+        );
+        let instances = self.client.get_paginated(&url, &query_params, "items", max_results).await?;
+
+        debug!("Retrieved {} instances", instances.len());
+        Ok(instances)
+    }
+
     /// Create a new event
     ///
     /// # Arguments
     /// * `calendar_id` - Calendar ID (use "primary" for user's primary calendar)
-    /// * `summary` - Event title
-    /// * `start` - Start time (RFC3339 timestamp or date-only YYYY-MM-DD)
-    /// * `end` - End time (RFC3339 timestamp or date-only YYYY-MM-DD)
-    /// * `location` - Event location (optional)
-    /// * `description` - Event description (optional)
+    /// * `fields` - Event fields; `summary`, `start` and `end` are required
+    ///   (RFC3339 timestamps or date-only YYYY-MM-DD)
+    /// * `send_updates` - Who to email invitations to (see [`SEND_UPDATES`])
     ///
     /// # Returns
     /// Created event object with generated ID
     pub async fn create_event(
         &self,
         calendar_id: &str,
-        summary: &str,
-        start: &str,
-        end: &str,
-        location: Option<&str>,
-        description: Option<&str>,
+        fields: &EventFields<'_>,
+        send_updates: Option<&str>,
     ) -> Result<Value, String> {
+        let (Some(summary), Some(_), Some(_)) = (fields.summary, fields.start, fields.end) else {
+            return Err("An event needs 'summary', 'start' and 'end'".to_string());
+        };
         info!("Creating event: {} in calendar: {}", summary, calendar_id);
 
-        let mut event = json!({
-            "summary": summary,
-            "start": self.parse_datetime(start),
-            "end": self.parse_datetime(end),
-        });
-
-        if let Some(loc) = location {
-            event["location"] = json!(loc);
-        }
-
-        if let Some(desc) = description {
-            event["description"] = json!(desc);
-        }
+        let mut event = json!({});
+        fields.apply(&mut event);
 
-        let url = format!("{}/calendars/{}/events", CALENDAR_API_BASE, calendar_id);
+        let url = format!(
+            "{}/calendars/{}/events{}",
+            CALENDAR_API_BASE,
+            calendar_id,
+            write_query(send_updates)
+        );
         let response = self.client.post(&url, &event).await?;
 
         info!("Event created successfully");
         Ok(response)
     }
 
-    /// Update an existing event
+    /// Update an existing event, changing only the given fields
     pub async fn update_event(
         &self,
         calendar_id: &str,
         event_id: &str,
-        summary: Option<&str>,
-        start: Option<&str>,
-        end: Option<&str>,
-        location: Option<&str>,
-        description: Option<&str>,
+        fields: &EventFields<'_>,
+        send_updates: Option<&str>,
     ) -> Result<Value, String> {
         info!("Updating event: {} in calendar: {}", event_id, calendar_id);
 
         // First, get the existing event
         let mut event = self.get_event(calendar_id, event_id).await?;
-
-        // Update only the provided fields
-        if let Some(s) = summary {
-            event["summary"] = json!(s);
-        }
-        if let Some(st) = start {
-            event["start"] = self.parse_datetime(st);
-        }
-        if let Some(e) = end {
-            event["end"] = self.parse_datetime(e);
-        }
-        if let Some(loc) = location {
-            event["location"] = json!(loc);
-        }
-        if let Some(desc) = description {
-            event["description"] = json!(desc);
-        }
+        fields.apply(&mut event);
 
         let url = format!(
-            "{}/calendars/{}/events/{}",
-            CALENDAR_API_BASE, calendar_id, event_id
+            "{}/calendars/{}/events/{}{}",
+            CALENDAR_API_BASE,
+            calendar_id,
+            event_id,
+            write_query(send_updates)
         );
         let response = self.client.put(&url, &event).await?;
 
@@ -186,30 +332,242 @@ impl CalendarApi {
         &self,
         calendar_id: &str,
         event_id: &str,
+        send_updates: Option<&str>,
     ) -> Result<(), String> {
         info!("Deleting event: {} from calendar: {}", event_id, calendar_id);
 
-        let url = format!(
+        let mut url = format!(
             "{}/calendars/{}/events/{}",
             CALENDAR_API_BASE, calendar_id, event_id
         );
+        if let Some(send) = send_updates {
+            url.push_str(&format!("?sendUpdates={}", send));
+        }
         self.client.delete(&url).await?;
 
         info!("Event deleted successfully");
         Ok(())
     }
 
-    /// Parse datetime string into Google Calendar format
-    /// Supports both RFC3339 timestamps and date-only formats
-    fn parse_datetime(&self, dt: &str) -> Value {
-        // Check if it's a date-only format (YYYY-MM-DD)
-        if dt.len() == 10 && !dt.contains('T') {
-            json!({ "date": dt })
-        } else {
-            // Assume it's an RFC3339 timestamp
-            json!({ "dateTime": dt })
+    /// Set the user's own response to an event they are invited to
+    ///
+    /// # Arguments
+    /// * `response` - One of [`RESPONSES`]
+    /// * `comment` - Optional note to the organizer
+    pub async fn respond(
+        &self,
+        calendar_id: &str,
+        event_id: &str,
+        response: &str,
+        comment: Option<&str>,
+        send_updates: Option<&str>,
+    ) -> Result<Value, String> {
+        info!("Responding '{}' to event: {}", response, event_id);
+
+        let event = self.get_event(calendar_id, event_id).await?;
+        let mut attendees = extract_array(&event, "attendees");
+        let me = attendees
+            .iter_mut()
+            .find(|a| a["self"].as_bool() == Some(true))
+            .ok_or("You are not an attendee of this event")?;
+        me["responseStatus"] = json!(response);
+        if let Some(note) = comment {
+            me["comment"] = json!(note);
+        }
+
+        let url = format!(
+            "{}/calendars/{}/events/{}{}",
+            CALENDAR_API_BASE,
+            calendar_id,
+            event_id,
+            write_query(send_updates)
+        );
+        self.client.patch(&url, &json!({ "attendees": attendees })).await
+    }
+
+    /// Busy blocks of several calendars (`freebusy.query`)
+    ///
+    /// # Arguments
+    /// * `calendars` - Calendar IDs or email addresses (colleagues' primary calendars)
+    /// * `time_min`/`time_max` - RFC3339 bounds
+    ///
+    /// # Returns
+    /// The API's `calendars` map: ID → `{busy: [{start, end}], errors?}`
+    pub async fn free_busy(
+        &self,
+        calendars: &[String],
+        time_min: &str,
+        time_max: &str,
+        time_zone: Option<&str>,
+    ) -> Result<Value, String> {
+        info!("Querying free/busy for {} calendars", calendars.len());
+
+        let mut body = json!({
+            "timeMin": time_min,
+            "timeMax": time_max,
+            "items": calendars.iter().map(|id| json!({ "id": id })).collect::<Vec<_>>(),
+        });
+        if let Some(tz) = time_zone {
+            body["timeZone"] = json!(tz);
+        }
+
+        let url = format!("{}/freeBusy", CALENDAR_API_BASE);
+        let response = self.client.post(&url, &body).await?;
+        Ok(response.get("calendars").cloned().unwrap_or_else(|| json!({})))
+    }
+}
+
+/// Query string for event writes. Conference data is always round-tripped,
+/// so existing Meet links survive updates and new ones can be requested.
+fn write_query(send_updates: Option<&str>) -> String {
+    match send_updates {
+        Some(send) => format!("?conferenceDataVersion=1&sendUpdates={}", send),
+        None => "?conferenceDataVersion=1".to_string(),
+    }
+}
+
+/// Unique ID for a conference create request
+fn request_id() -> String {
+    use rand::Rng;
+    let bytes: [u8; 12] = rand::rngs::OsRng.gen();
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Parse datetime string into Google Calendar format
+/// Supports both RFC3339 timestamps and date-only formats
+fn parse_datetime(dt: &str) -> Value {
+    // Check if it's a date-only format (YYYY-MM-DD)
+    if dt.len() == 10 && !dt.contains('T') {
+        json!({ "date": dt })
+    } else {
+        // Assume it's an RFC3339 timestamp
+        json!({ "dateTime": dt })
+    }
+}
+
+// ── Recurrence ──────────────────────────────────────────────────────────────
+
+/// Recurrence lines from raw RFC 5545 lines and/or a single rule.
+///
+/// `rrule` may omit the `RRULE:` prefix (`FREQ=WEEKLY;BYDAY=MO`). Raw lines
+/// must be RRULE, EXRULE, RDATE or EXDATE properties.
+pub fn recurrence_lines(lines: &[String], rrule: Option<&str>) -> Result<Vec<String>, String> {
+    let mut out = Vec::new();
+    for line in lines {
+        let name = line.split([':', ';']).next().unwrap_or_default().to_ascii_uppercase();
+        if !matches!(name.as_str(), "RRULE" | "EXRULE" | "RDATE" | "EXDATE") {
+            return Err(format!("Invalid recurrence line '{}': expected RRULE, EXRULE, RDATE or EXDATE", line));
+        }
+        out.push(line.clone());
+    }
+    if let Some(rule) = rrule {
+        let rule = rule.strip_prefix("RRULE:").unwrap_or(rule);
+        if !rule.to_ascii_uppercase().contains("FREQ=") {
+            return Err(format!("Invalid rrule '{}': FREQ is required", rule));
         }
+        out.push(format!("RRULE:{}", rule));
     }
+    Ok(out)
+}
+
+/// EXDATE line skipping one occurrence: a date (`2026-03-02`, all-day
+/// events) or an RFC3339 start time (timed events, matched in UTC)
+pub fn exdate_line(date: &str) -> Result<String, String> {
+    if let Ok(day) = chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d") {
+        return Ok(format!("EXDATE;VALUE=DATE:{}", day.format("%Y%m%d")));
+    }
+    DateTime::parse_from_rfc3339(date)
+        .map(|t| format!("EXDATE:{}", t.with_timezone(&chrono::Utc).format("%Y%m%dT%H%M%SZ")))
+        .map_err(|_| format!("Invalid exdate '{}': expected YYYY-MM-DD or an RFC3339 time", date))
+}
+
+// ── Finding free slots ──────────────────────────────────────────────────────
+
+/// Constraints for [`find_slots`]
+#[derive(Debug, Clone)]
+pub struct SlotSearch {
+    /// Search window
+    pub start: DateTime<FixedOffset>,
+    pub end: DateTime<FixedOffset>,
+    /// Zone whose wall clock the working hours and days follow, so they
+    /// stay put across daylight-saving changes
+    pub zone: Zone,
+    pub duration: Duration,
+    pub work_start: NaiveTime,
+    pub work_end: NaiveTime,
+    pub work_days: Vec<Weekday>,
+    /// Slots start on this grid, counted from `work_start`
+    pub step: Duration,
+    pub max_slots: usize,
+}
+
+/// Busy blocks from a [`CalendarApi::free_busy`] result, across all calendars
+pub fn busy_blocks(calendars: &Value) -> Vec<(DateTime<FixedOffset>, DateTime<FixedOffset>)> {
+    let Some(map) = calendars.as_object() else {
+        return Vec::new();
+    };
+    map.values()
+        .flat_map(|cal| extract_array(cal, "busy"))
+        .filter_map(|b| {
+            let start = DateTime::parse_from_rfc3339(b["start"].as_str()?).ok()?;
+            let end = DateTime::parse_from_rfc3339(b["end"].as_str()?).ok()?;
+            Some((start, end))
+        })
+        .collect()
+}
+
+/// Non-overlapping slots of `duration` within working hours that avoid every
+/// busy block, earliest first, in the search zone's offset at each slot
+pub fn find_slots(
+    search: &SlotSearch,
+    busy: &[(DateTime<FixedOffset>, DateTime<FixedOffset>)],
+) -> Vec<(DateTime<FixedOffset>, DateTime<FixedOffset>)> {
+    let zone = &search.zone;
+    let mut busy: Vec<_> = busy.to_vec();
+    busy.sort();
+
+    let mut slots = Vec::new();
+    if search.duration <= Duration::zero() || search.step <= Duration::zero() {
+        return slots;
+    }
+
+    let mut day = zone.localize(&search.start).date_naive();
+    let last_day = zone.localize(&search.end).date_naive();
+    while day <= last_day && slots.len() < search.max_slots {
+        // Each day's bounds are resolved on its own, in that day's offset
+        let day_start = zone.resolve(day.and_time(search.work_start));
+        let day_end = zone.resolve(day.and_time(search.work_end));
+        let window_end = day_end.min(search.end);
+
+        if search.work_days.contains(&day.weekday()) {
+            let mut candidate = align(search.start.max(day_start), day_start, search.step);
+            while candidate + search.duration <= window_end && slots.len() < search.max_slots {
+                let slot_end = candidate + search.duration;
+                match busy.iter().find(|(b_start, b_end)| *b_start < slot_end && *b_end > candidate) {
+                    Some((_, b_end)) => candidate = align(*b_end, day_start, search.step),
+                    None => {
+                        slots.push((zone.localize(&candidate), zone.localize(&slot_end)));
+                        candidate = align(slot_end, day_start, search.step);
+                    }
+                }
+            }
+        }
+
+        let Some(next) = day.succ_opt() else { break };
+        day = next;
+    }
+    slots
+}
+
+/// Round `t` up onto the `step` grid starting at `origin`
+fn align(t: DateTime<FixedOffset>, origin: DateTime<FixedOffset>, step: Duration) -> DateTime<FixedOffset> {
+    if t <= origin {
+        return origin;
+    }
+    let step_secs = step.num_seconds().max(1);
+    let elapsed = (t - origin).num_seconds();
+    let steps = (elapsed + step_secs - 1) / step_secs;
+    origin + Duration::seconds(steps * step_secs)
 }
 
 #[cfg(test)]
@@ -218,19 +576,110 @@ mod tests {
 
     #[test]
     fn test_parse_datetime_with_timestamp() {
-        let api = CalendarApi::new("test".to_string()).unwrap();
-
-        let result = api.parse_datetime("2026-02-08T15:00:00Z");
+        let result = parse_datetime("2026-02-08T15:00:00Z");
         assert!(result.get("dateTime").is_some());
         assert_eq!(result.get("dateTime").unwrap().as_str().unwrap(), "2026-02-08T15:00:00Z");
     }
 
     #[test]
     fn test_parse_datetime_with_date_only() {
-        let api = CalendarApi::new("test".to_string()).unwrap();
-
-        let result = api.parse_datetime("2026-02-08");
+        let result = parse_datetime("2026-02-08");
         assert!(result.get("date").is_some());
         assert_eq!(result.get("date").unwrap().as_str().unwrap(), "2026-02-08");
     }
+
+    #[test]
+    fn test_recurrence_lines() {
+        let lines = recurrence_lines(&["EXDATE;VALUE=DATE:20260309".to_string()], Some("FREQ=WEEKLY;BYDAY=MO"))
+            .unwrap();
+        assert_eq!(lines, ["EXDATE;VALUE=DATE:20260309", "RRULE:FREQ=WEEKLY;BYDAY=MO"]);
+        assert!(recurrence_lines(&["DTSTART:20260302".to_string()], None).is_err());
+        assert!(recurrence_lines(&[], Some("BYDAY=MO")).is_err());
+
+        assert_eq!(exdate_line("2026-03-09").unwrap(), "EXDATE;VALUE=DATE:20260309");
+        assert_eq!(exdate_line("2026-03-09T09:00:00+01:00").unwrap(), "EXDATE:20260309T080000Z");
+        assert!(exdate_line("next monday").is_err());
+    }
+
+    #[test]
+    fn test_apply_keeps_attendee_responses() {
+        let mut event = json!({
+            "start": {"dateTime": "2026-03-02T09:00:00Z"},
+            "attendees": [{"email": "Ann@example.com", "responseStatus": "accepted"}],
+            "recurrence": ["RRULE:FREQ=DAILY"]
+        });
+        let fields = EventFields {
+            time_zone: Some("Europe/Dublin"),
+            exdates: vec!["EXDATE:20260303T090000Z".to_string()],
+            attendees: Some(vec![
+                Attendee { email: "ann@example.com".into(), display_name: None, optional: false },
+                Attendee { email: "bob@example.com".into(), display_name: None, optional: true },
+            ]),
+            reminders: Some(Reminders::Overrides(vec![("popup".into(), 10)])),
+            add_meet: true,
+            ..Default::default()
+        };
+        fields.apply(&mut event);
+
+        assert_eq!(event["start"]["timeZone"], "Europe/Dublin");
+        assert_eq!(event["recurrence"], json!(["RRULE:FREQ=DAILY", "EXDATE:20260303T090000Z"]));
+        assert_eq!(event["attendees"][0]["responseStatus"], "accepted");
+        assert_eq!(event["attendees"][1], json!({"email": "bob@example.com", "optional": true}));
+        assert_eq!(event["reminders"]["overrides"][0]["minutes"], 10);
+        assert_eq!(event["conferenceData"]["createRequest"]["conferenceSolutionKey"]["type"], "hangoutsMeet");
+    }
+
+    #[test]
+    fn test_find_slots() {
+        let t = |s: &str| DateTime::parse_from_rfc3339(s).unwrap();
+        let search = SlotSearch {
+            // Friday 2026-03-06 10:10 to Monday 2026-03-09 end of day
+            start: t("2026-03-06T10:10:00+01:00"),
+            end: t("2026-03-09T23:00:00+01:00"),
+            zone: Zone::fixed(FixedOffset::east_opt(3600).unwrap()),
+            duration: Duration::minutes(60),
+            work_start: NaiveTime::from_hms_opt(9, 0, 0).unwrap(),
+            work_end: NaiveTime::from_hms_opt(17, 0, 0).unwrap(),
+            work_days: vec![Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri],
+            step: Duration::minutes(30),
+            max_slots: 4,
+        };
+        // Busy blocks in UTC, from two calendars
+        let busy = busy_blocks(&json!({
+            "primary": {"busy": [{"start": "2026-03-06T10:30:00Z", "end": "2026-03-06T14:00:00Z"}]},
+            "bob@example.com": {"busy": [{"start": "2026-03-06T14:30:00Z", "end": "2026-03-06T15:00:00Z"}]}
+        }));
+
+        let slots = find_slots(&search, &busy);
+        let starts: Vec<String> = slots.iter().map(|(s, _)| s.to_rfc3339()).collect();
+        assert_eq!(
+            starts,
+            [
+                "2026-03-06T10:30:00+01:00",
+                // 16:00-17:00 is free; the weekend is skipped
+                "2026-03-06T16:00:00+01:00",
+                "2026-03-09T09:00:00+01:00",
+                "2026-03-09T10:00:00+01:00",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_slots_across_dst() {
+        let t = |s: &str| DateTime::parse_from_rfc3339(s).unwrap();
+        // Europe/Dublin moves from +00:00 to +01:00 on Sunday 2026-03-29
+        let search = SlotSearch {
+            start: t("2026-03-27T00:00:00Z"),
+            end: t("2026-03-31T00:00:00Z"),
+            zone: Zone::load("Europe/Dublin").unwrap(),
+            duration: Duration::minutes(30),
+            work_start: NaiveTime::from_hms_opt(9, 0, 0).unwrap(),
+            work_end: NaiveTime::from_hms_opt(9, 30, 0).unwrap(),
+            work_days: vec![Weekday::Mon, Weekday::Fri],
+            step: Duration::minutes(30),
+            max_slots: 10,
+        };
+        let starts: Vec<String> = find_slots(&search, &[]).iter().map(|(s, _)| s.to_rfc3339()).collect();
+        assert_eq!(starts, ["2026-03-27T09:00:00+00:00", "2026-03-30T09:00:00+01:00"]);
+    }
 }
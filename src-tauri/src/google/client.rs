@@ -2,102 +2,368 @@
 //!
 //! Provides authenticated HTTP client that injects OAuth tokens from auth store.
 //! Handles pagination, rate limiting, and error responses according to Google API
-//! REST conventions.
+//! REST conventions. Requests go through the shared outbound layer
+//! (`common::http`), which retries 429/5xx and trips a circuit breaker.
 
-use reqwest::{Client, RequestBuilder, StatusCode};
+use reqwest::header::{CONTENT_RANGE, CONTENT_TYPE, LOCATION, RANGE};
+use reqwest::{Method, RequestBuilder, Response, StatusCode};
 use serde_json::Value;
-use std::time::Duration;
+use std::sync::{Arc, Mutex};
 use tracing::{debug, error, warn};
 
+use crate::common::http::HttpClient;
+use crate::common::pagination::{NextPage, Pagination, Paginator};
+
+/// A client's access token, and the account it belongs to so a rejected
+/// token can be force-refreshed through the auth broker
+#[derive(Debug)]
+pub struct TokenSource {
+    /// (provider, account); `None` means the token can't be refreshed
+    account: Option<(String, String)>,
+    access_token: Mutex<String>,
+    refresh_failure: Mutex<Option<String>>,
+}
+
+impl TokenSource {
+    /// Provider and account, if known
+    pub fn account(&self) -> Option<(&str, &str)> {
+        self.account.as_ref().map(|(p, a)| (p.as_str(), a.as_str()))
+    }
+
+    /// Why a 401 couldn't be fixed by refreshing, if that happened
+    pub fn refresh_failure(&self) -> Option<String> {
+        self.refresh_failure.lock().ok().and_then(|f| f.clone())
+    }
+
+    fn current(&self) -> String {
+        self.access_token.lock().map(|t| t.clone()).unwrap_or_default()
+    }
+
+    fn fail(&self, reason: &str) {
+        if let Ok(mut failure) = self.refresh_failure.lock() {
+            *failure = Some(reason.to_string());
+        }
+    }
+
+    /// Force-refresh the token. `Ok(false)` if there's no account to refresh.
+    async fn refresh(&self) -> Result<bool, String> {
+        let Some((provider, account)) = self.account() else {
+            return Ok(false);
+        };
+        warn!("Google API returned 401, refreshing token for {}:{}", provider, account);
+
+        let result = match crate::auth::get_or_init_broker().await {
+            Ok(broker) => broker.force_refresh(provider, account).await.map_err(|(_, e)| e),
+            Err(e) => Err(e),
+        };
+        let token = result.and_then(|data| {
+            data.get("access_token")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+                .ok_or_else(|| "Refreshed token has no access_token".to_string())
+        });
+
+        match token {
+            Ok(token) => {
+                if let Ok(mut current) = self.access_token.lock() {
+                    *current = token;
+                }
+                Ok(true)
+            }
+            Err(e) => {
+                self.fail(&e);
+                Err(format!("Token refresh failed: {}", e))
+            }
+        }
+    }
+}
+
+/// A downloaded (non-JSON) response body
+#[derive(Debug)]
+pub struct Download {
+    pub bytes: Vec<u8>,
+    pub content_type: Option<String>,
+}
+
+fn too_large(max_bytes: u64) -> String {
+    format!("Content exceeds the {} byte limit", max_bytes)
+}
+
+/// Result of sending one resumable upload chunk
+#[derive(Debug)]
+pub enum ChunkUpload {
+    /// More bytes are expected; the server holds this many so far
+    Incomplete(u64),
+    /// The upload finished and created this resource
+    Complete(Value),
+}
+
+/// Bytes a resumable session holds, from a 308's `Range: bytes=0-N` header
+fn parse_received_range(range: &str) -> Option<u64> {
+    let (_, end) = range.trim().strip_prefix("bytes=")?.split_once('-')?;
+    end.trim().parse::<u64>().ok().map(|end| end + 1)
+}
+
 /// Google API HTTP client with OAuth token injection
 pub struct GoogleClient {
-    client: Client,
-    access_token: String,
+    http: HttpClient,
+    token: Arc<TokenSource>,
 }
 
 impl GoogleClient {
-    /// Create a new Google API client with an OAuth access token
-    pub fn new(access_token: String) -> Result<Self, String> {
-        let client = Client::builder()
-            .timeout(Duration::from_secs(30))
-            .connect_timeout(Duration::from_secs(10))
-            .build()
-            .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
+    /// Create a new Google API client with an OAuth access token, using the
+    /// `http` policy of manifest `namespace`
+    #[allow(dead_code)]
+    pub fn new(namespace: &str, access_token: String) -> Result<Self, String> {
+        Self::with_token(namespace, access_token, None)
+    }
 
-        Ok(Self {
-            client,
+    /// Like [`GoogleClient::new`], refreshing the token for `provider`/`account`
+    /// and retrying once when Google answers 401
+    pub fn for_account(
+        namespace: &str,
+        access_token: String,
+        provider: &str,
+        account: &str,
+    ) -> Result<Self, String> {
+        Self::with_token(
+            namespace,
             access_token,
+            Some((provider.to_string(), account.to_string())),
+        )
+    }
+
+    fn with_token(
+        namespace: &str,
+        access_token: String,
+        account: Option<(String, String)>,
+    ) -> Result<Self, String> {
+        Ok(Self {
+            http: HttpClient::for_namespace(namespace),
+            token: Arc::new(TokenSource {
+                account,
+                access_token: Mutex::new(access_token),
+                refresh_failure: Mutex::new(None),
+            }),
         })
     }
 
+    /// The client's token state (shared, outlives the client)
+    pub fn token_source(&self) -> Arc<TokenSource> {
+        Arc::clone(&self.token)
+    }
+
     /// Make an authenticated GET request
     pub async fn get(&self, url: &str, query: &[(&str, String)]) -> Result<Value, String> {
-        let builder = self
-            .client
-            .get(url)
-            .query(query)
-            .bearer_auth(&self.access_token);
-
-        self.execute_request(builder).await
+        self.execute_request(|token| {
+            self.http
+                .request(Method::GET, url)
+                .query(query)
+                .bearer_auth(token)
+        })
+        .await
     }
 
     /// Make an authenticated POST request with JSON body
     pub async fn post(&self, url: &str, body: &Value) -> Result<Value, String> {
-        let builder = self
-            .client
-            .post(url)
-            .bearer_auth(&self.access_token)
-            .json(body);
-
-        self.execute_request(builder).await
+        self.execute_request(|token| {
+            self.http
+                .request(Method::POST, url)
+                .bearer_auth(token)
+                .json(body)
+        })
+        .await
     }
 
     /// Make an authenticated PUT request with JSON body
     pub async fn put(&self, url: &str, body: &Value) -> Result<Value, String> {
-        let builder = self
-            .client
-            .put(url)
-            .bearer_auth(&self.access_token)
-            .json(body);
-
-        self.execute_request(builder).await
+        self.execute_request(|token| {
+            self.http
+                .request(Method::PUT, url)
+                .bearer_auth(token)
+                .json(body)
+        })
+        .await
     }
 
     /// Make an authenticated PATCH request with JSON body
-    #[allow(dead_code)]
     pub async fn patch(&self, url: &str, body: &Value) -> Result<Value, String> {
-        let builder = self
-            .client
-            .patch(url)
-            .bearer_auth(&self.access_token)
-            .json(body);
-
-        self.execute_request(builder).await
+        self.execute_request(|token| {
+            self.http
+                .request(Method::PATCH, url)
+                .bearer_auth(token)
+                .json(body)
+        })
+        .await
     }
 
     /// Make an authenticated DELETE request
     pub async fn delete(&self, url: &str) -> Result<Value, String> {
-        let builder = self
-            .client
-            .delete(url)
-            .bearer_auth(&self.access_token);
+        self.execute_request(|token| {
+            self.http
+                .request(Method::DELETE, url)
+                .bearer_auth(token)
+        })
+        .await
+    }
 
-        self.execute_request(builder).await
+    /// Make an authenticated POST with a raw body (e.g. a multipart upload)
+    pub async fn post_bytes(
+        &self,
+        url: &str,
+        query: &[(&str, String)],
+        content_type: &str,
+        body: Vec<u8>,
+    ) -> Result<Value, String> {
+        self.execute_request(|token| {
+            self.http
+                .request(Method::POST, url)
+                .query(query)
+                .bearer_auth(token)
+                .header(CONTENT_TYPE, content_type)
+                .body(body.clone())
+        })
+        .await
     }
 
-    /// Execute a request and handle Google API response patterns
-    async fn execute_request(&self, builder: RequestBuilder) -> Result<Value, String> {
-        debug!("Executing Google API request");
+    /// GET a non-JSON body (file contents, exports), failing once it grows
+    /// past `max_bytes` rather than buffering all of it
+    pub async fn get_bytes(
+        &self,
+        url: &str,
+        query: &[(&str, String)],
+        max_bytes: u64,
+    ) -> Result<Download, String> {
+        let mut response = self
+            .send_authorized(|token| {
+                self.http
+                    .request(Method::GET, url)
+                    .query(query)
+                    .bearer_auth(token)
+            })
+            .await?;
+        if !response.status().is_success() {
+            return Err(self.error_from(response).await);
+        }
 
-        let response = builder
-            .send()
+        let content_type = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+        if response.content_length().is_some_and(|l| l > max_bytes) {
+            return Err(too_large(max_bytes));
+        }
+
+        let mut bytes = Vec::new();
+        while let Some(chunk) = response
+            .chunk()
             .await
-            .map_err(|e| format!("HTTP request failed: {}", e))?;
+            .map_err(|e| format!("Failed to read response body: {}", e))?
+        {
+            if (bytes.len() + chunk.len()) as u64 > max_bytes {
+                return Err(too_large(max_bytes));
+            }
+            bytes.extend_from_slice(&chunk);
+        }
+        Ok(Download { bytes, content_type })
+    }
+
+    /// Start a resumable upload session and return its session URL
+    ///
+    /// `url` is the API's upload endpoint with `uploadType=resumable`;
+    /// `metadata` is the JSON resource sent with the first request.
+    pub async fn start_resumable_upload(
+        &self,
+        url: &str,
+        metadata: &Value,
+        content_type: &str,
+        length: u64,
+    ) -> Result<String, String> {
+        let response = self
+            .send_authorized(|token| {
+                self.http
+                    .request(Method::POST, url)
+                    .bearer_auth(token)
+                    .header("X-Upload-Content-Type", content_type)
+                    .header("X-Upload-Content-Length", length)
+                    .json(metadata)
+            })
+            .await?;
+        if !response.status().is_success() {
+            return Err(self.error_from(response).await);
+        }
+        response
+            .headers()
+            .get(LOCATION)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string)
+            .ok_or_else(|| "Resumable upload response has no session URL".to_string())
+    }
+
+    /// Send one chunk of a resumable upload starting at byte `offset` of
+    /// `total`. While more bytes are expected (HTTP 308) the result carries
+    /// the offset to continue from, taken from the `Range` header since the
+    /// server may have kept only part of the chunk.
+    pub async fn upload_chunk(
+        &self,
+        session_url: &str,
+        chunk: &[u8],
+        offset: u64,
+        total: u64,
+    ) -> Result<ChunkUpload, String> {
+        let range = match chunk.len() as u64 {
+            0 => format!("bytes */{}", total),
+            len => format!("bytes {}-{}/{}", offset, offset + len - 1, total),
+        };
+        let response = self
+            .send_authorized(|token| {
+                self.http
+                    .request(Method::PUT, session_url)
+                    .bearer_auth(token)
+                    .header(CONTENT_RANGE, range.as_str())
+                    .body(chunk.to_vec())
+            })
+            .await?;
+        if response.status() == StatusCode::PERMANENT_REDIRECT {
+            let received = response
+                .headers()
+                .get(RANGE)
+                .and_then(|v| v.to_str().ok())
+                .and_then(parse_received_range)
+                .unwrap_or(0);
+            return Ok(ChunkUpload::Incomplete(received));
+        }
+        self.read_json(response).await.map(ChunkUpload::Complete)
+    }
+
+    /// Execute a request and handle Google API response patterns. `build`
+    /// makes the request for a given access token, so a 401 can be retried
+    /// once with a refreshed one.
+    async fn execute_request(&self, build: impl Fn(&str) -> RequestBuilder) -> Result<Value, String> {
+        debug!("Executing Google API request");
+        let response = self.send_authorized(build).await?;
+        self.read_json(response).await
+    }
+
+    /// Send a request, refreshing the token and retrying once on 401
+    async fn send_authorized(&self, build: impl Fn(&str) -> RequestBuilder) -> Result<Response, String> {
+        let mut response = self.send(build(&self.token.current())).await?;
+        if response.status() == StatusCode::UNAUTHORIZED && self.token.refresh().await? {
+            response = self.send(build(&self.token.current())).await?;
+            if response.status() == StatusCode::UNAUTHORIZED {
+                self.token.fail("still unauthorized after refreshing the token");
+            }
+        }
+        Ok(response)
+    }
 
+    /// Parse a JSON response, turning API errors into messages
+    async fn read_json(&self, response: Response) -> Result<Value, String> {
         let status = response.status();
         debug!("Response status: {}", status);
 
-        // Handle rate limiting
+        // Handle rate limiting (still limited after retries)
         if status == StatusCode::TOO_MANY_REQUESTS {
             warn!("Rate limited by Google API");
             return Err("Rate limited. Please try again later.".to_string());
@@ -128,6 +394,18 @@ impl GoogleClient {
         Ok(parsed)
     }
 
+    /// Error message for a failed non-JSON request
+    async fn error_from(&self, response: Response) -> String {
+        match self.read_json(response).await {
+            Err(e) => e,
+            Ok(_) => "Unexpected response from Google API".to_string(),
+        }
+    }
+
+    async fn send(&self, builder: RequestBuilder) -> Result<Response, String> {
+        self.http.send(builder, false).await.map_err(|e| e.to_string())
+    }
+
     /// Extract error message from Google API error response
     fn extract_error_message(&self, response: &Value, status: StatusCode) -> String {
         // Google APIs return errors in this format:
@@ -154,53 +432,45 @@ impl GoogleClient {
         format!("HTTP {} error", status)
     }
 
-    /// Handle paginated requests with nextPageToken
+    /// Handle paginated requests with nextPageToken, collecting the array at
+    /// `items_path` (e.g. `messages`, `items`) from each page
     pub async fn get_paginated(
         &self,
         url: &str,
         base_query: &[(&str, String)],
+        items_path: &str,
         max_results: Option<usize>,
     ) -> Result<Vec<Value>, String> {
-        let mut all_items = Vec::new();
-        let mut page_token: Option<String> = None;
-        let remaining = max_results.unwrap_or(usize::MAX);
+        let spec = Pagination::google(items_path, max_results, max_results);
+        self.get_paginated_with(url, base_query, &spec).await
+    }
+
+    /// Like [`get_paginated`](Self::get_paginated) with an explicit spec, for
+    /// APIs that name the page size differently (e.g. People's `pageSize`)
+    pub async fn get_paginated_with(
+        &self,
+        url: &str,
+        base_query: &[(&str, String)],
+        spec: &Pagination,
+    ) -> Result<Vec<Value>, String> {
+        let mut pager = Paginator::new(spec);
+        let mut paging = pager.first_page();
 
         loop {
             let mut query = base_query.to_vec();
-            if let Some(ref token) = page_token {
-                query.push(("pageToken", token.clone()));
-            }
-            if let Some(max) = max_results {
-                query.push(("maxResults", max.to_string()));
-            }
+            query.extend(paging.iter().map(|(k, v)| (k.as_str(), v.clone())));
 
             let response = self.get(url, &query).await?;
-
-            // Extract items (array can be under different field names: items, messages, events, etc.)
-            if let Some(items) = response
-                .get("items")
-                .or_else(|| response.get("messages"))
-                .or_else(|| response.get("events"))
-                .and_then(|v| v.as_array())
-            {
-                all_items.extend(items.clone());
-                
-                if all_items.len() >= remaining {
-                    all_items.truncate(remaining);
-                    break;
-                }
-            }
-
-            // Check for next page token
-            if let Some(next_token) = response.get("nextPageToken").and_then(|v| v.as_str()) {
-                page_token = Some(next_token.to_string());
-            } else {
-                // No more pages
-                break;
+            match pager.absorb(&response, None) {
+                Some(NextPage::Query(next)) => paging = next,
+                _ => break,
             }
         }
 
-        Ok(all_items)
+        if pager.truncated() {
+            debug!("Stopped after {} page(s) with more results available", pager.pages());
+        }
+        Ok(pager.into_items())
     }
 }
 
@@ -210,10 +480,7 @@ mod tests {
 
     #[test]
     fn test_extract_error_message() {
-        let client = GoogleClient {
-            client: Client::new(),
-            access_token: "test".to_string(),
-        };
+        let client = GoogleClient::new("gmail", "test".to_string()).unwrap();
 
         let error_response = serde_json::json!({
             "error": {
@@ -226,4 +493,11 @@ mod tests {
         assert!(msg.contains("400"));
         assert!(msg.contains("Invalid request format"));
     }
+
+    #[test]
+    fn test_parse_received_range() {
+        assert_eq!(parse_received_range("bytes=0-262143"), Some(262144));
+        assert_eq!(parse_received_range("bytes=0-0"), Some(1));
+        assert_eq!(parse_received_range("bytes 0-10/20"), None);
+    }
 }
@@ -24,24 +24,38 @@ pub fn extract_array(response: &Value, field: &str) -> Vec<Value> {
 /// pub struct FooApi { client: GoogleClient }
 /// impl FooApi {
 ///     pub fn new(access_token: String) -> Result<Self, String> {
-///         let client = GoogleClient::new(access_token)?;
+///         let client = GoogleClient::new("foo", access_token)?;
 ///         Ok(Self { client })
 ///     }
 /// }
 /// ```
-/// This macro eliminates that boilerplate.
+/// This macro eliminates that boilerplate. The second argument is the manifest
+/// ID whose `http` policy the client uses.
 macro_rules! google_api_wrapper {
-    ($name:ident) => {
+    ($name:ident, $namespace:expr) => {
         pub struct $name {
             client: super::client::GoogleClient,
         }
 
         impl $name {
             /// Create a new API client with an OAuth access token
+            #[allow(dead_code)]
             pub fn new(access_token: String) -> Result<Self, String> {
-                let client = super::client::GoogleClient::new(access_token)?;
+                let client = super::client::GoogleClient::new($namespace, access_token)?;
                 Ok(Self { client })
             }
+
+            /// Create a client that refreshes the token for `provider`/`account`
+            /// and retries once when the API answers 401
+            pub fn for_account(access_token: String, provider: &str, account: &str) -> Result<Self, String> {
+                let client = super::client::GoogleClient::for_account($namespace, access_token, provider, account)?;
+                Ok(Self { client })
+            }
+
+            /// Token state shared with the client, for reporting refresh failures
+            pub fn token_source(&self) -> std::sync::Arc<super::client::TokenSource> {
+                self.client.token_source()
+            }
         }
     };
 }
@@ -0,0 +1,468 @@
+//! Google People API v1 Client (Contacts)
+//!
+//! Provides methods for interacting with Google Contacts via the People API:
+//! - List connections (paginated) and search contacts
+//! - Get/create/update/delete contacts (updates are etag-checked)
+//! - List contact groups, their members, and change memberships
+//!
+//! Contacts are returned in the shape of the native `contacts.*` handlers
+//! (`firstName`, `lastName`, `fullName`, `emails`, `phones`, `organization`)
+//! plus the People API `resourceName` and `etag`.
+
+use super::common::{extract_array, google_api_wrapper};
+use crate::common::pagination::Pagination;
+use serde::Serialize;
+use serde_json::{json, Value};
+use tracing::{debug, info};
+
+const PEOPLE_API_BASE: &str = "https://people.googleapis.com/v1";
+
+/// Person fields requested for every read
+const PERSON_FIELDS: &str = "names,emailAddresses,phoneNumbers,organizations,memberships";
+
+/// People API page size limit for connections and groups
+const MAX_PAGE_SIZE: usize = 1000;
+/// People API page size limit for `searchContacts`
+const MAX_SEARCH_PAGE_SIZE: usize = 30;
+/// `people:batchGet` accepts at most this many resource names
+const MAX_BATCH_GET: usize = 200;
+
+google_api_wrapper!(ContactsApi, "google-contacts");
+
+/// A Google contact, shaped like the native `contacts::Contact`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GoogleContact {
+    /// Same as `resource_name` (`people/c123…`)
+    pub id: String,
+    pub resource_name: String,
+    /// Pass back to `update` to detect concurrent edits
+    pub etag: Option<String>,
+    pub first_name: Option<String>,
+    pub last_name: Option<String>,
+    pub full_name: Option<String>,
+    pub emails: Vec<String>,
+    pub phones: Vec<String>,
+    pub organization: Option<String>,
+    /// Contact group resource names (`contactGroups/…`)
+    pub groups: Vec<String>,
+}
+
+impl GoogleContact {
+    /// Map a People API `Person` resource.
+    pub fn from_person(person: &Value) -> Self {
+        let resource_name = str_field(person, "resourceName").unwrap_or_default();
+        let name = person.get("names").and_then(|n| primary_item(n));
+        let values = |field: &str| -> Vec<String> {
+            person
+                .get(field)
+                .and_then(Value::as_array)
+                .map(|items| items.iter().filter_map(|i| str_field(i, "value")).collect())
+                .unwrap_or_default()
+        };
+
+        Self {
+            id: resource_name.clone(),
+            resource_name,
+            etag: str_field(person, "etag"),
+            first_name: name.and_then(|n| str_field(n, "givenName")),
+            last_name: name.and_then(|n| str_field(n, "familyName")),
+            full_name: name.and_then(|n| str_field(n, "displayName")),
+            emails: values("emailAddresses"),
+            phones: values("phoneNumbers"),
+            organization: person
+                .get("organizations")
+                .and_then(|o| primary_item(o))
+                .and_then(|o| str_field(o, "name")),
+            groups: person
+                .get("memberships")
+                .and_then(Value::as_array)
+                .map(|items| {
+                    items
+                        .iter()
+                        .filter_map(|m| {
+                            m.pointer("/contactGroupMembership/contactGroupResourceName")
+                                .and_then(Value::as_str)
+                                .map(str::to_string)
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// Fields for creating or updating a contact. `None` leaves a field unset
+/// (create) or unchanged (update).
+#[derive(Debug, Default)]
+pub struct ContactFields<'a> {
+    pub first_name: Option<&'a str>,
+    pub last_name: Option<&'a str>,
+    pub organization: Option<&'a str>,
+    pub emails: Option<Vec<String>>,
+    pub phones: Option<Vec<String>>,
+}
+
+impl ContactFields<'_> {
+    /// Whether no field is set
+    fn is_empty(&self) -> bool {
+        self.first_name.is_none()
+            && self.last_name.is_none()
+            && self.organization.is_none()
+            && self.emails.is_none()
+            && self.phones.is_none()
+    }
+
+    /// Whether only one of first and last name is set
+    fn is_partial_name(&self) -> bool {
+        self.first_name.is_some() != self.last_name.is_some()
+    }
+
+    /// Person body and the `updatePersonFields` mask covering what was set.
+    /// The name is replaced as a whole, so the part not set is taken from
+    /// `current` (the stored contact, on update).
+    fn to_person(&self, current: Option<&GoogleContact>) -> (Value, Vec<&'static str>) {
+        let mut person = json!({});
+        let mut mask = Vec::new();
+
+        if self.first_name.is_some() || self.last_name.is_some() {
+            let given = self
+                .first_name
+                .or_else(|| current.and_then(|c| c.first_name.as_deref()));
+            let family = self
+                .last_name
+                .or_else(|| current.and_then(|c| c.last_name.as_deref()));
+            person["names"] = json!([{
+                "givenName": given.unwrap_or_default(),
+                "familyName": family.unwrap_or_default(),
+            }]);
+            mask.push("names");
+        }
+        if let Some(org) = self.organization {
+            person["organizations"] = json!([{ "name": org }]);
+            mask.push("organizations");
+        }
+        if let Some(emails) = &self.emails {
+            person["emailAddresses"] =
+                Value::Array(emails.iter().map(|e| json!({ "value": e })).collect());
+            mask.push("emailAddresses");
+        }
+        if let Some(phones) = &self.phones {
+            person["phoneNumbers"] =
+                Value::Array(phones.iter().map(|p| json!({ "value": p })).collect());
+            mask.push("phoneNumbers");
+        }
+        (person, mask)
+    }
+}
+
+impl ContactsApi {
+    /// List the user's contacts ("connections")
+    ///
+    /// # Arguments
+    /// * `max_results` - Stop after this many contacts (all when `None`)
+    /// * `sort_order` - `LAST_MODIFIED_ASCENDING`, `FIRST_NAME_ASCENDING`, etc.
+    pub async fn list_contacts(
+        &self,
+        max_results: Option<usize>,
+        sort_order: Option<&str>,
+    ) -> Result<Vec<GoogleContact>, String> {
+        info!("Listing Google contacts");
+
+        let mut query = vec![("personFields", PERSON_FIELDS.to_string())];
+        if let Some(order) = sort_order {
+            query.push(("sortOrder", order.to_string()));
+        }
+
+        let url = format!("{}/people/me/connections", PEOPLE_API_BASE);
+        let people = self
+            .client
+            .get_paginated_with(&url, &query, &people_pagination("connections", max_results))
+            .await?;
+
+        debug!("Retrieved {} contacts", people.len());
+        Ok(people.iter().map(GoogleContact::from_person).collect())
+    }
+
+    /// Search contacts by name, email, phone or organization (prefix match)
+    pub async fn search_contacts(&self, query: &str, limit: usize) -> Result<Vec<GoogleContact>, String> {
+        info!("Searching Google contacts");
+
+        let url = format!("{}/people:searchContacts", PEOPLE_API_BASE);
+        let read_mask = ("readMask", PERSON_FIELDS.to_string());
+
+        // Google asks for an empty "warmup" search first so the results
+        // reflect recent changes
+        self.client
+            .get(&url, &[("query", String::new()), read_mask.clone()])
+            .await?;
+
+        let response = self
+            .client
+            .get(
+                &url,
+                &[
+                    ("query", query.to_string()),
+                    read_mask,
+                    ("pageSize", limit.clamp(1, MAX_SEARCH_PAGE_SIZE).to_string()),
+                ],
+            )
+            .await?;
+
+        Ok(extract_array(&response, "results")
+            .iter()
+            .filter_map(|r| r.get("person"))
+            .map(GoogleContact::from_person)
+            .collect())
+    }
+
+    /// Get a contact by resource name (`people/c123`) or bare ID (`c123`)
+    pub async fn get_contact(&self, id: &str) -> Result<GoogleContact, String> {
+        let url = format!("{}/{}", PEOPLE_API_BASE, person_resource(id));
+        let person = self
+            .client
+            .get(&url, &[("personFields", PERSON_FIELDS.to_string())])
+            .await?;
+        Ok(GoogleContact::from_person(&person))
+    }
+
+    /// Create a contact, optionally adding it to contact groups
+    pub async fn create_contact(
+        &self,
+        fields: &ContactFields<'_>,
+        groups: &[String],
+    ) -> Result<GoogleContact, String> {
+        info!("Creating Google contact");
+
+        let (mut person, _) = fields.to_person(None);
+        if !groups.is_empty() {
+            person["memberships"] = Value::Array(
+                groups
+                    .iter()
+                    .map(|g| json!({ "contactGroupMembership": { "contactGroupResourceName": group_resource(g) } }))
+                    .collect(),
+            );
+        }
+
+        let url = format!(
+            "{}/people:createContact?personFields={}",
+            PEOPLE_API_BASE, PERSON_FIELDS
+        );
+        let created = self.client.post(&url, &person).await?;
+        Ok(GoogleContact::from_person(&created))
+    }
+
+    /// Update the fields set in `fields`. With `etag`, the update fails if the
+    /// contact changed since it was read; without it, the current etag is
+    /// fetched first (last write wins). Setting only one of first and last
+    /// name keeps the other.
+    pub async fn update_contact(
+        &self,
+        id: &str,
+        fields: &ContactFields<'_>,
+        etag: Option<&str>,
+    ) -> Result<GoogleContact, String> {
+        if fields.is_empty() {
+            return Err("No fields to update".to_string());
+        }
+
+        let resource = person_resource(id);
+        info!("Updating Google contact {}", resource);
+        let current = if etag.is_none() || fields.is_partial_name() {
+            Some(self.get_contact(&resource).await?)
+        } else {
+            None
+        };
+        let (mut person, mask) = fields.to_person(current.as_ref());
+        person["etag"] = match etag {
+            Some(etag) => json!(etag),
+            None => json!(current.and_then(|c| c.etag)),
+        };
+
+        let url = format!(
+            "{}/{}:updateContact?updatePersonFields={}&personFields={}",
+            PEOPLE_API_BASE,
+            resource,
+            mask.join(","),
+            PERSON_FIELDS
+        );
+        let updated = self.client.patch(&url, &person).await?;
+        Ok(GoogleContact::from_person(&updated))
+    }
+
+    /// Delete a contact
+    pub async fn delete_contact(&self, id: &str) -> Result<(), String> {
+        let resource = person_resource(id);
+        info!("Deleting Google contact {}", resource);
+        let url = format!("{}/{}:deleteContact", PEOPLE_API_BASE, resource);
+        self.client.delete(&url).await?;
+        Ok(())
+    }
+
+    /// List contact groups (user-defined and system groups such as `myContacts`)
+    pub async fn list_groups(&self) -> Result<Vec<Value>, String> {
+        let url = format!("{}/contactGroups", PEOPLE_API_BASE);
+        let query = [("groupFields", "name,groupType,memberCount".to_string())];
+        self.client
+            .get_paginated_with(&url, &query, &people_pagination("contactGroups", None))
+            .await
+    }
+
+    /// Contacts in a group, up to `limit`
+    pub async fn group_members(&self, group: &str, limit: usize) -> Result<Vec<GoogleContact>, String> {
+        let url = format!("{}/{}", PEOPLE_API_BASE, group_resource(group));
+        let response = self
+            .client
+            .get(&url, &[("maxMembers", limit.to_string())])
+            .await?;
+        let members: Vec<String> = extract_array(&response, "memberResourceNames")
+            .iter()
+            .filter_map(|m| m.as_str().map(str::to_string))
+            .collect();
+
+        let mut contacts = Vec::with_capacity(members.len());
+        for chunk in members.chunks(MAX_BATCH_GET) {
+            let mut query: Vec<(&str, String)> = chunk
+                .iter()
+                .map(|name| ("resourceNames", name.clone()))
+                .collect();
+            query.push(("personFields", PERSON_FIELDS.to_string()));
+
+            let url = format!("{}/people:batchGet", PEOPLE_API_BASE);
+            let response = self.client.get(&url, &query).await?;
+            contacts.extend(
+                extract_array(&response, "responses")
+                    .iter()
+                    .filter_map(|r| r.get("person"))
+                    .map(GoogleContact::from_person),
+            );
+        }
+        Ok(contacts)
+    }
+
+    /// Create a user-defined contact group
+    pub async fn create_group(&self, name: &str) -> Result<Value, String> {
+        info!("Creating contact group");
+        let url = format!("{}/contactGroups", PEOPLE_API_BASE);
+        self.client
+            .post(&url, &json!({ "contactGroup": { "name": name } }))
+            .await
+    }
+
+    /// Add and remove contacts from a group
+    pub async fn modify_group_members(
+        &self,
+        group: &str,
+        add: &[String],
+        remove: &[String],
+    ) -> Result<Value, String> {
+        let url = format!("{}/{}/members:modify", PEOPLE_API_BASE, group_resource(group));
+        let body = json!({
+            "resourceNamesToAdd": add.iter().map(|id| person_resource(id)).collect::<Vec<_>>(),
+            "resourceNamesToRemove": remove.iter().map(|id| person_resource(id)).collect::<Vec<_>>(),
+        });
+        self.client.post(&url, &body).await
+    }
+}
+
+/// Pagination with the People API's `pageSize` parameter
+fn people_pagination(items: &str, max_results: Option<usize>) -> Pagination {
+    let page_size = max_results.unwrap_or(MAX_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+    Pagination::google_page_size(items, Some(page_size), max_results)
+}
+
+/// `c123` → `people/c123`
+fn person_resource(id: &str) -> String {
+    if id.starts_with("people/") {
+        id.to_string()
+    } else {
+        format!("people/{}", id)
+    }
+}
+
+/// `friends` / `myContacts` → `contactGroups/…`
+fn group_resource(id: &str) -> String {
+    if id.starts_with("contactGroups/") {
+        id.to_string()
+    } else {
+        format!("contactGroups/{}", id)
+    }
+}
+
+/// The item flagged `metadata.primary`, else the first
+fn primary_item(items: &Value) -> Option<&Value> {
+    let items = items.as_array()?;
+    items
+        .iter()
+        .find(|i| i.pointer("/metadata/primary").and_then(Value::as_bool) == Some(true))
+        .or_else(|| items.first())
+}
+
+fn str_field(value: &Value, field: &str) -> Option<String> {
+    value.get(field).and_then(Value::as_str).map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_person() {
+        let person = json!({
+            "resourceName": "people/c42",
+            "etag": "%EgUBAi43PRoEAQIFByIMY2xTR2",
+            "names": [
+                {"displayName": "Other", "givenName": "Other"},
+                {"metadata": {"primary": true}, "displayName": "Ada Lovelace", "givenName": "Ada", "familyName": "Lovelace"}
+            ],
+            "emailAddresses": [{"value": "ada@example.com"}],
+            "phoneNumbers": [{"value": "+44 20 7946 0000"}],
+            "organizations": [{"name": "Analytical Engines Ltd"}],
+            "memberships": [{"contactGroupMembership": {"contactGroupResourceName": "contactGroups/myContacts"}}]
+        });
+
+        let contact = GoogleContact::from_person(&person);
+        assert_eq!(contact.id, "people/c42");
+        assert_eq!(contact.first_name.as_deref(), Some("Ada"));
+        assert_eq!(contact.full_name.as_deref(), Some("Ada Lovelace"));
+        assert_eq!(contact.emails, ["ada@example.com"]);
+        assert_eq!(contact.organization.as_deref(), Some("Analytical Engines Ltd"));
+        assert_eq!(contact.groups, ["contactGroups/myContacts"]);
+
+        let json = serde_json::to_value(&contact).unwrap();
+        assert_eq!(json["firstName"], "Ada");
+        assert_eq!(json["resourceName"], "people/c42");
+    }
+
+    #[test]
+    fn test_update_mask_covers_set_fields() {
+        let fields = ContactFields {
+            last_name: Some("Byron"),
+            emails: Some(vec![]),
+            ..Default::default()
+        };
+        assert!(fields.is_partial_name());
+        let current = GoogleContact::from_person(&json!({
+            "resourceName": "people/c42",
+            "names": [{"givenName": "Ada", "familyName": "Lovelace"}]
+        }));
+        let (person, mask) = fields.to_person(Some(&current));
+        assert_eq!(mask, ["names", "emailAddresses"]);
+        assert_eq!(person["names"][0]["familyName"], "Byron");
+        assert_eq!(person["names"][0]["givenName"], "Ada");
+        assert_eq!(person["emailAddresses"], json!([]));
+
+        let (person, _) = ContactFields { first_name: Some("Augusta"), ..Default::default() }
+            .to_person(Some(&current));
+        assert_eq!(person["names"][0]["givenName"], "Augusta");
+        assert_eq!(person["names"][0]["familyName"], "Lovelace");
+        assert!(ContactFields::default().is_empty());
+    }
+
+    #[test]
+    fn test_resource_names() {
+        assert_eq!(person_resource("c1"), "people/c1");
+        assert_eq!(person_resource("people/c1"), "people/c1");
+        assert_eq!(group_resource("myContacts"), "contactGroups/myContacts");
+    }
+}
@@ -0,0 +1,458 @@
+//! Google Drive API v3 Client
+//!
+//! Provides methods for interacting with Google Drive API:
+//! - Search files (Drive query syntax) and get metadata
+//! - Download files, export Google Docs/Sheets/Slides (size-capped)
+//! - Upload files (multipart for small files, resumable for large ones)
+//! - Create folders
+//! - List/add/remove sharing permissions
+//! - Poll the change feed
+//!
+//! All methods use the authenticated GoogleClient with Tier 1 proxy mode.
+
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use super::client::{ChunkUpload, Download};
+use super::common::{extract_array, google_api_wrapper};
+use crate::common::pagination::Pagination;
+use serde::Serialize;
+use serde_json::{json, Value};
+use tracing::{debug, info};
+
+const DRIVE_API_BASE: &str = "https://www.googleapis.com/drive/v3";
+const DRIVE_UPLOAD_BASE: &str = "https://www.googleapis.com/upload/drive/v3";
+
+/// File fields returned by every call
+const FILE_FIELDS: &str = "id,name,mimeType,size,createdTime,modifiedTime,parents,webViewLink,\
+owners(displayName,emailAddress),shared,trashed,md5Checksum";
+
+pub const FOLDER_MIME: &str = "application/vnd.google-apps.folder";
+/// Native Google formats (Docs, Sheets, ...) have no bytes and must be exported
+const GOOGLE_APPS_PREFIX: &str = "application/vnd.google-apps.";
+
+/// Files up to this size go up in one multipart request
+pub const SIMPLE_UPLOAD_LIMIT: u64 = 5 * 1024 * 1024;
+/// Resumable upload chunk size (Drive requires multiples of 256 KiB)
+const UPLOAD_CHUNK_SIZE: u64 = 32 * 256 * 1024;
+/// Drive's page size limit for files and changes
+const MAX_PAGE_SIZE: usize = 1000;
+
+google_api_wrapper!(DriveApi, "google-drive");
+
+/// One poll of the change feed
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangesPage {
+    pub changes: Vec<Value>,
+    /// Pass back as `pageToken` on the next poll
+    pub page_token: String,
+    /// `false` when `max_results` stopped the poll before the feed was drained
+    pub complete: bool,
+}
+
+impl DriveApi {
+    /// Search files
+    ///
+    /// # Arguments
+    /// * `query` - Drive query, e.g. `name contains 'report' and mimeType = 'application/pdf'`
+    /// * `order_by` - e.g. `modifiedTime desc`, `name`
+    /// * `include_trashed` - Include files in the trash
+    /// * `all_drives` - Include shared drives
+    pub async fn search(
+        &self,
+        query: Option<&str>,
+        order_by: Option<&str>,
+        max_results: usize,
+        include_trashed: bool,
+        all_drives: bool,
+    ) -> Result<Vec<Value>, String> {
+        info!("Searching Google Drive files");
+
+        let mut params = vec![("fields", format!("nextPageToken,files({})", FILE_FIELDS))];
+        let q = match (query, include_trashed) {
+            (Some(q), true) => Some(q.to_string()),
+            (Some(q), false) => Some(format!("({}) and trashed = false", q)),
+            (None, true) => None,
+            (None, false) => Some("trashed = false".to_string()),
+        };
+        if let Some(q) = q {
+            params.push(("q", q));
+        }
+        if let Some(order) = order_by {
+            params.push(("orderBy", order.to_string()));
+        }
+        if all_drives {
+            params.push(("supportsAllDrives", "true".to_string()));
+            params.push(("includeItemsFromAllDrives", "true".to_string()));
+        }
+
+        let url = format!("{}/files", DRIVE_API_BASE);
+        let spec = Pagination::google_page_size(
+            "files",
+            Some(max_results.clamp(1, MAX_PAGE_SIZE)),
+            Some(max_results),
+        );
+        let files = self.client.get_paginated_with(&url, &params, &spec).await?;
+
+        debug!("Found {} files", files.len());
+        Ok(files)
+    }
+
+    /// Get file metadata
+    pub async fn get_file(&self, file_id: &str) -> Result<Value, String> {
+        let url = format!("{}/files/{}", DRIVE_API_BASE, file_id);
+        self.client
+            .get(
+                &url,
+                &[
+                    ("fields", FILE_FIELDS.to_string()),
+                    ("supportsAllDrives", "true".to_string()),
+                ],
+            )
+            .await
+    }
+
+    /// Download a (non-Google-format) file's contents
+    pub async fn download(&self, file_id: &str, max_bytes: u64) -> Result<Download, String> {
+        info!("Downloading Drive file {}", file_id);
+        let url = format!("{}/files/{}", DRIVE_API_BASE, file_id);
+        self.client
+            .get_bytes(
+                &url,
+                &[
+                    ("alt", "media".to_string()),
+                    ("supportsAllDrives", "true".to_string()),
+                ],
+                max_bytes,
+            )
+            .await
+    }
+
+    /// Export a Google Doc/Sheet/Slides file to `mime_type`. Drive refuses
+    /// exports larger than 10 MB.
+    pub async fn export(&self, file_id: &str, mime_type: &str, max_bytes: u64) -> Result<Download, String> {
+        info!("Exporting Drive file {} as {}", file_id, mime_type);
+        let url = format!("{}/files/{}/export", DRIVE_API_BASE, file_id);
+        self.client
+            .get_bytes(&url, &[("mimeType", mime_type.to_string())], max_bytes)
+            .await
+    }
+
+    /// Create a folder, in `parent` or the root of My Drive
+    pub async fn create_folder(&self, name: &str, parent: Option<&str>) -> Result<Value, String> {
+        info!("Creating Drive folder");
+        let url = format!(
+            "{}/files?fields={}&supportsAllDrives=true",
+            DRIVE_API_BASE, FILE_FIELDS
+        );
+        self.client
+            .post(&url, &file_metadata(name, Some(FOLDER_MIME), parent))
+            .await
+    }
+
+    /// Upload a small file in a single multipart request
+    pub async fn upload_simple(
+        &self,
+        name: &str,
+        parent: Option<&str>,
+        content_type: &str,
+        bytes: &[u8],
+    ) -> Result<Value, String> {
+        info!("Uploading {} bytes to Drive (multipart)", bytes.len());
+
+        let boundary = format!("tairseach-{:016x}", rand::random::<u64>());
+        let metadata = file_metadata(name, None, parent);
+        let body = multipart_related(&boundary, &metadata, content_type, bytes);
+
+        let url = format!("{}/files", DRIVE_UPLOAD_BASE);
+        self.client
+            .post_bytes(
+                &url,
+                &[
+                    ("uploadType", "multipart".to_string()),
+                    ("fields", FILE_FIELDS.to_string()),
+                    ("supportsAllDrives", "true".to_string()),
+                ],
+                &format!("multipart/related; boundary={}", boundary),
+                body,
+            )
+            .await
+    }
+
+    /// Upload `length` bytes from `reader` through a resumable session, in
+    /// chunks, so large files are never held in memory at once. Each chunk
+    /// resumes from the offset the server reports, re-sending whatever part
+    /// of the previous chunk it did not keep.
+    pub async fn upload_resumable<R: AsyncRead + Unpin + Send>(
+        &self,
+        name: &str,
+        parent: Option<&str>,
+        content_type: &str,
+        length: u64,
+        mut reader: R,
+    ) -> Result<Value, String> {
+        info!("Uploading {} bytes to Drive (resumable)", length);
+
+        let url = format!(
+            "{}/files?uploadType=resumable&fields={}&supportsAllDrives=true",
+            DRIVE_UPLOAD_BASE, FILE_FIELDS
+        );
+        let session = self
+            .client
+            .start_resumable_upload(&url, &file_metadata(name, None, parent), content_type, length)
+            .await?;
+
+        // Bytes read from `reader` but not yet held by the server, starting at `offset`
+        let mut chunk = Vec::new();
+        let mut offset = 0;
+        loop {
+            let wanted = UPLOAD_CHUNK_SIZE.min(length - offset) as usize;
+            if chunk.len() < wanted {
+                let filled = chunk.len();
+                chunk.resize(wanted, 0);
+                reader
+                    .read_exact(&mut chunk[filled..])
+                    .await
+                    .map_err(|e| format!("Failed to read upload data: {}", e))?;
+            }
+            let chunk_len = chunk.len() as u64;
+
+            let received = match self.client.upload_chunk(&session, &chunk, offset, length).await? {
+                ChunkUpload::Complete(file) => return Ok(file),
+                ChunkUpload::Incomplete(received) => received,
+            };
+            if received <= offset || received > offset + chunk_len {
+                return Err(format!(
+                    "Upload session stopped at byte {} of {} (sent up to {})",
+                    received,
+                    length,
+                    offset + chunk_len
+                ));
+            }
+            chunk.drain(..(received - offset) as usize);
+            offset = received;
+            debug!("Uploaded {}/{} bytes", offset, length);
+            if offset >= length {
+                return Err("Upload session ended without returning the file".to_string());
+            }
+        }
+    }
+
+    /// List who a file is shared with
+    pub async fn list_permissions(&self, file_id: &str) -> Result<Vec<Value>, String> {
+        let url = format!("{}/files/{}/permissions", DRIVE_API_BASE, file_id);
+        let response = self
+            .client
+            .get(
+                &url,
+                &[
+                    ("fields", "permissions(id,type,role,emailAddress,domain,displayName)".to_string()),
+                    ("supportsAllDrives", "true".to_string()),
+                ],
+            )
+            .await?;
+        Ok(extract_array(&response, "permissions"))
+    }
+
+    /// Share a file
+    ///
+    /// # Arguments
+    /// * `permission` - Drive permission: `type` (`user`, `group`, `domain`,
+    ///   `anyone`), `role` (`reader`, `commenter`, `writer`, ...) and
+    ///   `emailAddress` or `domain`
+    /// * `notify` - Email the grantee (users and groups only)
+    pub async fn share(
+        &self,
+        file_id: &str,
+        permission: &Value,
+        notify: bool,
+        message: Option<&str>,
+    ) -> Result<Value, String> {
+        info!("Sharing Drive file {}", file_id);
+        let mut url = format!(
+            "{}/files/{}/permissions?supportsAllDrives=true&sendNotificationEmail={}",
+            DRIVE_API_BASE, file_id, notify
+        );
+        if let (true, Some(message)) = (notify, message) {
+            url.push_str(&format!("&emailMessage={}", urlencoding::encode(message)));
+        }
+        self.client.post(&url, permission).await
+    }
+
+    /// Remove a permission from a file
+    pub async fn unshare(&self, file_id: &str, permission_id: &str) -> Result<(), String> {
+        info!("Removing permission {} from Drive file {}", permission_id, file_id);
+        let url = format!(
+            "{}/files/{}/permissions/{}?supportsAllDrives=true",
+            DRIVE_API_BASE, file_id, permission_id
+        );
+        self.client.delete(&url).await?;
+        Ok(())
+    }
+
+    /// Token for the current end of the change feed; changes after this
+    /// point are returned by [`list_changes`](Self::list_changes)
+    pub async fn start_page_token(&self) -> Result<String, String> {
+        let url = format!("{}/changes/startPageToken", DRIVE_API_BASE);
+        let response = self
+            .client
+            .get(&url, &[("supportsAllDrives", "true".to_string())])
+            .await?;
+        response
+            .get("startPageToken")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .ok_or_else(|| "Drive returned no startPageToken".to_string())
+    }
+
+    /// Changes since `page_token`, up to `max_results`
+    pub async fn list_changes(&self, page_token: &str, max_results: usize) -> Result<ChangesPage, String> {
+        let url = format!("{}/changes", DRIVE_API_BASE);
+        let mut changes = Vec::new();
+        let mut token = page_token.to_string();
+
+        loop {
+            let page_size = max_results.saturating_sub(changes.len()).clamp(1, MAX_PAGE_SIZE);
+            let response = self
+                .client
+                .get(
+                    &url,
+                    &[
+                        ("pageToken", token.clone()),
+                        ("pageSize", page_size.to_string()),
+                        (
+                            "fields",
+                            format!(
+                                "nextPageToken,newStartPageToken,changes(changeType,removed,fileId,time,file({}))",
+                                FILE_FIELDS
+                            ),
+                        ),
+                        ("includeRemoved", "true".to_string()),
+                        ("supportsAllDrives", "true".to_string()),
+                        ("includeItemsFromAllDrives", "true".to_string()),
+                    ],
+                )
+                .await?;
+            changes.extend(extract_array(&response, "changes"));
+
+            // The last page carries newStartPageToken instead of nextPageToken
+            if let Some(start) = response.get("newStartPageToken").and_then(Value::as_str) {
+                return Ok(ChangesPage { changes, page_token: start.to_string(), complete: true });
+            }
+            match response.get("nextPageToken").and_then(Value::as_str) {
+                Some(next) if changes.len() < max_results => token = next.to_string(),
+                Some(next) => {
+                    return Ok(ChangesPage { changes, page_token: next.to_string(), complete: false })
+                }
+                None => return Err("Drive change feed returned no page token".to_string()),
+            }
+        }
+    }
+}
+
+/// Export MIME type for a `format` name (or a MIME type, passed through)
+pub fn export_mime(format: &str) -> Result<&str, String> {
+    Ok(match format {
+        "markdown" | "md" => "text/markdown",
+        "pdf" => "application/pdf",
+        "text" | "txt" => "text/plain",
+        "html" => "text/html",
+        "csv" => "text/csv",
+        "docx" => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        "xlsx" => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        "pptx" => "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+        "png" => "image/png",
+        mime if mime.contains('/') => mime,
+        other => {
+            return Err(format!(
+                "Unknown export format '{}' (expected markdown, pdf, text, html, csv, docx, xlsx, pptx, png or a MIME type)",
+                other
+            ))
+        }
+    })
+}
+
+/// Whether a file is a native Google format that must be exported
+pub fn needs_export(mime_type: &str) -> bool {
+    mime_type.starts_with(GOOGLE_APPS_PREFIX) && mime_type != FOLDER_MIME
+}
+
+/// Default export format: Docs → markdown, Sheets → CSV, Slides → plain
+/// text, anything else → PDF
+pub fn default_export_format(mime_type: &str) -> &'static str {
+    match mime_type.strip_prefix(GOOGLE_APPS_PREFIX) {
+        Some("document") => "markdown",
+        Some("spreadsheet") => "csv",
+        Some("presentation") => "text",
+        _ => "pdf",
+    }
+}
+
+/// Whether content of this type can be returned as UTF-8 text
+pub fn is_text_mime(content_type: &str) -> bool {
+    let mime = content_type.split(';').next().unwrap_or_default().trim();
+    mime.starts_with("text/")
+        || matches!(
+            mime,
+            "application/json" | "application/xml" | "application/javascript" | "image/svg+xml"
+        )
+}
+
+fn file_metadata(name: &str, mime_type: Option<&str>, parent: Option<&str>) -> Value {
+    let mut metadata = json!({ "name": name });
+    if let Some(mime) = mime_type {
+        metadata["mimeType"] = json!(mime);
+    }
+    if let Some(parent) = parent {
+        metadata["parents"] = json!([parent]);
+    }
+    metadata
+}
+
+/// `multipart/related` body: JSON metadata part, then the file contents
+fn multipart_related(boundary: &str, metadata: &Value, content_type: &str, bytes: &[u8]) -> Vec<u8> {
+    let mut body = format!(
+        "--{b}\r\nContent-Type: application/json; charset=UTF-8\r\n\r\n{m}\r\n--{b}\r\nContent-Type: {t}\r\n\r\n",
+        b = boundary,
+        m = metadata,
+        t = content_type
+    )
+    .into_bytes();
+    body.extend_from_slice(bytes);
+    body.extend_from_slice(format!("\r\n--{}--\r\n", boundary).as_bytes());
+    body
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_formats() {
+        assert_eq!(export_mime("markdown").unwrap(), "text/markdown");
+        assert_eq!(export_mime("application/rtf").unwrap(), "application/rtf");
+        assert!(export_mime("odt-ish").is_err());
+
+        assert!(needs_export("application/vnd.google-apps.document"));
+        assert!(!needs_export(FOLDER_MIME));
+        assert!(!needs_export("application/pdf"));
+        assert_eq!(default_export_format("application/vnd.google-apps.spreadsheet"), "csv");
+        assert_eq!(default_export_format("application/vnd.google-apps.drawing"), "pdf");
+    }
+
+    #[test]
+    fn test_is_text_mime() {
+        assert!(is_text_mime("text/markdown; charset=utf-8"));
+        assert!(is_text_mime("application/json"));
+        assert!(!is_text_mime("application/pdf"));
+    }
+
+    #[test]
+    fn test_multipart_related() {
+        let body = multipart_related("XYZ", &json!({"name": "a.txt"}), "text/plain", b"hello");
+        let body = String::from_utf8(body).unwrap();
+        assert!(body.starts_with("--XYZ\r\nContent-Type: application/json"));
+        assert!(body.contains(r#"{"name":"a.txt"}"#));
+        assert!(body.contains("Content-Type: text/plain\r\n\r\nhello\r\n--XYZ--"));
+    }
+}
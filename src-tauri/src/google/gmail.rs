@@ -1,20 +1,40 @@
 //! Gmail API v1 Client
 //!
 //! Provides methods for interacting with Gmail API:
-//! - List/search messages
-//! - Get message details
-//! - Send emails
+//! - List/search messages and threads
+//! - Get message details, with decoded plain-text/HTML bodies
+//! - Send emails (MIME, attachments, replies) and manage drafts
+//! - Download attachments
 //! - Manage labels
+//! - Incremental sync from a persisted `historyId` checkpoint
 //!
 //! All methods use the authenticated GoogleClient with Tier 1 proxy mode.
 
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Mutex;
+
 use super::common::{extract_array, google_api_wrapper};
+use super::mime::{self, OutgoingMessage};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
 const GMAIL_API_BASE: &str = "https://gmail.googleapis.com/gmail/v1";
 
-google_api_wrapper!(GmailApi);
+/// Sync checkpoints (one per account, label and consumer), under `~/.tairseach`
+const SYNC_STATE_FILE: &str = "gmail_sync.json";
+/// History records per page (the API maximum)
+const HISTORY_PAGE_SIZE: usize = 500;
+/// Give up on incremental sync (and resync) past this many history pages
+const MAX_HISTORY_PAGES: usize = 20;
+/// Messages listed by a full resync unless the caller asks for more
+pub const DEFAULT_RESYNC_MESSAGES: usize = 100;
+
+/// Serializes read-modify-write of the checkpoint file
+static SYNC_STATE_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+google_api_wrapper!(GmailApi, "gmail");
 
 impl GmailApi {
     /// List messages matching a query
@@ -47,7 +67,7 @@ impl GmailApi {
         }
 
         let url = format!("{}/users/me/messages", GMAIL_API_BASE);
-        let messages = self.client.get_paginated(&url, &query_params, max_results).await?;
+        let messages = self.client.get_paginated(&url, &query_params, "messages", max_results).await?;
 
         debug!("Retrieved {} messages", messages.len());
         Ok(messages)
@@ -74,7 +94,8 @@ impl GmailApi {
         }
 
         let url = format!("{}/users/me/messages/{}", GMAIL_API_BASE, id);
-        let message = self.client.get(&url, &query_params).await?;
+        let mut message = self.client.get(&url, &query_params).await?;
+        add_decoded_content(&mut message);
 
         Ok(message)
     }
@@ -82,60 +103,167 @@ impl GmailApi {
     /// Send an email
     ///
     /// # Arguments
-    /// * `to` - Recipient email addresses
-    /// * `subject` - Email subject
-    /// * `body` - Email body (plain text)
-    /// * `cc` - CC recipients (optional)
-    /// * `bcc` - BCC recipients (optional)
+    /// * `message` - The message; rendered as MIME with `OutgoingMessage::build`
+    /// * `thread_id` - Thread to add the message to (replies)
     ///
     /// # Returns
     /// Sent message object with id and threadId
     pub async fn send_message(
         &self,
-        to: Vec<String>,
-        subject: &str,
-        body: &str,
-        cc: Option<Vec<String>>,
-        bcc: Option<Vec<String>>,
+        message: &OutgoingMessage,
+        thread_id: Option<&str>,
     ) -> Result<Value, String> {
-        info!("Sending Gmail message to: {:?}", to);
+        info!("Sending Gmail message to: {:?}", message.to);
 
-        // Build RFC 2822 message
-        let mut message_parts = vec![
-            format!("To: {}", to.join(", ")),
-            format!("Subject: {}", subject),
-        ];
+        let url = format!("{}/users/me/messages/send", GMAIL_API_BASE);
+        let response = self.client.post(&url, &raw_message(message, thread_id)).await?;
 
-        if let Some(cc_addrs) = cc {
-            if !cc_addrs.is_empty() {
-                message_parts.push(format!("Cc: {}", cc_addrs.join(", ")));
-            }
+        info!("Message sent successfully");
+        Ok(response)
+    }
+
+    /// Headers needed to reply to a message: its thread, `Message-ID`,
+    /// `References`, `Subject` and who to reply to (`Reply-To`, else `From`)
+    pub async fn reply_context(&self, message_id: &str) -> Result<ReplyContext, String> {
+        let url = format!("{}/users/me/messages/{}", GMAIL_API_BASE, message_id);
+        let mut query = vec![("format", "metadata".to_string())];
+        for header in ["Message-ID", "References", "Subject", "From", "Reply-To"] {
+            query.push(("metadataHeaders", header.to_string()));
         }
+        let message = self.client.get(&url, &query).await?;
+        let payload = message.get("payload").cloned().unwrap_or_default();
+
+        let message_id_header = mime::header_value(&payload, "Message-ID")
+            .ok_or_else(|| format!("Message {} has no Message-ID header", message_id))?;
+        let references = match mime::header_value(&payload, "References") {
+            Some(refs) => format!("{} {}", refs, message_id_header),
+            None => message_id_header.clone(),
+        };
+        let subject = mime::header_value(&payload, "Subject").unwrap_or_default();
+
+        Ok(ReplyContext {
+            thread_id: message
+                .get("threadId")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+            in_reply_to: message_id_header,
+            references,
+            subject: if subject.to_ascii_lowercase().starts_with("re:") {
+                subject
+            } else {
+                format!("Re: {}", subject)
+            },
+            reply_to: mime::header_value(&payload, "Reply-To")
+                .or_else(|| mime::header_value(&payload, "From")),
+        })
+    }
 
-        if let Some(bcc_addrs) = bcc {
-            if !bcc_addrs.is_empty() {
-                message_parts.push(format!("Bcc: {}", bcc_addrs.join(", ")));
-            }
+    /// Download an attachment's bytes
+    pub async fn get_attachment(&self, message_id: &str, attachment_id: &str) -> Result<Vec<u8>, String> {
+        info!("Fetching attachment from message: {}", message_id);
+
+        let url = format!(
+            "{}/users/me/messages/{}/attachments/{}",
+            GMAIL_API_BASE, message_id, attachment_id
+        );
+        let response = self.client.get(&url, &[]).await?;
+        response
+            .get("data")
+            .and_then(Value::as_str)
+            .and_then(mime::decode_base64_url)
+            .ok_or_else(|| "Attachment response has no data".to_string())
+    }
+
+    /// List threads matching a query
+    ///
+    /// # Returns
+    /// Array of thread objects with id, snippet and historyId
+    pub async fn list_threads(
+        &self,
+        query: Option<&str>,
+        max_results: Option<usize>,
+        label_ids: Option<Vec<String>>,
+    ) -> Result<Vec<Value>, String> {
+        info!("Listing Gmail threads");
+
+        let mut query_params = vec![];
+        if let Some(q) = query {
+            query_params.push(("q", q.to_string()));
+        }
+        for label in label_ids.unwrap_or_default() {
+            query_params.push(("labelIds", label));
         }
 
-        message_parts.push("Content-Type: text/plain; charset=UTF-8".to_string());
-        message_parts.push(String::new()); // Empty line separates headers from body
-        message_parts.push(body.to_string());
+        let url = format!("{}/users/me/threads", GMAIL_API_BASE);
+        let threads = self.client.get_paginated(&url, &query_params, "threads", max_results).await?;
 
-        let raw_message = message_parts.join("\r\n");
+        debug!("Retrieved {} threads", threads.len());
+        Ok(threads)
+    }
 
-        // Base64url encode (no padding)
-        let encoded = base64_url_encode(raw_message.as_bytes());
+    /// Get a thread with all its messages (bodies decoded as in `get_message`)
+    pub async fn get_thread(&self, thread_id: &str, format: Option<&str>) -> Result<Value, String> {
+        info!("Fetching Gmail thread: {}", thread_id);
 
-        let request_body = json!({
-            "raw": encoded
-        });
+        let mut query_params = vec![];
+        if let Some(fmt) = format {
+            query_params.push(("format", fmt.to_string()));
+        }
 
-        let url = format!("{}/users/me/messages/send", GMAIL_API_BASE);
-        let response = self.client.post(&url, &request_body).await?;
+        let url = format!("{}/users/me/threads/{}", GMAIL_API_BASE, thread_id);
+        let mut thread = self.client.get(&url, &query_params).await?;
+        if let Some(messages) = thread.get_mut("messages").and_then(Value::as_array_mut) {
+            messages.iter_mut().for_each(add_decoded_content);
+        }
+        Ok(thread)
+    }
 
-        info!("Message sent successfully");
-        Ok(response)
+    /// List drafts
+    pub async fn list_drafts(&self, max_results: Option<usize>) -> Result<Vec<Value>, String> {
+        info!("Listing Gmail drafts");
+
+        let url = format!("{}/users/me/drafts", GMAIL_API_BASE);
+        self.client.get_paginated(&url, &[], "drafts", max_results).await
+    }
+
+    /// Create a draft
+    pub async fn create_draft(&self, message: &OutgoingMessage, thread_id: Option<&str>) -> Result<Value, String> {
+        info!("Creating Gmail draft");
+
+        let url = format!("{}/users/me/drafts", GMAIL_API_BASE);
+        let body = json!({ "message": raw_message(message, thread_id) });
+        self.client.post(&url, &body).await
+    }
+
+    /// Replace a draft's content
+    pub async fn update_draft(
+        &self,
+        draft_id: &str,
+        message: &OutgoingMessage,
+        thread_id: Option<&str>,
+    ) -> Result<Value, String> {
+        info!("Updating Gmail draft: {}", draft_id);
+
+        let url = format!("{}/users/me/drafts/{}", GMAIL_API_BASE, draft_id);
+        let body = json!({ "id": draft_id, "message": raw_message(message, thread_id) });
+        self.client.put(&url, &body).await
+    }
+
+    /// Send an existing draft
+    pub async fn send_draft(&self, draft_id: &str) -> Result<Value, String> {
+        info!("Sending Gmail draft: {}", draft_id);
+
+        let url = format!("{}/users/me/drafts/send", GMAIL_API_BASE);
+        self.client.post(&url, &json!({ "id": draft_id })).await
+    }
+
+    /// Delete a draft
+    pub async fn delete_draft(&self, draft_id: &str) -> Result<(), String> {
+        info!("Deleting Gmail draft: {}", draft_id);
+
+        let url = format!("{}/users/me/drafts/{}", GMAIL_API_BASE, draft_id);
+        self.client.delete(&url).await?;
+        Ok(())
     }
 
     /// List all labels
@@ -202,6 +330,377 @@ impl GmailApi {
         self.client.delete(&url).await?;
         Ok(())
     }
+
+    /// The mailbox's current `historyId`
+    pub async fn current_history_id(&self) -> Result<String, String> {
+        let url = format!("{}/users/me/profile", GMAIL_API_BASE);
+        let profile = self.client.get(&url, &[]).await?;
+        history_id_of(&profile).ok_or_else(|| "Gmail profile has no historyId".to_string())
+    }
+
+    /// Changes since the `checkpoint` cursor, advancing it.
+    ///
+    /// Without a checkpoint (first run, `reset`) or when Google no longer has
+    /// history that old, falls back to a full resync: the newest
+    /// `resync_limit` messages are returned as `added` with `fullSync` set,
+    /// and callers should rebuild their state from them.
+    ///
+    /// # Arguments
+    /// * `checkpoint` - Cursor key, from [`checkpoint_key`]
+    /// * `label_id` - Only report changes to messages with this label
+    pub async fn sync(
+        &self,
+        checkpoint: &str,
+        label_id: Option<&str>,
+        resync_limit: usize,
+        reset: bool,
+    ) -> Result<SyncResult, String> {
+        let previous = if reset { None } else { load_checkpoint(checkpoint)? };
+
+        let result = match &previous {
+            Some(start) => match self.history_since(start, label_id).await {
+                Ok(Some(result)) => result,
+                Ok(None) => {
+                    warn!("Gmail history for {} is too long; resyncing", checkpoint);
+                    self.full_sync(label_id, resync_limit, "historyTooLong").await?
+                }
+                Err(e) if is_history_expired(&e) => {
+                    info!("Gmail history {} for {} expired; resyncing", start, checkpoint);
+                    self.full_sync(label_id, resync_limit, "historyExpired").await?
+                }
+                Err(e) => return Err(e),
+            },
+            None => {
+                let reason = if reset { "reset" } else { "noCheckpoint" };
+                self.full_sync(label_id, resync_limit, reason).await?
+            }
+        };
+
+        save_checkpoint(checkpoint, &result.history_id)?;
+        Ok(SyncResult { previous_history_id: previous, ..result })
+    }
+
+    /// Walk `users.history.list` from `start`. `Ok(None)` if there are more
+    /// pages than we are willing to fetch.
+    async fn history_since(&self, start: &str, label_id: Option<&str>) -> Result<Option<SyncResult>, String> {
+        let url = format!("{}/users/me/history", GMAIL_API_BASE);
+        let mut changes = HistoryChanges::default();
+        let mut page_token: Option<String> = None;
+        let mut latest = start.to_string();
+
+        for _ in 0..MAX_HISTORY_PAGES {
+            let mut query = vec![
+                ("startHistoryId", start.to_string()),
+                ("maxResults", HISTORY_PAGE_SIZE.to_string()),
+            ];
+            for kind in ["messageAdded", "messageDeleted", "labelAdded", "labelRemoved"] {
+                query.push(("historyTypes", kind.to_string()));
+            }
+            if let Some(label) = label_id {
+                query.push(("labelId", label.to_string()));
+            }
+            if let Some(token) = &page_token {
+                query.push(("pageToken", token.clone()));
+            }
+
+            let response = self.client.get(&url, &query).await?;
+            for record in extract_array(&response, "history") {
+                changes.apply(&record);
+            }
+            if let Some(id) = history_id_of(&response) {
+                latest = id;
+            }
+
+            match response.get("nextPageToken").and_then(Value::as_str) {
+                Some(token) => page_token = Some(token.to_string()),
+                None => return Ok(Some(changes.into_result(latest))),
+            }
+        }
+        Ok(None)
+    }
+
+    /// Checkpoint "now" and list the newest messages
+    async fn full_sync(&self, label_id: Option<&str>, limit: usize, reason: &str) -> Result<SyncResult, String> {
+        // Take the history ID first so nothing that arrives while listing is missed
+        let history_id = self.current_history_id().await?;
+        let labels = label_id.map(|l| vec![l.to_string()]);
+        let messages = self.list_messages(None, Some(limit), labels).await?;
+
+        Ok(SyncResult {
+            history_id,
+            previous_history_id: None,
+            full_sync: true,
+            reason: Some(reason.to_string()),
+            added: messages
+                .iter()
+                .map(|m| SyncedMessage {
+                    id: str_of(m, "id"),
+                    thread_id: str_of(m, "threadId"),
+                    label_ids: Vec::new(),
+                })
+                .collect(),
+            deleted: Vec::new(),
+            relabelled: Vec::new(),
+        })
+    }
+
+}
+
+// ── Incremental sync ────────────────────────────────────────────────────────
+
+/// Outcome of [`GmailApi::sync`]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncResult {
+    /// The new checkpoint
+    pub history_id: String,
+    pub previous_history_id: Option<String>,
+    /// The results are a fresh listing, not a delta
+    pub full_sync: bool,
+    /// Why a full sync happened (`noCheckpoint`, `historyExpired`, ...)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    pub added: Vec<SyncedMessage>,
+    /// IDs of deleted messages
+    pub deleted: Vec<String>,
+    pub relabelled: Vec<Relabel>,
+}
+
+impl SyncResult {
+    pub fn has_changes(&self) -> bool {
+        !(self.added.is_empty() && self.deleted.is_empty() && self.relabelled.is_empty())
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncedMessage {
+    pub id: String,
+    pub thread_id: String,
+    /// Labels as of the last change seen (empty after a full sync)
+    pub label_ids: Vec<String>,
+}
+
+/// Label changes to a message that existed before the checkpoint
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Relabel {
+    pub id: String,
+    pub thread_id: String,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+/// History records folded into one change per message
+#[derive(Debug, Default)]
+struct HistoryChanges {
+    /// Message ID → (thread ID, labels); new since the checkpoint
+    added: HashMap<String, (String, BTreeSet<String>)>,
+    /// Message ID → (thread ID, labels added, labels removed)
+    relabelled: HashMap<String, (String, BTreeSet<String>, BTreeSet<String>)>,
+    deleted: BTreeSet<String>,
+    /// First-seen order of message IDs
+    order: Vec<String>,
+}
+
+impl HistoryChanges {
+    fn apply(&mut self, record: &Value) {
+        for entry in extract_array(record, "messagesAdded") {
+            let message = &entry["message"];
+            let id = str_of(message, "id");
+            self.note(&id);
+            let labels = string_set(&message["labelIds"]);
+            self.added.insert(id, (str_of(message, "threadId"), labels));
+        }
+        for entry in extract_array(record, "messagesDeleted") {
+            let id = str_of(&entry["message"], "id");
+            self.note(&id);
+            self.added.remove(&id);
+            self.relabelled.remove(&id);
+            self.deleted.insert(id);
+        }
+        for (field, adding) in [("labelsAdded", true), ("labelsRemoved", false)] {
+            for entry in extract_array(record, field) {
+                let message = &entry["message"];
+                let id = str_of(message, "id");
+                if self.deleted.contains(&id) {
+                    continue;
+                }
+                self.note(&id);
+                let labels = string_set(&entry["labelIds"]);
+
+                // New messages just track their current labels
+                if let Some((_, current)) = self.added.get_mut(&id) {
+                    for label in labels {
+                        if adding {
+                            current.insert(label);
+                        } else {
+                            current.remove(&label);
+                        }
+                    }
+                    continue;
+                }
+
+                let (_, added, removed) = self
+                    .relabelled
+                    .entry(id)
+                    .or_insert_with(|| (str_of(message, "threadId"), BTreeSet::new(), BTreeSet::new()));
+                let (gain, lose) = if adding { (added, removed) } else { (removed, added) };
+                for label in labels {
+                    // Added then removed (or the reverse) cancels out
+                    if !lose.remove(&label) {
+                        gain.insert(label);
+                    }
+                }
+            }
+        }
+    }
+
+    fn note(&mut self, id: &str) {
+        if !self.order.iter().any(|seen| seen == id) {
+            self.order.push(id.to_string());
+        }
+    }
+
+    fn into_result(mut self, history_id: String) -> SyncResult {
+        let mut added = Vec::new();
+        let mut relabelled = Vec::new();
+        for id in &self.order {
+            if let Some((thread_id, labels)) = self.added.remove(id) {
+                added.push(SyncedMessage {
+                    id: id.clone(),
+                    thread_id,
+                    label_ids: labels.into_iter().collect(),
+                });
+            } else if let Some((thread_id, plus, minus)) = self.relabelled.remove(id) {
+                if !(plus.is_empty() && minus.is_empty()) {
+                    relabelled.push(Relabel {
+                        id: id.clone(),
+                        thread_id,
+                        added: plus.into_iter().collect(),
+                        removed: minus.into_iter().collect(),
+                    });
+                }
+            }
+        }
+
+        SyncResult {
+            history_id,
+            previous_history_id: None,
+            full_sync: false,
+            reason: None,
+            added,
+            deleted: self.deleted.into_iter().collect(),
+            relabelled,
+        }
+    }
+}
+
+/// A start `historyId` older than Google keeps (about a week) gives 404
+fn is_history_expired(error: &str) -> bool {
+    error.starts_with("Google API error 404")
+}
+
+fn history_id_of(value: &Value) -> Option<String> {
+    match value.get("historyId")? {
+        Value::String(id) => Some(id.clone()),
+        Value::Number(id) => Some(id.to_string()),
+        _ => None,
+    }
+}
+
+fn str_of(value: &Value, field: &str) -> String {
+    value.get(field).and_then(Value::as_str).unwrap_or_default().to_string()
+}
+
+fn string_set(value: &Value) -> BTreeSet<String> {
+    value
+        .as_array()
+        .map(|items| items.iter().filter_map(|v| v.as_str().map(str::to_string)).collect())
+        .unwrap_or_default()
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Checkpoint {
+    history_id: String,
+    updated: String,
+}
+
+fn read_checkpoints() -> Result<HashMap<String, Checkpoint>, String> {
+    let path = crate::common::tairseach_path(SYNC_STATE_FILE)?;
+    match std::fs::read_to_string(&path) {
+        Ok(content) => serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse {:?}: {}", path, e)),
+        Err(_) => Ok(HashMap::new()),
+    }
+}
+
+/// Key of one consumer's sync cursor: `provider:account#label@consumer`
+/// (`*` for all labels). Each consumer, and each label it syncs, advances
+/// its own checkpoint, so one can't swallow changes another hasn't seen.
+pub fn checkpoint_key(account_key: &str, label_id: Option<&str>, consumer: &str) -> String {
+    format!("{}#{}@{}", account_key, label_id.unwrap_or("*"), consumer)
+}
+
+fn load_checkpoint(key: &str) -> Result<Option<String>, String> {
+    let _guard = SYNC_STATE_LOCK.lock().unwrap_or_else(|p| p.into_inner());
+    Ok(read_checkpoints()?.remove(key).map(|c| c.history_id))
+}
+
+fn save_checkpoint(key: &str, history_id: &str) -> Result<(), String> {
+    let _guard = SYNC_STATE_LOCK.lock().unwrap_or_else(|p| p.into_inner());
+    let mut checkpoints = read_checkpoints()?;
+    checkpoints.insert(
+        key.to_string(),
+        Checkpoint {
+            history_id: history_id.to_string(),
+            updated: chrono::Utc::now().to_rfc3339(),
+        },
+    );
+    let json = serde_json::to_string_pretty(&checkpoints)
+        .map_err(|e| format!("Failed to serialize sync state: {}", e))?;
+    let path = crate::common::tairseach_path(SYNC_STATE_FILE)?;
+    crate::common::write_atomic(&path, json.as_bytes(), Some(0o600))
+}
+
+/// What a reply needs from the message it answers
+#[derive(Debug)]
+pub struct ReplyContext {
+    pub thread_id: Option<String>,
+    pub in_reply_to: String,
+    pub references: String,
+    /// Original subject with `Re: ` prepended (once)
+    pub subject: String,
+    pub reply_to: Option<String>,
+}
+
+/// `{raw, threadId}` message resource for send and drafts
+fn raw_message(message: &OutgoingMessage, thread_id: Option<&str>) -> Value {
+    let mut resource = json!({ "raw": base64_url_encode(&message.build()) });
+    if let Some(thread_id) = thread_id {
+        resource["threadId"] = json!(thread_id);
+    }
+    resource
+}
+
+/// Add `headers` (common ones by name), `body` (`text`/`html`) and
+/// `attachments` to a `format=full` message, next to the raw `payload`
+fn add_decoded_content(message: &mut Value) {
+    let Some(payload) = message.get("payload") else {
+        return;
+    };
+    let content = mime::extract_content(payload);
+    let mut headers = serde_json::Map::new();
+    for name in ["From", "To", "Cc", "Subject", "Date", "Message-ID"] {
+        if let Some(value) = mime::header_value(payload, name) {
+            headers.insert(name.to_ascii_lowercase(), json!(value));
+        }
+    }
+
+    message["headers"] = Value::Object(headers);
+    message["body"] = json!({ "text": content.text, "html": content.html });
+    message["attachments"] = json!(content.attachments);
 }
 
 /// Base64url encode (no padding) per RFC 4648 §5
@@ -222,4 +721,43 @@ mod tests {
         assert!(!encoded.contains('+'));  // URL-safe
         assert!(!encoded.contains('/'));  // URL-safe
     }
+
+    #[test]
+    fn test_checkpoint_keys_are_per_label_and_consumer() {
+        let all = checkpoint_key("google:me@example.com", None, "agent");
+        assert_eq!(all, "google:me@example.com#*@agent");
+        assert_ne!(all, checkpoint_key("google:me@example.com", Some("INBOX"), "agent"));
+        assert_ne!(all, checkpoint_key("google:me@example.com", None, "watch"));
+    }
+
+    #[test]
+    fn test_history_changes_fold() {
+        let mut changes = HistoryChanges::default();
+        let msg = |id: &str| json!({"id": id, "threadId": format!("t-{}", id)});
+        changes.apply(&json!({
+            "messagesAdded": [{"message": {"id": "new", "threadId": "t-new", "labelIds": ["INBOX", "UNREAD"]}}],
+            "labelsRemoved": [
+                {"message": msg("new"), "labelIds": ["UNREAD"]},
+                {"message": msg("old"), "labelIds": ["UNREAD"]}
+            ]
+        }));
+        changes.apply(&json!({
+            "labelsAdded": [
+                {"message": msg("old"), "labelIds": ["STARRED", "UNREAD"]},
+                {"message": msg("gone"), "labelIds": ["STARRED"]}
+            ],
+            "messagesDeleted": [{"message": msg("gone")}]
+        }));
+
+        let result = changes.into_result("200".to_string());
+        assert_eq!(result.added.len(), 1);
+        assert_eq!(result.added[0].label_ids, ["INBOX"]);
+        assert_eq!(result.deleted, ["gone"]);
+        // UNREAD removed then re-added cancels out
+        assert_eq!(
+            result.relabelled,
+            [Relabel { id: "old".into(), thread_id: "t-old".into(), added: vec!["STARRED".into()], removed: vec![] }]
+        );
+        assert!(result.has_changes());
+    }
 }
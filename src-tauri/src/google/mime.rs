@@ -0,0 +1,445 @@
+//! MIME Messages for Gmail
+//!
+//! Building outgoing RFC 5322 messages:
+//! - `text/plain`, `text/html` or `multipart/alternative` bodies
+//! - attachments in `multipart/mixed`
+//! - RFC 2047 encoded-words for non-ASCII headers, RFC 2231 file names
+//! - `In-Reply-To`/`References` for replies
+//!
+//! and reading Gmail message payloads back into decoded plain-text/HTML bodies
+//! and an attachment list, so callers never walk the part tree themselves.
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use serde::Serialize;
+use serde_json::Value;
+
+/// Longest line of base64 in a part body (RFC 2045)
+const BASE64_LINE: usize = 76;
+/// Input bytes per encoded-word, keeping each word within 75 chars (RFC 2047)
+const ENCODED_WORD_BYTES: usize = 45;
+
+/// A file to attach
+#[derive(Debug, Clone)]
+pub struct Attachment {
+    pub filename: String,
+    pub content_type: String,
+    pub data: Vec<u8>,
+}
+
+/// An outgoing message
+#[derive(Debug, Clone, Default)]
+pub struct OutgoingMessage {
+    pub from: Option<String>,
+    pub to: Vec<String>,
+    pub cc: Vec<String>,
+    pub bcc: Vec<String>,
+    pub subject: String,
+    pub text: Option<String>,
+    pub html: Option<String>,
+    pub attachments: Vec<Attachment>,
+    /// `Message-ID` of the message being replied to
+    pub in_reply_to: Option<String>,
+    /// `References` chain, ending with `in_reply_to`
+    pub references: Option<String>,
+}
+
+impl OutgoingMessage {
+    /// Render as an RFC 5322 message with CRLF line endings
+    pub fn build(&self) -> Vec<u8> {
+        let mut out = String::new();
+        let mut header = |name: &str, value: String| {
+            out.push_str(name);
+            out.push_str(": ");
+            out.push_str(&value);
+            out.push_str("\r\n");
+        };
+
+        if let Some(from) = &self.from {
+            header("From", encode_address(from));
+        }
+        for (name, addrs) in [("To", &self.to), ("Cc", &self.cc), ("Bcc", &self.bcc)] {
+            if !addrs.is_empty() {
+                let list: Vec<String> = addrs.iter().map(|a| encode_address(a)).collect();
+                header(name, list.join(", "));
+            }
+        }
+        header("Subject", encode_header(&self.subject));
+        if let Some(id) = &self.in_reply_to {
+            header("In-Reply-To", single_line(id));
+        }
+        if let Some(refs) = &self.references {
+            header("References", single_line(refs));
+        }
+        header("MIME-Version", "1.0".to_string());
+
+        let body = self.body_part();
+        let mut bytes = out.into_bytes();
+        if self.attachments.is_empty() {
+            bytes.extend(body);
+        } else {
+            let mut parts = vec![body];
+            parts.extend(self.attachments.iter().map(attachment_part));
+            bytes.extend(multipart("mixed", &parts));
+        }
+        bytes
+    }
+
+    /// The text/HTML body as a part (headers included)
+    fn body_part(&self) -> Vec<u8> {
+        let text = self.text.as_deref().map(|t| text_part("plain", t));
+        let html = self.html.as_deref().map(|h| text_part("html", h));
+        match (text, html) {
+            (Some(text), Some(html)) => multipart("alternative", &[text, html]),
+            (None, Some(html)) => html,
+            (text, None) => text.unwrap_or_else(|| text_part("plain", "")),
+        }
+    }
+}
+
+fn text_part(subtype: &str, content: &str) -> Vec<u8> {
+    let mut part = format!(
+        "Content-Type: text/{}; charset=UTF-8\r\nContent-Transfer-Encoding: base64\r\n\r\n",
+        subtype
+    )
+    .into_bytes();
+    part.extend(wrapped_base64(content.as_bytes()));
+    part
+}
+
+fn attachment_part(attachment: &Attachment) -> Vec<u8> {
+    let name = &attachment.filename;
+    let mut part = format!(
+        "Content-Type: {}; name=\"{}\"\r\nContent-Disposition: attachment; {}\r\nContent-Transfer-Encoding: base64\r\n\r\n",
+        single_line(&attachment.content_type),
+        encode_header(&name.replace('"', "")),
+        filename_param(name)
+    )
+    .into_bytes();
+    part.extend(wrapped_base64(&attachment.data));
+    part
+}
+
+/// `multipart/<subtype>` part wrapping `parts` (each with its own headers)
+fn multipart(subtype: &str, parts: &[Vec<u8>]) -> Vec<u8> {
+    let boundary = format!("=_tairseach_{:016x}", rand::random::<u64>());
+    let mut out = format!(
+        "Content-Type: multipart/{}; boundary=\"{}\"\r\n\r\n",
+        subtype, boundary
+    )
+    .into_bytes();
+    for part in parts {
+        out.extend(format!("--{}\r\n", boundary).into_bytes());
+        out.extend(part);
+        out.extend(b"\r\n");
+    }
+    out.extend(format!("--{}--\r\n", boundary).into_bytes());
+    out
+}
+
+fn wrapped_base64(data: &[u8]) -> Vec<u8> {
+    let encoded = BASE64.encode(data);
+    let mut out = Vec::with_capacity(encoded.len() + encoded.len() / BASE64_LINE * 2 + 2);
+    for line in encoded.as_bytes().chunks(BASE64_LINE) {
+        out.extend_from_slice(line);
+        out.extend_from_slice(b"\r\n");
+    }
+    out
+}
+
+/// Encode a header value as RFC 2047 encoded-words if it isn't plain ASCII
+pub fn encode_header(value: &str) -> String {
+    if value.chars().all(|c| c.is_ascii() && !c.is_ascii_control()) {
+        return value.to_string();
+    }
+
+    // Split on char boundaries so no word holds part of a UTF-8 sequence
+    let mut words = Vec::new();
+    let mut chunk = String::new();
+    for c in value.chars().filter(|c| !matches!(c, '\r' | '\n')) {
+        if chunk.len() + c.len_utf8() > ENCODED_WORD_BYTES {
+            words.push(std::mem::take(&mut chunk));
+        }
+        chunk.push(c);
+    }
+    if !chunk.is_empty() {
+        words.push(chunk);
+    }
+    words
+        .iter()
+        .map(|w| format!("=?UTF-8?B?{}?=", BASE64.encode(w.as_bytes())))
+        .collect::<Vec<_>>()
+        .join("\r\n ")
+}
+
+/// Drop CR and LF so a value can't end its header line and start another
+fn single_line(value: &str) -> String {
+    value.chars().filter(|c| !matches!(c, '\r' | '\n')).collect()
+}
+
+/// Encode the display name of `Name <addr@example.com>`, leaving the address
+fn encode_address(address: &str) -> String {
+    let address = single_line(address);
+    match address.rsplit_once('<') {
+        Some((name, addr)) if !name.trim().is_empty() => {
+            let name = name.trim().trim_matches('"');
+            let encoded = encode_header(name);
+            if encoded == name {
+                format!("\"{}\" <{}", name.replace('"', ""), addr)
+            } else {
+                format!("{} <{}", encoded, addr)
+            }
+        }
+        _ => address.trim().to_string(),
+    }
+}
+
+/// `filename=` for ASCII names, RFC 2231 `filename*=` otherwise
+fn filename_param(name: &str) -> String {
+    if name.is_ascii() {
+        format!("filename=\"{}\"", single_line(name).replace('"', ""))
+    } else {
+        format!("filename*=UTF-8''{}", urlencoding::encode(name))
+    }
+}
+
+/// Content type from a file name's extension
+pub fn guess_content_type(name: &str) -> &'static str {
+    let extension = name.rsplit_once('.').map(|(_, ext)| ext.to_ascii_lowercase());
+    match extension.as_deref() {
+        Some("txt") | Some("log") => "text/plain",
+        Some("md") | Some("markdown") => "text/markdown",
+        Some("csv") => "text/csv",
+        Some("html") | Some("htm") => "text/html",
+        Some("ics") => "text/calendar",
+        Some("json") => "application/json",
+        Some("xml") => "application/xml",
+        Some("pdf") => "application/pdf",
+        Some("zip") => "application/zip",
+        Some("png") => "image/png",
+        Some("jpg") | Some("jpeg") => "image/jpeg",
+        Some("gif") => "image/gif",
+        Some("svg") => "image/svg+xml",
+        Some("docx") => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        Some("xlsx") => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        Some("pptx") => "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+        _ => "application/octet-stream",
+    }
+}
+
+// ── Reading payloads ────────────────────────────────────────────────────────
+
+/// Decoded content of a Gmail message payload
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct MessageContent {
+    pub text: Option<String>,
+    pub html: Option<String>,
+    pub attachments: Vec<AttachmentInfo>,
+}
+
+/// An attachment as listed in a payload; fetch its bytes with
+/// `GmailApi::get_attachment`
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttachmentInfo {
+    pub attachment_id: String,
+    pub filename: String,
+    pub mime_type: String,
+    pub size: u64,
+    pub part_id: Option<String>,
+}
+
+/// Walk a `format=full` payload, collecting the first plain-text and HTML
+/// bodies and every attachment
+pub fn extract_content(payload: &Value) -> MessageContent {
+    let mut content = MessageContent::default();
+    walk_part(payload, &mut content);
+    content
+}
+
+fn walk_part(part: &Value, content: &mut MessageContent) {
+    let mime_type = part.get("mimeType").and_then(Value::as_str).unwrap_or_default();
+    let filename = part.get("filename").and_then(Value::as_str).unwrap_or_default();
+    let body = part.get("body");
+
+    if let Some(parts) = part.get("parts").and_then(Value::as_array) {
+        for child in parts {
+            walk_part(child, content);
+        }
+        return;
+    }
+
+    let attachment_id = body
+        .and_then(|b| b.get("attachmentId"))
+        .and_then(Value::as_str);
+    let inline_text = filename.is_empty() && mime_type.starts_with("text/");
+
+    if !inline_text {
+        if let Some(attachment_id) = attachment_id {
+            content.attachments.push(AttachmentInfo {
+                attachment_id: attachment_id.to_string(),
+                filename: filename.to_string(),
+                mime_type: mime_type.to_string(),
+                size: body.and_then(|b| b.get("size")).and_then(Value::as_u64).unwrap_or(0),
+                part_id: part.get("partId").and_then(Value::as_str).map(str::to_string),
+            });
+        }
+        return;
+    }
+
+    let Some(data) = body.and_then(|b| b.get("data")).and_then(Value::as_str) else {
+        return;
+    };
+    let Some(bytes) = decode_base64_url(data) else {
+        return;
+    };
+    let text = decode_charset(&bytes, charset_of(part));
+    match mime_type {
+        "text/plain" if content.text.is_none() => content.text = Some(text),
+        "text/html" if content.html.is_none() => content.html = Some(text),
+        _ => {}
+    }
+}
+
+/// The `charset` parameter of a part's Content-Type header
+fn charset_of(part: &Value) -> Option<String> {
+    header_value(part, "Content-Type").and_then(|ct| {
+        ct.split(';').find_map(|param| {
+            let (key, value) = param.split_once('=')?;
+            (key.trim().eq_ignore_ascii_case("charset"))
+                .then(|| value.trim().trim_matches('"').to_ascii_lowercase())
+        })
+    })
+}
+
+/// Decode text in `charset`. UTF-8 and Latin-1 family charsets are
+/// handled; anything else is read as UTF-8, replacing invalid bytes.
+fn decode_charset(bytes: &[u8], charset: Option<String>) -> String {
+    match charset.as_deref() {
+        Some("iso-8859-1") | Some("latin1") | Some("windows-1252") | Some("cp1252") => {
+            bytes.iter().map(|&b| b as char).collect()
+        }
+        _ => String::from_utf8_lossy(bytes).into_owned(),
+    }
+}
+
+/// First header called `name` (case-insensitive) in a payload or part
+pub fn header_value(part: &Value, name: &str) -> Option<String> {
+    part.get("headers")?
+        .as_array()?
+        .iter()
+        .find(|h| {
+            h.get("name")
+                .and_then(Value::as_str)
+                .is_some_and(|n| n.eq_ignore_ascii_case(name))
+        })
+        .and_then(|h| h.get("value"))
+        .and_then(Value::as_str)
+        .map(str::to_string)
+}
+
+/// Decode Gmail's base64url (padding optional)
+pub fn decode_base64_url(data: &str) -> Option<Vec<u8>> {
+    use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+    URL_SAFE_NO_PAD.decode(data.trim_end_matches('=')).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_encode_header() {
+        assert_eq!(encode_header("Hello"), "Hello");
+        let encoded = encode_header("Grüße aus Köln");
+        assert!(encoded.starts_with("=?UTF-8?B?"));
+        let word = encoded.trim_start_matches("=?UTF-8?B?").trim_end_matches("?=");
+        assert_eq!(BASE64.decode(word).unwrap(), "Grüße aus Köln".as_bytes());
+
+        // Long values split into several words, none over 75 chars
+        let long = encode_header(&"é".repeat(100));
+        assert!(long.split("\r\n ").all(|w| w.len() <= 75));
+        assert!(long.split("\r\n ").count() > 1);
+    }
+
+    #[test]
+    fn test_encode_address() {
+        assert_eq!(encode_address("a@example.com"), "a@example.com");
+        assert_eq!(encode_address("Ada <a@example.com>"), "\"Ada\" <a@example.com>");
+        assert!(encode_address("Zoë <z@example.com>").starts_with("=?UTF-8?B?"));
+    }
+
+    #[test]
+    fn test_build_multipart_with_attachment() {
+        let message = OutgoingMessage {
+            to: vec!["a@example.com".into()],
+            subject: "Report".into(),
+            text: Some("See attached".into()),
+            html: Some("<p>See attached</p>".into()),
+            attachments: vec![Attachment {
+                filename: "résumé.pdf".into(),
+                content_type: "application/pdf".into(),
+                data: b"%PDF".to_vec(),
+            }],
+            in_reply_to: Some("<abc@mail.gmail.com>".into()),
+            references: Some("<abc@mail.gmail.com>".into()),
+            ..Default::default()
+        };
+        let raw = String::from_utf8(message.build()).unwrap();
+        assert!(raw.contains("Subject: Report\r\n"));
+        assert!(raw.contains("In-Reply-To: <abc@mail.gmail.com>\r\n"));
+        assert!(raw.contains("Content-Type: multipart/mixed;"));
+        assert!(raw.contains("Content-Type: multipart/alternative;"));
+        assert!(raw.contains("filename*=UTF-8''r%C3%A9sum%C3%A9.pdf"));
+        assert!(raw.contains(&BASE64.encode("%PDF")));
+    }
+
+    #[test]
+    fn test_header_values_stay_on_one_line() {
+        let message = OutgoingMessage {
+            from: Some("Ada <a@example.com>\r\nBcc: x@example.com".into()),
+            to: vec!["b@example.com\nBcc: y@example.com".into()],
+            subject: "Hi\r\nBcc: z@example.com".into(),
+            attachments: vec![Attachment {
+                filename: "a.txt\r\nX-Injected: 1".into(),
+                content_type: "text/plain\r\nX-Injected: 1".into(),
+                data: b"hi".to_vec(),
+            }],
+            in_reply_to: Some("<abc@mail.gmail.com>\r\nBcc: w@example.com".into()),
+            references: Some("<abc@mail.gmail.com>\nBcc: v@example.com".into()),
+            ..Default::default()
+        };
+        let raw = String::from_utf8(message.build()).unwrap();
+        assert!(!raw.contains("\nBcc:"));
+        assert!(!raw.contains("\nX-Injected:"));
+        assert!(raw.contains("In-Reply-To: <abc@mail.gmail.com>Bcc: w@example.com\r\n"));
+    }
+
+    #[test]
+    fn test_extract_content() {
+        let b64 = |s: &str| base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(s);
+        let payload = json!({
+            "mimeType": "multipart/mixed",
+            "parts": [
+                {"mimeType": "multipart/alternative", "parts": [
+                    {"mimeType": "text/plain", "filename": "", "body": {"data": b64("Hi there")}},
+                    {"mimeType": "text/html", "filename": "", "body": {"data": b64("<b>Hi</b>")}}
+                ]},
+                {"partId": "1", "mimeType": "application/pdf", "filename": "a.pdf",
+                 "body": {"attachmentId": "ANGj", "size": 1234}}
+            ]
+        });
+        let content = extract_content(&payload);
+        assert_eq!(content.text.as_deref(), Some("Hi there"));
+        assert_eq!(content.html.as_deref(), Some("<b>Hi</b>"));
+        assert_eq!(content.attachments.len(), 1);
+        assert_eq!(content.attachments[0].filename, "a.pdf");
+        assert_eq!(content.attachments[0].size, 1234);
+    }
+
+    #[test]
+    fn test_latin1_charset() {
+        let part = json!({"headers": [{"name": "content-type", "value": "text/plain; charset=\"ISO-8859-1\""}]});
+        assert_eq!(decode_charset(&[0x63, 0x61, 0x66, 0xe9], charset_of(&part)), "café");
+    }
+}
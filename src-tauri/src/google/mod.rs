@@ -1,26 +1,44 @@
 //! Google API Client Module
 //!
-//! Provides authenticated HTTP client access to Google APIs (Gmail, Calendar, Drive).
+//! Provides authenticated HTTP client access to Google APIs (Gmail, Calendar, Contacts, Drive).
 //! All methods use Tier 1 (proxy mode) — OAuth tokens never leave Tairseach process.
 
 pub mod client;
 pub mod common;
 pub mod gmail;
 pub mod calendar_api;
+pub mod contacts_api;
+pub mod drive_api;
+pub mod mime;
 
 pub use gmail::GmailApi;
 pub use calendar_api::CalendarApi;
+pub use contacts_api::ContactsApi;
+pub use drive_api::DriveApi;
 
 /// Macro to implement the standard Google API wrapper constructor pattern.
 /// Each API struct wraps a `GoogleClient` and provides `new(access_token)`.
 macro_rules! google_api_wrapper {
-    ($name:ident) => {
+    ($name:ident, $namespace:expr) => {
         impl $name {
             /// Create a new API client with an OAuth access token
+            #[allow(dead_code)]
             pub fn new(access_token: String) -> Result<Self, String> {
-                let client = crate::google::client::GoogleClient::new(access_token)?;
+                let client = crate::google::client::GoogleClient::new($namespace, access_token)?;
                 Ok(Self { client })
             }
+
+            /// Create a client that refreshes the token for `provider`/`account`
+            /// and retries once when the API answers 401
+            pub fn for_account(access_token: String, provider: &str, account: &str) -> Result<Self, String> {
+                let client = crate::google::client::GoogleClient::for_account($namespace, access_token, provider, account)?;
+                Ok(Self { client })
+            }
+
+            /// Token state shared with the client, for reporting refresh failures
+            pub fn token_source(&self) -> std::sync::Arc<crate::google::client::TokenSource> {
+                self.client.token_source()
+            }
         }
     };
 }
@@ -73,6 +73,9 @@ async fn start_proxy_server_internal() -> Result<(), String> {
         }
     };
     
+    // Publish permission changes to subscribed socket clients
+    proxy::notifications::spawn_permission_watcher(std::time::Duration::from_secs(10));
+    
     // Create capability router
     tracing::info!("Creating capability router");
     let router = Arc::new(router::CapabilityRouter::new(registry, auth_broker));
@@ -90,11 +93,18 @@ async fn start_proxy_server_internal() -> Result<(), String> {
     let socket_path = server.socket_path().clone();
     tracing::info!("Starting proxy server at {:?}", socket_path);
     
-    if let Err(e) = server.start().await {
-        tracing::error!("Proxy server error: {}", e);
-        let mut state = PROXY_STATE.write().await;
+    let result = server.start().await;
+    
+    // Stopped (via `server.shutdown` or `proxy_server_stop`) or failed
+    let mut state = PROXY_STATE.write().await;
+    if state.server.as_ref().is_some_and(|s| Arc::ptr_eq(s, &server)) {
         state.running = false;
         state.server = None;
+    }
+    drop(state);
+    
+    if let Err(e) = result {
+        tracing::error!("Proxy server error: {}", e);
         return Err(e.to_string());
     }
     
@@ -119,17 +129,21 @@ async fn proxy_server_start() -> Result<serde_json::Value, String> {
     proxy_status_get().await
 }
 
-/// Stop the proxy server
+/// Stop the proxy server, waiting for open connections to drain
 #[tauri::command]
 async fn proxy_server_stop() -> Result<serde_json::Value, String> {
-    let state = PROXY_STATE.read().await;
+    let server = PROXY_STATE.read().await.server.clone();
     
-    if let Some(server) = &state.server {
+    if let Some(server) = server {
         server.shutdown();
+        // The server enforces its own grace deadline; this only guards
+        // against a server that never started its accept loop
+        let limit = server.shutdown_grace() + std::time::Duration::from_secs(5);
+        if tokio::time::timeout(limit, server.wait_stopped()).await.is_err() {
+            tracing::warn!("Proxy server did not confirm shutdown within {:?}", limit);
+        }
     }
     
-    drop(state);
-    
     let mut state = PROXY_STATE.write().await;
     state.running = false;
     state.server = None;
@@ -139,6 +153,50 @@ async fn proxy_server_stop() -> Result<serde_json::Value, String> {
     }))
 }
 
+/// Forwards held tool calls to the webview
+struct TauriApprovalPrompter(tauri::AppHandle);
+
+impl proxy::approval::ApprovalPrompter for TauriApprovalPrompter {
+    fn requested(&self, pending: &proxy::approval::PendingApproval) {
+        use tauri::Emitter;
+        if let Err(e) = self.0.emit("tairseach://approval-requested", pending) {
+            tracing::warn!("Failed to emit approval request: {}", e);
+        }
+    }
+
+    fn resolved(&self, id: u64, outcome: proxy::approval::Outcome) {
+        use tauri::Emitter;
+        let _ = self.0.emit(
+            "tairseach://approval-resolved",
+            serde_json::json!({ "id": id, "outcome": outcome }),
+        );
+    }
+}
+
+/// List tool calls waiting for approval
+#[tauri::command]
+async fn approvals_pending_list() -> Result<Vec<proxy::approval::PendingApproval>, String> {
+    Ok(proxy::approval::gate().pending())
+}
+
+/// Approve or deny a held tool call
+#[tauri::command]
+async fn approvals_single_decide(id: u64, decision: proxy::approval::Decision) -> Result<(), String> {
+    proxy::approval::gate().decide(id, decision)
+}
+
+/// List remembered "always allow" decisions
+#[tauri::command]
+async fn approvals_remembered_list() -> Result<Vec<proxy::approval::RememberedApproval>, String> {
+    Ok(proxy::approval::gate().remembered())
+}
+
+/// Forget an "always allow" decision
+#[tauri::command]
+async fn approvals_remembered_revoke(client: String, tool: String) -> Result<bool, String> {
+    proxy::approval::gate().forget(&client, &tool)
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     // Initialize tracing
@@ -194,6 +252,7 @@ pub fn run() {
             auth::auth_revoke_token,
             auth::auth_store_token,
             auth::auth_start_google_oauth,
+            auth::auth_start_oauth,
             // Credentials
             auth::auth_credential_types_list,
             auth::auth_credentials_store,
@@ -208,6 +267,11 @@ pub fn run() {
             proxy_status_get,
             proxy_server_start,
             proxy_server_stop,
+            // Approvals
+            approvals_pending_list,
+            approvals_single_decide,
+            approvals_remembered_list,
+            approvals_remembered_revoke,
         ])
         .setup(|_app| {
             #[cfg(debug_assertions)]
@@ -216,6 +280,9 @@ pub fn run() {
                 window.open_devtools();
             }
             
+            // Destructive tool calls are approved from the UI
+            proxy::approval::gate().set_prompter(Arc::new(TauriApprovalPrompter(_app.handle().clone())));
+            
             // Start the proxy server in a background task using Tauri's runtime
             tracing::info!("Spawning proxy server task");
             tauri::async_runtime::spawn(async {
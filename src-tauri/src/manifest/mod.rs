@@ -4,6 +4,7 @@
 
 pub mod loader;
 pub mod registry;
+pub mod schema;
 pub mod types;
 
 #[allow(unused_imports)]
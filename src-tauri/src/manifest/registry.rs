@@ -72,6 +72,13 @@ impl ManifestRegistry {
         let count = manifests.len();
         let tool_count = tool_index.len();
 
+        crate::common::http::set_policies(
+            manifests
+                .iter()
+                .filter_map(|(id, m)| m.http.clone().map(|policy| (id.clone(), policy)))
+                .collect(),
+        );
+
         // Update registry atomically
         *self.manifests.write().await = manifests;
         *self.tool_index.write().await = tool_index;
@@ -171,10 +178,18 @@ impl ManifestRegistry {
                 while rx.try_recv().is_ok() {}
 
                 info!("Manifest change detected, reloading...");
-                if let Err(e) = self.load_from_disk().await {
-                    warn!("Failed to reload manifests: {}", e);
-                } else {
-                    info!("Manifests reloaded successfully");
+                match self.load_from_disk().await {
+                    Ok(count) => {
+                        info!("Manifests reloaded successfully");
+                        crate::proxy::notifications::publish(
+                            crate::proxy::notifications::topics::MANIFEST_RELOADED,
+                            serde_json::json!({
+                                "manifests": count,
+                                "tools": self.list_tool_names().await.len(),
+                            }),
+                        );
+                    }
+                    Err(e) => warn!("Failed to reload manifests: {}", e),
                 }
             }
         });
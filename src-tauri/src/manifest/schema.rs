@@ -0,0 +1,368 @@
+//! Tool Schema Validation
+//!
+//! Checks tool params against a manifest tool's `inputSchema` (and results
+//! against `outputSchema`) at call time.
+//!
+//! Covers the JSON Schema subset manifests use: `type`, `enum`, `const`,
+//! `properties`, `required`, `additionalProperties`, `items`,
+//! `minItems`/`maxItems`, `minimum`/`maximum` (and the exclusive forms),
+//! `minLength`/`maxLength`, `pattern`, `oneOf`/`anyOf`/`allOf`. Other
+//! keywords (`format`, `description`, …) are annotations and are ignored.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use tracing::warn;
+
+/// Compiled `pattern` keywords (`None` for invalid ones, warned about once).
+/// Patterns only come from manifests, so the cache stays small.
+static PATTERNS: Lazy<Mutex<HashMap<String, Option<Regex>>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// A single validation failure
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaError {
+    /// JSON Pointer to the offending value (`""` = the whole document)
+    pub path: String,
+    /// Schema keyword that failed
+    pub keyword: &'static str,
+    pub message: String,
+}
+
+impl std::fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let path = if self.path.is_empty() { "(root)" } else { &self.path };
+        write!(f, "{}: {}", path, self.message)
+    }
+}
+
+/// Fill in `default`s, then validate `params` against an input schema.
+/// Absent params are treated as `{}` when the schema describes an object.
+pub fn validate_input(schema: &Value, params: &mut Value) -> Result<(), Vec<SchemaError>> {
+    if params.is_null() && describes_object(schema) {
+        *params = Value::Object(Map::new());
+    }
+    apply_defaults(schema, params);
+    let errors = validate(schema, params);
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Validate `instance` against `schema`, collecting every failure
+pub fn validate(schema: &Value, instance: &Value) -> Vec<SchemaError> {
+    let mut errors = Vec::new();
+    check(schema, instance, "", &mut errors);
+    errors
+}
+
+/// Insert declared `default`s for missing object properties, recursively
+pub fn apply_defaults(schema: &Value, instance: &mut Value) {
+    match instance {
+        Value::Object(obj) => {
+            let Some(props) = schema.get("properties").and_then(|p| p.as_object()) else {
+                return;
+            };
+            for (name, sub) in props {
+                if !obj.contains_key(name) {
+                    if let Some(default) = sub.get("default") {
+                        obj.insert(name.clone(), default.clone());
+                    }
+                }
+                if let Some(value) = obj.get_mut(name) {
+                    apply_defaults(sub, value);
+                }
+            }
+        }
+        Value::Array(items) => {
+            if let Some(item_schema) = schema.get("items").filter(|s| s.is_object()) {
+                for item in items {
+                    apply_defaults(item_schema, item);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn describes_object(schema: &Value) -> bool {
+    schema.get("type").and_then(|t| t.as_str()) == Some("object") || schema.get("properties").is_some()
+}
+
+fn check(schema: &Value, instance: &Value, path: &str, errors: &mut Vec<SchemaError>) {
+    let schema = match schema {
+        Value::Object(s) => s,
+        // `false` rejects everything, `true` (or anything else) accepts
+        Value::Bool(false) => {
+            errors.push(error(path, "false", "no value is allowed here"));
+            return;
+        }
+        _ => return,
+    };
+
+    if let Some(ty) = schema.get("type") {
+        let allowed: Vec<&str> = match ty {
+            Value::String(t) => vec![t.as_str()],
+            Value::Array(ts) => ts.iter().filter_map(|t| t.as_str()).collect(),
+            _ => Vec::new(),
+        };
+        if !allowed.is_empty() && !allowed.iter().any(|t| type_matches(t, instance)) {
+            errors.push(error(
+                path,
+                "type",
+                format!("expected {}, got {}", allowed.join(" or "), type_name(instance)),
+            ));
+            // Further keywords would only repeat the mismatch
+            return;
+        }
+    }
+
+    if let Some(options) = schema.get("enum").and_then(|e| e.as_array()) {
+        if !options.contains(instance) {
+            errors.push(error(path, "enum", format!("must be one of {}", Value::Array(options.clone()))));
+        }
+    }
+    if let Some(expected) = schema.get("const") {
+        if expected != instance {
+            errors.push(error(path, "const", format!("must equal {}", expected)));
+        }
+    }
+
+    match instance {
+        Value::Object(obj) => check_object(schema, obj, path, errors),
+        Value::Array(items) => check_array(schema, items, path, errors),
+        Value::String(s) => check_string(schema, s, path, errors),
+        Value::Number(n) => check_number(schema, n.as_f64().unwrap_or_default(), path, errors),
+        _ => {}
+    }
+
+    check_combinators(schema, instance, path, errors);
+}
+
+fn check_object(schema: &Map<String, Value>, obj: &Map<String, Value>, path: &str, errors: &mut Vec<SchemaError>) {
+    if let Some(required) = schema.get("required").and_then(|r| r.as_array()) {
+        for name in required.iter().filter_map(|n| n.as_str()) {
+            if !obj.contains_key(name) {
+                errors.push(error(path, "required", format!("missing required property '{}'", name)));
+            }
+        }
+    }
+
+    let props = schema.get("properties").and_then(|p| p.as_object());
+    for (name, value) in obj {
+        let child = child_path(path, name);
+        match props.and_then(|p| p.get(name)) {
+            Some(sub) => check(sub, value, &child, errors),
+            None => match schema.get("additionalProperties") {
+                Some(Value::Bool(false)) => {
+                    errors.push(error(&child, "additionalProperties", format!("unexpected property '{}'", name)));
+                }
+                Some(sub @ Value::Object(_)) => check(sub, value, &child, errors),
+                _ => {}
+            },
+        }
+    }
+}
+
+fn check_array(schema: &Map<String, Value>, items: &[Value], path: &str, errors: &mut Vec<SchemaError>) {
+    if let Some(min) = schema.get("minItems").and_then(|v| v.as_u64()) {
+        if (items.len() as u64) < min {
+            errors.push(error(path, "minItems", format!("must have at least {} item(s)", min)));
+        }
+    }
+    if let Some(max) = schema.get("maxItems").and_then(|v| v.as_u64()) {
+        if items.len() as u64 > max {
+            errors.push(error(path, "maxItems", format!("must have at most {} item(s)", max)));
+        }
+    }
+    if let Some(item_schema) = schema.get("items") {
+        for (index, item) in items.iter().enumerate() {
+            check(item_schema, item, &child_path(path, &index.to_string()), errors);
+        }
+    }
+}
+
+fn check_string(schema: &Map<String, Value>, s: &str, path: &str, errors: &mut Vec<SchemaError>) {
+    let len = s.chars().count() as u64;
+    if let Some(min) = schema.get("minLength").and_then(|v| v.as_u64()) {
+        if len < min {
+            errors.push(error(path, "minLength", format!("must be at least {} character(s)", min)));
+        }
+    }
+    if let Some(max) = schema.get("maxLength").and_then(|v| v.as_u64()) {
+        if len > max {
+            errors.push(error(path, "maxLength", format!("must be at most {} character(s)", max)));
+        }
+    }
+    if let Some(pattern) = schema.get("pattern").and_then(|v| v.as_str()) {
+        if compiled(pattern).is_some_and(|re| !re.is_match(s)) {
+            errors.push(error(path, "pattern", format!("does not match pattern '{}'", pattern)));
+        }
+    }
+}
+
+/// Cached compile of a `pattern` keyword
+fn compiled(pattern: &str) -> Option<Regex> {
+    let mut cache = PATTERNS.lock().unwrap_or_else(|p| p.into_inner());
+    cache
+        .entry(pattern.to_string())
+        .or_insert_with(|| match Regex::new(pattern) {
+            Ok(re) => Some(re),
+            Err(e) => {
+                warn!("Ignoring invalid schema pattern '{}': {}", pattern, e);
+                None
+            }
+        })
+        .clone()
+}
+
+fn check_number(schema: &Map<String, Value>, n: f64, path: &str, errors: &mut Vec<SchemaError>) {
+    let bound = |key: &str| schema.get(key).and_then(|v| v.as_f64());
+    if let Some(min) = bound("minimum").filter(|min| n < *min) {
+        errors.push(error(path, "minimum", format!("must be >= {}", min)));
+    }
+    if let Some(max) = bound("maximum").filter(|max| n > *max) {
+        errors.push(error(path, "maximum", format!("must be <= {}", max)));
+    }
+    if let Some(min) = bound("exclusiveMinimum").filter(|min| n <= *min) {
+        errors.push(error(path, "exclusiveMinimum", format!("must be > {}", min)));
+    }
+    if let Some(max) = bound("exclusiveMaximum").filter(|max| n >= *max) {
+        errors.push(error(path, "exclusiveMaximum", format!("must be < {}", max)));
+    }
+}
+
+fn check_combinators(schema: &Map<String, Value>, instance: &Value, path: &str, errors: &mut Vec<SchemaError>) {
+    let passes = |sub: &Value| validate_at(sub, instance, path).is_empty();
+
+    if let Some(all) = schema.get("allOf").and_then(|v| v.as_array()) {
+        for sub in all {
+            check(sub, instance, path, errors);
+        }
+    }
+    if let Some(any) = schema.get("anyOf").and_then(|v| v.as_array()) {
+        if !any.iter().any(passes) {
+            errors.push(error(path, "anyOf", "does not match any allowed schema"));
+        }
+    }
+    if let Some(one) = schema.get("oneOf").and_then(|v| v.as_array()) {
+        let matched = one.iter().filter(|sub| passes(sub)).count();
+        if matched != 1 {
+            errors.push(error(
+                path,
+                "oneOf",
+                format!("must match exactly one allowed schema (matched {})", matched),
+            ));
+        }
+    }
+}
+
+fn validate_at(schema: &Value, instance: &Value, path: &str) -> Vec<SchemaError> {
+    let mut errors = Vec::new();
+    check(schema, instance, path, &mut errors);
+    errors
+}
+
+fn type_matches(ty: &str, v: &Value) -> bool {
+    match ty {
+        "null" => v.is_null(),
+        "boolean" => v.is_boolean(),
+        "object" => v.is_object(),
+        "array" => v.is_array(),
+        "string" => v.is_string(),
+        "number" => v.is_number(),
+        "integer" => v.is_i64() || v.is_u64() || v.as_f64().is_some_and(|f| f.fract() == 0.0),
+        // Unknown type names don't constrain
+        _ => true,
+    }
+}
+
+fn type_name(v: &Value) -> &'static str {
+    match v {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Object(_) => "object",
+        Value::Array(_) => "array",
+        Value::String(_) => "string",
+        Value::Number(n) if n.is_i64() || n.is_u64() => "integer",
+        Value::Number(_) => "number",
+    }
+}
+
+fn child_path(parent: &str, segment: &str) -> String {
+    format!("{}/{}", parent, segment.replace('~', "~0").replace('/', "~1"))
+}
+
+fn error(path: &str, keyword: &'static str, message: impl Into<String>) -> SchemaError {
+    SchemaError {
+        path: path.to_string(),
+        keyword,
+        message: message.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn send_schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "to": { "oneOf": [
+                    { "type": "string" },
+                    { "type": "array", "items": { "type": "string" }, "minItems": 1 }
+                ]},
+                "format": { "type": "string", "enum": ["plain", "html"], "default": "plain" },
+                "limit": { "type": "integer", "minimum": 1, "maximum": 50 },
+                "date": { "type": "string", "pattern": "^\\d{4}-\\d{2}-\\d{2}$" }
+            },
+            "required": ["to"],
+            "additionalProperties": false
+        })
+    }
+
+    #[test]
+    fn test_defaults_are_applied() {
+        let mut params = json!({ "to": "a@example.com" });
+        validate_input(&send_schema(), &mut params).unwrap();
+        assert_eq!(params["format"], "plain");
+
+        let mut params = Value::Null;
+        let errors = validate_input(&send_schema(), &mut params).unwrap_err();
+        assert_eq!(errors[0].keyword, "required");
+        assert_eq!(params["format"], "plain");
+    }
+
+    #[test]
+    fn test_errors_carry_paths() {
+        let mut params = json!({
+            "to": ["a@example.com", 7],
+            "limit": 2.5,
+            "date": "yesterday",
+            "cc": "b@example.com"
+        });
+        let errors = validate_input(&send_schema(), &mut params).unwrap_err();
+        let found: Vec<(&str, &str)> = errors.iter().map(|e| (e.path.as_str(), e.keyword)).collect();
+        assert!(found.contains(&("/to", "oneOf")));
+        assert!(found.contains(&("/limit", "type")));
+        assert!(found.contains(&("/date", "pattern")));
+        assert!(found.contains(&("/cc", "additionalProperties")));
+        assert_eq!(errors.len(), 4);
+    }
+
+    #[test]
+    fn test_integer_and_bounds() {
+        let schema = json!({ "type": "integer", "minimum": 0 });
+        assert!(validate(&schema, &json!(3.0)).is_empty());
+        assert_eq!(validate(&schema, &json!(-1))[0].keyword, "minimum");
+        assert_eq!(validate(&schema, &json!("3"))[0].to_string(), "(root): expected integer, got string");
+    }
+}
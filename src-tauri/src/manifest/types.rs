@@ -5,6 +5,9 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+pub use crate::common::http::HttpPolicy;
+pub use crate::common::pagination::Pagination;
+
 /// Manifest schema version
 pub const MANIFEST_VERSION: &str = "1.0.0";
 
@@ -21,6 +24,12 @@ pub struct Manifest {
     pub requires: Requirements,
     pub tools: Vec<Tool>,
     pub implementation: Implementation,
+    /// Shared limit across all tools of this manifest
+    #[serde(default, rename = "rateLimit", skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<RateLimitSpec>,
+    /// Timeouts, retries and circuit breaker for this manifest's outbound calls
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub http: Option<HttpPolicy>,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -67,6 +76,35 @@ pub struct Tool {
     pub annotations: HashMap<String, serde_json::Value>,
     #[serde(default)]
     pub requires: Option<Requirements>,
+    #[serde(default, rename = "rateLimit", skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<RateLimitSpec>,
+}
+
+/// Token-bucket rate limit and daily quota, applied per calling client
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RateLimitSpec {
+    /// Sustained calls per minute (bucket refill rate)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub per_minute: Option<f64>,
+    /// Bucket size — calls allowed back to back (default: `perMinute`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub burst: Option<u32>,
+    /// Calls allowed per local calendar day
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub daily: Option<u32>,
+}
+
+impl RateLimitSpec {
+    pub fn validate(&self, what: &str) -> Result<(), String> {
+        if self.per_minute.is_some_and(|r| !(r > 0.0 && r.is_finite())) {
+            return Err(format!("{}: rateLimit.perMinute must be positive", what));
+        }
+        if self.burst == Some(0) {
+            return Err(format!("{}: rateLimit.burst must be at least 1", what));
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -96,6 +134,17 @@ pub enum Implementation {
     },
 }
 
+impl Implementation {
+    /// The `type` tag as it appears in manifest JSON
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Implementation::Internal { .. } => "internal",
+            Implementation::Script { .. } => "script",
+            Implementation::Proxy { .. } => "proxy",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ScriptToolBinding {
     pub action: String,
@@ -130,6 +179,9 @@ pub struct ProxyToolBinding {
     pub headers: HashMap<String, String>,
     #[serde(default, rename = "responsePath")]
     pub response_path: Option<String>,
+    /// Walk pages and concatenate their items
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pagination: Option<Pagination>,
 }
 
 impl Manifest {
@@ -155,6 +207,15 @@ impl Manifest {
             if !is_valid_tool_name(&tool.name) {
                 return Err(format!("Invalid tool name: {}", tool.name));
             }
+            if let Some(limit) = &tool.rate_limit {
+                limit.validate(&tool.name)?;
+            }
+        }
+        if let Some(limit) = &self.rate_limit {
+            limit.validate(&self.id)?;
+        }
+        if let Some(policy) = &self.http {
+            policy.validate()?;
         }
 
         // Validate implementation has bindings for all tools
@@ -188,6 +249,11 @@ impl Manifest {
                         ));
                     }
                 }
+                for (name, binding) in tool_bindings {
+                    if let Some(pagination) = &binding.pagination {
+                        pagination.validate().map_err(|e| format!("{}: {}", name, e))?;
+                    }
+                }
             }
         }
 
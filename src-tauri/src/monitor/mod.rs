@@ -33,6 +33,33 @@ fn proxy_log_path() -> PathBuf {
         .join("proxy.log")
 }
 
+fn audit_log_path() -> PathBuf {
+    crate::common::logs_dir()
+        .unwrap_or_else(|_| PathBuf::from(".tairseach/logs"))
+        .join("audit.jsonl")
+}
+
+/// Read the last `limit` lines of a log file (empty if it doesn't exist)
+fn tail_lines(path: &PathBuf, limit: usize) -> Result<VecDeque<String>, String> {
+    let mut ring: VecDeque<String> = VecDeque::with_capacity(limit + 1);
+    if !path.exists() {
+        return Ok(ring);
+    }
+
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("log");
+    let file = File::open(path).map_err(|e| format!("Failed to open {}: {}", name, e))?;
+    let reader = BufReader::new(file);
+
+    for line in reader.lines() {
+        let line = line.map_err(|e| format!("Failed to read {} line: {}", name, e))?;
+        if ring.len() >= limit {
+            ring.pop_front();
+        }
+        ring.push_back(line);
+    }
+    Ok(ring)
+}
+
 fn manifests_root() -> PathBuf {
     crate::common::manifest_dir()
         .unwrap_or_else(|_| PathBuf::from(".tairseach/manifests"))
@@ -92,33 +119,26 @@ fn parse_activity_line(line: &str, fallback_idx: usize) -> ActivityEvent {
     }
 }
 
+/// Recent activity: proxy audit entries merged with `proxy.log`, oldest first
 #[tauri::command]
 pub async fn monitor_events_list(limit: Option<usize>) -> Result<Vec<ActivityEvent>, String> {
     let limit = limit.unwrap_or(100).max(1).min(2000);
-    let path = proxy_log_path();
 
-    if !path.exists() {
-        return Ok(vec![]);
-    }
-
-    let file = File::open(&path).map_err(|e| format!("Failed to open proxy.log: {}", e))?;
-    let reader = BufReader::new(file);
-    let mut ring: VecDeque<String> = VecDeque::with_capacity(limit + 1);
+    let mut lines = tail_lines(&audit_log_path(), limit)?;
+    lines.extend(tail_lines(&proxy_log_path(), limit)?);
 
-    for line in reader.lines() {
-        let line = line.map_err(|e| format!("Failed to read proxy.log line: {}", e))?;
-        if ring.len() >= limit {
-            ring.pop_front();
-        }
-        ring.push_back(line);
-    }
-
-    let events = ring
+    let mut events = lines
         .into_iter()
         .enumerate()
         .map(|(idx, line)| parse_activity_line(&line, idx))
         .collect::<Vec<_>>();
 
+    // RFC 3339 timestamps sort lexically; stable sort keeps file order for ties
+    events.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+    if events.len() > limit {
+        events.drain(..events.len() - limit);
+    }
+
     Ok(events)
 }
 
@@ -297,12 +317,14 @@ pub async fn monitor_mcp_tool_test(
     Ok(response.get("result").cloned().unwrap_or(response))
 }
 
-/// Check namespace connection statuses by pinging socket with a tool from each manifest
+/// Check namespace connection statuses by pinging socket with a tool from each manifest,
+/// and report the circuit breakers of the APIs each namespace calls
 #[tauri::command]
 pub async fn monitor_namespace_statuses_get() -> Result<Vec<serde_json::Value>, String> {
     let manifests = manifests_all_list().await?;
     let socket_path = crate::common::socket_path()
         .unwrap_or_else(|_| PathBuf::from(".tairseach/tairseach.sock"));
+    let circuit_statuses = crate::common::http::circuit_statuses();
     
     let mut statuses = Vec::new();
     
@@ -321,9 +343,28 @@ pub async fn monitor_namespace_statuses_get() -> Result<Vec<serde_json::Value>,
             false // No tools means can't test connectivity
         };
         
+        // Breakers this namespace has used, plus its proxy base URL's
+        let proxy_origin = match &manifest.implementation {
+            crate::manifest::Implementation::Proxy { base_url, .. } => {
+                crate::common::http::origin_of(base_url)
+            }
+            _ => None,
+        };
+        let circuits: Vec<_> = circuit_statuses
+            .iter()
+            .filter(|c| {
+                c.namespaces.contains(&namespace) || proxy_origin.as_deref() == Some(c.base_url.as_str())
+            })
+            .collect();
+        let degraded = circuits
+            .iter()
+            .any(|c| c.state != crate::common::http::CircuitState::Closed);
+        
         statuses.push(serde_json::json!({
             "namespace": namespace,
-            "connected": connected
+            "connected": connected,
+            "degraded": degraded,
+            "circuits": circuits
         }));
     }
     
@@ -0,0 +1,594 @@
+//! Approval Gate
+//!
+//! Holds destructive tool calls until a human approves them.
+//!
+//! A call needs approval when its manifest tool carries
+//! `"destructiveHint": true`, or when the caller's client policy lists the
+//! method under `requireApproval`. The request is parked as a
+//! [`PendingApproval`], handed to the registered [`ApprovalPrompter`] (the
+//! Tauri UI) and resumed once [`ApprovalGate::decide`] is called — or denied
+//! when `approvalTimeoutSecs` elapses.
+//!
+//! An "always allow" decision is remembered per client and tool in
+//! `~/.tairseach/tool-approvals.json`, so later calls skip the prompt.
+//!
+//! With `"approvalMode": "deny"` in `proxy.json` every gated call is denied
+//! immediately, remembered ones included. When no prompter is registered
+//! (headless runs, tests), gated calls without a remembered decision are
+//! denied the same way.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+
+use chrono::Utc;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::oneshot;
+use tracing::{info, warn};
+
+use super::protocol::JsonRpcResponse;
+use crate::common::ErrorCode;
+
+/// How gated calls are handled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ApprovalMode {
+    /// Ask the UI and wait for a decision
+    #[default]
+    Prompt,
+    /// Deny every gated call without asking (headless / tests)
+    Deny,
+}
+
+/// Why a call was held
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ApprovalReason {
+    /// The manifest tool is annotated `destructiveHint: true`
+    Destructive,
+    /// The client policy lists the method under `requireApproval`
+    Policy,
+}
+
+/// A call waiting for a decision
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingApproval {
+    pub id: u64,
+    /// Client name (policy name, else executable label)
+    pub client: String,
+    pub tool: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub manifest: Option<String>,
+    /// Request params for display, with secrets masked (see [`redact_params`])
+    pub params: Value,
+    pub reason: ApprovalReason,
+    pub requested_at: String,
+    pub expires_at: String,
+}
+
+/// A human decision on a pending call
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Decision {
+    /// Run this call once
+    Approve,
+    /// Run this call and every later call of the same tool by the same client
+    AlwaysAllow,
+    Deny,
+}
+
+/// How a pending call ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Outcome {
+    Approved,
+    Denied,
+    TimedOut,
+    /// Denied without asking (`deny` mode or no prompter)
+    AutoDenied,
+}
+
+/// Receives pending calls so a human can decide on them
+pub trait ApprovalPrompter: Send + Sync {
+    /// A call is waiting for a decision
+    fn requested(&self, pending: &PendingApproval);
+
+    /// A pending call was decided or timed out
+    fn resolved(&self, _id: u64, _outcome: Outcome) {}
+}
+
+/// A remembered "always allow" decision
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RememberedApproval {
+    pub client: String,
+    pub tool: String,
+    pub granted_at: String,
+}
+
+/// `~/.tairseach/tool-approvals.json`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RememberedFile {
+    #[serde(default)]
+    always_allow: Vec<RememberedApproval>,
+}
+
+/// Why a gated call did not run
+#[derive(Debug, Clone)]
+pub struct ApprovalDenied {
+    pub id: Option<u64>,
+    pub client: String,
+    pub tool: String,
+    pub outcome: Outcome,
+}
+
+impl std::fmt::Display for ApprovalDenied {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let why = match self.outcome {
+            Outcome::TimedOut => "approval timed out",
+            Outcome::AutoDenied => "approval is required but no approver is available",
+            _ => "denied by user",
+        };
+        write!(f, "Call to '{}' by client '{}' {}", self.tool, self.client, why)
+    }
+}
+
+impl ApprovalDenied {
+    pub fn into_response(self, id: Value) -> JsonRpcResponse {
+        let data = serde_json::json!({
+            "client": self.client,
+            "tool": self.tool,
+            "approvalId": self.id,
+            "outcome": self.outcome,
+        });
+        JsonRpcResponse::error(id, ErrorCode::ApprovalDenied.code(), self.to_string(), Some(data))
+    }
+}
+
+type Waiter = (PendingApproval, oneshot::Sender<Decision>);
+
+/// Process-wide approval gate
+pub struct ApprovalGate {
+    mode: RwLock<ApprovalMode>,
+    timeout: RwLock<Duration>,
+    prompter: RwLock<Option<Arc<dyn ApprovalPrompter>>>,
+    next_id: AtomicU64,
+    pending: Mutex<HashMap<u64, Waiter>>,
+    /// `None` until first use, then loaded from disk
+    remembered: Mutex<Option<Vec<RememberedApproval>>>,
+    /// Where remembered decisions are persisted (`None` = memory only)
+    store_path: Option<PathBuf>,
+}
+
+static GATE: Lazy<ApprovalGate> =
+    Lazy::new(|| ApprovalGate::new(crate::common::tairseach_path("tool-approvals.json").ok()));
+
+/// Get the global approval gate
+pub fn gate() -> &'static ApprovalGate {
+    &GATE
+}
+
+impl ApprovalGate {
+    pub fn new(store_path: Option<PathBuf>) -> Self {
+        Self {
+            mode: RwLock::new(ApprovalMode::default()),
+            timeout: RwLock::new(Duration::from_secs(120)),
+            prompter: RwLock::new(None),
+            next_id: AtomicU64::new(1),
+            pending: Mutex::new(HashMap::new()),
+            remembered: Mutex::new(None),
+            store_path,
+        }
+    }
+
+    /// Apply `approvalMode` / `approvalTimeoutSecs` from the proxy config
+    pub fn configure(&self, mode: ApprovalMode, timeout: Duration) {
+        if let Ok(mut m) = self.mode.write() {
+            *m = mode;
+        }
+        if let Ok(mut t) = self.timeout.write() {
+            *t = timeout;
+        }
+        info!("Approval gate: mode {:?}, timeout {:?}", mode, timeout);
+    }
+
+    /// Register the UI that decides on pending calls
+    pub fn set_prompter(&self, prompter: Arc<dyn ApprovalPrompter>) {
+        if let Ok(mut p) = self.prompter.write() {
+            *p = Some(prompter);
+        }
+    }
+
+    /// Wait for approval of a call. Returns immediately if the client has an
+    /// "always allow" for the tool, unless the gate is in deny mode.
+    pub async fn request(
+        &self,
+        client: &str,
+        tool: &str,
+        manifest: Option<&str>,
+        params: &Value,
+        reason: ApprovalReason,
+    ) -> Result<(), ApprovalDenied> {
+        let denied = |id, outcome| ApprovalDenied {
+            id,
+            client: client.to_string(),
+            tool: tool.to_string(),
+            outcome,
+        };
+
+        let mode = self.mode.read().map(|m| *m).unwrap_or(ApprovalMode::Deny);
+        if mode == ApprovalMode::Deny {
+            info!("Auto-denying {} for {} (deny mode)", tool, client);
+            return Err(denied(None, Outcome::AutoDenied));
+        }
+        if self.is_remembered(client, tool) {
+            return Ok(());
+        }
+
+        let prompter = self.prompter.read().ok().and_then(|p| p.clone());
+        let prompter = match (mode, prompter) {
+            (ApprovalMode::Prompt, Some(prompter)) => prompter,
+            _ => {
+                info!("Auto-denying {} for {} ({:?} mode)", tool, client, mode);
+                return Err(denied(None, Outcome::AutoDenied));
+            }
+        };
+
+        let timeout = self.timeout.read().map(|t| *t).unwrap_or(Duration::from_secs(120));
+        let now = Utc::now();
+        let pending = PendingApproval {
+            id: self.next_id.fetch_add(1, Ordering::Relaxed),
+            client: client.to_string(),
+            tool: tool.to_string(),
+            manifest: manifest.map(str::to_string),
+            params: redact_params(params),
+            reason,
+            requested_at: now.to_rfc3339(),
+            expires_at: (now + chrono::Duration::from_std(timeout).unwrap_or_default()).to_rfc3339(),
+        };
+        let id = pending.id;
+
+        let (tx, rx) = oneshot::channel();
+        self.lock_pending().insert(id, (pending.clone(), tx));
+        info!("Holding {} for {} pending approval #{}", tool, client, id);
+        prompter.requested(&pending);
+
+        let decision = tokio::time::timeout(timeout, rx).await;
+        // Still present only if nobody decided (timeout or dropped sender)
+        self.lock_pending().remove(&id);
+
+        match decision {
+            Ok(Ok(Decision::Approve)) | Ok(Ok(Decision::AlwaysAllow)) => Ok(()),
+            Ok(Ok(Decision::Deny)) | Ok(Err(_)) => Err(denied(Some(id), Outcome::Denied)),
+            Err(_) => {
+                warn!("Approval #{} for {} timed out", id, tool);
+                prompter.resolved(id, Outcome::TimedOut);
+                Err(denied(Some(id), Outcome::TimedOut))
+            }
+        }
+    }
+
+    /// Decide on a pending call
+    pub fn decide(&self, id: u64, decision: Decision) -> Result<(), String> {
+        let (pending, tx) = self
+            .lock_pending()
+            .remove(&id)
+            .ok_or_else(|| format!("No pending approval with id {}", id))?;
+
+        if decision == Decision::AlwaysAllow {
+            self.remember(&pending.client, &pending.tool)?;
+        }
+
+        let outcome = match decision {
+            Decision::Deny => Outcome::Denied,
+            _ => Outcome::Approved,
+        };
+        info!("Approval #{} for {} by {}: {:?}", id, pending.tool, pending.client, decision);
+        let _ = tx.send(decision);
+
+        if let Some(prompter) = self.prompter.read().ok().and_then(|p| p.clone()) {
+            prompter.resolved(id, outcome);
+        }
+        Ok(())
+    }
+
+    /// Deny every pending call (server shutdown). Returns how many were held.
+    pub fn deny_all(&self) -> usize {
+        let drained: Vec<(u64, Waiter)> = self.lock_pending().drain().collect();
+        let count = drained.len();
+        let prompter = self.prompter.read().ok().and_then(|p| p.clone());
+        for (id, (_, tx)) in drained {
+            let _ = tx.send(Decision::Deny);
+            if let Some(prompter) = &prompter {
+                prompter.resolved(id, Outcome::Denied);
+            }
+        }
+        count
+    }
+
+    /// Calls currently waiting for a decision, oldest first
+    pub fn pending(&self) -> Vec<PendingApproval> {
+        let mut list: Vec<PendingApproval> =
+            self.lock_pending().values().map(|(p, _)| p.clone()).collect();
+        list.sort_by_key(|p| p.id);
+        list
+    }
+
+    /// All remembered "always allow" decisions
+    pub fn remembered(&self) -> Vec<RememberedApproval> {
+        self.with_remembered(|list| list.clone())
+    }
+
+    /// Drop a remembered decision. Returns whether one existed.
+    pub fn forget(&self, client: &str, tool: &str) -> Result<bool, String> {
+        let (removed, snapshot) = self.with_remembered(|list| {
+            let before = list.len();
+            list.retain(|r| !(r.client == client && r.tool == tool));
+            (list.len() != before, list.clone())
+        });
+        if removed {
+            self.save_remembered(snapshot)?;
+        }
+        Ok(removed)
+    }
+
+    fn is_remembered(&self, client: &str, tool: &str) -> bool {
+        self.with_remembered(|list| list.iter().any(|r| r.client == client && r.tool == tool))
+    }
+
+    fn remember(&self, client: &str, tool: &str) -> Result<(), String> {
+        let snapshot = self.with_remembered(|list| {
+            if !list.iter().any(|r| r.client == client && r.tool == tool) {
+                list.push(RememberedApproval {
+                    client: client.to_string(),
+                    tool: tool.to_string(),
+                    granted_at: Utc::now().to_rfc3339(),
+                });
+            }
+            list.clone()
+        });
+        self.save_remembered(snapshot)
+    }
+
+    fn with_remembered<T>(&self, f: impl FnOnce(&mut Vec<RememberedApproval>) -> T) -> T {
+        let mut guard = self
+            .remembered
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let list = guard.get_or_insert_with(|| self.load_remembered());
+        f(list)
+    }
+
+    fn load_remembered(&self) -> Vec<RememberedApproval> {
+        let Some(path) = self.store_path.as_ref().filter(|p| p.exists()) else {
+            return Vec::new();
+        };
+        match std::fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|c| serde_json::from_str::<RememberedFile>(&c).map_err(|e| e.to_string()))
+        {
+            Ok(file) => file.always_allow,
+            Err(e) => {
+                warn!("Ignoring unreadable {:?}: {}", path, e);
+                Vec::new()
+            }
+        }
+    }
+
+    fn save_remembered(&self, always_allow: Vec<RememberedApproval>) -> Result<(), String> {
+        let Some(path) = &self.store_path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create approvals directory: {}", e))?;
+        }
+        let content = serde_json::to_string_pretty(&RememberedFile { always_allow })
+            .map_err(|e| format!("Failed to serialize approvals: {}", e))?;
+        crate::common::write_atomic(path, content.as_bytes(), Some(0o600))
+    }
+
+    fn lock_pending(&self) -> std::sync::MutexGuard<'_, HashMap<u64, Waiter>> {
+        self.pending
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Key fragments whose values are never shown in the approval prompt
+const SENSITIVE_KEYS: &[&str] = &[
+    "token", "secret", "password", "passphrase", "apikey", "api_key", "authorization",
+    "credential", "cookie", "privatekey", "private_key",
+];
+/// Longest string shown in full in the approval prompt
+const MAX_PARAM_CHARS: usize = 200;
+/// Most array items shown in the approval prompt
+const MAX_PARAM_ITEMS: usize = 20;
+
+/// Copy of request params safe to show in the UI: values under sensitive
+/// keys are masked, long strings truncated and long arrays cut short
+pub fn redact_params(params: &Value) -> Value {
+    match params {
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, value)| {
+                    let lower = key.to_ascii_lowercase();
+                    let shown = if SENSITIVE_KEYS.iter().any(|k| lower.contains(k)) {
+                        Value::String("[redacted]".to_string())
+                    } else {
+                        redact_params(value)
+                    };
+                    (key.clone(), shown)
+                })
+                .collect(),
+        ),
+        Value::Array(items) => {
+            let mut shown: Vec<Value> = items.iter().take(MAX_PARAM_ITEMS).map(redact_params).collect();
+            if items.len() > MAX_PARAM_ITEMS {
+                shown.push(Value::String(format!("… {} more", items.len() - MAX_PARAM_ITEMS)));
+            }
+            Value::Array(shown)
+        }
+        Value::String(text) if text.chars().count() > MAX_PARAM_CHARS => {
+            let head: String = text.chars().take(MAX_PARAM_CHARS).collect();
+            Value::String(format!("{}… ({} chars)", head, text.chars().count()))
+        }
+        other => other.clone(),
+    }
+}
+
+/// Whether a manifest tool's annotations mark it destructive
+pub fn is_destructive(annotations: &HashMap<String, Value>) -> bool {
+    annotations
+        .get("destructiveHint")
+        .and_then(Value::as_bool)
+        .unwrap_or(false)
+}
+
+/// Hold the current request for approval if `tool` needs it.
+///
+/// `destructive` comes from the manifest annotations; the client policy's
+/// `requireApproval` globs are checked here. Calls outside a socket request
+/// (Tauri commands) are never gated.
+pub async fn check(
+    tool: &str,
+    manifest: Option<&str>,
+    destructive: bool,
+    params: &Value,
+) -> Result<(), ApprovalDenied> {
+    let Some(client) = super::peer::current_client() else {
+        return Ok(());
+    };
+
+    let reason = if super::policy::requires_approval(tool) {
+        ApprovalReason::Policy
+    } else if destructive {
+        ApprovalReason::Destructive
+    } else {
+        return Ok(());
+    };
+
+    gate().request(&client, tool, manifest, params, reason).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Recorder(Mutex<Vec<u64>>);
+
+    impl ApprovalPrompter for Recorder {
+        fn requested(&self, pending: &PendingApproval) {
+            self.0.lock().unwrap().push(pending.id);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_headless_gate_denies() {
+        let gate = ApprovalGate::new(None);
+        let err = gate
+            .request("agent", "files.write", None, &Value::Null, ApprovalReason::Destructive)
+            .await
+            .unwrap_err();
+        assert_eq!(err.outcome, Outcome::AutoDenied);
+        let resp = err.into_response(Value::from(1));
+        assert_eq!(resp.error.unwrap().code, ErrorCode::ApprovalDenied.code());
+    }
+
+    #[tokio::test]
+    async fn test_always_allow_is_remembered() {
+        let gate = Arc::new(ApprovalGate::new(None));
+        let recorder = Arc::new(Recorder(Mutex::new(Vec::new())));
+        gate.set_prompter(recorder.clone());
+
+        let waiting = {
+            let gate = Arc::clone(&gate);
+            tokio::spawn(async move {
+                gate.request("agent", "contacts.delete", Some("contacts"), &Value::Null, ApprovalReason::Destructive)
+                    .await
+            })
+        };
+        while gate.pending().is_empty() {
+            tokio::task::yield_now().await;
+        }
+        let id = gate.pending()[0].id;
+        assert_eq!(recorder.0.lock().unwrap().as_slice(), &[id]);
+
+        gate.decide(id, Decision::AlwaysAllow).unwrap();
+        assert!(waiting.await.unwrap().is_ok());
+        assert!(gate.pending().is_empty());
+
+        // Second call skips the prompt; another client does not
+        gate.request("agent", "contacts.delete", None, &Value::Null, ApprovalReason::Destructive)
+            .await
+            .unwrap();
+        assert_eq!(recorder.0.lock().unwrap().len(), 1);
+
+        // Deny mode overrides the remembered decision
+        gate.configure(ApprovalMode::Deny, Duration::from_secs(1));
+        let err = gate
+            .request("agent", "contacts.delete", None, &Value::Null, ApprovalReason::Destructive)
+            .await
+            .unwrap_err();
+        assert_eq!(err.outcome, Outcome::AutoDenied);
+        assert!(gate.forget("agent", "contacts.delete").unwrap());
+        assert!(gate.decide(999, Decision::Deny).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_deny_all_releases_waiters() {
+        let gate = Arc::new(ApprovalGate::new(None));
+        gate.set_prompter(Arc::new(Recorder(Mutex::new(Vec::new()))));
+
+        let waiting = {
+            let gate = Arc::clone(&gate);
+            tokio::spawn(async move {
+                gate.request("agent", "files.delete", None, &Value::Null, ApprovalReason::Destructive)
+                    .await
+            })
+        };
+        while gate.pending().is_empty() {
+            tokio::task::yield_now().await;
+        }
+
+        assert_eq!(gate.deny_all(), 1);
+        assert_eq!(waiting.await.unwrap().unwrap_err().outcome, Outcome::Denied);
+        assert!(gate.pending().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_pending_call_times_out() {
+        let gate = ApprovalGate::new(None);
+        gate.set_prompter(Arc::new(Recorder(Mutex::new(Vec::new()))));
+        gate.configure(ApprovalMode::Prompt, Duration::from_millis(10));
+        let err = gate
+            .request("agent", "gmail.send", None, &Value::Null, ApprovalReason::Policy)
+            .await
+            .unwrap_err();
+        assert_eq!(err.outcome, Outcome::TimedOut);
+        assert!(gate.pending().is_empty());
+    }
+
+    #[test]
+    fn test_redact_params() {
+        let params = serde_json::json!({
+            "to": "a@example.com",
+            "accessToken": "ya29.secret",
+            "auth": {"password": "hunter2"},
+            "body": "x".repeat(500),
+            "ids": (0..25).collect::<Vec<_>>(),
+        });
+        let shown = redact_params(&params);
+        assert_eq!(shown["to"], "a@example.com");
+        assert_eq!(shown["accessToken"], "[redacted]");
+        assert_eq!(shown["auth"]["password"], "[redacted]");
+        assert!(shown["body"].as_str().unwrap().ends_with("… (500 chars)"));
+        assert_eq!(shown["ids"].as_array().unwrap().len(), MAX_PARAM_ITEMS + 1);
+    }
+}
@@ -0,0 +1,386 @@
+//! Audit Log
+//!
+//! Structured record of every request handled by the socket proxy, written
+//! as JSON lines to `~/.tairseach/logs/audit.jsonl`.
+//!
+//! Each entry carries the peer PID/UID/executable, method, manifest id,
+//! implementation type, duration, outcome and error code, plus the ids of
+//! credentials that were touched — never their values. The file is rotated
+//! daily to `audit-YYYY-MM-DD.jsonl` and old files are pruned after the
+//! configured retention.
+//!
+//! Request-scoped details that only deeper layers know (which manifest
+//! matched, which credentials were loaded) are attached with [`annotate`],
+//! which writes into a task-local context set up by [`scope`].
+
+use std::future::Future;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use once_cell::sync::OnceCell;
+use serde::Serialize;
+use tokio::sync::{mpsc, oneshot};
+use tracing::{error, info, warn};
+
+use super::peer::PeerInfo;
+
+/// Active audit file name
+const AUDIT_FILE: &str = "audit.jsonl";
+/// Queued entries before new ones are dropped
+const QUEUE_CAPACITY: usize = 1024;
+
+// ── Request Context ─────────────────────────────────────────────────────────
+
+/// Details attached to the in-flight request by the router and auth broker
+#[derive(Debug, Clone, Default)]
+pub struct RequestAnnotations {
+    /// The method named a real tool or handler method (metrics label it by
+    /// name only then)
+    pub resolved: bool,
+    pub manifest: Option<String>,
+    pub implementation: Option<String>,
+    pub credentials: Vec<String>,
+}
+
+tokio::task_local! {
+    static CURRENT: Arc<Mutex<RequestAnnotations>>;
+}
+
+/// Run `fut` with a fresh annotation context and return what was recorded
+pub async fn scope<F: Future>(fut: F) -> (F::Output, RequestAnnotations) {
+    let ctx = Arc::new(Mutex::new(RequestAnnotations::default()));
+    let output = CURRENT.scope(Arc::clone(&ctx), fut).await;
+    let annotations = ctx.lock().map(|a| a.clone()).unwrap_or_default();
+    (output, annotations)
+}
+
+/// Attach details to the current request's audit entry.
+/// No-op outside a request (e.g. Tauri commands).
+pub fn annotate(f: impl FnOnce(&mut RequestAnnotations)) {
+    let _ = CURRENT.try_with(|ctx| {
+        if let Ok(mut a) = ctx.lock() {
+            f(&mut a);
+        }
+    });
+}
+
+/// Record that a credential was read for the current request
+pub fn note_credential(id: impl Into<String>) {
+    let id = id.into();
+    annotate(|a| {
+        if !a.credentials.contains(&id) {
+            a.credentials.push(id);
+        }
+    });
+}
+
+// ── Entries ─────────────────────────────────────────────────────────────────
+
+/// One audit log line
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditEntry {
+    pub id: String,
+    pub timestamp: String,
+    /// Always `"request"` (read as the event type by the monitor view)
+    pub event: &'static str,
+    /// Short client label (executable name or `pid:N`)
+    pub client: String,
+    /// Method as requested (read as the message by the monitor view)
+    pub tool: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<serde_json::Value>,
+    pub pid: Option<i32>,
+    pub uid: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exe: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub manifest: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub implementation: Option<String>,
+    pub duration_ms: u64,
+    /// `"ok"` or `"error"`
+    pub outcome: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<i32>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub credentials: Vec<String>,
+    /// Name of the client policy in force, if a policy file exists
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub policy: Option<String>,
+}
+
+impl AuditEntry {
+    pub fn new(
+        peer: &PeerInfo,
+        method: &str,
+        request_id: Option<serde_json::Value>,
+        annotations: RequestAnnotations,
+        duration: std::time::Duration,
+        error_code: Option<i32>,
+    ) -> Self {
+        let now = Utc::now();
+        Self {
+            id: format!("{}-{:08x}", now.timestamp_micros(), rand::random::<u32>()),
+            timestamp: now.to_rfc3339(),
+            event: "request",
+            client: peer.label(),
+            tool: method.to_string(),
+            request_id,
+            pid: peer.pid,
+            uid: peer.uid,
+            exe: peer.exe.as_ref().map(|p| p.display().to_string()),
+            manifest: annotations.manifest,
+            implementation: annotations.implementation,
+            duration_ms: duration.as_millis() as u64,
+            outcome: if error_code.is_some() { "error" } else { "ok" },
+            error_code,
+            credentials: annotations.credentials,
+            policy: None,
+        }
+    }
+}
+
+// ── Writer ──────────────────────────────────────────────────────────────────
+
+/// Messages accepted by the background writer
+enum WriterMessage {
+    Entry(Box<AuditEntry>),
+    /// Reply once everything queued before this message is on disk
+    Flush(oneshot::Sender<()>),
+}
+
+static SENDER: OnceCell<mpsc::Sender<WriterMessage>> = OnceCell::new();
+
+/// Queue an entry for the background writer. Never blocks; entries are
+/// dropped (with a warning) if the writer isn't running or is backed up.
+pub fn record(entry: AuditEntry) {
+    let Some(tx) = SENDER.get() else {
+        return;
+    };
+    if let Err(e) = tx.try_send(WriterMessage::Entry(Box::new(entry))) {
+        warn!("Dropping audit entry: {}", e);
+    }
+}
+
+/// Wait until every entry recorded so far has been written. Returns
+/// immediately if the writer isn't running.
+pub async fn flush() {
+    let Some(tx) = SENDER.get() else {
+        return;
+    };
+    let (done_tx, done_rx) = oneshot::channel();
+    if tx.send(WriterMessage::Flush(done_tx)).await.is_ok() {
+        let _ = done_rx.await;
+    }
+}
+
+/// Start the background audit writer. Only the first call has any effect.
+pub fn spawn_writer(retention_days: u32) {
+    let dir = match crate::common::logs_dir() {
+        Ok(d) => d,
+        Err(e) => {
+            error!("Audit log disabled: {}", e);
+            return;
+        }
+    };
+
+    let (tx, mut rx) = mpsc::channel::<WriterMessage>(QUEUE_CAPACITY);
+    if SENDER.set(tx).is_err() {
+        return;
+    }
+
+    info!("Audit log writing to {:?} (retention {} days)", dir.join(AUDIT_FILE), retention_days);
+
+    tokio::spawn(async move {
+        while let Some(message) = rx.recv().await {
+            let mut batch = Vec::new();
+            let mut flushed = Vec::new();
+            let mut next = Some(message);
+            while let Some(message) = next {
+                match message {
+                    WriterMessage::Entry(entry) => batch.push(*entry),
+                    WriterMessage::Flush(done) => flushed.push(done),
+                }
+                next = rx.try_recv().ok();
+            }
+
+            if !batch.is_empty() {
+                let dir = dir.clone();
+                let result = tokio::task::spawn_blocking(move || {
+                    let today = Local::now().date_naive();
+                    rotate_if_needed(&dir, today)?;
+                    prune_old(&dir, today, retention_days);
+                    append(&dir.join(AUDIT_FILE), &batch)
+                })
+                .await;
+
+                match result {
+                    Ok(Err(e)) => error!("Failed to write audit log: {}", e),
+                    Err(e) => error!("Audit writer task failed: {}", e),
+                    Ok(Ok(())) => {}
+                }
+            }
+
+            for done in flushed {
+                let _ = done.send(());
+            }
+        }
+    });
+}
+
+fn append(path: &Path, entries: &[AuditEntry]) -> Result<(), String> {
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("Failed to open audit log: {}", e))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let _ = std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600));
+    }
+
+    for entry in entries {
+        let line = serde_json::to_string(entry)
+            .map_err(|e| format!("Failed to serialize audit entry: {}", e))?;
+        writeln!(file, "{}", line).map_err(|e| format!("Failed to write audit log: {}", e))?;
+    }
+    Ok(())
+}
+
+/// Move `audit.jsonl` aside if it was last written on an earlier day
+fn rotate_if_needed(dir: &Path, today: NaiveDate) -> Result<(), String> {
+    std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create logs directory: {}", e))?;
+
+    let active = dir.join(AUDIT_FILE);
+    let modified = match std::fs::metadata(&active).and_then(|m| m.modified()) {
+        Ok(t) => DateTime::<Local>::from(t).date_naive(),
+        Err(_) => return Ok(()),
+    };
+    if modified >= today {
+        return Ok(());
+    }
+
+    let rotated = rotated_path(dir, modified);
+    // If a file for that day already exists (clock skew), append to it
+    if rotated.exists() {
+        let content = std::fs::read(&active).map_err(|e| format!("Failed to read audit log: {}", e))?;
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&rotated)
+            .map_err(|e| format!("Failed to open rotated audit log: {}", e))?;
+        file.write_all(&content)
+            .map_err(|e| format!("Failed to rotate audit log: {}", e))?;
+        std::fs::remove_file(&active).map_err(|e| format!("Failed to rotate audit log: {}", e))?;
+    } else {
+        std::fs::rename(&active, &rotated).map_err(|e| format!("Failed to rotate audit log: {}", e))?;
+    }
+    info!("Rotated audit log to {:?}", rotated);
+    Ok(())
+}
+
+/// Delete rotated files older than `retention_days`
+fn prune_old(dir: &Path, today: NaiveDate, retention_days: u32) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        let Some(day) = name
+            .to_str()
+            .and_then(|n| n.strip_prefix("audit-"))
+            .and_then(|n| n.strip_suffix(".jsonl"))
+            .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+        else {
+            continue;
+        };
+        if (today - day).num_days() > i64::from(retention_days) {
+            if let Err(e) = std::fs::remove_file(entry.path()) {
+                warn!("Failed to prune audit log {:?}: {}", entry.path(), e);
+            }
+        }
+    }
+}
+
+fn rotated_path(dir: &Path, day: NaiveDate) -> PathBuf {
+    dir.join(format!("audit-{}.jsonl", day.format("%Y-%m-%d")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("tairseach-audit-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[tokio::test]
+    async fn test_annotations_are_scoped() {
+        annotate(|a| a.manifest = Some("outside".into()));
+
+        let ((), notes) = scope(async {
+            annotate(|a| a.manifest = Some("google-gmail".into()));
+            note_credential("google:me");
+            note_credential("google:me");
+        })
+        .await;
+
+        assert_eq!(notes.manifest.as_deref(), Some("google-gmail"));
+        assert_eq!(notes.credentials, vec!["google:me"]);
+    }
+
+    #[test]
+    fn test_entry_never_contains_values() {
+        let peer = PeerInfo { pid: Some(7), uid: 501, exe: None };
+        let notes = RequestAnnotations {
+            credentials: vec!["jira:default".into()],
+            ..Default::default()
+        };
+        let entry = AuditEntry::new(&peer, "jira.search", None, notes, Default::default(), Some(-32001));
+        let json = serde_json::to_value(&entry).unwrap();
+        assert_eq!(json["client"], "pid:7");
+        assert_eq!(json["outcome"], "error");
+        assert_eq!(json["errorCode"], -32001);
+        assert_eq!(json["credentials"][0], "jira:default");
+    }
+
+    #[test]
+    fn test_prune_respects_retention() {
+        let dir = scratch_dir("prune");
+        let today = NaiveDate::from_ymd_opt(2026, 3, 31).unwrap();
+        for day in ["2026-03-30", "2026-02-01", "not-a-date"] {
+            std::fs::write(dir.join(format!("audit-{}.jsonl", day)), "{}\n").unwrap();
+        }
+
+        prune_old(&dir, today, 30);
+
+        assert!(dir.join("audit-2026-03-30.jsonl").exists());
+        assert!(!dir.join("audit-2026-02-01.jsonl").exists());
+        assert!(dir.join("audit-not-a-date.jsonl").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_rotate_moves_stale_file() {
+        let dir = scratch_dir("rotate");
+        std::fs::write(dir.join(AUDIT_FILE), "{}\n").unwrap();
+        let written = Local::now().date_naive();
+
+        // Same day: untouched
+        rotate_if_needed(&dir, written).unwrap();
+        assert!(dir.join(AUDIT_FILE).exists());
+
+        // Next day: moved aside under the day it was written
+        rotate_if_needed(&dir, written.succ_opt().unwrap()).unwrap();
+        assert!(!dir.join(AUDIT_FILE).exists());
+        assert!(rotated_path(&dir, written).exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
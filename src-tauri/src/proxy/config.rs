@@ -0,0 +1,111 @@
+//! Proxy Server Configuration
+//!
+//! Runtime tuning for the socket server, read from `~/.tairseach/proxy.json`.
+//! Every field is optional; a missing or unreadable file yields the defaults.
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tracing::{info, warn};
+
+use super::approval::ApprovalMode;
+use super::ratelimit::RateLimitConfig;
+use crate::router::OutputValidation;
+
+/// Socket server runtime configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ProxyConfig {
+    /// Maximum requests processed concurrently on a single connection.
+    /// Further lines are not read until a slot frees up.
+    pub max_concurrent_requests: usize,
+    /// Maximum entries of one batch request executed in parallel
+    pub batch_concurrency: usize,
+    /// Days to keep rotated `audit-YYYY-MM-DD.jsonl` files
+    pub audit_retention_days: u32,
+    /// `prompt` to ask the UI about destructive calls, `deny` to refuse them
+    pub approval_mode: ApprovalMode,
+    /// Seconds a call waits for approval before it is denied
+    pub approval_timeout_secs: u64,
+    /// Per-method and per-manifest limits replacing those in manifests
+    pub rate_limits: RateLimitConfig,
+    /// Seconds in-flight requests get to finish once shutdown starts
+    pub shutdown_grace_secs: u64,
+    /// Loopback `host:port` serving Prometheus metrics at `/metrics`
+    /// (disabled when unset)
+    pub metrics_listen: Option<String>,
+    /// Check tool results against `outputSchema`: `off`, `warn` or `strict`
+    pub output_validation: OutputValidation,
+}
+
+impl Default for ProxyConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrent_requests: 16,
+            batch_concurrency: 8,
+            audit_retention_days: 30,
+            approval_mode: ApprovalMode::Prompt,
+            approval_timeout_secs: 120,
+            rate_limits: RateLimitConfig::default(),
+            shutdown_grace_secs: 10,
+            metrics_listen: None,
+            output_validation: OutputValidation::Off,
+        }
+    }
+}
+
+impl ProxyConfig {
+    /// Path of the proxy config file
+    pub fn path() -> Result<PathBuf, String> {
+        crate::common::tairseach_path("proxy.json")
+    }
+
+    /// Load the config from disk, falling back to defaults
+    pub fn load() -> Self {
+        let path = match Self::path() {
+            Ok(p) => p,
+            Err(_) => return Self::default(),
+        };
+        if !path.exists() {
+            return Self::default();
+        }
+
+        match std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|content| serde_json::from_str::<Self>(&content).map_err(|e| e.to_string()))
+        {
+            Ok(config) => {
+                info!("Loaded proxy config from {:?}", path);
+                config.normalized()
+            }
+            Err(e) => {
+                warn!("Failed to load proxy config {:?}, using defaults: {}", path, e);
+                Self::default()
+            }
+        }
+    }
+
+    /// Clamp limits so a zero in the file can't deadlock the server
+    fn normalized(mut self) -> Self {
+        self.max_concurrent_requests = self.max_concurrent_requests.max(1);
+        self.batch_concurrency = self.batch_concurrency.max(1);
+        self.approval_timeout_secs = self.approval_timeout_secs.max(1);
+        self.rate_limits = self.rate_limits.validated();
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partial_config_uses_defaults() {
+        let config: ProxyConfig = serde_json::from_str(r#"{"batchConcurrency": 0}"#).unwrap();
+        let config = config.normalized();
+        assert_eq!(config.batch_concurrency, 1);
+        assert_eq!(
+            config.max_concurrent_requests,
+            ProxyConfig::default().max_concurrent_requests
+        );
+    }
+}
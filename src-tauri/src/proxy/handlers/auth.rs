@@ -10,6 +10,9 @@ use super::common::*;
 use super::super::protocol::JsonRpcResponse;
 use crate::auth::{get_or_init_broker, TokenRecord};
 
+/// How long a rotated-out secret stays readable by default (24 hours)
+const DEFAULT_ROTATION_GRACE_SECS: u64 = 24 * 60 * 60;
+
 /// Get the shared auth broker instance.
 async fn get_broker() -> Result<std::sync::Arc<crate::auth::AuthBroker>, JsonRpcResponse> {
     get_or_init_broker()
@@ -33,8 +36,17 @@ pub async fn handle(action: &str, params: &Value, id: Value) -> JsonRpcResponse
         "token" | "get" => handle_token(params, id).await,
         "refresh" => handle_refresh(params, id).await,
         "revoke" => handle_revoke(params, id).await,
-        "store" | "import" => handle_store(params, id).await,
+        "store" => handle_store(params, id).await,
+        "store.verify" => handle_verify_store(params, id).await,
+        "import" => handle_store(params, id).await,
+        "importBundle" => handle_import_bundle(params, id).await,
+        "export" => handle_export_bundle(params, id).await,
+        "deviceStart" => handle_device_start(params, id).await,
+        "devicePoll" => handle_device_poll(params, id).await,
         "gogPassphrase" => handle_gog_passphrase(params, id).await,
+        "rekey" => handle_rekey(params, id).await,
+        "masterKey.status" => handle_master_key_status(params, id).await,
+        "masterKey.switch" => handle_master_key_switch(params, id).await,
         // Credential type registry
         "credential_types" | "credentialTypes" => handle_credential_types(params, id).await,
         "credential_types.custom.create" => handle_create_custom_type(params, id).await,
@@ -44,6 +56,8 @@ pub async fn handle(action: &str, params: &Value, id: Value) -> JsonRpcResponse
         "credentials.list" => handle_list_credentials(params, id).await,
         "credentials.delete" => handle_delete_credential(params, id).await,
         "credentials.rename" => handle_rename_credential(params, id).await,
+        "credentials.rotate" => handle_rotate_credential(params, id).await,
+        "credentials.expiring" => handle_expiring_credentials(params, id).await,
         _ => method_not_found(id, &format!("auth.{}", action)),
     }
 }
@@ -157,6 +171,51 @@ async fn handle_refresh(params: &Value, id: Value) -> JsonRpcResponse {
     }
 }
 
+/// `auth.deviceStart` — begin a device-code authorization (RFC 8628).
+async fn handle_device_start(params: &Value, id: Value) -> JsonRpcResponse {
+    let broker = match get_broker().await {
+        Ok(b) => b,
+        Err(mut resp) => {
+            resp.id = id;
+            return resp;
+        }
+    };
+
+    let provider = match require_string(params, "provider", &id) {
+        Ok(p) => p,
+        Err(response) => return response,
+    };
+    let scopes = optional_string_array(params, "scopes");
+    let account = optional_string(params, "account").map(String::from);
+
+    match broker.device_start(provider, scopes, account).await {
+        Ok(result) => ok(id, result),
+        Err((code, msg)) => error(id, code, msg),
+    }
+}
+
+/// `auth.devicePoll` — poll a device-code authorization; stores the token
+/// once the user approves.
+async fn handle_device_poll(params: &Value, id: Value) -> JsonRpcResponse {
+    let broker = match get_broker().await {
+        Ok(b) => b,
+        Err(mut resp) => {
+            resp.id = id;
+            return resp;
+        }
+    };
+
+    let session = match require_string(params, "session", &id) {
+        Ok(s) => s,
+        Err(response) => return response,
+    };
+
+    match broker.device_poll(session).await {
+        Ok(result) => ok(id, result),
+        Err((code, msg)) => error(id, code, msg),
+    }
+}
+
 /// `auth.revoke` — revoke and remove an account's tokens.
 async fn handle_revoke(params: &Value, id: Value) -> JsonRpcResponse {
     let broker = match get_broker().await {
@@ -337,13 +396,30 @@ async fn handle_store_credential(params: &Value, id: Value) -> JsonRpcResponse {
         None => return invalid_params(id, "Missing or invalid 'fields' parameter"),
     };
 
-    match broker
+    if let Err(e) = broker
         .store_credential(provider, account, cred_type, fields, label)
         .await
     {
-        Ok(()) => ok(id, serde_json::json!({ "success": true })),
-        Err(e) => generic_error(id, e),
+        return generic_error(id, e);
+    }
+
+    let expires_at = optional_string(params, "expiresAt");
+    let rotate_after = optional_string(params, "rotateAfter");
+    if expires_at.is_some() || rotate_after.is_some() {
+        if let Err(e) = broker
+            .set_credential_lifecycle(
+                provider,
+                account,
+                expires_at.map(String::from),
+                rotate_after.map(String::from),
+            )
+            .await
+        {
+            return invalid_params(id, e);
+        }
     }
+
+    ok(id, serde_json::json!({ "success": true }))
 }
 
 /// `auth.credentials.get` — retrieve a credential (uses resolution chain)
@@ -363,7 +439,12 @@ async fn handle_get_credential(params: &Value, id: Value) -> JsonRpcResponse {
 
     let label = optional_string(params, "label");
 
-    match broker.get_credential(provider, label).await {
+    let result = if bool_with_default(params, "previous", false) {
+        broker.get_previous_credential(provider, label).await
+    } else {
+        broker.get_credential(provider, label).await
+    };
+    match result {
         Ok(fields) => ok(id, serde_json::json!({ "fields": fields })),
         Err(e) => generic_error(id, e),
     }
@@ -462,3 +543,262 @@ async fn handle_rename_credential(params: &Value, id: Value) -> JsonRpcResponse
         Err(e) => generic_error(id, e),
     }
 }
+
+/// `auth.credentials.rotate` — swap in a new secret, keeping the old one
+/// readable (`auth.credentials.get` with `previous: true`) for a grace window
+async fn handle_rotate_credential(params: &Value, id: Value) -> JsonRpcResponse {
+    let broker = match get_broker().await {
+        Ok(b) => b,
+        Err(mut resp) => {
+            resp.id = id;
+            return resp;
+        }
+    };
+
+    let provider = match require_string(params, "provider", &id) {
+        Ok(p) => p,
+        Err(response) => return response,
+    };
+    let account = optional_string(params, "label").unwrap_or("default");
+
+    let fields = match params.get("fields").and_then(|v| v.as_object()) {
+        Some(obj) => obj
+            .iter()
+            .filter_map(|(k, v)| v.as_str().map(|s| (k.clone(), s.to_string())))
+            .collect(),
+        None => return invalid_params(id, "Missing or invalid 'fields' parameter"),
+    };
+    let grace_secs = u64_with_default(params, "graceSeconds", DEFAULT_ROTATION_GRACE_SECS);
+    let expires_at = optional_string(params, "expiresAt").map(String::from);
+    let rotate_after = optional_string(params, "rotateAfter").map(String::from);
+
+    match broker
+        .rotate_credential(
+            provider,
+            account,
+            fields,
+            chrono::Duration::seconds(grace_secs.min(i64::MAX as u64) as i64),
+            expires_at,
+            rotate_after,
+        )
+        .await
+    {
+        Ok(credential) => ok(
+            id,
+            serde_json::json!({ "success": true, "credential": credential }),
+        ),
+        Err(e) => generic_error(id, e),
+    }
+}
+
+/// `auth.credentials.expiring` — static credentials that have expired, expire
+/// within `withinDays` (default 7), or are due for rotation
+async fn handle_expiring_credentials(params: &Value, id: Value) -> JsonRpcResponse {
+    let broker = match get_broker().await {
+        Ok(b) => b,
+        Err(mut resp) => {
+            resp.id = id;
+            return resp;
+        }
+    };
+
+    let within_days = u64_with_default(params, "withinDays", 7).min(36500) as i64;
+    let credentials = broker
+        .expiring_credentials(chrono::Duration::days(within_days))
+        .await;
+    ok(
+        id,
+        serde_json::json!({
+            "credentials": credentials,
+            "count": credentials.len(),
+        }),
+    )
+}
+
+/// Expand a leading `~/` to the home directory.
+fn expand_home(path: &str) -> std::path::PathBuf {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => std::path::PathBuf::from(path),
+    }
+}
+
+/// `auth.export` — write selected credentials and tokens to a
+/// passphrase-protected bundle
+async fn handle_export_bundle(params: &Value, id: Value) -> JsonRpcResponse {
+    let broker = match get_broker().await {
+        Ok(b) => b,
+        Err(mut resp) => {
+            resp.id = id;
+            return resp;
+        }
+    };
+
+    let passphrase = match require_string(params, "passphrase", &id) {
+        Ok(p) => p,
+        Err(response) => return response,
+    };
+    let selectors = optional_string_array(params, "credentials").unwrap_or_default();
+    let path = match optional_string(params, "path") {
+        Some(p) => {
+            let path = expand_home(p);
+            if !path.is_absolute() {
+                return invalid_params(id, "Path must be absolute");
+            }
+            if let Err(e) = super::files::validate_write_path(&path) {
+                return error(id, -32004, format!("Path not allowed for writing: {}", e));
+            }
+            path
+        }
+        None => {
+            let name = format!(
+                "exports/credentials-{}.bundle.json",
+                chrono::Utc::now().format("%Y%m%d-%H%M%S")
+            );
+            match crate::common::tairseach_path(&name) {
+                Ok(p) => p,
+                Err(e) => return generic_error(id, e),
+            }
+        }
+    };
+
+    match broker.export_bundle(&path, passphrase, &selectors).await {
+        Ok(result) => ok(id, result),
+        Err(e) => generic_error(id, e),
+    }
+}
+
+/// `auth.importBundle` — import a bundle from `auth.export`,
+/// or preview it with `dryRun`
+async fn handle_import_bundle(params: &Value, id: Value) -> JsonRpcResponse {
+    let broker = match get_broker().await {
+        Ok(b) => b,
+        Err(mut resp) => {
+            resp.id = id;
+            return resp;
+        }
+    };
+
+    let path = match require_string(params, "path", &id) {
+        Ok(p) => expand_home(p),
+        Err(response) => return response,
+    };
+    if !path.is_absolute() {
+        return invalid_params(id, "Path must be absolute");
+    }
+    // Bundles written to the default export directory may be read back;
+    // anything else under ~/.tairseach may not
+    let in_exports = crate::common::tairseach_path("exports")
+        .and_then(|dir| dir.canonicalize().map_err(|e| e.to_string()))
+        .is_ok_and(|dir| path.canonicalize().is_ok_and(|p| p.starts_with(dir)));
+    if !in_exports {
+        if let Err(e) = super::files::validate_read_path(&path) {
+            return error(id, -32004, format!("Path not allowed for reading: {}", e));
+        }
+    }
+    let passphrase = match require_string(params, "passphrase", &id) {
+        Ok(p) => p,
+        Err(response) => return response,
+    };
+    let conflict = match params.get("conflict") {
+        None => crate::auth::store::ConflictPolicy::default(),
+        Some(v) => match serde_json::from_value(v.clone()) {
+            Ok(c) => c,
+            Err(_) => {
+                return invalid_params(id, "conflict must be 'skip', 'overwrite' or 'rename'")
+            }
+        },
+    };
+    let dry_run = bool_with_default(params, "dryRun", false);
+
+    match broker.import_bundle(&path, passphrase, conflict, dry_run).await {
+        Ok(result) => ok(id, result),
+        Err(e) => generic_error(id, e),
+    }
+}
+
+/// `auth.store.verify` — check the token store for corruption, repairing
+/// from backups with `repair: true`
+async fn handle_verify_store(params: &Value, id: Value) -> JsonRpcResponse {
+    let broker = match get_broker().await {
+        Ok(b) => b,
+        Err(mut resp) => {
+            resp.id = id;
+            return resp;
+        }
+    };
+
+    let repair = bool_with_default(params, "repair", false);
+    match broker.verify_store(repair).await {
+        Ok(report) => ok(id, serde_json::to_value(report).unwrap_or_default()),
+        Err(e) => generic_error(id, e),
+    }
+}
+
+/// `auth.rekey` — rotate the data key that encrypts stored entries
+async fn handle_rekey(params: &Value, id: Value) -> JsonRpcResponse {
+    let broker = match get_broker().await {
+        Ok(b) => b,
+        Err(mut resp) => {
+            resp.id = id;
+            return resp;
+        }
+    };
+
+    let reencrypt_all = bool_with_default(params, "reencryptAll", false);
+    match broker.rekey(reencrypt_all).await {
+        Ok(status) => ok(id, serde_json::to_value(status).unwrap_or_default()),
+        Err(e) => generic_error(id, e),
+    }
+}
+
+/// `auth.masterKey.status` — the master key source in use
+async fn handle_master_key_status(_params: &Value, id: Value) -> JsonRpcResponse {
+    match get_broker().await {
+        Ok(broker) => ok(id, broker.master_key_status().await),
+        Err(mut resp) => {
+            resp.id = id;
+            resp
+        }
+    }
+}
+
+/// `auth.masterKey.switch` — re-encrypt the store under another key source
+async fn handle_master_key_switch(params: &Value, id: Value) -> JsonRpcResponse {
+    let broker = match get_broker().await {
+        Ok(b) => b,
+        Err(mut resp) => {
+            resp.id = id;
+            return resp;
+        }
+    };
+
+    let name = match require_string(params, "source", &id) {
+        Ok(s) => s,
+        Err(response) => return response,
+    };
+    // A key file is created if missing, so a caller-chosen one is checked
+    // like any other write; the default (~/.tairseach/master.key) needs no path
+    let path = match optional_string(params, "path") {
+        Some(p) => {
+            let path = expand_home(p);
+            if !path.is_absolute() {
+                return invalid_params(id, "Path must be absolute");
+            }
+            if let Err(e) = super::files::validate_write_path(&path) {
+                return error(id, -32004, format!("Path not allowed for writing: {}", e));
+            }
+            Some(path)
+        }
+        None => None,
+    };
+    let source = match crate::auth::master_key::MasterKeySource::from_name(name, path) {
+        Ok(s) => s,
+        Err(e) => return invalid_params(id, e),
+    };
+
+    match broker.switch_master_key(source).await {
+        Ok(result) => ok(id, result),
+        Err(e) => generic_error(id, e),
+    }
+}
@@ -186,6 +186,21 @@ pub fn extract_oauth_credentials(
     Ok((provider, account))
 }
 
+/// Replace a Google handler's response with a structured
+/// `TOKEN_REFRESH_FAILED` error if its token was rejected and couldn't be
+/// refreshed
+pub fn with_refresh_failure(
+    response: JsonRpcResponse,
+    token: &crate::google::client::TokenSource,
+) -> JsonRpcResponse {
+    match (token.refresh_failure(), token.account()) {
+        (Some(reason), Some((provider, account))) => {
+            JsonRpcResponse::token_refresh_failed(response.id, provider, account, &reason)
+        }
+        _ => response,
+    }
+}
+
 /// Extract OAuth access token from auth broker response
 pub fn extract_access_token(token_data: &Value, id: &Value) -> Result<String, JsonRpcResponse> {
     token_data
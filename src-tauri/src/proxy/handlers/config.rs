@@ -168,7 +168,7 @@ async fn handle_set(params: &Value, id: Value) -> JsonRpcResponse {
         }
     };
 
-    match std::fs::write(&config_path, &content) {
+    match crate::common::write_atomic(&config_path, content.as_bytes(), None) {
         Ok(()) => {
             info!("Config updated at {:?}", config_path);
             ok(
@@ -180,7 +180,7 @@ async fn handle_set(params: &Value, id: Value) -> JsonRpcResponse {
                 }),
             )
         }
-        Err(e) => generic_error(id, format!("Failed to write config: {}", e)),
+        Err(e) => generic_error(id, e),
     }
 }
 
@@ -316,7 +316,7 @@ async fn handle_set_node_config(params: &Value, id: Value) -> JsonRpcResponse {
         }
     };
     
-    match std::fs::write(&node_path, &content) {
+    match crate::common::write_atomic(&node_path, content.as_bytes(), None) {
         Ok(()) => {
             info!("Node config updated at {:?}", node_path);
             ok(
@@ -327,7 +327,7 @@ async fn handle_set_node_config(params: &Value, id: Value) -> JsonRpcResponse {
                 }),
             )
         }
-        Err(e) => generic_error(id, format!("Failed to write node config: {}", e)),
+        Err(e) => generic_error(id, e),
     }
 }
 
@@ -400,7 +400,7 @@ async fn handle_set_exec_approvals(params: &Value, id: Value) -> JsonRpcResponse
         }
     };
     
-    match std::fs::write(&approvals_path, &content) {
+    match crate::common::write_atomic(&approvals_path, content.as_bytes(), None) {
         Ok(()) => {
             info!("Exec approvals updated at {:?}", approvals_path);
             ok(
@@ -411,7 +411,7 @@ async fn handle_set_exec_approvals(params: &Value, id: Value) -> JsonRpcResponse
                 }),
             )
         }
-        Err(e) => generic_error(id, format!("Failed to write exec approvals: {}", e)),
+        Err(e) => generic_error(id, e),
     }
 }
 
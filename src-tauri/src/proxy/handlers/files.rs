@@ -37,7 +37,7 @@ const WRITE_DENY_LIST: &[&str] = &[
 ///
 /// **SECURITY:** Prevents writes to critical system paths and Tairseach's own
 /// auth store. This is defense-in-depth against compromised agents.
-fn validate_write_path(path: &Path) -> Result<(), String> {
+pub(super) fn validate_write_path(path: &Path) -> Result<(), String> {
     // Resolve symlinks to real path
     let canonical = path.canonicalize().unwrap_or_else(|_| {
         // If canonicalize fails (path doesn't exist yet), check the parent
@@ -102,6 +102,25 @@ fn validate_write_path(path: &Path) -> Result<(), String> {
     Ok(())
 }
 
+/// Validate that a path may be read (by `files.read` or handlers that upload
+/// local files).
+///
+/// **SECURITY:** Blocks reads of Tairseach's config directory; agents should
+/// use `auth.*` methods instead.
+pub(super) fn validate_read_path(path: &Path) -> Result<(), String> {
+    if let Some(home) = dirs::home_dir() {
+        let tairseach_dir = home.join(".tairseach");
+        let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        if canonical.starts_with(&tairseach_dir) {
+            return Err(
+                "Reads from Tairseach configuration directory are not allowed. Use auth.* methods instead."
+                    .to_string(),
+            );
+        }
+    }
+    Ok(())
+}
+
 /// Handle file-related methods
 pub async fn handle(action: &str, params: &Value, id: Value) -> JsonRpcResponse {
     match action {
@@ -141,18 +160,8 @@ async fn handle_read(params: &Value, id: Value) -> JsonRpcResponse {
         return error(id, -32002, format!("Not a file: {}", file_path));
     }
 
-    // SECURITY: Block reads to Tairseach config directory
-    // Agents should use auth.get / auth.list instead
-    if let Some(home) = dirs::home_dir() {
-        let tairseach_dir = home.join(".tairseach");
-        let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
-        if canonical.starts_with(&tairseach_dir) {
-            return error(
-                id,
-                -32004,
-                "Reads from Tairseach configuration directory are not allowed. Use auth.* methods instead.",
-            );
-        }
+    if let Err(e) = validate_read_path(path) {
+        return error(id, -32004, e);
     }
 
     // Check file size
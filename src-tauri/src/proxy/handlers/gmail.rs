@@ -2,14 +2,62 @@
 //!
 //! Socket handlers for Gmail API methods.
 //! Retrieves OAuth tokens from auth broker and uses Google API client.
+//!
+//! `send`, `createDraft` and `updateDraft` share their params: recipients,
+//! `subject`, `body` (plain text) and/or `html`, `attachments` and
+//! `replyTo` (a message ID to answer in its thread).
+//!
+//! `sync` returns changes since the calling client's last checkpoint for the
+//! account and label; `watch` polls with its own checkpoint and publishes
+//! changes as `gmail.changed` notifications, which only reach connections
+//! whose policy allows the account.
 
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use once_cell::sync::Lazy;
 use serde_json::Value;
-use tracing::{debug, error, info};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{debug, error, info, warn};
 
 use super::common::*;
+use super::files::{validate_read_path, validate_write_path};
+use super::super::audit::{self, AuditEntry};
+use super::super::notifications::{self, topics};
+use super::super::peer::{self, PeerInfo};
+use super::super::policy;
 use super::super::protocol::JsonRpcResponse;
+use crate::google::gmail::{checkpoint_key, DEFAULT_RESYNC_MESSAGES};
+use crate::google::mime::{self, Attachment, OutgoingMessage};
 use crate::google::GmailApi;
 
+/// Gmail's limit on total attachment size per message (25 MB)
+const MAX_ATTACHMENT_TOTAL: u64 = 25 * 1024 * 1024;
+
+const GMAIL_SCOPES: &[&str] = &[
+    "https://www.googleapis.com/auth/gmail.modify",
+    "https://www.googleapis.com/auth/gmail.settings.basic",
+];
+
+/// Checkpoint consumer name used by `gmail.watch` pollers
+const WATCH_CONSUMER: &str = "watch";
+
+/// A running `gmail.watch` poller
+struct Watcher {
+    label_id: Option<String>,
+    interval_secs: u64,
+    /// Changes on restart; a poller stops once its generation is replaced
+    generation: u64,
+}
+
+/// Running pollers by account (`provider:account`)
+static WATCHERS: Lazy<Mutex<HashMap<String, Watcher>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+static NEXT_WATCH_GENERATION: AtomicU64 = AtomicU64::new(1);
+
 /// Handle Gmail-related methods
 pub async fn handle(
     action: &str,
@@ -36,10 +84,7 @@ pub async fn handle(
         .get_token(
             &provider,
             &account,
-            Some(&[
-                "https://www.googleapis.com/auth/gmail.modify".to_string(),
-                "https://www.googleapis.com/auth/gmail.settings.basic".to_string(),
-            ]),
+            Some(&scopes()),
         )
         .await
     {
@@ -56,7 +101,7 @@ pub async fn handle(
     };
 
     // Create Gmail API client
-    let gmail = match GmailApi::new(access_token) {
+    let gmail = match GmailApi::for_account(access_token, &provider, &account) {
         Ok(api) => api,
         Err(e) => {
             error!("Failed to create Gmail API client: {}", e);
@@ -64,17 +109,35 @@ pub async fn handle(
         }
     };
 
+    let token = gmail.token_source();
+
     // Dispatch to specific handler
-    match action {
+    let response = match action {
         "list_messages" | "listMessages" => handle_list_messages(params, id, gmail).await,
         "get_message" | "getMessage" => handle_get_message(params, id, gmail).await,
         "send" | "sendMessage" => handle_send_message(params, id, gmail).await,
+        "get_attachment" | "getAttachment" => handle_get_attachment(params, id, gmail).await,
+        "list_threads" | "listThreads" => handle_list_threads(params, id, gmail).await,
+        "get_thread" | "getThread" => handle_get_thread(params, id, gmail).await,
+        "list_drafts" | "listDrafts" => handle_list_drafts(params, id, gmail).await,
+        "create_draft" | "createDraft" => handle_create_draft(params, id, gmail).await,
+        "update_draft" | "updateDraft" => handle_update_draft(params, id, gmail).await,
+        "send_draft" | "sendDraft" => handle_send_draft(params, id, gmail).await,
+        "delete_draft" | "deleteDraft" => handle_delete_draft(params, id, gmail).await,
         "list_labels" | "listLabels" => handle_list_labels(id, gmail).await,
         "modify_message" | "modifyMessage" => handle_modify_message(params, id, gmail).await,
         "trash_message" | "trashMessage" => handle_trash_message(params, id, gmail).await,
         "delete_message" | "deleteMessage" => handle_delete_message(params, id, gmail).await,
+        "sync" => handle_sync(params, id, gmail, &provider, &account).await,
+        "watch" => handle_watch(params, id, &provider, &account),
         _ => method_not_found(id, &format!("gmail.{}", action)),
-    }
+    };
+
+    with_refresh_failure(response, &token)
+}
+
+fn scopes() -> Vec<String> {
+    GMAIL_SCOPES.iter().map(|s| s.to_string()).collect()
 }
 
 async fn handle_list_messages(
@@ -136,41 +199,399 @@ async fn handle_send_message(
 ) -> JsonRpcResponse {
     info!("Handling gmail.send");
 
-    let to = match params.get("to").and_then(|v| v.as_array()) {
-        Some(arr) => arr
-            .iter()
-            .filter_map(|v| v.as_str().map(String::from))
-            .collect::<Vec<_>>(),
-        None => {
-            // Also try as a single string
-            if let Some(to_str) = params.get("to").and_then(|v| v.as_str()) {
-                vec![to_str.to_string()]
-            } else {
-                return invalid_params(id, "Missing required parameter: to");
+    let (message, thread_id) = match outgoing_message(params, &id, &gmail).await {
+        Ok(m) => m,
+        Err(response) => return response,
+    };
+
+    match gmail.send_message(&message, thread_id.as_deref()).await {
+        Ok(response) => {
+            info!("Message sent successfully");
+            ok(id, response)
+        }
+        Err(e) => {
+            error!("Failed to send message: {}", e);
+            generic_error(id, e)
+        }
+    }
+}
+
+/// Build the outgoing message (and thread) from send/draft params
+///
+/// Params:
+///   - to, cc, bcc: address or array of addresses (`to` defaults to the
+///     sender of `replyTo`)
+///   - subject (defaults to "Re: <original>" with `replyTo`)
+///   - body (plain text) and/or html
+///   - attachments: array of `{path}` or `{filename, content (base64), mimeType}`
+///   - replyTo: message ID to reply to (sets threading headers and thread)
+///   - threadId: thread to add the message to
+///   - from: sender address (a verified send-as alias)
+async fn outgoing_message(
+    params: &Value,
+    id: &Value,
+    gmail: &GmailApi,
+) -> Result<(OutgoingMessage, Option<String>), JsonRpcResponse> {
+    let reply = match optional_string(params, "replyTo") {
+        Some(message_id) => match gmail.reply_context(message_id).await {
+            Ok(context) => Some(context),
+            Err(e) => return Err(generic_error(id.clone(), e)),
+        },
+        None => None,
+    };
+
+    let mut to = address_list(params, "to");
+    if to.is_empty() {
+        match reply.as_ref().and_then(|r| r.reply_to.clone()) {
+            Some(sender) => to.push(sender),
+            None => return Err(invalid_params(id.clone(), "Missing required parameter: to")),
+        }
+    }
+
+    let subject = match (optional_string(params, "subject"), &reply) {
+        (Some(subject), _) => subject.to_string(),
+        (None, Some(reply)) => reply.subject.clone(),
+        (None, None) => return Err(invalid_params(id.clone(), "Missing required parameter: subject")),
+    };
+
+    let text = optional_string(params, "body").map(str::to_string);
+    let html = optional_string(params, "html").map(str::to_string);
+    if text.is_none() && html.is_none() {
+        return Err(invalid_params(id.clone(), "At least one of 'body' or 'html' is required"));
+    }
+
+    let attachments = match params.get("attachments").and_then(Value::as_array) {
+        Some(specs) => match load_attachments(specs) {
+            Ok(a) => a,
+            Err(e) => return Err(invalid_params(id.clone(), e)),
+        },
+        None => Vec::new(),
+    };
+
+    let thread_id = optional_string(params, "threadId")
+        .map(str::to_string)
+        .or_else(|| reply.as_ref().and_then(|r| r.thread_id.clone()));
+
+    let message = OutgoingMessage {
+        from: optional_string(params, "from").map(str::to_string),
+        to,
+        cc: address_list(params, "cc"),
+        bcc: address_list(params, "bcc"),
+        subject,
+        text,
+        html,
+        attachments,
+        in_reply_to: reply.as_ref().map(|r| r.in_reply_to.clone()),
+        references: reply.map(|r| r.references),
+    };
+    Ok((message, thread_id))
+}
+
+/// An address param given as a string or an array of strings
+fn address_list(params: &Value, key: &str) -> Vec<String> {
+    match params.get(key) {
+        Some(Value::String(addr)) => vec![addr.clone()],
+        Some(Value::Array(_)) => optional_string_array(params, key).unwrap_or_default(),
+        _ => Vec::new(),
+    }
+}
+
+/// Read attachments from local paths (checked like `files.read`) or
+/// base64 `content`
+fn load_attachments(specs: &[Value]) -> Result<Vec<Attachment>, String> {
+    let mut attachments = Vec::with_capacity(specs.len());
+    let mut total = 0u64;
+
+    for spec in specs {
+        let (filename, data) = match optional_string(spec, "path") {
+            Some(file_path) => {
+                let path = Path::new(file_path);
+                if !path.is_absolute() {
+                    return Err(format!("Attachment path must be absolute: {}", file_path));
+                }
+                validate_read_path(path)?;
+                let data = read_attachment(path, MAX_ATTACHMENT_TOTAL.saturating_sub(total))
+                    .map_err(|e| format!("Attachment {}: {}", file_path, e))?;
+                let name = optional_string(spec, "filename")
+                    .or_else(|| path.file_name().and_then(|n| n.to_str()))
+                    .unwrap_or("attachment");
+                (name.to_string(), data)
             }
+            None => {
+                let content = optional_string(spec, "content")
+                    .ok_or("Each attachment needs 'path' or 'content'")?;
+                let filename = optional_string(spec, "filename")
+                    .ok_or("Attachments given as 'content' need a 'filename'")?;
+                let data = BASE64
+                    .decode(content)
+                    .map_err(|e| format!("Invalid base64 content for {}: {}", filename, e))?;
+                (filename.to_string(), data)
+            }
+        };
+
+        total += data.len() as u64;
+        if total > MAX_ATTACHMENT_TOTAL {
+            return Err(attachment_limit_error());
+        }
+        let content_type = optional_string(spec, "mimeType")
+            .unwrap_or_else(|| mime::guess_content_type(&filename))
+            .to_string();
+        attachments.push(Attachment { filename, content_type, data });
+    }
+    Ok(attachments)
+}
+
+/// Read a regular file of at most `budget` bytes. The size is checked
+/// before opening, so devices, FIFOs and oversized files are refused
+/// without reading them.
+fn read_attachment(path: &Path, budget: u64) -> Result<Vec<u8>, String> {
+    let metadata = fs::metadata(path).map_err(|e| format!("cannot read: {}", e))?;
+    if !metadata.is_file() {
+        return Err("not a regular file".to_string());
+    }
+    if metadata.len() > budget {
+        return Err(attachment_limit_error());
+    }
+    let file = fs::File::open(path).map_err(|e| format!("cannot read: {}", e))?;
+    // The file may have grown since the check
+    let mut data = Vec::with_capacity(metadata.len() as usize);
+    file.take(budget + 1)
+        .read_to_end(&mut data)
+        .map_err(|e| format!("cannot read: {}", e))?;
+    if data.len() as u64 > budget {
+        return Err(attachment_limit_error());
+    }
+    Ok(data)
+}
+
+fn attachment_limit_error() -> String {
+    format!("Attachments exceed Gmail's {} byte limit", MAX_ATTACHMENT_TOTAL)
+}
+
+/// Download an attachment
+///
+/// Params:
+///   - messageId, attachmentId (required; from `attachments` in getMessage)
+///   - path (optional): absolute path to save to instead of returning content
+async fn handle_get_attachment(
+    params: &Value,
+    id: Value,
+    gmail: GmailApi,
+) -> JsonRpcResponse {
+    info!("Handling gmail.get_attachment");
+
+    let message_id = match require_string(params, "messageId", &id) {
+        Ok(m) => m,
+        Err(response) => return response,
+    };
+    let attachment_id = match require_string(params, "attachmentId", &id) {
+        Ok(a) => a,
+        Err(response) => return response,
+    };
+
+    let save_to = optional_string(params, "path").map(Path::new);
+    if let Some(path) = save_to {
+        if !path.is_absolute() {
+            return invalid_params(id, "Path must be absolute");
+        }
+        if let Err(e) = validate_write_path(path) {
+            return error(id, -32004, format!("Path not allowed for writing: {}", e));
+        }
+    }
+
+    let data = match gmail.get_attachment(message_id, attachment_id).await {
+        Ok(d) => d,
+        Err(e) => {
+            error!("Failed to get attachment: {}", e);
+            return generic_error(id, e);
         }
     };
 
-    let subject = match require_string(params, "subject", &id) {
-        Ok(s) => s,
+    match save_to {
+        Some(path) => match fs::write(path, &data) {
+            Ok(()) => ok(
+                id,
+                serde_json::json!({
+                    "messageId": message_id,
+                    "attachmentId": attachment_id,
+                    "path": path,
+                    "written": true,
+                    "size": data.len(),
+                }),
+            ),
+            Err(e) => generic_error(id, format!("Failed to write file: {}", e)),
+        },
+        None => ok(
+            id,
+            serde_json::json!({
+                "messageId": message_id,
+                "attachmentId": attachment_id,
+                "encoding": "base64",
+                "content": BASE64.encode(&data),
+                "size": data.len(),
+            }),
+        ),
+    }
+}
+
+async fn handle_list_threads(
+    params: &Value,
+    id: Value,
+    gmail: GmailApi,
+) -> JsonRpcResponse {
+    info!("Handling gmail.list_threads");
+
+    let query = optional_string(params, "query");
+    let max_results = optional_u64_or(params, "maxResults", "max_results").map(|n| n as usize);
+    let label_ids = optional_string_array_or(params, "labelIds", "label_ids");
+
+    match gmail.list_threads(query, max_results, label_ids).await {
+        Ok(threads) => {
+            debug!("Retrieved {} threads", threads.len());
+            ok(
+                id,
+                serde_json::json!({
+                    "threads": threads,
+                    "count": threads.len(),
+                }),
+            )
+        }
+        Err(e) => {
+            error!("Failed to list threads: {}", e);
+            generic_error(id, e)
+        }
+    }
+}
+
+async fn handle_get_thread(
+    params: &Value,
+    id: Value,
+    gmail: GmailApi,
+) -> JsonRpcResponse {
+    info!("Handling gmail.get_thread");
+
+    let thread_id = match require_string_or(params, "id", "threadId", &id) {
+        Ok(id) => id,
         Err(response) => return response,
     };
+    let format = optional_string(params, "format");
+
+    match gmail.get_thread(thread_id, format).await {
+        Ok(thread) => ok(id, thread),
+        Err(e) => {
+            error!("Failed to get thread: {}", e);
+            generic_error(id, e)
+        }
+    }
+}
+
+async fn handle_list_drafts(
+    params: &Value,
+    id: Value,
+    gmail: GmailApi,
+) -> JsonRpcResponse {
+    info!("Handling gmail.list_drafts");
+
+    let max_results = optional_u64_or(params, "maxResults", "max_results").map(|n| n as usize);
+
+    match gmail.list_drafts(max_results).await {
+        Ok(drafts) => ok(
+            id,
+            serde_json::json!({
+                "drafts": drafts,
+                "count": drafts.len(),
+            }),
+        ),
+        Err(e) => {
+            error!("Failed to list drafts: {}", e);
+            generic_error(id, e)
+        }
+    }
+}
 
-    let body = match require_string(params, "body", &id) {
-        Ok(b) => b,
+async fn handle_create_draft(
+    params: &Value,
+    id: Value,
+    gmail: GmailApi,
+) -> JsonRpcResponse {
+    info!("Handling gmail.create_draft");
+
+    let (message, thread_id) = match outgoing_message(params, &id, &gmail).await {
+        Ok(m) => m,
         Err(response) => return response,
     };
 
-    let cc = optional_string_array(params, "cc");
-    let bcc = optional_string_array(params, "bcc");
+    match gmail.create_draft(&message, thread_id.as_deref()).await {
+        Ok(draft) => ok(id, draft),
+        Err(e) => {
+            error!("Failed to create draft: {}", e);
+            generic_error(id, e)
+        }
+    }
+}
 
-    match gmail.send_message(to, subject, body, cc, bcc).await {
-        Ok(response) => {
-            info!("Message sent successfully");
-            ok(id, response)
+async fn handle_update_draft(
+    params: &Value,
+    id: Value,
+    gmail: GmailApi,
+) -> JsonRpcResponse {
+    info!("Handling gmail.update_draft");
+
+    let draft_id = match require_string(params, "draftId", &id) {
+        Ok(d) => d,
+        Err(response) => return response,
+    };
+    let (message, thread_id) = match outgoing_message(params, &id, &gmail).await {
+        Ok(m) => m,
+        Err(response) => return response,
+    };
+
+    match gmail.update_draft(draft_id, &message, thread_id.as_deref()).await {
+        Ok(draft) => ok(id, draft),
+        Err(e) => {
+            error!("Failed to update draft: {}", e);
+            generic_error(id, e)
+        }
+    }
+}
+
+async fn handle_send_draft(
+    params: &Value,
+    id: Value,
+    gmail: GmailApi,
+) -> JsonRpcResponse {
+    info!("Handling gmail.send_draft");
+
+    let draft_id = match require_string(params, "draftId", &id) {
+        Ok(d) => d,
+        Err(response) => return response,
+    };
+
+    match gmail.send_draft(draft_id).await {
+        Ok(message) => ok(id, message),
+        Err(e) => {
+            error!("Failed to send draft: {}", e);
+            generic_error(id, e)
         }
+    }
+}
+
+async fn handle_delete_draft(
+    params: &Value,
+    id: Value,
+    gmail: GmailApi,
+) -> JsonRpcResponse {
+    info!("Handling gmail.delete_draft");
+
+    let draft_id = match require_string(params, "draftId", &id) {
+        Ok(d) => d,
+        Err(response) => return response,
+    };
+
+    match gmail.delete_draft(draft_id).await {
+        Ok(()) => ok(id, serde_json::json!({ "deleted": true, "draftId": draft_id })),
         Err(e) => {
-            error!("Failed to send message: {}", e);
+            error!("Failed to delete draft: {}", e);
             generic_error(id, e)
         }
     }
@@ -259,3 +680,201 @@ async fn handle_delete_message(
         }
     }
 }
+
+async fn handle_sync(
+    params: &Value,
+    id: Value,
+    gmail: GmailApi,
+    provider: &str,
+    account: &str,
+) -> JsonRpcResponse {
+    info!("Handling gmail.sync");
+
+    let label_id = optional_string_or(params, "labelId", "label_id");
+    let max_results = optional_u64_or(params, "maxResults", "max_results")
+        .map(|n| n as usize)
+        .unwrap_or(DEFAULT_RESYNC_MESSAGES);
+    let reset = bool_with_default(params, "reset", false);
+    let account_key = format!("{}:{}", provider, account);
+    let consumer = peer::current_client().unwrap_or_else(|| "local".to_string());
+
+    match gmail
+        .sync(&checkpoint_key(&account_key, label_id, &consumer), label_id, max_results, reset)
+        .await
+    {
+        Ok(result) => {
+            debug!(
+                "Gmail sync: {} added, {} deleted, {} relabelled",
+                result.added.len(),
+                result.deleted.len(),
+                result.relabelled.len()
+            );
+            let mut body = serde_json::to_value(&result).unwrap_or_default();
+            body["account"] = Value::String(account.to_string());
+            if result.has_changes() {
+                notifications::publish_for_credential(topics::GMAIL_CHANGED, &account_key, body.clone());
+            }
+            ok(id, body)
+        }
+        Err(e) => {
+            error!("Failed to sync Gmail: {}", e);
+            generic_error(id, e)
+        }
+    }
+}
+
+/// Start publishing `gmail.changed` notifications for this account.
+///
+/// Callers must `subscribe` to `gmail.changed` first. One poller per account
+/// runs `sync` every `interval` seconds with its own checkpoint, under the
+/// policy of the client that started it (each poll is audited as
+/// `gmail.watch`), and stops once no connection is subscribed. Calling
+/// `watch` again with another `labelId` or `interval` restarts it.
+fn handle_watch(params: &Value, id: Value, provider: &str, account: &str) -> JsonRpcResponse {
+    let interval_secs = u64_with_default(params, "interval", 60).max(15);
+    let label_id = optional_string_or(params, "labelId", "label_id").map(String::from);
+
+    if !notifications::hub().has_subscribers(topics::GMAIL_CHANGED) {
+        return invalid_params(
+            id,
+            format!(
+                "No subscriber for '{}'. Call subscribe with topics=['{}'] on this connection first.",
+                topics::GMAIL_CHANGED,
+                topics::GMAIL_CHANGED
+            ),
+        );
+    }
+
+    let key = format!("{}:{}", provider, account);
+    let (started, restarted) = {
+        let mut watchers = WATCHERS.lock().unwrap_or_else(|p| p.into_inner());
+        match watchers.get(&key) {
+            Some(w) if w.label_id == label_id && w.interval_secs == interval_secs => (None, false),
+            existing => {
+                let restarted = existing.is_some();
+                let generation = NEXT_WATCH_GENERATION.fetch_add(1, Ordering::Relaxed);
+                watchers.insert(
+                    key.clone(),
+                    Watcher { label_id: label_id.clone(), interval_secs, generation },
+                );
+                (Some(generation), restarted)
+            }
+        }
+    };
+
+    if let Some(generation) = started {
+        info!(
+            "{} Gmail watcher for {} (label={:?}, interval={}s)",
+            if restarted { "Restarting" } else { "Starting" },
+            key,
+            label_id,
+            interval_secs
+        );
+        let client_policy = policy::current();
+        let client = peer::current_client().unwrap_or_else(|| "local".to_string());
+        let peer_info = peer::current_peer().unwrap_or_else(|| Arc::new(PeerInfo::default()));
+        let (provider, account) = (provider.to_string(), account.to_string());
+        let watch_label = label_id.clone();
+        tokio::spawn(async move {
+            let current = || {
+                WATCHERS
+                    .lock()
+                    .unwrap_or_else(|p| p.into_inner())
+                    .get(&key)
+                    .is_some_and(|w| w.generation == generation)
+            };
+            let mut ticker = tokio::time::interval(Duration::from_secs(interval_secs));
+            loop {
+                ticker.tick().await;
+                if !current() {
+                    info!("Gmail watcher for {} replaced", key);
+                    return;
+                }
+                if !notifications::hub().has_subscribers(topics::GMAIL_CHANGED) {
+                    break;
+                }
+
+                let poll_started = Instant::now();
+                let (result, annotations) = policy::scope(
+                    client_policy.clone(),
+                    peer::scope_client(
+                        client.clone(),
+                        Arc::clone(&peer_info),
+                        audit::scope(watch_tick(&provider, &account, watch_label.as_deref())),
+                    ),
+                )
+                .await;
+                let mut entry = AuditEntry::new(
+                    &peer_info,
+                    "gmail.watch",
+                    None,
+                    annotations,
+                    poll_started.elapsed(),
+                    result.as_ref().err().map(|_| -32000),
+                );
+                entry.policy = client_policy.as_ref().map(|p| p.client.clone());
+                audit::record(entry);
+
+                match result {
+                    Ok(Some(body)) => {
+                        notifications::publish_for_credential(topics::GMAIL_CHANGED, &key, body)
+                    }
+                    Ok(None) => {}
+                    Err(e) => {
+                        warn!("Gmail watcher for {} failed: {}", key, e);
+                        notifications::publish_for_credential(
+                            topics::GMAIL_CHANGED,
+                            &key,
+                            serde_json::json!({ "account": account, "error": e }),
+                        );
+                    }
+                }
+            }
+            let mut watchers = WATCHERS.lock().unwrap_or_else(|p| p.into_inner());
+            if watchers.get(&key).is_some_and(|w| w.generation == generation) {
+                watchers.remove(&key);
+            }
+            info!("Gmail watcher for {} stopped (no subscribers)", key);
+        });
+    }
+
+    ok(
+        id,
+        serde_json::json!({
+            "topic": topics::GMAIL_CHANGED,
+            "account": account,
+            "labelId": label_id,
+            "interval": interval_secs,
+            "started": started.is_some(),
+            "restarted": restarted,
+        }),
+    )
+}
+
+/// One watcher poll: the notification body if anything changed
+async fn watch_tick(provider: &str, account: &str, label_id: Option<&str>) -> Result<Option<Value>, String> {
+    let broker = get_auth_broker()
+        .await
+        .map_err(|_| "Auth broker not initialized".to_string())?;
+    let token_data = broker
+        .get_token(provider, account, Some(&scopes()))
+        .await
+        .map_err(|(_, msg)| msg)?;
+    let access_token = token_data
+        .get("access_token")
+        .and_then(|v| v.as_str())
+        .ok_or("Invalid token response: missing access_token")?;
+
+    let gmail = GmailApi::for_account(access_token.to_string(), provider, account)?;
+    let checkpoint = checkpoint_key(&format!("{}:{}", provider, account), label_id, WATCH_CONSUMER);
+    let result = gmail
+        .sync(&checkpoint, label_id, DEFAULT_RESYNC_MESSAGES, false)
+        .await?;
+    if !result.has_changes() {
+        return Ok(None);
+    }
+
+    let mut body = serde_json::to_value(&result).map_err(|e| e.to_string())?;
+    body["account"] = Value::String(account.to_string());
+    Ok(Some(body))
+}
@@ -2,14 +2,26 @@
 //!
 //! Socket handlers for Google Calendar API methods.
 //! Retrieves OAuth tokens from auth broker and uses Google API client.
+//!
+//! `createEvent` and `updateEvent` share their params: times, recurrence
+//! (`recurrence`, `rrule`, `exdates`), `attendees`, `reminders` and `addMeet`.
+//! Writes accept `sendUpdates` to control invitation emails.
 
+use chrono::{DateTime, Duration, NaiveTime, Weekday};
 use serde_json::Value;
 use tracing::{debug, error, info};
 
 use super::common::*;
 use super::super::protocol::JsonRpcResponse;
+use crate::common::timezone::Zone;
+use crate::google::calendar_api::{
+    self, Attendee, EventFields, Reminders, SlotSearch, MAX_REMINDERS, RESPONSES, SEND_UPDATES,
+};
 use crate::google::CalendarApi;
 
+/// Longest window `findSlot` will search
+const MAX_SLOT_SEARCH_DAYS: i64 = 62;
+
 /// Handle Google Calendar-related methods
 pub async fn handle(
     action: &str,
@@ -54,7 +66,7 @@ pub async fn handle(
     };
 
     // Create Calendar API client
-    let calendar = match CalendarApi::new(access_token) {
+    let calendar = match CalendarApi::for_account(access_token, &provider, &account) {
         Ok(api) => api,
         Err(e) => {
             error!("Failed to create Calendar API client: {}", e);
@@ -62,18 +74,130 @@ pub async fn handle(
         }
     };
 
+    let token = calendar.token_source();
+
     // Dispatch to specific handler
-    match action {
+    let response = match action {
         "list_calendars" | "listCalendars" => handle_list_calendars(id, calendar).await,
         "list_events" | "listEvents" => handle_list_events(params, id, calendar).await,
         "get_event" | "getEvent" => handle_get_event(params, id, calendar).await,
         "create_event" | "createEvent" => handle_create_event(params, id, calendar).await,
         "update_event" | "updateEvent" => handle_update_event(params, id, calendar).await,
         "delete_event" | "deleteEvent" => handle_delete_event(params, id, calendar).await,
+        "list_instances" | "listInstances" => handle_list_instances(params, id, calendar).await,
+        "respond" => handle_respond(params, id, calendar).await,
+        "free_busy" | "freeBusy" => handle_free_busy(params, id, calendar).await,
+        "find_slot" | "findSlot" => handle_find_slot(params, id, calendar).await,
         _ => method_not_found(id, &format!("gcalendar.{}", action)),
+    };
+
+    with_refresh_failure(response, &token)
+}
+
+/// Fields shared by create and update
+fn event_fields(params: &Value) -> Result<EventFields<'_>, String> {
+    let lines = optional_string_array(params, "recurrence");
+    let rrule = optional_string(params, "rrule");
+    let recurrence = if lines.is_some() || rrule.is_some() {
+        Some(calendar_api::recurrence_lines(&lines.unwrap_or_default(), rrule)?)
+    } else {
+        None
+    };
+    let exdates = optional_string_array(params, "exdates")
+        .unwrap_or_default()
+        .iter()
+        .map(|d| calendar_api::exdate_line(d))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(EventFields {
+        summary: optional_string(params, "summary"),
+        start: optional_string(params, "start"),
+        end: optional_string(params, "end"),
+        time_zone: optional_string(params, "timeZone"),
+        location: optional_string(params, "location"),
+        description: optional_string(params, "description"),
+        recurrence,
+        exdates,
+        attendees: attendees(params)?,
+        reminders: reminders(params)?,
+        add_meet: bool_with_default(params, "addMeet", false),
+    })
+}
+
+/// `attendees`: email strings or `{email, displayName, optional}` objects
+fn attendees(params: &Value) -> Result<Option<Vec<Attendee>>, String> {
+    let Some(list) = params.get("attendees") else {
+        return Ok(None);
+    };
+    let list = list.as_array().ok_or("'attendees' must be an array")?;
+    list.iter()
+        .map(|a| match a {
+            Value::String(email) => Ok(Attendee {
+                email: email.clone(),
+                display_name: None,
+                optional: false,
+            }),
+            Value::Object(_) => Ok(Attendee {
+                email: a["email"]
+                    .as_str()
+                    .ok_or("Each attendee needs an 'email'")?
+                    .to_string(),
+                display_name: a["displayName"].as_str().map(str::to_string),
+                optional: a["optional"].as_bool().unwrap_or(false),
+            }),
+            _ => Err("Attendees must be email strings or objects".to_string()),
+        })
+        .collect::<Result<Vec<_>, String>>()
+        .map(Some)
+}
+
+/// `reminders`: `"default"`, or a list of `{method: popup|email, minutes}`
+fn reminders(params: &Value) -> Result<Option<Reminders>, String> {
+    match params.get("reminders") {
+        None => Ok(None),
+        Some(Value::String(s)) if s == "default" => Ok(Some(Reminders::Default)),
+        Some(Value::Array(list)) => {
+            if list.len() > MAX_REMINDERS {
+                return Err(format!("At most {} reminders are allowed", MAX_REMINDERS));
+            }
+            list.iter()
+                .map(|r| {
+                    let method = r["method"].as_str().unwrap_or("popup");
+                    if method != "popup" && method != "email" {
+                        return Err(format!("Invalid reminder method '{}': use 'popup' or 'email'", method));
+                    }
+                    let minutes = r["minutes"]
+                        .as_u64()
+                        .filter(|m| *m <= 40320)
+                        .ok_or("Each reminder needs 'minutes' (0 to 40320)")?;
+                    Ok((method.to_string(), minutes as u32))
+                })
+                .collect::<Result<Vec<_>, String>>()
+                .map(|list| Some(Reminders::Overrides(list)))
+        }
+        Some(_) => Err("'reminders' must be \"default\" or an array".to_string()),
+    }
+}
+
+/// Validated `sendUpdates`
+fn send_updates(params: &Value) -> Result<Option<&str>, String> {
+    match optional_string_or(params, "sendUpdates", "send_updates") {
+        Some(send) if !SEND_UPDATES.contains(&send) => Err(format!(
+            "Invalid sendUpdates '{}': expected one of {}",
+            send,
+            SEND_UPDATES.join(", ")
+        )),
+        send => Ok(send),
     }
 }
 
+/// `calendars` param, defaulting to the primary calendar
+fn calendar_ids(params: &Value) -> Vec<String> {
+    optional_string_array(params, "calendars")
+        .filter(|c| !c.is_empty())
+        .unwrap_or_else(|| vec!["primary".to_string()])
+}
+
 async fn handle_list_calendars(id: Value, calendar: CalendarApi) -> JsonRpcResponse {
     info!("Handling gcalendar.list_calendars");
 
@@ -106,9 +230,10 @@ async fn handle_list_events(
     let time_min = optional_string_or(params, "timeMin", "time_min");
     let time_max = optional_string_or(params, "timeMax", "time_max");
     let max_results = optional_u64_or(params, "maxResults", "max_results").map(|n| n as usize);
+    let single_events = bool_with_default(params, "singleEvents", true);
 
     match calendar
-        .list_events(calendar_id, time_min, time_max, max_results)
+        .list_events(calendar_id, time_min, time_max, max_results, single_events)
         .await
     {
         Ok(events) => {
@@ -160,27 +285,17 @@ async fn handle_create_event(
 
     let calendar_id = optional_string_or(params, "calendarId", "calendar_id").unwrap_or("primary");
     
-    let summary = match require_string(params, "summary", &id) {
-        Ok(s) => s,
-        Err(response) => return response,
-    };
-
-    let start = match require_string(params, "start", &id) {
-        Ok(s) => s,
-        Err(response) => return response,
-    };
-
-    let end = match require_string(params, "end", &id) {
-        Ok(e) => e,
-        Err(response) => return response,
+    for field in ["summary", "start", "end"] {
+        if let Err(response) = require_string(params, field, &id) {
+            return response;
+        }
+    }
+    let (fields, send) = match event_fields(params).and_then(|f| Ok((f, send_updates(params)?))) {
+        Ok(parsed) => parsed,
+        Err(e) => return invalid_params(id, e),
     };
 
-    let location = optional_string(params, "location");
-    let description = optional_string(params, "description");
-
-    match calendar
-        .create_event(calendar_id, summary, start, end, location, description)
-        .await
+    match calendar.create_event(calendar_id, &fields, send).await
     {
         Ok(event) => {
             info!("Event created successfully");
@@ -207,22 +322,13 @@ async fn handle_update_event(
         Err(response) => return response,
     };
 
-    let summary = optional_string(params, "summary");
-    let start = optional_string(params, "start");
-    let end = optional_string(params, "end");
-    let location = optional_string(params, "location");
-    let description = optional_string(params, "description");
+    let (fields, send) = match event_fields(params).and_then(|f| Ok((f, send_updates(params)?))) {
+        Ok(parsed) => parsed,
+        Err(e) => return invalid_params(id, e),
+    };
 
     match calendar
-        .update_event(
-            calendar_id,
-            event_id,
-            summary,
-            start,
-            end,
-            location,
-            description,
-        )
+        .update_event(calendar_id, event_id, &fields, send)
         .await
     {
         Ok(event) => {
@@ -250,7 +356,12 @@ async fn handle_delete_event(
         Err(response) => return response,
     };
 
-    match calendar.delete_event(calendar_id, event_id).await {
+    let send = match send_updates(params) {
+        Ok(s) => s,
+        Err(e) => return invalid_params(id, e),
+    };
+
+    match calendar.delete_event(calendar_id, event_id, send).await {
         Ok(_) => {
             info!("Event deleted successfully");
             ok(id, serde_json::json!({ "deleted": true }))
@@ -261,3 +372,231 @@ async fn handle_delete_event(
         }
     }
 }
+
+async fn handle_list_instances(
+    params: &Value,
+    id: Value,
+    calendar: CalendarApi,
+) -> JsonRpcResponse {
+    info!("Handling gcalendar.list_instances");
+
+    let calendar_id = optional_string_or(params, "calendarId", "calendar_id").unwrap_or("primary");
+
+    let event_id = match require_string_or(params, "eventId", "event_id", &id) {
+        Ok(id) => id,
+        Err(response) => return response,
+    };
+
+    let time_min = optional_string_or(params, "timeMin", "time_min");
+    let time_max = optional_string_or(params, "timeMax", "time_max");
+    let max_results = optional_u64_or(params, "maxResults", "max_results").map(|n| n as usize);
+
+    match calendar
+        .list_instances(calendar_id, event_id, time_min, time_max, max_results)
+        .await
+    {
+        Ok(instances) => ok(
+            id,
+            serde_json::json!({
+                "eventId": event_id,
+                "instances": instances,
+                "count": instances.len(),
+            }),
+        ),
+        Err(e) => {
+            error!("Failed to list event instances: {}", e);
+            generic_error(id, e)
+        }
+    }
+}
+
+async fn handle_respond(
+    params: &Value,
+    id: Value,
+    calendar: CalendarApi,
+) -> JsonRpcResponse {
+    info!("Handling gcalendar.respond");
+
+    let calendar_id = optional_string_or(params, "calendarId", "calendar_id").unwrap_or("primary");
+
+    let event_id = match require_string_or(params, "eventId", "event_id", &id) {
+        Ok(id) => id,
+        Err(response) => return response,
+    };
+
+    let response = match require_string(params, "response", &id) {
+        Ok(r) => r,
+        Err(response) => return response,
+    };
+    if !RESPONSES.contains(&response) {
+        return invalid_params(
+            id,
+            format!("Invalid response '{}': expected one of {}", response, RESPONSES.join(", ")),
+        );
+    }
+
+    let send = match send_updates(params) {
+        Ok(s) => s,
+        Err(e) => return invalid_params(id, e),
+    };
+
+    match calendar
+        .respond(calendar_id, event_id, response, optional_string(params, "comment"), send)
+        .await
+    {
+        Ok(event) => ok(id, event),
+        Err(e) => {
+            error!("Failed to respond to event: {}", e);
+            generic_error(id, e)
+        }
+    }
+}
+
+async fn handle_free_busy(
+    params: &Value,
+    id: Value,
+    calendar: CalendarApi,
+) -> JsonRpcResponse {
+    info!("Handling gcalendar.free_busy");
+
+    let time_min = match require_string_or(params, "timeMin", "time_min", &id) {
+        Ok(t) => t,
+        Err(response) => return response,
+    };
+    let time_max = match require_string_or(params, "timeMax", "time_max", &id) {
+        Ok(t) => t,
+        Err(response) => return response,
+    };
+    let calendars = calendar_ids(params);
+    let time_zone = optional_string(params, "timeZone");
+
+    match calendar.free_busy(&calendars, time_min, time_max, time_zone).await {
+        Ok(result) => ok(
+            id,
+            serde_json::json!({
+                "timeMin": time_min,
+                "timeMax": time_max,
+                "calendars": result,
+            }),
+        ),
+        Err(e) => {
+            error!("Failed to query free/busy: {}", e);
+            generic_error(id, e)
+        }
+    }
+}
+
+async fn handle_find_slot(
+    params: &Value,
+    id: Value,
+    calendar: CalendarApi,
+) -> JsonRpcResponse {
+    info!("Handling gcalendar.find_slot");
+
+    let search = match slot_search(params) {
+        Ok(s) => s,
+        Err(e) => return invalid_params(id, e),
+    };
+    let calendars = calendar_ids(params);
+
+    let busy = match calendar
+        .free_busy(
+            &calendars,
+            &search.start.to_rfc3339(),
+            &search.end.to_rfc3339(),
+            optional_string(params, "timeZone"),
+        )
+        .await
+    {
+        Ok(result) => result,
+        Err(e) => {
+            error!("Failed to query free/busy: {}", e);
+            return generic_error(id, e);
+        }
+    };
+
+    // Calendars Google could not read (no access, unknown address) count as free
+    let errors: serde_json::Map<String, Value> = busy
+        .as_object()
+        .map(|map| {
+            map.iter()
+                .filter_map(|(cal, info)| info.get("errors").map(|e| (cal.clone(), e.clone())))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let slots: Vec<Value> = calendar_api::find_slots(&search, &calendar_api::busy_blocks(&busy))
+        .into_iter()
+        .map(|(start, end)| serde_json::json!({ "start": start.to_rfc3339(), "end": end.to_rfc3339() }))
+        .collect();
+    debug!("Found {} free slots", slots.len());
+
+    ok(
+        id,
+        serde_json::json!({
+            "slots": slots,
+            "count": slots.len(),
+            "duration": search.duration.num_minutes(),
+            "calendars": calendars,
+            "errors": errors,
+        }),
+    )
+}
+
+/// Parse and validate `findSlot` params
+fn slot_search(params: &Value) -> Result<SlotSearch, String> {
+    let time = |name: &str, alias: &str| {
+        let value = optional_string_or(params, name, alias)
+            .ok_or_else(|| format!("Missing required parameter: {} (or {})", name, alias))?;
+        DateTime::parse_from_rfc3339(value).map_err(|_| format!("'{}' must be an RFC3339 time", name))
+    };
+    let clock = |name: &str, default: &str| {
+        let value = optional_string(params, name).unwrap_or(default);
+        NaiveTime::parse_from_str(value, "%H:%M").map_err(|_| format!("'{}' must be HH:MM", name))
+    };
+
+    let start = time("timeMin", "time_min")?;
+    let end = time("timeMax", "time_max")?;
+    if end <= start {
+        return Err("'timeMax' must be after 'timeMin'".to_string());
+    }
+    if end - start > Duration::days(MAX_SLOT_SEARCH_DAYS) {
+        return Err(format!("The search window is limited to {} days", MAX_SLOT_SEARCH_DAYS));
+    }
+
+    let work_start = clock("workdayStart", "09:00")?;
+    let work_end = clock("workdayEnd", "17:00")?;
+    if work_end <= work_start {
+        return Err("'workdayEnd' must be after 'workdayStart'".to_string());
+    }
+
+    let work_days = match optional_string_array(params, "workingDays") {
+        Some(days) => days
+            .iter()
+            .map(|d| d.parse::<Weekday>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| "'workingDays' must be day names like 'mon'".to_string())?,
+        None => vec![Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri],
+    };
+
+    let duration = u64_with_default(params, "duration", 30).clamp(5, 24 * 60);
+    let step = u64_with_default(params, "step", 30).clamp(5, 24 * 60);
+
+    // Without a zone, working hours follow timeMin's fixed offset
+    let zone = match optional_string(params, "timeZone") {
+        Some(name) => Zone::load(name)?,
+        None => Zone::fixed(*start.offset()),
+    };
+
+    Ok(SlotSearch {
+        start,
+        end,
+        zone,
+        duration: Duration::minutes(duration as i64),
+        work_start,
+        work_end,
+        work_days,
+        step: Duration::minutes(step as i64),
+        max_slots: u64_with_default(params, "maxResults", 10).clamp(1, 100) as usize,
+    })
+}
@@ -3,13 +3,17 @@
 //! Socket handlers for Google Contacts (People) API methods.
 //! Retrieves OAuth tokens from auth broker and uses Google API client.
 //!
-//! Namespace: `gcontacts.*` (distinct from macOS native `contacts.*`)
+//! Namespace: `gcontacts.*` (distinct from macOS native `contacts.*`).
+//! Responses mirror the native handlers; contacts carry their People API
+//! `resourceName` as `id` plus an `etag` for conflict-checked updates.
 
 use serde_json::Value;
-use tracing::{error, info};
+use tracing::{debug, error, info};
 
 use super::common::*;
 use super::super::protocol::JsonRpcResponse;
+use crate::google::contacts_api::ContactFields;
+use crate::google::ContactsApi;
 
 /// Handle Google Contacts-related methods
 pub async fn handle(
@@ -24,7 +28,7 @@ pub async fn handle(
             return resp;
         }
     };
-    
+
     // Retrieve OAuth token from auth broker
     let (provider, account) = match extract_oauth_credentials(params, "google") {
         Ok(creds) => creds,
@@ -49,77 +53,287 @@ pub async fn handle(
         }
     };
 
-    let _access_token = match extract_access_token(&token_data, &id) {
+    let access_token = match extract_access_token(&token_data, &id) {
         Ok(token) => token,
         Err(response) => return response,
     };
 
+    // Create People API client
+    let contacts = match ContactsApi::for_account(access_token, &provider, &account) {
+        Ok(api) => api,
+        Err(e) => {
+            error!("Failed to create Contacts API client: {}", e);
+            return generic_error(id, e);
+        }
+    };
+
+    let token = contacts.token_source();
+
     // Dispatch to specific handler
-    match action {
-        "list" => handle_list(id).await,
-        "search" => handle_search(params, id).await,
-        "get" => handle_get(params, id).await,
-        "create" => handle_create(params, id).await,
-        "update" => handle_update(params, id).await,
-        "delete" => handle_delete(params, id).await,
+    let response = match action {
+        "list" => handle_list(params, id, contacts).await,
+        "search" => handle_search(params, id, contacts).await,
+        "get" => handle_get(params, id, contacts).await,
+        "create" => handle_create(params, id, contacts).await,
+        "update" => handle_update(params, id, contacts).await,
+        "delete" => handle_delete(params, id, contacts).await,
+        "groups" | "listGroups" => handle_list_groups(id, contacts).await,
+        "groupMembers" => handle_group_members(params, id, contacts).await,
+        "createGroup" => handle_create_group(params, id, contacts).await,
+        "modifyGroup" => handle_modify_group(params, id, contacts).await,
         _ => method_not_found(id, &format!("gcontacts.{}", action)),
+    };
+
+    with_refresh_failure(response, &token)
+}
+
+/// Name, organization, email and phone fields shared by create and update
+fn contact_fields(params: &Value) -> ContactFields<'_> {
+    ContactFields {
+        first_name: optional_string(params, "firstName"),
+        last_name: optional_string(params, "lastName"),
+        organization: optional_string(params, "organization"),
+        emails: optional_string_array(params, "emails"),
+        phones: optional_string_array(params, "phones"),
     }
 }
 
-// ── Placeholder Handlers ────────────────────────────────────────────────────
-// TODO: Implement Google Contacts API client (src-tauri/src/google/contacts_api.rs)
-// and replace these stubs with actual API calls.
-
-async fn handle_list(id: Value) -> JsonRpcResponse {
-    info!("gcontacts.list called (not yet implemented)");
-    error(
-        id,
-        -32000,
-        "Google Contacts API not yet implemented. Use native 'contacts.*' for macOS Contacts.app",
-    )
+async fn handle_list(params: &Value, id: Value, contacts: ContactsApi) -> JsonRpcResponse {
+    info!("Handling gcontacts.list");
+
+    let limit = u64_with_default(params, "limit", 100) as usize;
+    let sort_order = optional_string(params, "sortOrder");
+
+    match contacts.list_contacts(Some(limit), sort_order).await {
+        Ok(list) => {
+            debug!("Retrieved {} contacts", list.len());
+            ok(
+                id,
+                serde_json::json!({
+                    "contacts": list,
+                    "count": list.len(),
+                    "limit": limit,
+                }),
+            )
+        }
+        Err(e) => {
+            error!("Failed to list contacts: {}", e);
+            generic_error(id, e)
+        }
+    }
 }
 
-async fn handle_search(_params: &Value, id: Value) -> JsonRpcResponse {
-    info!("gcontacts.search called (not yet implemented)");
-    error(
-        id,
-        -32000,
-        "Google Contacts API not yet implemented. Use native 'contacts.*' for macOS Contacts.app",
-    )
+async fn handle_search(params: &Value, id: Value, contacts: ContactsApi) -> JsonRpcResponse {
+    info!("Handling gcontacts.search");
+
+    let query = match require_string(params, "query", &id) {
+        Ok(q) => q,
+        Err(response) => return response,
+    };
+    let limit = u64_with_default(params, "limit", 30) as usize;
+
+    match contacts.search_contacts(query, limit).await {
+        Ok(list) => ok(
+            id,
+            serde_json::json!({
+                "query": query,
+                "contacts": list,
+                "count": list.len(),
+            }),
+        ),
+        Err(e) => {
+            error!("Failed to search contacts: {}", e);
+            generic_error(id, e)
+        }
+    }
 }
 
-async fn handle_get(_params: &Value, id: Value) -> JsonRpcResponse {
-    info!("gcontacts.get called (not yet implemented)");
-    error(
-        id,
-        -32000,
-        "Google Contacts API not yet implemented. Use native 'contacts.*' for macOS Contacts.app",
-    )
+async fn handle_get(params: &Value, id: Value, contacts: ContactsApi) -> JsonRpcResponse {
+    info!("Handling gcontacts.get");
+
+    let contact_id = match require_string(params, "id", &id) {
+        Ok(id) => id,
+        Err(response) => return response,
+    };
+
+    match contacts.get_contact(contact_id).await {
+        Ok(contact) => ok(id, serde_json::to_value(contact).unwrap_or_default()),
+        Err(e) => {
+            error!("Failed to get contact: {}", e);
+            generic_error(id, e)
+        }
+    }
 }
 
-async fn handle_create(_params: &Value, id: Value) -> JsonRpcResponse {
-    info!("gcontacts.create called (not yet implemented)");
-    error(
-        id,
-        -32000,
-        "Google Contacts API not yet implemented. Use native 'contacts.*' for macOS Contacts.app",
-    )
+async fn handle_create(params: &Value, id: Value, contacts: ContactsApi) -> JsonRpcResponse {
+    info!("Handling gcontacts.create");
+
+    let fields = contact_fields(params);
+    if fields.first_name.is_none() && fields.last_name.is_none() && fields.organization.is_none() {
+        return invalid_params(
+            id,
+            "At least one of 'firstName', 'lastName', or 'organization' is required",
+        );
+    }
+    let groups = optional_string_array(params, "groups").unwrap_or_default();
+
+    match contacts.create_contact(&fields, &groups).await {
+        Ok(contact) => ok(
+            id,
+            serde_json::json!({
+                "created": true,
+                "contact": contact,
+            }),
+        ),
+        Err(e) => {
+            error!("Failed to create contact: {}", e);
+            generic_error(id, e)
+        }
+    }
 }
 
-async fn handle_update(_params: &Value, id: Value) -> JsonRpcResponse {
-    info!("gcontacts.update called (not yet implemented)");
-    error(
-        id,
-        -32000,
-        "Google Contacts API not yet implemented. Use native 'contacts.*' for macOS Contacts.app",
-    )
+async fn handle_update(params: &Value, id: Value, contacts: ContactsApi) -> JsonRpcResponse {
+    info!("Handling gcontacts.update");
+
+    let contact_id = match require_string(params, "id", &id) {
+        Ok(id) => id,
+        Err(response) => return response,
+    };
+    let etag = optional_string(params, "etag");
+
+    match contacts
+        .update_contact(contact_id, &contact_fields(params), etag)
+        .await
+    {
+        Ok(contact) => ok(
+            id,
+            serde_json::json!({
+                "updated": true,
+                "contact": contact,
+            }),
+        ),
+        Err(e) => {
+            error!("Failed to update contact: {}", e);
+            generic_error(id, e)
+        }
+    }
+}
+
+async fn handle_delete(params: &Value, id: Value, contacts: ContactsApi) -> JsonRpcResponse {
+    info!("Handling gcontacts.delete");
+
+    let contact_id = match require_string(params, "id", &id) {
+        Ok(id) => id,
+        Err(response) => return response,
+    };
+
+    match contacts.delete_contact(contact_id).await {
+        Ok(()) => ok(
+            id,
+            serde_json::json!({
+                "deleted": true,
+                "id": contact_id,
+            }),
+        ),
+        Err(e) => {
+            error!("Failed to delete contact: {}", e);
+            generic_error(id, e)
+        }
+    }
 }
 
-async fn handle_delete(_params: &Value, id: Value) -> JsonRpcResponse {
-    info!("gcontacts.delete called (not yet implemented)");
-    error(
-        id,
-        -32000,
-        "Google Contacts API not yet implemented. Use native 'contacts.*' for macOS Contacts.app",
-    )
+async fn handle_list_groups(id: Value, contacts: ContactsApi) -> JsonRpcResponse {
+    info!("Handling gcontacts.groups");
+
+    match contacts.list_groups().await {
+        Ok(groups) => ok(
+            id,
+            serde_json::json!({
+                "groups": groups,
+                "count": groups.len(),
+            }),
+        ),
+        Err(e) => {
+            error!("Failed to list contact groups: {}", e);
+            generic_error(id, e)
+        }
+    }
+}
+
+async fn handle_group_members(params: &Value, id: Value, contacts: ContactsApi) -> JsonRpcResponse {
+    info!("Handling gcontacts.groupMembers");
+
+    let group = match require_string(params, "group", &id) {
+        Ok(g) => g,
+        Err(response) => return response,
+    };
+    let limit = u64_with_default(params, "limit", 100) as usize;
+
+    match contacts.group_members(group, limit).await {
+        Ok(list) => ok(
+            id,
+            serde_json::json!({
+                "group": group,
+                "contacts": list,
+                "count": list.len(),
+            }),
+        ),
+        Err(e) => {
+            error!("Failed to list group members: {}", e);
+            generic_error(id, e)
+        }
+    }
+}
+
+async fn handle_create_group(params: &Value, id: Value, contacts: ContactsApi) -> JsonRpcResponse {
+    info!("Handling gcontacts.createGroup");
+
+    let name = match require_string(params, "name", &id) {
+        Ok(n) => n,
+        Err(response) => return response,
+    };
+
+    match contacts.create_group(name).await {
+        Ok(group) => ok(
+            id,
+            serde_json::json!({
+                "created": true,
+                "group": group,
+            }),
+        ),
+        Err(e) => {
+            error!("Failed to create contact group: {}", e);
+            generic_error(id, e)
+        }
+    }
+}
+
+async fn handle_modify_group(params: &Value, id: Value, contacts: ContactsApi) -> JsonRpcResponse {
+    info!("Handling gcontacts.modifyGroup");
+
+    let group = match require_string(params, "group", &id) {
+        Ok(g) => g,
+        Err(response) => return response,
+    };
+    let add = optional_string_array(params, "add").unwrap_or_default();
+    let remove = optional_string_array(params, "remove").unwrap_or_default();
+    if add.is_empty() && remove.is_empty() {
+        return invalid_params(id, "At least one of 'add' or 'remove' is required");
+    }
+
+    match contacts.modify_group_members(group, &add, &remove).await {
+        Ok(result) => ok(
+            id,
+            serde_json::json!({
+                "group": group,
+                "added": add.len(),
+                "removed": remove.len(),
+                "notFound": result.get("notFoundResourceNames").cloned().unwrap_or_else(|| serde_json::json!([])),
+            }),
+        ),
+        Err(e) => {
+            error!("Failed to modify contact group: {}", e);
+            generic_error(id, e)
+        }
+    }
 }
@@ -0,0 +1,522 @@
+//! Google Drive Handler
+//!
+//! Socket handlers for Google Drive API methods.
+//! Retrieves OAuth tokens from auth broker and uses Google API client.
+//!
+//! Local paths given to `download`/`upload` go through the same checks as
+//! `files.write`/`files.read`.
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+use tracing::{debug, error, info};
+
+use super::common::*;
+use super::files::{validate_read_path, validate_write_path};
+use super::super::protocol::JsonRpcResponse;
+use crate::google::drive_api::{self, SIMPLE_UPLOAD_LIMIT};
+use crate::google::mime;
+use crate::google::DriveApi;
+
+/// Default cap on downloaded/exported content held in memory (10 MB)
+const MAX_DOWNLOAD_SIZE: u64 = 10 * 1024 * 1024;
+
+/// Handle Google Drive-related methods
+pub async fn handle(
+    action: &str,
+    params: &Value,
+    id: Value,
+) -> JsonRpcResponse {
+    let auth_broker = match get_auth_broker().await {
+        Ok(broker) => broker,
+        Err(mut resp) => {
+            resp.id = id;
+            return resp;
+        }
+    };
+
+    // Retrieve OAuth token from auth broker
+    let (provider, account) = match extract_oauth_credentials(params, "google") {
+        Ok(creds) => creds,
+        Err(mut resp) => {
+            resp.id = id;
+            return resp;
+        }
+    };
+
+    let token_data = match auth_broker
+        .get_token(
+            &provider,
+            &account,
+            Some(&["https://www.googleapis.com/auth/drive".to_string()]),
+        )
+        .await
+    {
+        Ok(data) => data,
+        Err((code, msg)) => {
+            error!("Failed to get OAuth token for Google Drive: {}", msg);
+            return error(id, code, msg);
+        }
+    };
+
+    let access_token = match extract_access_token(&token_data, &id) {
+        Ok(token) => token,
+        Err(response) => return response,
+    };
+
+    // Create Drive API client
+    let drive = match DriveApi::for_account(access_token, &provider, &account) {
+        Ok(api) => api,
+        Err(e) => {
+            error!("Failed to create Drive API client: {}", e);
+            return generic_error(id, e);
+        }
+    };
+
+    let token = drive.token_source();
+
+    // Dispatch to specific handler
+    let response = match action {
+        "search" | "list" => handle_search(params, id, drive).await,
+        "get" => handle_get(params, id, drive).await,
+        "download" | "export" => handle_download(params, id, drive).await,
+        "upload" => handle_upload(params, id, drive).await,
+        "createFolder" => handle_create_folder(params, id, drive).await,
+        "permissions" => handle_permissions(params, id, drive).await,
+        "share" => handle_share(params, id, drive).await,
+        "unshare" => handle_unshare(params, id, drive).await,
+        "changes" => handle_changes(params, id, drive).await,
+        _ => method_not_found(id, &format!("gdrive.{}", action)),
+    };
+
+    with_refresh_failure(response, &token)
+}
+
+async fn handle_search(params: &Value, id: Value, drive: DriveApi) -> JsonRpcResponse {
+    info!("Handling gdrive.search");
+
+    let query = optional_string(params, "query");
+    let order_by = optional_string(params, "orderBy");
+    let limit = u64_with_default(params, "limit", 100) as usize;
+    let include_trashed = bool_with_default(params, "includeTrashed", false);
+    let all_drives = bool_with_default(params, "allDrives", false);
+
+    match drive
+        .search(query, order_by, limit, include_trashed, all_drives)
+        .await
+    {
+        Ok(files) => {
+            debug!("Found {} files", files.len());
+            ok(
+                id,
+                serde_json::json!({
+                    "files": files,
+                    "count": files.len(),
+                }),
+            )
+        }
+        Err(e) => {
+            error!("Failed to search Drive: {}", e);
+            generic_error(id, e)
+        }
+    }
+}
+
+async fn handle_get(params: &Value, id: Value, drive: DriveApi) -> JsonRpcResponse {
+    info!("Handling gdrive.get");
+
+    let file_id = match require_string(params, "fileId", &id) {
+        Ok(f) => f,
+        Err(response) => return response,
+    };
+
+    match drive.get_file(file_id).await {
+        Ok(file) => ok(id, file),
+        Err(e) => {
+            error!("Failed to get Drive file: {}", e);
+            generic_error(id, e)
+        }
+    }
+}
+
+/// Download a file, exporting Google Docs/Sheets/Slides
+///
+/// Params:
+///   - fileId (required)
+///   - format (optional): export format for Google files (markdown, pdf,
+///     text, html, csv, docx, ... or a MIME type); default depends on the type
+///   - maxSize (optional): maximum bytes (default 10MB)
+///   - encoding (optional): "utf8" or "base64" (default: utf8 for text types)
+///   - path (optional): absolute path to save to instead of returning content
+async fn handle_download(params: &Value, id: Value, drive: DriveApi) -> JsonRpcResponse {
+    info!("Handling gdrive.download");
+
+    let file_id = match require_string(params, "fileId", &id) {
+        Ok(f) => f,
+        Err(response) => return response,
+    };
+    let max_size = u64_with_default(params, "maxSize", MAX_DOWNLOAD_SIZE);
+
+    let save_to = optional_string(params, "path").map(Path::new);
+    if let Some(path) = save_to {
+        if !path.is_absolute() {
+            return invalid_params(id, "Path must be absolute");
+        }
+        if let Err(e) = validate_write_path(path) {
+            return error(id, -32004, format!("Path not allowed for writing: {}", e));
+        }
+    }
+
+    let file = match drive.get_file(file_id).await {
+        Ok(f) => f,
+        Err(e) => return generic_error(id, e),
+    };
+    let name = file.get("name").and_then(Value::as_str).unwrap_or_default();
+    let mime_type = file.get("mimeType").and_then(Value::as_str).unwrap_or_default();
+
+    if mime_type == drive_api::FOLDER_MIME {
+        return invalid_params(id, "Folders can't be downloaded; use gdrive.search with \"'<id>' in parents\"");
+    }
+
+    let result = if drive_api::needs_export(mime_type) {
+        let format = optional_string(params, "format")
+            .unwrap_or_else(|| drive_api::default_export_format(mime_type));
+        let export_mime = match drive_api::export_mime(format) {
+            Ok(m) => m,
+            Err(e) => return invalid_params(id, e),
+        };
+        drive.export(file_id, export_mime, max_size).await
+    } else {
+        // Check the reported size first so oversized files fail fast
+        let size = file
+            .get("size")
+            .and_then(Value::as_str)
+            .and_then(|s| s.parse::<u64>().ok());
+        if let Some(size) = size.filter(|s| *s > max_size) {
+            return error(
+                id,
+                -32003,
+                format!(
+                    "File too large: {} bytes (max: {} bytes). Use 'maxSize' to increase limit.",
+                    size, max_size
+                ),
+            );
+        }
+        drive.download(file_id, max_size).await
+    };
+
+    let download = match result {
+        Ok(d) => d,
+        Err(e) => {
+            error!("Failed to download Drive file: {}", e);
+            return generic_error(id, e);
+        }
+    };
+    let content_type = download
+        .content_type
+        .clone()
+        .unwrap_or_else(|| "application/octet-stream".to_string());
+    let size = download.bytes.len();
+
+    if let Some(path) = save_to {
+        return match fs::write(path, &download.bytes) {
+            Ok(()) => ok(
+                id,
+                serde_json::json!({
+                    "fileId": file_id,
+                    "name": name,
+                    "contentType": content_type,
+                    "path": path,
+                    "written": true,
+                    "size": size,
+                }),
+            ),
+            Err(e) => generic_error(id, format!("Failed to write file: {}", e)),
+        };
+    }
+
+    let default_encoding = if drive_api::is_text_mime(&content_type) { "utf8" } else { "base64" };
+    let encoding = string_with_default(params, "encoding", default_encoding);
+    let content = match encoding {
+        "base64" => BASE64.encode(&download.bytes),
+        _ => match String::from_utf8(download.bytes) {
+            Ok(text) => text,
+            Err(e) => {
+                return generic_error(id, format!("Failed to read as UTF-8: {}. Try encoding='base64'.", e))
+            }
+        },
+    };
+
+    ok(
+        id,
+        serde_json::json!({
+            "fileId": file_id,
+            "name": name,
+            "mimeType": mime_type,
+            "contentType": content_type,
+            "encoding": if encoding == "base64" { "base64" } else { "utf8" },
+            "content": content,
+            "size": size,
+        }),
+    )
+}
+
+/// Upload a file
+///
+/// Params:
+///   - path (optional): absolute path of a local file to upload
+///   - content (optional): content to upload when no path is given
+///   - encoding (optional): "utf8" (default) or "base64" for `content`
+///   - name (optional with path): file name in Drive
+///   - parentId (optional): folder to upload into
+///   - mimeType (optional): content type (guessed from the name otherwise)
+///   - resumable (optional): force a resumable upload (automatic above 5MB)
+async fn handle_upload(params: &Value, id: Value, drive: DriveApi) -> JsonRpcResponse {
+    info!("Handling gdrive.upload");
+
+    let parent = optional_string(params, "parentId");
+    let resumable = bool_with_default(params, "resumable", false);
+
+    let result = if let Some(file_path) = optional_string(params, "path") {
+        let path = Path::new(file_path);
+        if !path.is_absolute() {
+            return invalid_params(id, "Path must be absolute");
+        }
+        if !path.is_file() {
+            return error(id, -32002, format!("File not found: {}", file_path));
+        }
+        if let Err(e) = validate_read_path(path) {
+            return error(id, -32004, e);
+        }
+
+        let name = optional_string(params, "name")
+            .or_else(|| path.file_name().and_then(|n| n.to_str()))
+            .unwrap_or("untitled");
+        let content_type = optional_string(params, "mimeType").unwrap_or_else(|| mime::guess_content_type(name));
+        let length = match fs::metadata(path) {
+            Ok(m) => m.len(),
+            Err(e) => return generic_error(id, format!("Failed to read file metadata: {}", e)),
+        };
+
+        if resumable || length > SIMPLE_UPLOAD_LIMIT {
+            match tokio::fs::File::open(path).await {
+                Ok(file) => drive.upload_resumable(name, parent, content_type, length, file).await,
+                Err(e) => return generic_error(id, format!("Failed to open file: {}", e)),
+            }
+        } else {
+            match fs::read(path) {
+                Ok(bytes) => drive.upload_simple(name, parent, content_type, &bytes).await,
+                Err(e) => return generic_error(id, format!("Failed to read file: {}", e)),
+            }
+        }
+    } else {
+        let content = match require_string(params, "content", &id) {
+            Ok(c) => c,
+            Err(response) => return response,
+        };
+        let name = match require_string(params, "name", &id) {
+            Ok(n) => n,
+            Err(response) => return response,
+        };
+        let content_type = optional_string(params, "mimeType").unwrap_or_else(|| mime::guess_content_type(name));
+        let bytes = match string_with_default(params, "encoding", "utf8") {
+            "base64" => match BASE64.decode(content) {
+                Ok(b) => b,
+                Err(e) => return generic_error(id, format!("Invalid base64 content: {}", e)),
+            },
+            _ => content.as_bytes().to_vec(),
+        };
+
+        let length = bytes.len() as u64;
+        if resumable || length > SIMPLE_UPLOAD_LIMIT {
+            drive
+                .upload_resumable(name, parent, content_type, length, std::io::Cursor::new(bytes))
+                .await
+        } else {
+            drive.upload_simple(name, parent, content_type, &bytes).await
+        }
+    };
+
+    match result {
+        Ok(file) => ok(
+            id,
+            serde_json::json!({
+                "uploaded": true,
+                "file": file,
+            }),
+        ),
+        Err(e) => {
+            error!("Failed to upload to Drive: {}", e);
+            generic_error(id, e)
+        }
+    }
+}
+
+async fn handle_create_folder(params: &Value, id: Value, drive: DriveApi) -> JsonRpcResponse {
+    info!("Handling gdrive.createFolder");
+
+    let name = match require_string(params, "name", &id) {
+        Ok(n) => n,
+        Err(response) => return response,
+    };
+    let parent = optional_string(params, "parentId");
+
+    match drive.create_folder(name, parent).await {
+        Ok(folder) => ok(
+            id,
+            serde_json::json!({
+                "created": true,
+                "folder": folder,
+            }),
+        ),
+        Err(e) => {
+            error!("Failed to create Drive folder: {}", e);
+            generic_error(id, e)
+        }
+    }
+}
+
+async fn handle_permissions(params: &Value, id: Value, drive: DriveApi) -> JsonRpcResponse {
+    info!("Handling gdrive.permissions");
+
+    let file_id = match require_string(params, "fileId", &id) {
+        Ok(f) => f,
+        Err(response) => return response,
+    };
+
+    match drive.list_permissions(file_id).await {
+        Ok(permissions) => ok(
+            id,
+            serde_json::json!({
+                "fileId": file_id,
+                "permissions": permissions,
+                "count": permissions.len(),
+            }),
+        ),
+        Err(e) => {
+            error!("Failed to list Drive permissions: {}", e);
+            generic_error(id, e)
+        }
+    }
+}
+
+/// Share a file
+///
+/// Params:
+///   - fileId (required)
+///   - role (required): reader, commenter, writer, fileOrganizer, organizer
+///   - type (optional): user (default), group, domain, anyone
+///   - emailAddress (required for user/group), domain (required for domain)
+///   - notify (optional): send a notification email (default true)
+///   - message (optional): text for the notification email
+async fn handle_share(params: &Value, id: Value, drive: DriveApi) -> JsonRpcResponse {
+    info!("Handling gdrive.share");
+
+    let file_id = match require_string(params, "fileId", &id) {
+        Ok(f) => f,
+        Err(response) => return response,
+    };
+    let role = match require_string(params, "role", &id) {
+        Ok(r) => r,
+        Err(response) => return response,
+    };
+    let grantee_type = string_with_default(params, "type", "user");
+
+    let mut permission = serde_json::json!({ "type": grantee_type, "role": role });
+    match grantee_type {
+        "user" | "group" => match require_string(params, "emailAddress", &id) {
+            Ok(email) => permission["emailAddress"] = serde_json::json!(email),
+            Err(response) => return response,
+        },
+        "domain" => match require_string(params, "domain", &id) {
+            Ok(domain) => permission["domain"] = serde_json::json!(domain),
+            Err(response) => return response,
+        },
+        "anyone" => {}
+        other => {
+            return invalid_params(
+                id,
+                format!("Unknown type '{}' (expected user, group, domain or anyone)", other),
+            )
+        }
+    }
+    // Drive only emails users and groups
+    let notify = bool_with_default(params, "notify", true) && matches!(grantee_type, "user" | "group");
+    let message = optional_string(params, "message");
+
+    match drive.share(file_id, &permission, notify, message).await {
+        Ok(created) => ok(
+            id,
+            serde_json::json!({
+                "shared": true,
+                "fileId": file_id,
+                "permission": created,
+            }),
+        ),
+        Err(e) => {
+            error!("Failed to share Drive file: {}", e);
+            generic_error(id, e)
+        }
+    }
+}
+
+async fn handle_unshare(params: &Value, id: Value, drive: DriveApi) -> JsonRpcResponse {
+    info!("Handling gdrive.unshare");
+
+    let file_id = match require_string(params, "fileId", &id) {
+        Ok(f) => f,
+        Err(response) => return response,
+    };
+    let permission_id = match require_string(params, "permissionId", &id) {
+        Ok(p) => p,
+        Err(response) => return response,
+    };
+
+    match drive.unshare(file_id, permission_id).await {
+        Ok(()) => ok(
+            id,
+            serde_json::json!({
+                "removed": true,
+                "fileId": file_id,
+                "permissionId": permission_id,
+            }),
+        ),
+        Err(e) => {
+            error!("Failed to remove Drive permission: {}", e);
+            generic_error(id, e)
+        }
+    }
+}
+
+/// Poll the change feed
+///
+/// Without `pageToken`, returns a token for "now" and no changes; pass the
+/// returned `pageToken` on each following call to get what changed since.
+async fn handle_changes(params: &Value, id: Value, drive: DriveApi) -> JsonRpcResponse {
+    info!("Handling gdrive.changes");
+
+    let limit = u64_with_default(params, "limit", 100) as usize;
+
+    let result = match optional_string(params, "pageToken") {
+        Some(token) => drive.list_changes(token, limit).await,
+        None => drive.start_page_token().await.map(|token| drive_api::ChangesPage {
+            changes: Vec::new(),
+            page_token: token,
+            complete: true,
+        }),
+    };
+
+    match result {
+        Ok(page) => {
+            let count = page.changes.len();
+            let mut body = serde_json::to_value(page).unwrap_or_default();
+            body["count"] = serde_json::json!(count);
+            ok(id, body)
+        }
+        Err(e) => {
+            error!("Failed to poll Drive changes: {}", e);
+            generic_error(id, e)
+        }
+    }
+}
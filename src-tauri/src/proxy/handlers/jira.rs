@@ -3,9 +3,12 @@
 //! Socket handlers for Jira Cloud REST API v3 methods.
 //! Retrieves API token (basic auth: email + token) from auth broker.
 
+use reqwest::Method;
 use serde_json::Value;
 use tracing::{debug, error, info};
 
+use crate::common::http::HttpClient;
+
 use super::common::*;
 use super::super::protocol::JsonRpcResponse;
 
@@ -14,21 +17,16 @@ struct JiraApi {
     email: String,
     token: String,
     base_url: String,
-    client: reqwest::Client,
+    http: HttpClient,
 }
 
 impl JiraApi {
     fn new(email: String, token: String, base_url: String) -> Result<Self, String> {
-        let client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_secs(30))
-            .build()
-            .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
-
         Ok(Self {
             email,
             token,
             base_url,
-            client,
+            http: HttpClient::for_namespace("jira"),
         })
     }
 
@@ -40,15 +38,13 @@ impl JiraApi {
     async fn get(&self, path: &str, query_params: Vec<(&str, &str)>) -> Result<Value, String> {
         let url = format!("{}/rest/api/3{}", self.base_url, path);
         
-        let response = self
-            .client
-            .get(&url)
+        let request = self
+            .http
+            .request(Method::GET, &url)
             .header("Authorization", self.basic_auth())
             .header("Accept", "application/json")
-            .query(&query_params)
-            .send()
-            .await
-            .map_err(|e| format!("HTTP request failed: {}", e))?;
+            .query(&query_params);
+        let response = self.http.send(request, false).await.map_err(|e| e.to_string())?;
 
         if !response.status().is_success() {
             let status = response.status();
@@ -69,16 +65,14 @@ impl JiraApi {
     async fn post(&self, path: &str, body: Value) -> Result<Value, String> {
         let url = format!("{}/rest/api/3{}", self.base_url, path);
         
-        let response = self
-            .client
-            .post(&url)
+        let request = self
+            .http
+            .request(Method::POST, &url)
             .header("Authorization", self.basic_auth())
             .header("Accept", "application/json")
             .header("Content-Type", "application/json")
-            .json(&body)
-            .send()
-            .await
-            .map_err(|e| format!("HTTP request failed: {}", e))?;
+            .json(&body);
+        let response = self.http.send(request, false).await.map_err(|e| e.to_string())?;
 
         if !response.status().is_success() {
             let status = response.status();
@@ -99,16 +93,14 @@ impl JiraApi {
     async fn put(&self, path: &str, body: Value) -> Result<Value, String> {
         let url = format!("{}/rest/api/3{}", self.base_url, path);
         
-        let response = self
-            .client
-            .put(&url)
+        let request = self
+            .http
+            .request(Method::PUT, &url)
             .header("Authorization", self.basic_auth())
             .header("Accept", "application/json")
             .header("Content-Type", "application/json")
-            .json(&body)
-            .send()
-            .await
-            .map_err(|e| format!("HTTP request failed: {}", e))?;
+            .json(&body);
+        let response = self.http.send(request, false).await.map_err(|e| e.to_string())?;
 
         if !response.status().is_success() {
             let status = response.status();
@@ -184,14 +176,12 @@ impl JiraApi {
         // Using /rest/agile/1.0/ instead
         let url = format!("{}/rest/agile/1.0/board/{}/sprint", self.base_url, board_id);
         
-        let response = self
-            .client
-            .get(&url)
+        let request = self
+            .http
+            .request(Method::GET, &url)
             .header("Authorization", self.basic_auth())
-            .header("Accept", "application/json")
-            .send()
-            .await
-            .map_err(|e| format!("HTTP request failed: {}", e))?;
+            .header("Accept", "application/json");
+        let response = self.http.send(request, false).await.map_err(|e| e.to_string())?;
 
         if !response.status().is_success() {
             let status = response.status();
@@ -3,10 +3,14 @@
 //! Handles location-related JSON-RPC methods using CoreLocation.
 //! Returns lat/lng/altitude/accuracy from CLLocationManager.
 
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
 use serde_json::Value;
 use tracing::info;
 
 use super::common::*;
+use super::super::notifications::{self, topics};
 use super::super::protocol::JsonRpcResponse;
 
 /// Handle location-related methods
@@ -28,12 +32,63 @@ async fn handle_get(params: &Value, id: Value) -> JsonRpcResponse {
     }
 }
 
-/// Watch for location updates (single-shot with subscription stub)
-async fn handle_watch(_params: &Value, id: Value) -> JsonRpcResponse {
-    error(
+/// Whether a background location watcher is currently running
+static WATCHER_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// Start streaming location updates as `location.update` notifications.
+///
+/// Some connection must be subscribed to `location.update` first (callers
+/// subscribe on their own connection to receive the updates). One shared
+/// watcher polls CoreLocation every `interval` seconds and stops
+/// on its own once no connection is subscribed any more.
+async fn handle_watch(params: &Value, id: Value) -> JsonRpcResponse {
+    let interval_secs = u64_with_default(params, "interval", 30).max(5);
+    let timeout_secs = u64_with_default(params, "timeout", 10);
+
+    if !notifications::hub().has_subscribers(topics::LOCATION_UPDATE) {
+        return invalid_params(
+            id,
+            format!(
+                "No connection is subscribed to '{}'. Call subscribe with topics=['{}'] first to receive updates.",
+                topics::LOCATION_UPDATE,
+                topics::LOCATION_UPDATE
+            ),
+        );
+    }
+
+    let started = WATCHER_RUNNING
+        .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+        .is_ok();
+
+    if started {
+        info!("Starting location watcher (interval={}s)", interval_secs);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(Duration::from_secs(interval_secs));
+            loop {
+                ticker.tick().await;
+                if !notifications::hub().has_subscribers(topics::LOCATION_UPDATE) {
+                    break;
+                }
+                match get_current_location(timeout_secs).await {
+                    Ok(loc) => notifications::publish(topics::LOCATION_UPDATE, loc),
+                    Err(e) => notifications::publish(
+                        topics::LOCATION_UPDATE,
+                        serde_json::json!({ "error": e }),
+                    ),
+                }
+            }
+            WATCHER_RUNNING.store(false, Ordering::SeqCst);
+            info!("Location watcher stopped (no subscribers)");
+        });
+    }
+
+    ok(
         id,
-        -32001,
-        "location.watch requires a streaming connection, not yet supported. Use location.get instead.",
+        serde_json::json!({
+            "topic": topics::LOCATION_UPDATE,
+            "interval": interval_secs,
+            "started": started,
+        }),
     )
 }
 
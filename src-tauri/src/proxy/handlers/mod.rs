@@ -13,6 +13,7 @@ pub mod files;
 pub mod gmail;
 pub mod google_calendar;
 pub mod google_contacts;
+pub mod google_drive;
 pub mod jira;
 pub mod location;
 pub mod onepassword;
@@ -23,7 +24,7 @@ pub mod reminders;
 pub mod screen;
 
 use serde_json::Value;
-use tracing::{debug, warn};
+use tracing::{debug, info, warn};
 
 use super::protocol::{JsonRpcRequest, JsonRpcResponse};
 
@@ -89,7 +90,9 @@ fn required_permission(method: &str) -> Option<&'static str> {
         // Auth methods don't require macOS permissions (socket security suffices)
         "auth.status" | "auth.providers" | "auth.accounts" | "auth.list" |
         "auth.token" | "auth.get" | "auth.refresh" | "auth.revoke" |
-        "auth.store" | "auth.import" | "auth.gogPassphrase" => None,
+        "auth.store" | "auth.store.verify" | "auth.import" | "auth.gogPassphrase" |
+        "auth.deviceStart" | "auth.devicePoll" | "auth.export" | "auth.importBundle" |
+        "auth.rekey" | "auth.masterKey.status" | "auth.masterKey.switch" => None,
         
         // Permission methods don't require special permissions
         "permissions.check" | "permissions.list" | "permissions.request" => None,
@@ -220,16 +223,45 @@ async fn check_automation_permission() -> PermissionStatus {
 pub struct HandlerRegistry {
     // Capability router for manifest-based routing (optional for backward compatibility)
     router: Option<std::sync::Arc<crate::router::CapabilityRouter>>,
+    // Owning server's shutdown signal and state (set by `ProxyServer`)
+    server: std::sync::OnceLock<ServerHandle>,
+}
+
+/// What `server.*` methods need from the server that owns the registry
+struct ServerHandle {
+    shutdown: tokio::sync::broadcast::Sender<()>,
+    state: std::sync::Arc<crate::proxy::ProxyState>,
 }
 
 impl HandlerRegistry {
     pub fn new() -> Self {
-        Self { router: None }
+        Self {
+            router: None,
+            server: std::sync::OnceLock::new(),
+        }
     }
 
     pub fn with_router(router: std::sync::Arc<crate::router::CapabilityRouter>) -> Self {
         Self {
             router: Some(router),
+            server: std::sync::OnceLock::new(),
+        }
+    }
+
+    /// Connect `server.*` methods to a server's shutdown signal and state.
+    /// Only the first server to attach is reported on and controlled.
+    pub fn attach_server(
+        &self,
+        shutdown: tokio::sync::broadcast::Sender<()>,
+        state: std::sync::Arc<crate::proxy::ProxyState>,
+    ) {
+        let _ = self.server.set(ServerHandle { shutdown, state });
+    }
+
+    /// Flush handler-owned state (credential store) during shutdown
+    pub async fn shutdown(&self) {
+        if let Some(router) = &self.router {
+            router.shutdown().await;
         }
     }
     
@@ -239,6 +271,12 @@ impl HandlerRegistry {
         
         debug!("Handling method: {}", request.method);
         
+        // Client policy (per-agent allowlist) comes before everything else
+        if let Err(violation) = crate::proxy::policy::check_method(&request.method) {
+            warn!("{}", violation);
+            return violation.into_response(id);
+        }
+        
         // Try manifest-based routing first (if router is available)
         if let Some(router) = &self.router {
             let response = router.route(request).await;
@@ -250,10 +288,16 @@ impl HandlerRegistry {
             // Otherwise, fall through to legacy routing
         }
         
+        crate::proxy::audit::annotate(|a| {
+            a.implementation.get_or_insert_with(|| "handler".to_string());
+        });
+        
         // Legacy routing: check permissions for known methods
         if let Some(required) = required_permission(&request.method) {
             let status = check_permission_status(required).await;
             
+            // Only known methods need a permission
+            crate::proxy::audit::annotate(|a| a.resolved = true);
             if status != PermissionStatus::Granted {
                 warn!(
                     "Permission denied for method {}: {} is {}",
@@ -265,10 +309,24 @@ impl HandlerRegistry {
             }
         }
         
+        // Not in a manifest, so only the client policy can require approval
+        if let Err(denied) =
+            crate::proxy::approval::check(&request.method, None, false, &request.params).await
+        {
+            return denied.into_response(id);
+        }
+        
+        // Legacy methods have no manifest, so only `proxy.json` limits apply;
+        // charged last, so refused calls don't use up the quota
+        if let Err(limited) = crate::proxy::ratelimit::check(&request.method, None, None) {
+            warn!("{}", limited);
+            return limited.into_response(id);
+        }
+        
         // Dispatch to handler
         let (namespace, action) = request.parse_method();
         
-        match namespace {
+        let response = match namespace {
             "auth" => auth::handle(action, &request.params, id).await,
             "permissions" => permissions::handle(action, &request.params, id).await,
             "contacts" => contacts::handle(action, &request.params, id).await,
@@ -284,33 +342,173 @@ impl HandlerRegistry {
             "gmail" => gmail::handle(action, &request.params, id).await,
             "gcalendar" => google_calendar::handle(action, &request.params, id).await,
             "gcontacts" => google_contacts::handle(action, &request.params, id).await,
+            "gdrive" => google_drive::handle(action, &request.params, id).await,
             "op" | "onepassword" => onepassword::handle(action, &request.params, id).await,
             "oura" => oura::handle(action, &request.params, id).await,
             "jira" => jira::handle(action, &request.params, id).await,
             "server" => self.handle_server(action, &request.params, id).await,
             _ => JsonRpcResponse::method_not_found(id, &request.method),
+        };
+        
+        if !is_method_not_found(&response) {
+            crate::proxy::audit::annotate(|a| a.resolved = true);
         }
+        response
     }
     
     /// Handle server control methods
-    async fn handle_server(&self, action: &str, _params: &Value, id: Value) -> JsonRpcResponse {
+    async fn handle_server(&self, action: &str, params: &Value, id: Value) -> JsonRpcResponse {
         match action {
-            "status" => JsonRpcResponse::success(
-                id,
-                serde_json::json!({
-                    "status": "running",
-                    "version": env!("CARGO_PKG_VERSION"),
-                }),
-            ),
-            "shutdown" => {
-                // TODO: Implement graceful shutdown
-                JsonRpcResponse::success(id, serde_json::json!({"message": "Shutdown initiated"}))
-            }
+            "status" => JsonRpcResponse::success(id, self.server_status().await),
+            "metrics" => match params.get("format").and_then(|v| v.as_str()).unwrap_or("json") {
+                "json" => JsonRpcResponse::success(id, self.server_metrics().await),
+                "prometheus" => JsonRpcResponse::success(
+                    id,
+                    serde_json::json!({
+                        "contentType": crate::proxy::metrics::PROMETHEUS_CONTENT_TYPE,
+                        "text": self.prometheus_metrics().await,
+                    }),
+                ),
+                other => JsonRpcResponse::invalid_params(
+                    id,
+                    format!("Unknown format '{}' (expected 'json' or 'prometheus')", other),
+                ),
+            },
+            "shutdown" => match self.server.get() {
+                Some(server) => {
+                    info!("Shutdown requested over socket");
+                    let _ = server.shutdown.send(());
+                    JsonRpcResponse::success(id, serde_json::json!({"message": "Shutdown initiated"}))
+                }
+                None => JsonRpcResponse::internal_error(id, "Server shutdown is not available"),
+            },
             _ => JsonRpcResponse::method_not_found(id, &format!("server.{}", action)),
         }
     }
 }
 
+impl HandlerRegistry {
+    /// Health summary: uptime, connections, request totals, manifests, auth
+    async fn server_status(&self) -> Value {
+        let mut status = serde_json::json!({
+            "status": "running",
+            "version": env!("CARGO_PKG_VERSION"),
+        });
+
+        if let Some(server) = self.server.get() {
+            let state = &server.state;
+            let metrics = state.metrics.snapshot();
+            status["startedAt"] = Value::from(state.started_at.to_rfc3339());
+            status["uptimeSecs"] = Value::from(state.uptime_secs());
+            status["connections"] = serde_json::json!({
+                "total": *state.connection_count.read().await,
+                "active": *state.active_connections.read().await,
+            });
+            status["requests"] = serde_json::json!({
+                "total": metrics.requests,
+                "errors": metrics.errors,
+            });
+        }
+
+        if let Some(router) = &self.router {
+            let router_status = router.status().await;
+            status["manifests"] = serde_json::json!({
+                "count": router_status.manifests,
+                "tools": router_status.tools,
+            });
+            status["auth"] = serde_json::to_value(&router_status.auth).unwrap_or_default();
+            status["auth"]["refreshDaemon"] =
+                serde_json::to_value(&router_status.refresh_daemon).unwrap_or_default();
+        }
+
+        status
+    }
+
+    /// Per-method counters, error codes and latency histograms
+    async fn server_metrics(&self) -> Value {
+        let Some(server) = self.server.get() else {
+            return serde_json::json!({});
+        };
+        let state = &server.state;
+        serde_json::json!({
+            "uptimeSecs": state.uptime_secs(),
+            "connections": {
+                "total": *state.connection_count.read().await,
+                "active": *state.active_connections.read().await,
+            },
+            "latencyBucketsMs": crate::proxy::metrics::LATENCY_BUCKETS_MS,
+            "metrics": state.metrics.snapshot(),
+        })
+    }
+
+    /// Everything `server.status` and `server.metrics` report, in the
+    /// Prometheus text format
+    pub async fn prometheus_metrics(&self) -> String {
+        use crate::proxy::metrics::{render_prometheus, Gauge, MetricsSnapshot};
+
+        let mut gauges = Vec::new();
+        let mut snapshot = MetricsSnapshot::default();
+        if let Some(server) = self.server.get() {
+            let state = &server.state;
+            snapshot = state.metrics.snapshot();
+            gauges.push(Gauge {
+                name: "tairseach_uptime_seconds",
+                help: "Seconds since the proxy server started",
+                kind: "gauge",
+                value: state.uptime_secs() as f64,
+            });
+            gauges.push(Gauge {
+                name: "tairseach_connections_total",
+                help: "Socket connections accepted",
+                kind: "counter",
+                value: *state.connection_count.read().await as f64,
+            });
+            gauges.push(Gauge {
+                name: "tairseach_connections_active",
+                help: "Socket connections currently open",
+                kind: "gauge",
+                value: *state.active_connections.read().await as f64,
+            });
+        }
+
+        if let Some(router) = &self.router {
+            let status = router.status().await;
+            gauges.push(Gauge {
+                name: "tairseach_manifests",
+                help: "Loaded manifests",
+                kind: "gauge",
+                value: status.manifests as f64,
+            });
+            gauges.push(Gauge {
+                name: "tairseach_tools",
+                help: "Tools across loaded manifests",
+                kind: "gauge",
+                value: status.tools as f64,
+            });
+            gauges.push(Gauge {
+                name: "tairseach_auth_accounts",
+                help: "Accounts in the auth broker",
+                kind: "gauge",
+                value: status.auth.account_count as f64,
+            });
+            gauges.push(Gauge {
+                name: "tairseach_refresh_daemon_up",
+                help: "Whether the token refresh daemon is running",
+                kind: "gauge",
+                value: if status.refresh_daemon.running { 1.0 } else { 0.0 },
+            });
+            gauges.push(Gauge {
+                name: "tairseach_token_refresh_failures_total",
+                help: "Failed background token refreshes",
+                kind: "counter",
+                value: status.refresh_daemon.failures as f64,
+            });
+        }
+
+        render_prometheus(&snapshot, &gauges)
+    }
+}
+
 impl Default for HandlerRegistry {
     fn default() -> Self {
         Self::new()
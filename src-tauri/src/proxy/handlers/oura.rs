@@ -3,36 +3,38 @@
 //! Socket handlers for Oura Ring API v2 methods.
 //! Retrieves personal access token from auth broker.
 
+use reqwest::Method;
 use serde_json::Value;
 use tracing::{debug, error, info};
 
+use crate::common::http::HttpClient;
+
 use super::common::*;
 use super::super::protocol::JsonRpcResponse;
 
 /// Oura Ring API client
 struct OuraApi {
     token: String,
-    client: reqwest::Client,
+    http: HttpClient,
 }
 
 impl OuraApi {
     fn new(token: String) -> Result<Self, String> {
-        use crate::common::create_http_client;
-        let client = create_http_client()?;
-        Ok(Self { token, client })
+        Ok(Self {
+            token,
+            http: HttpClient::for_namespace("oura"),
+        })
     }
 
     async fn get(&self, path: &str, query_params: Vec<(&str, &str)>) -> Result<Value, String> {
         let url = format!("https://api.ouraring.com/v2/usercollection{}", path);
         
-        let response = self
-            .client
-            .get(&url)
+        let request = self
+            .http
+            .request(Method::GET, &url)
             .header("Authorization", format!("Bearer {}", self.token))
-            .query(&query_params)
-            .send()
-            .await
-            .map_err(|e| format!("HTTP request failed: {}", e))?;
+            .query(&query_params);
+        let response = self.http.send(request, false).await.map_err(|e| e.to_string())?;
 
         if !response.status().is_success() {
             let status = response.status();
@@ -0,0 +1,332 @@
+//! Request Metrics
+//!
+//! Per-method call counts, error counts by JSON-RPC code and latency
+//! histograms for the socket server, reported by `server.metrics`.
+//!
+//! The same numbers can be rendered in the Prometheus text exposition
+//! format, either through `server.metrics` with `{"format": "prometheus"}`
+//! or from a loopback HTTP endpoint enabled with `metricsListen` in
+//! `proxy.json`:
+//!
+//! ```json
+//! { "metricsListen": "127.0.0.1:9464" }
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::Serialize;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::sync::broadcast;
+use tracing::{debug, error, info, warn};
+
+use super::handlers::HandlerRegistry;
+
+/// Upper bounds (ms) of the latency histogram buckets; `+Inf` is implied
+pub const LATENCY_BUCKETS_MS: &[f64] = &[
+    5.0, 10.0, 25.0, 50.0, 100.0, 250.0, 500.0, 1000.0, 2500.0, 5000.0, 10000.0,
+];
+
+/// Content type of the Prometheus text exposition
+pub const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+/// Methods that didn't resolve to a real tool (not found, or refused by
+/// policy or a rate limit before lookup) are counted under one key so
+/// clients can't grow the table without bound
+const UNKNOWN_METHOD: &str = "(unknown)";
+
+/// How long a scrape client gets to send its request
+const SCRAPE_READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Latency histogram with fixed buckets
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Histogram {
+    /// Non-cumulative count per bucket in [`LATENCY_BUCKETS_MS`], plus a
+    /// final overflow bucket
+    pub buckets: Vec<u64>,
+    pub count: u64,
+    pub sum_ms: f64,
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Self {
+            buckets: vec![0; LATENCY_BUCKETS_MS.len() + 1],
+            count: 0,
+            sum_ms: 0.0,
+        }
+    }
+}
+
+impl Histogram {
+    fn observe(&mut self, ms: f64) {
+        let index = LATENCY_BUCKETS_MS
+            .iter()
+            .position(|bound| ms <= *bound)
+            .unwrap_or(LATENCY_BUCKETS_MS.len());
+        self.buckets[index] += 1;
+        self.count += 1;
+        self.sum_ms += ms;
+    }
+}
+
+/// Counters for a single method
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MethodMetrics {
+    pub calls: u64,
+    pub errors: u64,
+    pub latency: Histogram,
+}
+
+/// Point-in-time copy of the request metrics
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetricsSnapshot {
+    pub requests: u64,
+    pub errors: u64,
+    pub methods: BTreeMap<String, MethodMetrics>,
+    /// JSON-RPC error code → count
+    pub errors_by_code: BTreeMap<i32, u64>,
+}
+
+/// Request counters owned by [`super::ProxyState`]
+#[derive(Debug, Default)]
+pub struct RequestMetrics {
+    inner: Mutex<MetricsSnapshot>,
+}
+
+impl RequestMetrics {
+    /// Record one dispatched request. `resolved` says whether `method`
+    /// named a real tool; other names are all counted as [`UNKNOWN_METHOD`].
+    pub fn record(&self, method: &str, resolved: bool, elapsed: Duration, error_code: Option<i32>) {
+        let Ok(mut m) = self.inner.lock() else {
+            return;
+        };
+        let key = if resolved { method } else { UNKNOWN_METHOD };
+
+        m.requests += 1;
+        if let Some(code) = error_code {
+            m.errors += 1;
+            *m.errors_by_code.entry(code).or_default() += 1;
+        }
+
+        let entry = m.methods.entry(key.to_string()).or_default();
+        entry.calls += 1;
+        if error_code.is_some() {
+            entry.errors += 1;
+        }
+        entry.latency.observe(elapsed.as_secs_f64() * 1000.0);
+    }
+
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        self.inner.lock().map(|m| m.clone()).unwrap_or_default()
+    }
+}
+
+// ── Prometheus ──────────────────────────────────────────────────────────────
+
+/// A single unlabelled value appended to the exposition
+pub struct Gauge {
+    pub name: &'static str,
+    pub help: &'static str,
+    /// `gauge` or `counter`
+    pub kind: &'static str,
+    pub value: f64,
+}
+
+/// Render the snapshot and extra gauges in the Prometheus text format
+pub fn render_prometheus(snapshot: &MetricsSnapshot, gauges: &[Gauge]) -> String {
+    let mut out = String::new();
+
+    for gauge in gauges {
+        header(&mut out, gauge.name, gauge.help, gauge.kind);
+        let _ = writeln!(out, "{} {}", gauge.name, gauge.value);
+    }
+
+    header(&mut out, "tairseach_requests_total", "Requests dispatched, by method", "counter");
+    for (method, m) in &snapshot.methods {
+        let _ = writeln!(out, "tairseach_requests_total{{method=\"{}\"}} {}", escape(method), m.calls);
+    }
+
+    header(&mut out, "tairseach_request_errors_total", "Error responses, by method", "counter");
+    for (method, m) in &snapshot.methods {
+        let _ = writeln!(out, "tairseach_request_errors_total{{method=\"{}\"}} {}", escape(method), m.errors);
+    }
+
+    header(&mut out, "tairseach_errors_total", "Error responses, by JSON-RPC code", "counter");
+    for (code, count) in &snapshot.errors_by_code {
+        let _ = writeln!(out, "tairseach_errors_total{{code=\"{}\"}} {}", code, count);
+    }
+
+    let name = "tairseach_request_duration_seconds";
+    header(&mut out, name, "Request latency, by method", "histogram");
+    for (method, m) in &snapshot.methods {
+        let method = escape(method);
+        let mut cumulative = 0;
+        for (bound, count) in LATENCY_BUCKETS_MS.iter().zip(&m.latency.buckets) {
+            cumulative += count;
+            let _ = writeln!(out, "{}_bucket{{method=\"{}\",le=\"{}\"}} {}", name, method, bound / 1000.0, cumulative);
+        }
+        let _ = writeln!(out, "{}_bucket{{method=\"{}\",le=\"+Inf\"}} {}", name, method, m.latency.count);
+        let _ = writeln!(out, "{}_sum{{method=\"{}\"}} {}", name, method, m.latency.sum_ms / 1000.0);
+        let _ = writeln!(out, "{}_count{{method=\"{}\"}} {}", name, method, m.latency.count);
+    }
+
+    out
+}
+
+fn header(out: &mut String, name: &str, help: &str, kind: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+fn escape(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+// ── HTTP Exporter ───────────────────────────────────────────────────────────
+
+/// Largest request head the exporter reads
+const MAX_REQUEST_HEAD: usize = 8 * 1024;
+
+/// Serve `GET /metrics` on a loopback address until shutdown. Non-loopback
+/// addresses are refused: the metrics name every method clients call.
+pub fn spawn_exporter(
+    listen: &str,
+    handlers: Arc<HandlerRegistry>,
+    mut shutdown_rx: broadcast::Receiver<()>,
+) {
+    let addr: SocketAddr = match listen.parse() {
+        Ok(addr) => addr,
+        Err(e) => {
+            error!("Invalid metricsListen address '{}': {}", listen, e);
+            return;
+        }
+    };
+    if !addr.ip().is_loopback() {
+        error!("Refusing to expose metrics on non-loopback address {}", addr);
+        return;
+    }
+
+    tokio::spawn(async move {
+        let listener = match TcpListener::bind(addr).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                error!("Failed to bind metrics exporter on {}: {}", addr, e);
+                return;
+            }
+        };
+        info!("Prometheus metrics at http://{}/metrics", addr);
+
+        loop {
+            tokio::select! {
+                result = listener.accept() => match result {
+                    Ok((stream, _)) => {
+                        let handlers = Arc::clone(&handlers);
+                        tokio::spawn(async move {
+                            if let Err(e) = serve_scrape(stream, &handlers).await {
+                                debug!("Metrics scrape failed: {}", e);
+                            }
+                        });
+                    }
+                    Err(e) => warn!("Metrics accept error: {}", e),
+                },
+                _ = shutdown_rx.recv() => break,
+            }
+        }
+        info!("Metrics exporter stopped");
+    });
+}
+
+async fn serve_scrape(
+    mut stream: tokio::net::TcpStream,
+    handlers: &HandlerRegistry,
+) -> std::io::Result<()> {
+    let mut head = Vec::new();
+    let mut buf = [0u8; 1024];
+    let read_head = async {
+        while !head.windows(4).any(|w| w == b"\r\n\r\n") && head.len() < MAX_REQUEST_HEAD {
+            let n = stream.read(&mut buf).await?;
+            if n == 0 {
+                break;
+            }
+            head.extend_from_slice(&buf[..n]);
+        }
+        Ok::<_, std::io::Error>(())
+    };
+    // Don't let an idle client hold the connection open
+    tokio::time::timeout(SCRAPE_READ_TIMEOUT, read_head)
+        .await
+        .map_err(|_| std::io::Error::new(std::io::ErrorKind::TimedOut, "scrape request timed out"))??;
+
+    let request_line = String::from_utf8_lossy(&head);
+    let mut parts = request_line.split_whitespace();
+    let (status, content_type, body) = match (parts.next(), parts.next()) {
+        (Some("GET"), Some("/metrics")) => (
+            "200 OK",
+            PROMETHEUS_CONTENT_TYPE,
+            handlers.prometheus_metrics().await,
+        ),
+        _ => ("404 Not Found", "text/plain", "Not found\n".to_string()),
+    };
+
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_buckets_and_unknown_methods() {
+        let metrics = RequestMetrics::default();
+        metrics.record("server.status", true, Duration::from_millis(3), None);
+        metrics.record("server.status", true, Duration::from_millis(40), Some(-32000));
+        metrics.record("no.such.method", false, Duration::from_millis(1), Some(-32601));
+        // Refused by policy before lookup
+        metrics.record("made.up", false, Duration::from_millis(1), Some(-32010));
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.requests, 4);
+        assert_eq!(snapshot.errors, 3);
+        assert_eq!(snapshot.errors_by_code[&-32000], 1);
+        assert!(!snapshot.methods.contains_key("no.such.method"));
+        assert!(!snapshot.methods.contains_key("made.up"));
+        assert_eq!(snapshot.methods[UNKNOWN_METHOD].calls, 2);
+
+        let status = &snapshot.methods["server.status"];
+        assert_eq!((status.calls, status.errors), (2, 1));
+        assert_eq!(status.latency.buckets[0], 1); // <= 5ms
+        assert_eq!(status.latency.buckets[3], 1); // <= 50ms
+    }
+
+    #[test]
+    fn test_prometheus_histogram_is_cumulative() {
+        let metrics = RequestMetrics::default();
+        metrics.record("gmail.send", true, Duration::from_millis(3), None);
+        metrics.record("gmail.send", true, Duration::from_secs(30), None);
+
+        let gauges = [Gauge { name: "tairseach_up", help: "Up", kind: "gauge", value: 1.0 }];
+        let text = render_prometheus(&metrics.snapshot(), &gauges);
+        assert!(text.contains("tairseach_up 1\n"));
+        assert!(text.contains("tairseach_requests_total{method=\"gmail.send\"} 2\n"));
+        assert!(text.contains("tairseach_request_duration_seconds_bucket{method=\"gmail.send\",le=\"0.005\"} 1\n"));
+        assert!(text.contains("tairseach_request_duration_seconds_bucket{method=\"gmail.send\",le=\"10\"} 1\n"));
+        assert!(text.contains("tairseach_request_duration_seconds_bucket{method=\"gmail.send\",le=\"+Inf\"} 2\n"));
+        assert!(text.contains("# TYPE tairseach_request_duration_seconds histogram\n"));
+    }
+}
@@ -5,8 +5,16 @@
 //!
 //! Protocol: JSON-RPC 2.0 over Unix socket at `~/.tairseach/tairseach.sock`
 
+pub mod approval;
+pub mod audit;
+pub mod config;
 pub mod handlers;
+pub mod metrics;
+pub mod notifications;
+pub mod peer;
+pub mod policy;
 pub mod protocol;
+pub mod ratelimit;
 pub mod server;
 
 use std::sync::Arc;
@@ -22,14 +30,20 @@ pub struct ProxyState {
     pub connection_count: RwLock<u64>,
     /// Active connections
     pub active_connections: RwLock<u32>,
+    /// When the server was created, for uptime
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    /// Per-method call counts, errors and latencies
+    pub metrics: metrics::RequestMetrics,
 }
 
 impl ProxyState {
     pub fn new() -> Arc<Self> {
-        Arc::new(Self {
-            connection_count: RwLock::new(0),
-            active_connections: RwLock::new(0),
-        })
+        Arc::new(Self::default())
+    }
+
+    /// Seconds since the server was created
+    pub fn uptime_secs(&self) -> i64 {
+        (chrono::Utc::now() - self.started_at).num_seconds()
     }
 }
 
@@ -38,6 +52,8 @@ impl Default for ProxyState {
         Self {
             connection_count: RwLock::new(0),
             active_connections: RwLock::new(0),
+            started_at: chrono::Utc::now(),
+            metrics: metrics::RequestMetrics::default(),
         }
     }
 }
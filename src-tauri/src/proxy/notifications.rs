@@ -0,0 +1,324 @@
+//! Server-Push Notifications
+//!
+//! Topic-based publish/subscribe hub for the socket proxy. Connections call
+//! `subscribe` / `unsubscribe` with a list of topic patterns; anything published
+//! to a matching topic is written back on that connection as a JSON-RPC 2.0
+//! notification (`{"jsonrpc":"2.0","method":"<topic>","params":{...}}`).
+//!
+//! Patterns are exact topic names, a namespace wildcard (`"manifest.*"`), or
+//! `"*"` for everything.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use once_cell::sync::Lazy;
+use serde_json::Value;
+use tokio::sync::broadcast;
+use tracing::{debug, info};
+
+/// Well-known notification topics
+pub mod topics {
+    /// A fresh location fix from `location.watch`
+    pub const LOCATION_UPDATE: &str = "location.update";
+    /// Manifests were reloaded from disk
+    pub const MANIFEST_RELOADED: &str = "manifest.reloaded";
+    /// An OAuth token was refreshed (successfully or not)
+    pub const AUTH_TOKEN_REFRESHED: &str = "auth.tokenRefreshed";
+    /// A static credential expired, is about to, or is due for rotation
+    pub const AUTH_CREDENTIAL_EXPIRING: &str = "auth.credentialExpiring";
+    /// New, deleted or relabelled Gmail messages from `gmail.sync`/`gmail.watch`
+    pub const GMAIL_CHANGED: &str = "gmail.changed";
+    /// A macOS permission changed status
+    pub const PERMISSIONS_CHANGED: &str = "permissions.changed";
+    /// The server is draining; sent to every connection without a subscription
+    pub const SERVER_SHUTDOWN: &str = "server.shutdown";
+
+    /// All topics the server currently publishes
+    pub const ALL: &[&str] = &[
+        LOCATION_UPDATE,
+        MANIFEST_RELOADED,
+        AUTH_TOKEN_REFRESHED,
+        AUTH_CREDENTIAL_EXPIRING,
+        GMAIL_CHANGED,
+        PERMISSIONS_CHANGED,
+    ];
+}
+
+/// Capacity of the broadcast channel. Slow connections that fall further
+/// behind than this lose the oldest notifications (and get a warning logged).
+const CHANNEL_CAPACITY: usize = 256;
+
+/// A published notification
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub topic: String,
+    pub params: Value,
+    /// Credential (`provider:account`) the notification is about; only
+    /// connections whose policy allows it receive the notification
+    pub credential: Option<String>,
+}
+
+impl Notification {
+    /// Serialize as a JSON-RPC 2.0 notification (no `id`)
+    pub fn to_json_rpc(&self) -> Value {
+        serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": self.topic,
+            "params": self.params,
+        })
+    }
+}
+
+/// Process-wide notification hub
+pub struct NotificationHub {
+    tx: broadcast::Sender<Notification>,
+    next_connection_id: AtomicU64,
+    /// Connection ID → subscribed topic patterns
+    subscriptions: Mutex<HashMap<u64, HashSet<String>>>,
+}
+
+static HUB: Lazy<NotificationHub> = Lazy::new(NotificationHub::new);
+
+/// Get the global notification hub
+pub fn hub() -> &'static NotificationHub {
+    &HUB
+}
+
+/// Publish a notification on the global hub.
+///
+/// Cheap when nobody is listening — the notification is dropped without
+/// being serialized.
+pub fn publish(topic: &str, params: Value) {
+    hub().publish(topic, params);
+}
+
+/// Publish a notification about `credential` (`provider:account`) on the
+/// global hub, delivered only to connections whose policy allows it.
+pub fn publish_for_credential(topic: &str, credential: &str, params: Value) {
+    hub().publish_for_credential(topic, credential, params);
+}
+
+impl NotificationHub {
+    pub fn new() -> Self {
+        let (tx, _) = broadcast::channel(CHANNEL_CAPACITY);
+        Self {
+            tx,
+            next_connection_id: AtomicU64::new(1),
+            subscriptions: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Register a connection and return its ID plus a receiver for all
+    /// published notifications (filter with `is_subscribed`).
+    pub fn register(&self) -> (u64, broadcast::Receiver<Notification>) {
+        let id = self.next_connection_id.fetch_add(1, Ordering::Relaxed);
+        (id, self.tx.subscribe())
+    }
+
+    /// Drop all subscriptions for a connection
+    pub fn unregister(&self, connection_id: u64) {
+        self.lock().remove(&connection_id);
+    }
+
+    /// Add topic patterns for a connection. Returns the full subscription set.
+    pub fn subscribe(&self, connection_id: u64, patterns: &[String]) -> Vec<String> {
+        let mut subs = self.lock();
+        let set = subs.entry(connection_id).or_default();
+        set.extend(patterns.iter().cloned());
+        debug!("Connection {} subscribed to {:?}", connection_id, patterns);
+        sorted(set)
+    }
+
+    /// Remove topic patterns for a connection (all of them if `patterns` is
+    /// `None`). Returns the remaining subscription set.
+    pub fn unsubscribe(&self, connection_id: u64, patterns: Option<&[String]>) -> Vec<String> {
+        let mut subs = self.lock();
+        let Some(set) = subs.get_mut(&connection_id) else {
+            return Vec::new();
+        };
+        match patterns {
+            Some(patterns) => {
+                for p in patterns {
+                    set.remove(p);
+                }
+            }
+            None => set.clear(),
+        }
+        let remaining = sorted(set);
+        if remaining.is_empty() {
+            subs.remove(&connection_id);
+        }
+        remaining
+    }
+
+    /// Whether a connection is subscribed to `topic`
+    pub fn is_subscribed(&self, connection_id: u64, topic: &str) -> bool {
+        self.lock()
+            .get(&connection_id)
+            .map(|set| set.iter().any(|p| topic_matches(p, topic)))
+            .unwrap_or(false)
+    }
+
+    /// Whether any connection is subscribed to `topic`
+    pub fn has_subscribers(&self, topic: &str) -> bool {
+        self.lock()
+            .values()
+            .any(|set| set.iter().any(|p| topic_matches(p, topic)))
+    }
+
+    /// Publish a notification to all subscribed connections
+    pub fn publish(&self, topic: &str, params: Value) {
+        self.send(topic, None, params);
+    }
+
+    /// Publish a notification about `credential` to the subscribed
+    /// connections whose policy allows it
+    pub fn publish_for_credential(&self, topic: &str, credential: &str, params: Value) {
+        self.send(topic, Some(credential.to_string()), params);
+    }
+
+    fn send(&self, topic: &str, credential: Option<String>, params: Value) {
+        if !self.has_subscribers(topic) {
+            return;
+        }
+        debug!("Publishing notification: {}", topic);
+        let _ = self.tx.send(Notification {
+            topic: topic.to_string(),
+            params,
+            credential,
+        });
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<u64, HashSet<String>>> {
+        self.subscriptions
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Default for NotificationHub {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Match a subscription pattern against a topic.
+///
+/// - `"*"` matches everything
+/// - `"manifest.*"` matches `"manifest.reloaded"` (and deeper topics)
+/// - anything else must match exactly
+pub fn topic_matches(pattern: &str, topic: &str) -> bool {
+    if pattern == "*" || pattern == topic {
+        return true;
+    }
+    match pattern.strip_suffix(".*") {
+        Some(prefix) => topic
+            .strip_prefix(prefix)
+            .is_some_and(|rest| rest.starts_with('.')),
+        None => false,
+    }
+}
+
+fn sorted(set: &HashSet<String>) -> Vec<String> {
+    let mut v: Vec<String> = set.iter().cloned().collect();
+    v.sort();
+    v
+}
+
+// ── Permission Watcher ──────────────────────────────────────────────────────
+
+/// Poll macOS permission status while anyone is subscribed to
+/// `permissions.changed`, publishing the permissions whose status changed.
+///
+/// macOS offers no callback for TCC changes, so this is the best we can do.
+/// Only the first call spawns a watcher; later calls are no-ops.
+pub fn spawn_permission_watcher(interval: Duration) {
+    static STARTED: AtomicBool = AtomicBool::new(false);
+    if STARTED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    tokio::spawn(async move {
+        let mut last: HashMap<String, crate::permissions::PermissionStatus> = HashMap::new();
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+
+            if !hub().has_subscribers(topics::PERMISSIONS_CHANGED) {
+                // Forget the snapshot so a new subscriber doesn't get a
+                // flood of stale diffs.
+                last.clear();
+                continue;
+            }
+
+            let current = match tokio::task::spawn_blocking(crate::permissions::check_all_permissions).await {
+                Ok(perms) => perms,
+                Err(_) => continue,
+            };
+
+            let changed: Vec<Value> = current
+                .iter()
+                .filter(|p| last.get(&p.id).is_some_and(|prev| *prev != p.status))
+                .map(|p| {
+                    serde_json::json!({
+                        "permission": p.id,
+                        "previous": last.get(&p.id),
+                        "status": p.status,
+                    })
+                })
+                .collect();
+
+            last = current.iter().map(|p| (p.id.clone(), p.status)).collect();
+
+            if !changed.is_empty() {
+                info!("{} permission(s) changed status", changed.len());
+                publish(
+                    topics::PERMISSIONS_CHANGED,
+                    serde_json::json!({ "changes": changed }),
+                );
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_topic_matches() {
+        assert!(topic_matches("*", "location.update"));
+        assert!(topic_matches("location.update", "location.update"));
+        assert!(topic_matches("location.*", "location.update"));
+        assert!(!topic_matches("location.*", "locations.update"));
+        assert!(!topic_matches("location.*", "location"));
+        assert!(!topic_matches("manifest.reloaded", "location.update"));
+    }
+
+    #[tokio::test]
+    async fn test_publish_reaches_subscribers_only() {
+        let hub = NotificationHub::new();
+        let (a, mut rx_a) = hub.register();
+        let (b, _rx_b) = hub.register();
+
+        hub.subscribe(a, &["auth.*".to_string()]);
+        assert!(hub.is_subscribed(a, topics::AUTH_TOKEN_REFRESHED));
+        assert!(!hub.is_subscribed(b, topics::AUTH_TOKEN_REFRESHED));
+
+        hub.publish(topics::AUTH_TOKEN_REFRESHED, serde_json::json!({ "account": "me" }));
+        let n = rx_a.recv().await.unwrap();
+        assert_eq!(n.topic, topics::AUTH_TOKEN_REFRESHED);
+        assert_eq!(n.to_json_rpc()["params"]["account"], "me");
+        assert!(n.to_json_rpc().get("id").is_none());
+
+        assert!(n.credential.is_none());
+
+        hub.publish_for_credential(topics::AUTH_TOKEN_REFRESHED, "google:me", serde_json::json!({}));
+        assert_eq!(rx_a.recv().await.unwrap().credential.as_deref(), Some("google:me"));
+
+        assert!(hub.unsubscribe(a, None).is_empty());
+        assert!(!hub.has_subscribers(topics::AUTH_TOKEN_REFRESHED));
+    }
+}
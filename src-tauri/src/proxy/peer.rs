@@ -0,0 +1,113 @@
+//! Peer Identity
+//!
+//! Who is on the other end of a socket connection: PID/UID from the kernel
+//! (`SO_PEERCRED` / `LOCAL_PEERCRED`) plus the executable path of that PID.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use serde::Serialize;
+use tokio::net::UnixStream;
+
+/// Identity of a connected client process
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PeerInfo {
+    pub pid: Option<i32>,
+    pub uid: u32,
+    /// Absolute path of the peer's executable, if it could be resolved
+    pub exe: Option<PathBuf>,
+}
+
+impl PeerInfo {
+    /// Read peer credentials from a connected stream
+    pub fn from_stream(stream: &UnixStream) -> Option<Self> {
+        let cred = stream.peer_cred().ok()?;
+        let pid = cred.pid();
+        Some(Self {
+            pid,
+            uid: cred.uid(),
+            exe: pid.and_then(exe_path_for_pid),
+        })
+    }
+
+    /// Short client label for logs: the executable's file name, else `pid:N`
+    pub fn label(&self) -> String {
+        if let Some(name) = self.exe.as_ref().and_then(|p| p.file_name()) {
+            return name.to_string_lossy().into_owned();
+        }
+        match self.pid {
+            Some(pid) => format!("pid:{}", pid),
+            None => "unknown".to_string(),
+        }
+    }
+}
+
+// ── Request Context ─────────────────────────────────────────────────────────
+
+tokio::task_local! {
+    static CLIENT: (String, Arc<PeerInfo>);
+}
+
+/// Run `fut` on behalf of `client` — the policy name if one matched, else
+/// the peer label. Approvals and rate limits are keyed by this name.
+pub async fn scope_client<F: std::future::Future>(
+    client: String,
+    peer: Arc<PeerInfo>,
+    fut: F,
+) -> F::Output {
+    CLIENT.scope((client, peer), fut).await
+}
+
+/// The client behind the current socket request (`None` elsewhere)
+pub fn current_client() -> Option<String> {
+    CLIENT.try_with(|(name, _)| name.clone()).ok()
+}
+
+/// The peer behind the current socket request (`None` elsewhere)
+pub fn current_peer() -> Option<Arc<PeerInfo>> {
+    CLIENT.try_with(|(_, peer)| Arc::clone(peer)).ok()
+}
+
+/// Resolve the executable path of a running process
+#[cfg(target_os = "macos")]
+fn exe_path_for_pid(pid: i32) -> Option<PathBuf> {
+    // PROC_PIDPATHINFO_MAXSIZE = 4 * MAXPATHLEN
+    let mut buf = vec![0u8; 4096];
+    let len = unsafe {
+        libc::proc_pidpath(pid, buf.as_mut_ptr() as *mut libc::c_void, buf.len() as u32)
+    };
+    if len <= 0 {
+        return None;
+    }
+    buf.truncate(len as usize);
+    String::from_utf8(buf).ok().map(PathBuf::from)
+}
+
+#[cfg(not(target_os = "macos"))]
+fn exe_path_for_pid(pid: i32) -> Option<PathBuf> {
+    std::fs::read_link(format!("/proc/{}/exe", pid)).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_label_prefers_exe_name() {
+        let peer = PeerInfo {
+            pid: Some(42),
+            uid: 501,
+            exe: Some(PathBuf::from("/usr/local/bin/tairseach-mcp")),
+        };
+        assert_eq!(peer.label(), "tairseach-mcp");
+
+        let peer = PeerInfo { exe: None, ..peer };
+        assert_eq!(peer.label(), "pid:42");
+    }
+
+    #[test]
+    fn test_own_exe_resolves() {
+        let pid = std::process::id() as i32;
+        assert!(exe_path_for_pid(pid).is_some());
+    }
+}
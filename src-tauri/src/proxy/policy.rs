@@ -0,0 +1,380 @@
+//! Client Policies
+//!
+//! Per-client allow/deny rules loaded from `~/.tairseach/policy.json`.
+//!
+//! A connecting client is identified by its executable path (from the peer
+//! PID) or, after a `hello` handshake, by the token it presented. The matched
+//! policy restricts which methods it may call, which manifests it may reach
+//! and which credentials (`provider:account`) may be read on its behalf.
+//!
+//! Without a policy file every same-UID client is unrestricted (the original
+//! behaviour). Once the file exists, clients that match no entry get the
+//! `default` policy — or are denied everything if there is none.
+//!
+//! ```json
+//! {
+//!   "default": { "methods": ["server.*", "permissions.*"] },
+//!   "clients": [
+//!     {
+//!       "name": "mail-agent",
+//!       "exe": "/Users/*/bin/mail-agent",
+//!       "methods": ["gmail.*", "gmail_*"],
+//!       "denyMethods": ["gmail.delete*"],
+//!       "manifests": ["google-gmail"],
+//!       "credentials": ["google:*"],
+//!       "requireApproval": ["gmail.send"]
+//!     },
+//!     { "name": "ci", "token": "3f0c…", "methods": ["*"] }
+//!   ]
+//! }
+//! ```
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::{debug, info, warn};
+
+use super::peer::PeerInfo;
+use super::protocol::JsonRpcResponse;
+use crate::common::{any_match, ErrorCode};
+
+/// What a client may do. Omitted allowlists mean "anything".
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientPolicy {
+    /// Method globs the client may call
+    #[serde(default = "allow_all")]
+    pub methods: Vec<String>,
+    /// Method globs that are refused even if `methods` allows them
+    #[serde(default)]
+    pub deny_methods: Vec<String>,
+    /// Manifest ids (globs) the client may reach through the router
+    #[serde(default)]
+    pub manifests: Option<Vec<String>>,
+    /// Credential ids (`provider:account` globs) that may be read for it
+    #[serde(default)]
+    pub credentials: Option<Vec<String>>,
+    /// Method globs that are held for user approval before running
+    #[serde(default)]
+    pub require_approval: Vec<String>,
+}
+
+fn allow_all() -> Vec<String> {
+    vec!["*".to_string()]
+}
+
+impl ClientPolicy {
+    /// A policy that permits nothing
+    pub fn deny_all() -> Self {
+        Self {
+            methods: Vec::new(),
+            deny_methods: Vec::new(),
+            manifests: Some(Vec::new()),
+            credentials: Some(Vec::new()),
+            require_approval: Vec::new(),
+        }
+    }
+
+    pub fn allows_method(&self, method: &str) -> bool {
+        any_match(&self.methods, method) && !any_match(&self.deny_methods, method)
+    }
+
+    pub fn allows_manifest(&self, manifest_id: &str) -> bool {
+        self.manifests
+            .as_ref()
+            .is_none_or(|allowed| any_match(allowed, manifest_id))
+    }
+
+    pub fn needs_approval(&self, method: &str) -> bool {
+        any_match(&self.require_approval, method)
+    }
+
+    pub fn allows_credential(&self, credential_id: &str) -> bool {
+        self.credentials
+            .as_ref()
+            .is_none_or(|allowed| any_match(allowed, credential_id))
+    }
+}
+
+/// One client entry in the policy file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientEntry {
+    pub name: String,
+    /// Executable path glob matched against the peer process
+    #[serde(default)]
+    pub exe: Option<String>,
+    /// Shared secret presented via `hello`
+    #[serde(default)]
+    pub token: Option<String>,
+    #[serde(flatten)]
+    pub policy: ClientPolicy,
+}
+
+/// `~/.tairseach/policy.json`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PolicyFile {
+    #[serde(default)]
+    pub default: Option<ClientPolicy>,
+    #[serde(default)]
+    pub clients: Vec<ClientEntry>,
+}
+
+/// The policy in force for a connection
+#[derive(Debug, Clone)]
+pub struct ResolvedPolicy {
+    /// Client name from the policy file (`"default"` / `"unidentified"` otherwise)
+    pub client: String,
+    pub policy: ClientPolicy,
+}
+
+impl PolicyFile {
+    /// Path of the policy file
+    pub fn path() -> Result<PathBuf, String> {
+        crate::common::tairseach_path("policy.json")
+    }
+
+    /// Load the policy file. `Ok(None)` means no file, i.e. unrestricted.
+    pub fn load() -> Result<Option<Self>, String> {
+        let path = Self::path()?;
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = std::fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read policy file: {}", e))?;
+        let file: Self = serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse policy file: {}", e))?;
+        debug!("Loaded policy file with {} client(s)", file.clients.len());
+        Ok(Some(file))
+    }
+
+    /// Resolve the policy for a peer identified by its executable path
+    pub fn resolve_peer(&self, peer: &PeerInfo) -> ResolvedPolicy {
+        let exe = peer.exe.as_ref().map(|p| p.to_string_lossy().into_owned());
+        let matched = exe.as_deref().and_then(|exe| {
+            self.clients.iter().find(|c| {
+                c.exe
+                    .as_deref()
+                    .is_some_and(|pattern| crate::common::glob_match(pattern, exe))
+            })
+        });
+
+        match matched {
+            Some(entry) => ResolvedPolicy {
+                client: entry.name.clone(),
+                policy: entry.policy.clone(),
+            },
+            None => self.fallback(),
+        }
+    }
+
+    /// Resolve the policy for a `hello` token
+    pub fn resolve_token(&self, token: &str) -> Option<ResolvedPolicy> {
+        self.clients
+            .iter()
+            .find(|c| c.token.as_deref().is_some_and(|t| constant_time_eq(t, token)))
+            .map(|entry| ResolvedPolicy {
+                client: entry.name.clone(),
+                policy: entry.policy.clone(),
+            })
+    }
+
+    fn fallback(&self) -> ResolvedPolicy {
+        match &self.default {
+            Some(policy) => ResolvedPolicy {
+                client: "default".to_string(),
+                policy: policy.clone(),
+            },
+            None => ResolvedPolicy {
+                client: "unidentified".to_string(),
+                policy: ClientPolicy::deny_all(),
+            },
+        }
+    }
+}
+
+/// Resolve the policy for a newly connected peer.
+///
+/// `None` means unrestricted. An unreadable policy file fails closed.
+pub fn resolve_for_peer(peer: &PeerInfo) -> Option<Arc<ResolvedPolicy>> {
+    match PolicyFile::load() {
+        Ok(None) => None,
+        Ok(Some(file)) => {
+            let resolved = file.resolve_peer(peer);
+            info!("Client {} ({:?}) resolved to policy '{}'", peer.label(), peer.exe, resolved.client);
+            Some(Arc::new(resolved))
+        }
+        Err(e) => {
+            warn!("{} — denying all methods", e);
+            Some(Arc::new(ResolvedPolicy {
+                client: "invalid-policy".to_string(),
+                policy: ClientPolicy::deny_all(),
+            }))
+        }
+    }
+}
+
+fn constant_time_eq(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+// ── Request Context ─────────────────────────────────────────────────────────
+
+tokio::task_local! {
+    static CURRENT: Option<Arc<ResolvedPolicy>>;
+}
+
+/// Run `fut` with `policy` in force for the current request
+pub async fn scope<F: std::future::Future>(policy: Option<Arc<ResolvedPolicy>>, fut: F) -> F::Output {
+    CURRENT.scope(policy, fut).await
+}
+
+/// The policy for the current request (`None` outside a socket request or
+/// when unrestricted)
+pub fn current() -> Option<Arc<ResolvedPolicy>> {
+    CURRENT.try_with(|p| p.clone()).ok().flatten()
+}
+
+/// Why a request was refused
+#[derive(Debug, Clone)]
+pub struct PolicyViolation {
+    pub client: String,
+    pub kind: &'static str,
+    pub target: String,
+}
+
+impl std::fmt::Display for PolicyViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Policy for client '{}' does not allow {} '{}'",
+            self.client, self.kind, self.target
+        )
+    }
+}
+
+impl PolicyViolation {
+    pub fn into_response(self, id: Value) -> JsonRpcResponse {
+        let data = serde_json::json!({
+            "client": self.client,
+            self.kind: self.target,
+        });
+        JsonRpcResponse::error(id, ErrorCode::PolicyDenied.code(), self.to_string(), Some(data))
+    }
+}
+
+fn check(
+    kind: &'static str,
+    target: &str,
+    allowed: impl Fn(&ClientPolicy, &str) -> bool,
+) -> Result<(), PolicyViolation> {
+    match current() {
+        Some(resolved) if !allowed(&resolved.policy, target) => Err(PolicyViolation {
+            client: resolved.client.clone(),
+            kind,
+            target: target.to_string(),
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Check that the current client may call `method`
+pub fn check_method(method: &str) -> Result<(), PolicyViolation> {
+    check("method", method, ClientPolicy::allows_method)
+}
+
+/// Check that the current client may reach `manifest_id`
+pub fn check_manifest(manifest_id: &str) -> Result<(), PolicyViolation> {
+    check("manifest", manifest_id, ClientPolicy::allows_manifest)
+}
+
+/// Check that `credential_id` (`provider:account`) may be read for the current client
+pub fn check_credential(credential_id: &str) -> Result<(), PolicyViolation> {
+    check("credential", credential_id, ClientPolicy::allows_credential)
+}
+
+/// Whether the current client's policy holds `method` for approval
+pub fn requires_approval(method: &str) -> bool {
+    current().is_some_and(|resolved| resolved.policy.needs_approval(method))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> PolicyFile {
+        serde_json::from_value(serde_json::json!({
+            "clients": [
+                {
+                    "name": "mail-agent",
+                    "exe": "/opt/agents/mail*",
+                    "methods": ["gmail.*"],
+                    "denyMethods": ["gmail.delete*"],
+                    "credentials": ["google:work"]
+                },
+                { "name": "ci", "token": "s3cret" }
+            ]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_resolve_by_exe_and_token() {
+        let file = sample();
+        let peer = PeerInfo {
+            pid: Some(1),
+            uid: 501,
+            exe: Some(PathBuf::from("/opt/agents/mail-agent")),
+        };
+        let resolved = file.resolve_peer(&peer);
+        assert_eq!(resolved.client, "mail-agent");
+        assert!(resolved.policy.allows_method("gmail.send"));
+        assert!(!resolved.policy.allows_method("gmail.deleteMessage"));
+        assert!(!resolved.policy.allows_method("contacts.list"));
+        assert!(resolved.policy.allows_manifest("anything"));
+        assert!(!resolved.policy.allows_credential("google:me"));
+
+        let ci = file.resolve_token("s3cret").unwrap();
+        assert_eq!(ci.client, "ci");
+        assert!(ci.policy.allows_method("files.write"));
+        assert!(file.resolve_token("wrong").is_none());
+    }
+
+    #[test]
+    fn test_unmatched_client_without_default_is_denied() {
+        let file = sample();
+        let resolved = file.resolve_peer(&PeerInfo::default());
+        assert_eq!(resolved.client, "unidentified");
+        assert!(!resolved.policy.allows_method("server.status"));
+    }
+
+    #[tokio::test]
+    async fn test_checks_follow_scope() {
+        assert!(check_method("files.write").is_ok());
+
+        let policy = Arc::new(ResolvedPolicy {
+            client: "reader".into(),
+            policy: ClientPolicy {
+                methods: vec!["files.read".into()],
+                ..ClientPolicy::deny_all()
+            },
+        });
+        scope(Some(policy), async {
+            assert!(check_method("files.read").is_ok());
+            let err = check_method("files.write").unwrap_err();
+            let resp = err.into_response(Value::from(1));
+            let error = resp.error.unwrap();
+            assert_eq!(error.code, ErrorCode::PolicyDenied.code());
+            assert_eq!(error.data.unwrap()["method"], "files.write");
+            assert!(check_credential("google:me").is_err());
+        })
+        .await;
+    }
+}
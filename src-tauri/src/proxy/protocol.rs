@@ -171,6 +171,29 @@ impl JsonRpcResponse {
             })),
         )
     }
+
+    /// The API rejected a credential and refreshing it didn't help
+    pub fn token_refresh_failed(id: Value, provider: &str, account: &str, reason: &str) -> Self {
+        Self::error(
+            id,
+            crate::auth::error_codes::TOKEN_REFRESH_FAILED,
+            format!(
+                "Credential {}:{} was rejected and could not be refreshed; re-authorization is required",
+                provider, account
+            ),
+            Some(serde_json::json!({
+                "error": "TOKEN_REFRESH_FAILED",
+                "provider": provider,
+                "account": account,
+                "reason": reason,
+                "reauthorize": true,
+                "remediation": format!(
+                    "Ask the user to sign in to the {} account '{}' again in the Tairseach app",
+                    provider, account
+                )
+            })),
+        )
+    }
 }
 
 /// JSON-RPC 2.0 Error Object
@@ -267,4 +290,20 @@ mod tests {
         assert!(resp.error.is_none());
         assert!(resp.result.is_some());
     }
+    
+    #[test]
+    fn test_token_refresh_failed_names_account() {
+        let resp = JsonRpcResponse::token_refresh_failed(
+            Value::Number(1.into()),
+            "google",
+            "me@example.com",
+            "invalid_grant",
+        );
+        let error = resp.error.unwrap();
+        assert_eq!(error.code, -32011);
+        let data = error.data.unwrap();
+        assert_eq!(data["error"], "TOKEN_REFRESH_FAILED");
+        assert_eq!(data["account"], "me@example.com");
+        assert_eq!(data["reauthorize"], true);
+    }
 }
@@ -0,0 +1,490 @@
+//! Rate Limits and Quotas
+//!
+//! Token-bucket limits and daily quotas on socket calls, tracked per
+//! calling client.
+//!
+//! Limits come from the manifest (`rateLimit` on a tool, or on the manifest
+//! for a budget shared by all its tools) and can be overridden in
+//! `proxy.json`:
+//!
+//! ```json
+//! {
+//!   "rateLimits": {
+//!     "methods": { "gmail.send": { "perMinute": 5, "daily": 100 }, "automation.*": { "perMinute": 30 } },
+//!     "manifests": { "oura": { "perMinute": 60 } }
+//!   }
+//! }
+//! ```
+//!
+//! Method keys are globs; the longest matching pattern wins. Daily counts
+//! reset at local midnight and are kept in `~/.tairseach/quotas.json` so a
+//! restart does not refill them. The file is rewritten at most every
+//! [`QUOTA_SAVE_INTERVAL`] and on shutdown, never while the limiter is locked.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use chrono::{Local, NaiveDate};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::{debug, info, warn};
+
+use super::protocol::JsonRpcResponse;
+use crate::common::{glob_match, ErrorCode};
+use crate::manifest::types::RateLimitSpec;
+
+/// `rateLimits` section of `proxy.json`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RateLimitConfig {
+    /// Method glob → limit (replaces the manifest tool's `rateLimit`)
+    pub methods: BTreeMap<String, RateLimitSpec>,
+    /// Manifest id → limit (replaces the manifest's `rateLimit`)
+    pub manifests: BTreeMap<String, RateLimitSpec>,
+}
+
+/// Minimum time between writes of `quotas.json`
+pub const QUOTA_SAVE_INTERVAL: Duration = Duration::from_secs(5);
+
+impl RateLimitConfig {
+    /// Drop overrides that could not work (`perMinute` ≤ 0, `burst` 0)
+    pub fn validated(mut self) -> Self {
+        for (kind, limits) in [("method", &mut self.methods), ("manifest", &mut self.manifests)] {
+            limits.retain(|key, spec| match spec.validate(&format!("rateLimits {} '{}'", kind, key)) {
+                Ok(()) => true,
+                Err(e) => {
+                    warn!("Ignoring {}", e);
+                    false
+                }
+            });
+        }
+        self
+    }
+
+    /// Most specific method override for `method`
+    fn method_override(&self, method: &str) -> Option<&RateLimitSpec> {
+        self.methods
+            .iter()
+            .filter(|(pattern, _)| glob_match(pattern, method))
+            .max_by_key(|(pattern, _)| pattern.len())
+            .map(|(_, spec)| spec)
+    }
+}
+
+/// Which limit refused a call
+#[derive(Debug, Clone)]
+pub struct RateLimited {
+    pub client: String,
+    /// `method:<name>` or `manifest:<id>`
+    pub limit: String,
+    /// `"rate"` (bucket empty) or `"daily"` (quota used up)
+    pub kind: &'static str,
+    pub retry_after: Duration,
+}
+
+impl std::fmt::Display for RateLimited {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let what = if self.kind == "daily" { "Daily quota" } else { "Rate limit" };
+        write!(
+            f,
+            "{} exceeded for {} (client '{}'); retry after {}s",
+            what,
+            self.limit,
+            self.client,
+            self.retry_after_secs()
+        )
+    }
+}
+
+impl RateLimited {
+    /// Whole seconds to wait, rounded up
+    pub fn retry_after_secs(&self) -> u64 {
+        let secs = self.retry_after.as_secs();
+        if self.retry_after.subsec_nanos() > 0 {
+            secs + 1
+        } else {
+            secs
+        }
+    }
+
+    pub fn into_response(self, id: Value) -> JsonRpcResponse {
+        let data = serde_json::json!({
+            "client": self.client,
+            "limit": self.limit,
+            "kind": self.kind,
+            "retryAfter": self.retry_after_secs(),
+        });
+        JsonRpcResponse::error(id, ErrorCode::RateLimited.code(), self.to_string(), Some(data))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    fn capacity(spec: &RateLimitSpec, per_minute: f64) -> f64 {
+        spec.burst.map(f64::from).unwrap_or(per_minute.ceil()).max(1.0)
+    }
+
+    /// Tokens available at `now`
+    fn refilled(&self, spec: &RateLimitSpec, per_minute: f64, now: Instant) -> f64 {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        (self.tokens + elapsed * per_minute / 60.0).min(Self::capacity(spec, per_minute))
+    }
+}
+
+/// `~/.tairseach/quotas.json`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct QuotaFile {
+    date: Option<NaiveDate>,
+    /// Client → limit key → calls today
+    #[serde(default)]
+    counts: HashMap<String, HashMap<String, u32>>,
+}
+
+#[derive(Default)]
+struct State {
+    buckets: HashMap<(String, String), Bucket>,
+    /// `None` until loaded from disk
+    quotas: Option<QuotaFile>,
+    /// Bumped on every quota change
+    quota_generation: u64,
+    /// Generation last handed to a writer
+    quota_saved_generation: u64,
+    quota_saved_at: Option<Instant>,
+}
+
+/// Process-wide limiter
+pub struct RateLimiter {
+    config: RwLock<RateLimitConfig>,
+    state: Mutex<State>,
+    /// Where daily counts are persisted (`None` = memory only)
+    store_path: Option<PathBuf>,
+    /// Generation on disk; serializes writers and drops stale snapshots
+    written: Mutex<u64>,
+}
+
+static LIMITER: Lazy<RateLimiter> =
+    Lazy::new(|| RateLimiter::new(crate::common::tairseach_path("quotas.json").ok()));
+
+/// Get the global rate limiter
+pub fn limiter() -> &'static RateLimiter {
+    &LIMITER
+}
+
+impl RateLimiter {
+    pub fn new(store_path: Option<PathBuf>) -> Self {
+        Self {
+            config: RwLock::new(RateLimitConfig::default()),
+            state: Mutex::new(State::default()),
+            store_path,
+            written: Mutex::new(0),
+        }
+    }
+
+    /// Apply the `rateLimits` overrides from the proxy config (already
+    /// [validated](RateLimitConfig::validated) when it was loaded)
+    pub fn configure(&self, config: RateLimitConfig) {
+        if let Ok(mut c) = self.config.write() {
+            *c = config;
+        }
+    }
+
+    /// Take one call from every limit that applies.
+    ///
+    /// Either all limits are charged or none is, so a call refused by the
+    /// manifest budget does not use up the tool's own.
+    pub fn acquire(
+        &self,
+        client: &str,
+        method: &str,
+        tool_limit: Option<&RateLimitSpec>,
+        manifest: Option<(&str, Option<&RateLimitSpec>)>,
+    ) -> Result<(), RateLimited> {
+        let limits = self.resolve(method, tool_limit, manifest);
+        if limits.is_empty() {
+            return Ok(());
+        }
+        self.acquire_at(client, &limits, Instant::now(), Local::now().date_naive())
+    }
+
+    fn resolve(
+        &self,
+        method: &str,
+        tool_limit: Option<&RateLimitSpec>,
+        manifest: Option<(&str, Option<&RateLimitSpec>)>,
+    ) -> Vec<(String, RateLimitSpec)> {
+        let config = self.config.read().unwrap_or_else(|p| p.into_inner());
+        let mut limits = Vec::new();
+
+        if let Some(spec) = config.method_override(method).or(tool_limit) {
+            limits.push((format!("method:{}", method), spec.clone()));
+        }
+        if let Some((id, declared)) = manifest {
+            if let Some(spec) = config.manifests.get(id).or(declared) {
+                limits.push((format!("manifest:{}", id), spec.clone()));
+            }
+        }
+        limits
+    }
+
+    fn acquire_at(
+        &self,
+        client: &str,
+        limits: &[(String, RateLimitSpec)],
+        now: Instant,
+        today: NaiveDate,
+    ) -> Result<(), RateLimited> {
+        let mut guard = self.state.lock().unwrap_or_else(|p| p.into_inner());
+        let state = &mut *guard;
+        let quotas = state.quotas.get_or_insert_with(|| self.load_quotas());
+        if quotas.date != Some(today) {
+            quotas.date = Some(today);
+            quotas.counts.clear();
+        }
+
+        let refused = |key: &String, kind, retry_after| RateLimited {
+            client: client.to_string(),
+            limit: key.clone(),
+            kind,
+            retry_after,
+        };
+
+        // Check everything before charging anything
+        for (key, spec) in limits {
+            if let Some(daily) = spec.daily {
+                let used = quotas
+                    .counts
+                    .get(client)
+                    .and_then(|c| c.get(key))
+                    .copied()
+                    .unwrap_or(0);
+                if used >= daily {
+                    return Err(refused(key, "daily", until_midnight(today)));
+                }
+            }
+            if let Some(per_minute) = spec.per_minute {
+                let available = state
+                    .buckets
+                    .get(&(client.to_string(), key.clone()))
+                    .map_or(Bucket::capacity(spec, per_minute), |b| b.refilled(spec, per_minute, now));
+                if available < 1.0 {
+                    let wait = (1.0 - available) * 60.0 / per_minute;
+                    let retry_after = Duration::try_from_secs_f64(wait).unwrap_or(Duration::from_secs(60));
+                    return Err(refused(key, "rate", retry_after));
+                }
+            }
+        }
+
+        let mut quota_changed = false;
+        for (key, spec) in limits {
+            if spec.daily.is_some() {
+                *quotas
+                    .counts
+                    .entry(client.to_string())
+                    .or_default()
+                    .entry(key.clone())
+                    .or_insert(0) += 1;
+                quota_changed = true;
+            }
+            if let Some(per_minute) = spec.per_minute {
+                let bucket = state
+                    .buckets
+                    .entry((client.to_string(), key.clone()))
+                    .or_insert(Bucket {
+                        tokens: Bucket::capacity(spec, per_minute),
+                        updated: now,
+                    });
+                bucket.tokens = bucket.refilled(spec, per_minute, now) - 1.0;
+                bucket.updated = now;
+            }
+        }
+        debug!("Rate limits charged for {}: {:?}", client, limits.iter().map(|(k, _)| k).collect::<Vec<_>>());
+
+        let snapshot = if quota_changed {
+            state.quota_generation += 1;
+            let due = state
+                .quota_saved_at
+                .is_none_or(|t| now.saturating_duration_since(t) >= QUOTA_SAVE_INTERVAL);
+            due.then(|| {
+                state.quota_saved_at = Some(now);
+                Self::take_snapshot(state)
+            })
+        } else {
+            None
+        };
+        drop(guard);
+
+        if let Some(snapshot) = snapshot {
+            self.save_quotas(snapshot);
+        }
+        Ok(())
+    }
+
+    /// Write any quota changes not yet on disk (called on shutdown)
+    pub fn flush(&self) {
+        let snapshot = {
+            let mut state = self.state.lock().unwrap_or_else(|p| p.into_inner());
+            (state.quota_generation > state.quota_saved_generation).then(|| Self::take_snapshot(&mut state))
+        };
+        if let Some(snapshot) = snapshot {
+            info!("Flushing daily quotas");
+            self.save_quotas(snapshot);
+        }
+    }
+
+    fn take_snapshot(state: &mut State) -> (u64, QuotaFile) {
+        state.quota_saved_generation = state.quota_generation;
+        (state.quota_generation, state.quotas.clone().unwrap_or_default())
+    }
+
+    fn load_quotas(&self) -> QuotaFile {
+        let Some(path) = self.store_path.as_ref().filter(|p| p.exists()) else {
+            return QuotaFile::default();
+        };
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|c| serde_json::from_str(&c).ok())
+            .unwrap_or_else(|| {
+                warn!("Ignoring unreadable {:?}", path);
+                QuotaFile::default()
+            })
+    }
+
+    fn save_quotas(&self, (generation, quotas): (u64, QuotaFile)) {
+        let Some(path) = &self.store_path else {
+            return;
+        };
+        let mut written = self.written.lock().unwrap_or_else(|p| p.into_inner());
+        // A newer snapshot got here first
+        if generation <= *written {
+            return;
+        }
+        let result = serde_json::to_string_pretty(&quotas)
+            .map_err(|e| e.to_string())
+            .and_then(|content| crate::common::write_atomic(path, content.as_bytes(), None));
+        match result {
+            Ok(()) => *written = generation,
+            Err(e) => warn!("Failed to persist daily quotas: {}", e),
+        }
+    }
+}
+
+/// Time left until the next local midnight after `today`
+fn until_midnight(today: NaiveDate) -> Duration {
+    let midnight = today
+        .succ_opt()
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .and_then(|dt| dt.and_local_timezone(Local).earliest());
+    midnight
+        .and_then(|m| (m - Local::now()).to_std().ok())
+        .unwrap_or(Duration::from_secs(60))
+}
+
+/// Charge the current socket client for a call to `method`.
+///
+/// Calls outside a socket request (Tauri commands) are not limited.
+pub fn check(
+    method: &str,
+    tool_limit: Option<&RateLimitSpec>,
+    manifest: Option<(&str, Option<&RateLimitSpec>)>,
+) -> Result<(), RateLimited> {
+    let Some(client) = super::peer::current_client() else {
+        return Ok(());
+    };
+    limiter().acquire(&client, method, tool_limit, manifest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(per_minute: Option<f64>, burst: Option<u32>, daily: Option<u32>) -> RateLimitSpec {
+        RateLimitSpec { per_minute, burst, daily }
+    }
+
+    #[test]
+    fn test_bucket_refills_over_time() {
+        let limiter = RateLimiter::new(None);
+        let limits = vec![("method:gmail.send".to_string(), spec(Some(60.0), Some(2), None))];
+        let today = Local::now().date_naive();
+        let t0 = Instant::now();
+
+        assert!(limiter.acquire_at("a", &limits, t0, today).is_ok());
+        assert!(limiter.acquire_at("a", &limits, t0, today).is_ok());
+        let err = limiter.acquire_at("a", &limits, t0, today).unwrap_err();
+        assert_eq!(err.kind, "rate");
+        assert_eq!(err.retry_after_secs(), 1);
+
+        // Other clients have their own bucket
+        assert!(limiter.acquire_at("b", &limits, t0, today).is_ok());
+        // One token per second at 60/min
+        assert!(limiter.acquire_at("a", &limits, t0 + Duration::from_secs(1), today).is_ok());
+    }
+
+    #[test]
+    fn test_daily_quota_resets_and_charges_atomically() {
+        let limiter = RateLimiter::new(None);
+        let limits = vec![
+            ("method:gmail.send".to_string(), spec(None, None, Some(5))),
+            ("manifest:gmail".to_string(), spec(None, None, Some(1))),
+        ];
+        let today = Local::now().date_naive();
+        let now = Instant::now();
+
+        assert!(limiter.acquire_at("a", &limits, now, today).is_ok());
+        let err = limiter.acquire_at("a", &limits, now, today).unwrap_err();
+        assert_eq!(err.kind, "daily");
+        assert_eq!(err.limit, "manifest:gmail");
+
+        // The refused call did not count against the tool quota
+        let state = limiter.state.lock().unwrap();
+        let counts = &state.quotas.as_ref().unwrap().counts["a"];
+        assert_eq!(counts["method:gmail.send"], 1);
+        drop(state);
+
+        let tomorrow = today.succ_opt().unwrap();
+        assert!(limiter.acquire_at("a", &limits, now, tomorrow).is_ok());
+    }
+
+    #[test]
+    fn test_config_overrides_manifest() {
+        let limiter = RateLimiter::new(None);
+        let config: RateLimitConfig = serde_json::from_value(serde_json::json!({
+            "methods": { "gmail.*": { "daily": 10 }, "gmail.send": { "daily": 1 } },
+            "manifests": { "oura": { "perMinute": 30 } }
+        }))
+        .unwrap();
+        limiter.configure(config);
+
+        let declared = spec(Some(5.0), None, None);
+        let invalid: RateLimitConfig = serde_json::from_value(serde_json::json!({
+            "methods": { "a.*": { "perMinute": 0 }, "b.*": { "perMinute": -1 }, "c.*": { "burst": 0 } }
+        }))
+        .unwrap();
+        assert!(invalid.validated().methods.is_empty());
+
+        let limits = limiter.resolve("gmail.send", Some(&declared), Some(("gmail", None)));
+        assert_eq!(limits, vec![("method:gmail.send".to_string(), spec(None, None, Some(1)))]);
+
+        let limits = limiter.resolve("oura.sleep", None, Some(("oura", Some(&declared))));
+        assert_eq!(limits[0].1.per_minute, Some(30.0));
+
+        let err = RateLimited {
+            client: "a".into(),
+            limit: "method:gmail.send".into(),
+            kind: "rate",
+            retry_after: Duration::from_millis(1500),
+        };
+        let resp = err.into_response(Value::from(1));
+        let error = resp.error.unwrap();
+        assert_eq!(error.code, ErrorCode::RateLimited.code());
+        assert_eq!(error.data.unwrap()["retryAfter"], 2);
+    }
+}
@@ -5,15 +5,29 @@
 
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::net::{UnixListener, UnixStream};
-use tokio::sync::broadcast;
+use tokio::sync::{broadcast, mpsc, watch, Semaphore};
+use tokio::task::JoinSet;
 use tracing::{debug, error, info, warn};
 
+use super::approval;
+use super::audit::{self, AuditEntry};
+use super::config::ProxyConfig;
 use super::handlers::HandlerRegistry;
-use super::protocol::{parse_request, JsonRpcResponse};
+use super::metrics;
+use super::notifications::{self, Notification};
+use super::peer::{self, PeerInfo};
+use super::policy::{self, PolicyFile, ResolvedPolicy};
+use super::protocol::{parse_request, JsonRpcRequest, JsonRpcResponse};
+use super::ratelimit;
 use super::ProxyState;
+use crate::common::ErrorCode;
+
+/// Maximum queued outbound lines per connection before writers back off
+const OUTBOUND_QUEUE: usize = 64;
 
 /// Default socket path
 pub fn default_socket_path() -> PathBuf {
@@ -31,35 +45,45 @@ pub struct ProxyServer {
     /// Shared state
     state: Arc<ProxyState>,
     
+    /// Runtime configuration (concurrency limits)
+    config: Arc<ProxyConfig>,
+    
     /// Shutdown signal sender
     shutdown_tx: broadcast::Sender<()>,
+    
+    /// Set once the shutdown sequence has finished
+    stopped_tx: watch::Sender<bool>,
 }
 
 #[allow(dead_code)]
 impl ProxyServer {
     /// Create a new proxy server
     pub fn new(socket_path: Option<PathBuf>) -> Self {
-        let (shutdown_tx, _) = broadcast::channel(1);
-        
-        Self {
-            socket_path: socket_path.unwrap_or_else(default_socket_path),
-            handlers: Arc::new(HandlerRegistry::new()),
-            state: ProxyState::new(),
-            shutdown_tx,
-        }
+        Self::with_handlers(socket_path, Arc::new(HandlerRegistry::new()))
     }
 
     /// Create a new proxy server with a custom handler registry
     pub fn with_handlers(socket_path: Option<PathBuf>, handlers: Arc<HandlerRegistry>) -> Self {
         let (shutdown_tx, _) = broadcast::channel(1);
+        let (stopped_tx, _) = watch::channel(false);
+        let state = ProxyState::new();
+        handlers.attach_server(shutdown_tx.clone(), Arc::clone(&state));
         
         Self {
             socket_path: socket_path.unwrap_or_else(default_socket_path),
             handlers,
-            state: ProxyState::new(),
+            state,
+            config: Arc::new(ProxyConfig::load()),
             shutdown_tx,
+            stopped_tx,
         }
     }
+
+    /// Override the runtime configuration
+    pub fn with_config(mut self, config: ProxyConfig) -> Self {
+        self.config = Arc::new(config);
+        self
+    }
     
     /// Get the socket path
     pub fn socket_path(&self) -> &PathBuf {
@@ -71,11 +95,23 @@ impl ProxyServer {
         self.shutdown_tx.subscribe()
     }
     
-    /// Signal shutdown
+    /// Signal shutdown. `start()` returns once the drain has finished;
+    /// use [`ProxyServer::wait_stopped`] to wait for that from elsewhere.
     pub fn shutdown(&self) {
         let _ = self.shutdown_tx.send(());
     }
     
+    /// How long in-flight requests get to finish after shutdown starts
+    pub fn shutdown_grace(&self) -> Duration {
+        Duration::from_secs(self.config.shutdown_grace_secs)
+    }
+    
+    /// Wait until the shutdown sequence has completed
+    pub async fn wait_stopped(&self) {
+        let mut rx = self.stopped_tx.subscribe();
+        let _ = rx.wait_for(|stopped| *stopped).await;
+    }
+    
     /// Start the server
     pub async fn start(&self) -> Result<(), std::io::Error> {
         // Ensure parent directory exists
@@ -100,7 +136,21 @@ impl ProxyServer {
         
         info!("Proxy server listening on {:?}", self.socket_path);
         
+        audit::spawn_writer(self.config.audit_retention_days);
+        approval::gate().configure(
+            self.config.approval_mode,
+            std::time::Duration::from_secs(self.config.approval_timeout_secs),
+        );
+        ratelimit::limiter().configure(self.config.rate_limits.clone());
+        crate::router::set_output_validation(self.config.output_validation);
+        if let Some(listen) = &self.config.metrics_listen {
+            metrics::spawn_exporter(listen, Arc::clone(&self.handlers), self.shutdown_tx.subscribe());
+        }
+        
         let mut shutdown_rx = self.shutdown_tx.subscribe();
+        // Carries the drain deadline to open connections once shutdown starts
+        let (drain_tx, drain_rx) = watch::channel(None);
+        let mut connections = JoinSet::new();
         
         loop {
             tokio::select! {
@@ -109,7 +159,9 @@ impl ProxyServer {
                     match result {
                         Ok((stream, _addr)) => {
                             let handlers = Arc::clone(&self.handlers);
+                            let config = Arc::clone(&self.config);
                             let state = Arc::clone(&self.state);
+                            let drain_rx = drain_rx.clone();
                             
                             // Increment connection count
                             {
@@ -121,9 +173,12 @@ impl ProxyServer {
                                 *active += 1;
                             }
                             
+                            // Reap finished connections so the set doesn't grow unbounded
+                            while connections.try_join_next().is_some() {}
+                            
                             let state_clone = Arc::clone(&state);
-                            tokio::spawn(async move {
-                                if let Err(e) = handle_connection(stream, handlers).await {
+                            connections.spawn(async move {
+                                if let Err(e) = handle_connection(stream, handlers, config, Arc::clone(&state_clone), drain_rx).await {
                                     error!("Connection error: {}", e);
                                 }
                                 
@@ -146,82 +201,206 @@ impl ProxyServer {
             }
         }
         
-        // Cleanup socket file
+        // Stop accepting: close the listener and remove the socket file
+        drop(listener);
         if self.socket_path.exists() {
             let _ = std::fs::remove_file(&self.socket_path);
         }
         
+        self.drain(drain_tx, connections).await;
+        
+        info!("Proxy server stopped");
+        let _ = self.stopped_tx.send(true);
+        
         Ok(())
     }
+    
+    /// Tell open connections to wind down, give in-flight requests until the
+    /// grace deadline, then flush audit, quota and credential state.
+    async fn drain(&self, drain_tx: watch::Sender<Option<tokio::time::Instant>>, mut connections: JoinSet<()>) {
+        let grace = self.shutdown_grace();
+        let deadline = tokio::time::Instant::now() + grace;
+        let _ = drain_tx.send(Some(deadline));
+        
+        // Held calls would otherwise sit out the full approval timeout
+        let denied = approval::gate().deny_all();
+        if denied > 0 {
+            info!("Denied {} call(s) pending approval", denied);
+        }
+        
+        info!("Draining {} connection(s) (grace {:?})", connections.len(), grace);
+        // Connections abandon their own stragglers at the deadline; the margin
+        // covers writing those final lines.
+        let all_closed = async { while connections.join_next().await.is_some() {} };
+        if tokio::time::timeout_at(deadline + Duration::from_secs(1), all_closed).await.is_err() {
+            warn!("Closing {} connection(s) that did not drain in time", connections.len());
+            connections.shutdown().await;
+        }
+        *self.state.active_connections.write().await = 0;
+        
+        self.handlers.shutdown().await;
+        audit::flush().await;
+        ratelimit::limiter().flush();
+    }
 }
 
-/// Handle a single connection
+/// Handle a single connection.
+///
+/// Requests are pipelined: each line is processed on its own task (up to
+/// `max_concurrent_requests` at once) and responses are written as soon as
+/// they are ready, so they may arrive out of order. Clients correlate by `id`.
 async fn handle_connection(
     stream: UnixStream,
     handlers: Arc<HandlerRegistry>,
+    config: Arc<ProxyConfig>,
+    state: Arc<ProxyState>,
+    mut drain_rx: watch::Receiver<Option<tokio::time::Instant>>,
 ) -> Result<(), std::io::Error> {
-    let peer_cred = stream.peer_cred().ok();
-    if let Some(cred) = &peer_cred {
-        debug!("Connection from PID: {:?}, UID: {:?}", cred.pid(), cred.uid());
-        
-        // SECURITY: Verify peer UID matches our UID (owner-only socket enforcement)
-        #[cfg(unix)]
-        {
-            let my_uid = unsafe { libc::getuid() };
-            let peer_uid = cred.uid();
-            if peer_uid != my_uid {
-                warn!(
-                    "Rejecting connection from UID {} (expected {})",
-                    peer_uid,
-                    my_uid
-                );
-                return Ok(()); // Close connection without processing
-            }
+    let peer = match PeerInfo::from_stream(&stream) {
+        Some(peer) => peer,
+        None => {
+            warn!("Could not retrieve peer credentials, rejecting connection");
+            return Ok(());
+        }
+    };
+    debug!("Connection from PID: {:?}, UID: {:?}, exe: {:?}", peer.pid, peer.uid, peer.exe);
+    
+    // SECURITY: Verify peer UID matches our UID (owner-only socket enforcement)
+    #[cfg(unix)]
+    {
+        let my_uid = unsafe { libc::getuid() };
+        if peer.uid != my_uid {
+            warn!(
+                "Rejecting connection from UID {} (expected {})",
+                peer.uid,
+                my_uid
+            );
+            return Ok(()); // Close connection without processing
         }
-    } else {
-        warn!("Could not retrieve peer credentials, rejecting connection");
-        return Ok(());
     }
+    let policy = Arc::new(std::sync::RwLock::new(policy::resolve_for_peer(&peer)));
+    let peer = Arc::new(peer);
     
     let (reader, mut writer) = stream.into_split();
     let mut reader = BufReader::new(reader);
     let mut line = String::new();
-    
+
+    // All output (responses and server-push notifications) goes through a
+    // single writer task so lines never interleave.
+    let (out_tx, mut out_rx) = mpsc::channel::<String>(OUTBOUND_QUEUE);
+    let writer_task = tokio::spawn(async move {
+        while let Some(json) = out_rx.recv().await {
+            if let Err(e) = writer.write_all(json.as_bytes()).await {
+                warn!("Failed to write response: {}", e);
+                break;
+            }
+            if let Err(e) = writer.write_all(b"\n").await {
+                warn!("Failed to write newline: {}", e);
+                break;
+            }
+            if let Err(e) = writer.flush().await {
+                warn!("Failed to flush: {}", e);
+                break;
+            }
+        }
+    });
+
+    // Forward notifications this connection is subscribed to
+    let hub = notifications::hub();
+    let (connection_id, mut notify_rx) = hub.register();
+    let notify_tx = out_tx.clone();
+    let notify_policy = Arc::clone(&policy);
+    let forward_task = tokio::spawn(async move {
+        loop {
+            match notify_rx.recv().await {
+                Ok(notification) => {
+                    if !hub.is_subscribed(connection_id, &notification.topic) {
+                        continue;
+                    }
+                    if let Some(credential) = &notification.credential {
+                        let allowed = notify_policy
+                            .read()
+                            .map(|p| p.as_ref().is_none_or(|p| p.policy.allows_credential(credential)))
+                            .unwrap_or(false);
+                        if !allowed {
+                            continue;
+                        }
+                    }
+                    let json = notification.to_json_rpc().to_string();
+                    if notify_tx.send(json).await.is_err() {
+                        break;
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!(
+                        "Connection {} lagging, dropped {} notifications",
+                        connection_id, skipped
+                    );
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+
+    let limit = Arc::new(Semaphore::new(config.max_concurrent_requests));
+    let mut in_flight = JoinSet::new();
+
     loop {
         line.clear();
         
-        // Read a line (each request is newline-delimited)
-        match reader.read_line(&mut line).await {
+        // Read a line (each request is newline-delimited) unless draining
+        let read = tokio::select! {
+            read = reader.read_line(&mut line) => Some(read),
+            _ = drain_rx.wait_for(Option::is_some) => None,
+        };
+        let Some(read) = read else {
+            let notice = Notification {
+                topic: notifications::topics::SERVER_SHUTDOWN.to_string(),
+                params: serde_json::json!({ "graceSecs": config.shutdown_grace_secs }),
+                credential: None,
+            };
+            let _ = out_tx.send(notice.to_json_rpc().to_string()).await;
+            break;
+        };
+        match read {
             Ok(0) => {
                 // EOF - client disconnected
                 debug!("Client disconnected");
                 break;
             }
             Ok(_) => {
-                let response = process_request(&line, &handlers).await;
-                
-                // Serialize and send response
-                let response_json = match serde_json::to_string(&response) {
-                    Ok(json) => json,
-                    Err(e) => {
-                        error!("Failed to serialize response: {}", e);
-                        continue;
-                    }
+                // Backpressure: stop reading while the connection is at its limit
+                let permit = match Arc::clone(&limit).acquire_owned().await {
+                    Ok(permit) => permit,
+                    Err(_) => break,
                 };
                 
-                // Write response with newline
-                if let Err(e) = writer.write_all(response_json.as_bytes()).await {
-                    warn!("Failed to write response: {}", e);
-                    break;
-                }
-                if let Err(e) = writer.write_all(b"\n").await {
-                    warn!("Failed to write newline: {}", e);
-                    break;
-                }
-                if let Err(e) = writer.flush().await {
-                    warn!("Failed to flush: {}", e);
-                    break;
-                }
+                // Reap finished tasks so the set doesn't grow unbounded
+                while in_flight.try_join_next().is_some() {}
+                
+                let request_line = std::mem::take(&mut line);
+                let handlers = Arc::clone(&handlers);
+                let config = Arc::clone(&config);
+                let peer = Arc::clone(&peer);
+                let policy = Arc::clone(&policy);
+                let state = Arc::clone(&state);
+                let out_tx = out_tx.clone();
+                in_flight.spawn(async move {
+                    let conn = Connection { id: connection_id, peer, policy, state };
+                    let response = process_request(&request_line, &handlers, &config, &conn).await;
+                    drop(permit);
+                    if response.is_null() {
+                        return;
+                    }
+                    
+                    // Serialize and queue response
+                    match serde_json::to_string(&response) {
+                        Ok(json) => {
+                            let _ = out_tx.send(json).await;
+                        }
+                        Err(e) => error!("Failed to serialize response: {}", e),
+                    }
+                });
             }
             Err(e) => {
                 error!("Read error: {}", e);
@@ -229,14 +408,53 @@ async fn handle_connection(
             }
         }
     }
+
+    // Let in-flight requests finish so their responses are flushed; during
+    // shutdown, only until the drain deadline
+    let finished = async { while in_flight.join_next().await.is_some() {} };
+    let deadline = *drain_rx.borrow();
+    match deadline {
+        Some(deadline) => {
+            if tokio::time::timeout_at(deadline, finished).await.is_err() {
+                warn!("Abandoning {} in-flight request(s) at shutdown deadline", in_flight.len());
+                in_flight.shutdown().await;
+            }
+        }
+        None => finished.await,
+    }
+
+    hub.unregister(connection_id);
+    forward_task.abort();
+    drop(out_tx);
+    let _ = writer_task.await;
     
     Ok(())
 }
 
+/// Per-connection context passed down to request dispatch
+#[derive(Clone)]
+struct Connection {
+    /// Notification hub registration
+    id: u64,
+    peer: Arc<PeerInfo>,
+    /// Client policy in force (`None` = unrestricted); replaced by `hello`
+    policy: Arc<std::sync::RwLock<Option<Arc<ResolvedPolicy>>>>,
+    /// Server state, for request metrics
+    state: Arc<ProxyState>,
+}
+
+impl Connection {
+    fn policy(&self) -> Option<Arc<ResolvedPolicy>> {
+        self.policy.read().map(|p| p.clone()).unwrap_or(None)
+    }
+}
+
 /// Process a single request line
 async fn process_request(
     line: &str,
-    handlers: &HandlerRegistry,
+    handlers: &Arc<HandlerRegistry>,
+    config: &ProxyConfig,
+    conn: &Connection,
 ) -> serde_json::Value {
     // Parse the request
     let requests = match parse_request(line) {
@@ -258,7 +476,7 @@ async fn process_request(
         }
         
         // Dispatch to handler
-        let response = handlers.handle(request).await;
+        let response = dispatch(request, handlers, conn).await;
         
         // Skip response for notifications
         if request.is_notification() {
@@ -267,23 +485,39 @@ async fn process_request(
         
         serde_json::to_value(response).unwrap_or_default()
     } else {
-        // Batch request - process all and return array
-        let mut responses = Vec::new();
+        // Batch request - run entries in parallel (bounded), keep array order
+        let limit = Arc::new(Semaphore::new(config.batch_concurrency));
+        let mut tasks = JoinSet::new();
         
-        for request in &requests {
+        for (index, request) in requests.into_iter().enumerate() {
             if let Err(e) = request.validate() {
                 let id = request.id.clone().unwrap_or(serde_json::Value::Null);
-                responses.push(JsonRpcResponse::invalid_request(id, e));
+                tasks.spawn(async move { (index, Some(JsonRpcResponse::invalid_request(id, e))) });
                 continue;
             }
             
-            let response = handlers.handle(request).await;
-            
-            // Only include response if not a notification
-            if !request.is_notification() {
-                responses.push(response);
+            let handlers = Arc::clone(handlers);
+            let limit = Arc::clone(&limit);
+            let conn = conn.clone();
+            tasks.spawn(async move {
+                let _permit = limit.acquire_owned().await;
+                let response = dispatch(&request, &handlers, &conn).await;
+                
+                // Only include response if not a notification
+                (index, (!request.is_notification()).then_some(response))
+            });
+        }
+        
+        let mut responses = Vec::new();
+        while let Some(joined) = tasks.join_next().await {
+            match joined {
+                Ok((index, Some(response))) => responses.push((index, response)),
+                Ok((_, None)) => {}
+                Err(e) => error!("Batch entry task failed: {}", e),
             }
         }
+        responses.sort_by_key(|(index, _)| *index);
+        let responses: Vec<JsonRpcResponse> = responses.into_iter().map(|(_, r)| r).collect();
         
         if responses.is_empty() {
             serde_json::Value::Null
@@ -293,6 +527,147 @@ async fn process_request(
     }
 }
 
+/// Dispatch a validated request and write its audit entry
+async fn dispatch(
+    request: &JsonRpcRequest,
+    handlers: &HandlerRegistry,
+    conn: &Connection,
+) -> JsonRpcResponse {
+    let started = Instant::now();
+    let client_policy = conn.policy();
+    let client = client_policy.as_ref().map(|p| p.client.clone());
+    let client_name = client.clone().unwrap_or_else(|| conn.peer.label());
+    let (response, annotations) = policy::scope(
+        client_policy,
+        peer::scope_client(
+            client_name,
+            Arc::clone(&conn.peer),
+            audit::scope(dispatch_inner(request, handlers, conn)),
+        ),
+    )
+    .await;
+    
+    let elapsed = started.elapsed();
+    let error_code = response.error.as_ref().map(|e| e.code);
+    conn.state.metrics.record(&request.method, annotations.resolved, elapsed, error_code);
+    
+    let mut entry = AuditEntry::new(
+        &conn.peer,
+        &request.method,
+        request.id.clone(),
+        annotations,
+        elapsed,
+        error_code,
+    );
+    entry.policy = client;
+    audit::record(entry);
+    
+    response
+}
+
+/// Connection-scoped methods (`hello`, `subscribe`, `unsubscribe`) are
+/// handled here; everything else goes to the registry.
+async fn dispatch_inner(
+    request: &JsonRpcRequest,
+    handlers: &HandlerRegistry,
+    conn: &Connection,
+) -> JsonRpcResponse {
+    let id = request.id.clone().unwrap_or(serde_json::Value::Null);
+    let connection_id = conn.id;
+    match request.method.as_str() {
+        "hello" => handle_hello(&request.params, id, conn),
+        "subscribe" => {
+            if let Err(violation) = policy::check_method("subscribe") {
+                return violation.into_response(id);
+            }
+            let patterns = match subscription_topics(&request.params) {
+                Some(p) if !p.is_empty() => p,
+                _ => {
+                    return JsonRpcResponse::invalid_params(
+                        id,
+                        "Missing 'topics' (array of topic patterns) or 'topic'",
+                    )
+                }
+            };
+            let subscriptions = notifications::hub().subscribe(connection_id, &patterns);
+            JsonRpcResponse::success(
+                id,
+                serde_json::json!({
+                    "subscriptions": subscriptions,
+                    "available": notifications::topics::ALL,
+                }),
+            )
+        }
+        "unsubscribe" => {
+            let patterns = subscription_topics(&request.params);
+            let subscriptions =
+                notifications::hub().unsubscribe(connection_id, patterns.as_deref());
+            JsonRpcResponse::success(
+                id,
+                serde_json::json!({ "subscriptions": subscriptions }),
+            )
+        }
+        _ => handlers.handle(request).await,
+    }
+}
+
+/// `hello` handshake: identify the client by a policy token
+fn handle_hello(params: &serde_json::Value, id: serde_json::Value, conn: &Connection) -> JsonRpcResponse {
+    let file = match PolicyFile::load() {
+        Ok(Some(file)) => file,
+        Ok(None) => {
+            return JsonRpcResponse::success(
+                id,
+                serde_json::json!({ "client": null, "restricted": false }),
+            )
+        }
+        Err(e) => return JsonRpcResponse::internal_error(id, e),
+    };
+
+    let resolved = match params.get("token").and_then(|v| v.as_str()) {
+        Some(token) => match file.resolve_token(token) {
+            Some(resolved) => resolved,
+            None => {
+                warn!("Rejected hello with unknown token from {}", conn.peer.label());
+                return JsonRpcResponse::error(
+                    id,
+                    ErrorCode::PolicyDenied.code(),
+                    "Unknown client token",
+                    None,
+                );
+            }
+        },
+        // No token: re-resolve by executable (picks up policy file edits)
+        None => file.resolve_peer(&conn.peer),
+    };
+
+    info!("Client {} identified as '{}'", conn.peer.label(), resolved.client);
+    let client = resolved.client.clone();
+    if let Ok(mut slot) = conn.policy.write() {
+        *slot = Some(Arc::new(resolved));
+    }
+
+    JsonRpcResponse::success(
+        id,
+        serde_json::json!({ "client": client, "restricted": true }),
+    )
+}
+
+/// Extract topic patterns from `{"topics": [...]}` or `{"topic": "..."}`
+fn subscription_topics(params: &serde_json::Value) -> Option<Vec<String>> {
+    if let Some(arr) = params.get("topics").and_then(|v| v.as_array()) {
+        return Some(
+            arr.iter()
+                .filter_map(|v| v.as_str().map(String::from))
+                .collect(),
+        );
+    }
+    params
+        .get("topic")
+        .and_then(|v| v.as_str())
+        .map(|t| vec![t.to_string()])
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -303,4 +678,68 @@ mod tests {
         assert!(path.ends_with("tairseach.sock"));
         assert!(path.to_string_lossy().contains(".tairseach"));
     }
+
+    #[tokio::test]
+    async fn test_subscribe_and_unsubscribe() {
+        let handlers = Arc::new(HandlerRegistry::new());
+        let config = ProxyConfig::default();
+        let (conn_id, _rx) = notifications::hub().register();
+        let conn = Connection { id: conn_id, peer: Arc::default(), policy: Arc::default(), state: Arc::default() };
+
+        let resp = process_request(
+            r#"{"jsonrpc":"2.0","id":1,"method":"subscribe","params":{"topics":["manifest.*"]}}"#,
+            &handlers,
+            &config,
+            &conn,
+        )
+        .await;
+        assert_eq!(resp["result"]["subscriptions"][0], "manifest.*");
+        assert!(notifications::hub().is_subscribed(conn_id, "manifest.reloaded"));
+
+        let resp = process_request(
+            r#"{"jsonrpc":"2.0","id":2,"method":"unsubscribe"}"#,
+            &handlers,
+            &config,
+            &conn,
+        )
+        .await;
+        assert_eq!(resp["result"]["subscriptions"], serde_json::json!([]));
+        assert!(!notifications::hub().is_subscribed(conn_id, "manifest.reloaded"));
+
+        let resp = process_request(
+            r#"{"jsonrpc":"2.0","id":3,"method":"subscribe","params":{}}"#,
+            &handlers,
+            &config,
+            &conn,
+        )
+        .await;
+        assert_eq!(resp["error"]["code"], -32602);
+    }
+
+    #[tokio::test]
+    async fn test_parallel_batch_preserves_order() {
+        let handlers = Arc::new(HandlerRegistry::new());
+        let config = ProxyConfig {
+            batch_concurrency: 2,
+            ..ProxyConfig::default()
+        };
+
+        let batch = r#"[
+            {"jsonrpc":"2.0","id":1,"method":"server.status"},
+            {"jsonrpc":"1.0","id":2,"method":"server.status"},
+            {"jsonrpc":"2.0","method":"server.status"},
+            {"jsonrpc":"2.0","id":4,"method":"unsubscribe"}
+        ]"#;
+        let conn = Connection { id: 0, peer: Arc::default(), policy: Arc::default(), state: Arc::default() };
+        let resp = process_request(batch, &handlers, &config, &conn).await;
+        let ids: Vec<i64> = resp
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r["id"].as_i64().unwrap())
+            .collect();
+        assert_eq!(ids, vec![1, 2, 4]);
+        assert_eq!(resp[0]["result"]["status"], "running");
+        assert_eq!(resp[1]["error"]["code"], -32600);
+    }
 }
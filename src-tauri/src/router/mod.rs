@@ -8,17 +8,52 @@ pub mod proxy;
 pub mod script;
 
 use std::collections::HashMap;
-use std::sync::Arc;
-use tracing::debug;
+use std::sync::{Arc, RwLock};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
 
 use crate::auth::AuthBroker;
-use crate::manifest::types::{Implementation, Manifest};
+use crate::manifest::schema;
+use crate::manifest::types::{Implementation, Manifest, Tool};
 use crate::manifest::ManifestRegistry;
+use crate::proxy::{approval, policy, ratelimit};
 use crate::proxy::protocol::{JsonRpcRequest, JsonRpcResponse};
 use serde_json::Value;
 
 pub use dispatcher::CapabilityRouter;
 
+/// What to do when a tool result doesn't match its `outputSchema`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputValidation {
+    /// Don't check results
+    #[default]
+    Off,
+    /// Log mismatches, return the result unchanged
+    Warn,
+    /// Replace mismatching results with an internal error
+    Strict,
+}
+
+static OUTPUT_VALIDATION: RwLock<OutputValidation> = RwLock::new(OutputValidation::Off);
+
+/// Apply `outputValidation` from the proxy config
+pub fn set_output_validation(mode: OutputValidation) {
+    if let Ok(mut m) = OUTPUT_VALIDATION.write() {
+        *m = mode;
+    }
+}
+
+/// Snapshot reported under `server.status`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RouterStatus {
+    pub manifests: usize,
+    pub tools: usize,
+    pub auth: crate::auth::AuthStatus,
+    pub refresh_daemon: crate::auth::RefreshDaemonHealth,
+}
+
 impl CapabilityRouter {
     pub fn new(registry: Arc<ManifestRegistry>, auth_broker: Arc<AuthBroker>) -> Self {
         Self {
@@ -27,6 +62,22 @@ impl CapabilityRouter {
         }
     }
 
+    /// Manifest, tool and auth broker status for `server.status`
+    pub async fn status(&self) -> RouterStatus {
+        let manifests = self.registry.list_manifests().await;
+        RouterStatus {
+            manifests: manifests.len(),
+            tools: manifests.iter().map(|m| m.tools.len()).sum(),
+            auth: self.auth_broker.status().await,
+            refresh_daemon: self.auth_broker.daemon_health(),
+        }
+    }
+
+    /// Flush state owned by the router's dependencies before the server exits
+    pub async fn shutdown(&self) {
+        self.auth_broker.shutdown().await;
+    }
+
     /// Route a JSON-RPC request to the appropriate implementation
     pub async fn route(&self, request: &JsonRpcRequest) -> JsonRpcResponse {
         let id = request.id.clone().unwrap_or(Value::Null);
@@ -49,6 +100,25 @@ impl CapabilityRouter {
             "Found tool {} in manifest {}",
             tool_name, manifest.name
         );
+        crate::proxy::audit::annotate(|a| a.resolved = true);
+
+        // Client policy: method and manifest allowlists
+        if let Err(violation) = policy::check_method(tool_name)
+            .and_then(|_| policy::check_manifest(&manifest.id))
+        {
+            return violation.into_response(id);
+        }
+
+        crate::proxy::audit::annotate(|a| {
+            a.manifest = Some(manifest.id.clone());
+            a.implementation = Some(manifest.implementation.kind().to_string());
+        });
+
+        // Validate params (filling in schema defaults) before anything is charged
+        let mut params = request.params.clone();
+        if let Err(errors) = schema::validate_input(&tool.input_schema, &mut params) {
+            return invalid_params(id, tool_name, &errors);
+        }
 
         // Check required permissions
         for perm_req in &manifest.requires.permissions {
@@ -66,19 +136,41 @@ impl CapabilityRouter {
             }
         }
 
+        // Destructive tools (or policy-listed ones) wait for user approval
+        if let Err(denied) = approval::check(
+            tool_name,
+            Some(&manifest.id),
+            approval::is_destructive(&tool.annotations),
+            &params,
+        )
+        .await
+        {
+            return denied.into_response(id);
+        }
+
         // Load required credentials
-        let credentials = match self.load_credentials(&manifest, &tool, &request.params).await {
+        let mut credentials = match self.load_credentials(&manifest, &tool, &params, &id).await {
             Ok(creds) => creds,
             Err(response) => return response,
         };
 
+        // Per-client rate limits and daily quotas (tool and manifest level),
+        // charged only once the call has cleared every other check
+        if let Err(limited) = ratelimit::check(
+            tool_name,
+            tool.rate_limit.as_ref(),
+            Some((&manifest.id, manifest.rate_limit.as_ref())),
+        ) {
+            return limited.into_response(id);
+        }
+
         // Dispatch to implementation type
-        match &manifest.implementation {
+        let response = match &manifest.implementation {
             Implementation::Internal { module, methods } => {
                 internal::dispatch(
                     &manifest,
                     &tool,
-                    &request.params,
+                    &params,
                     id,
                     module,
                     methods,
@@ -95,7 +187,7 @@ impl CapabilityRouter {
                 script::dispatch(
                     &manifest,
                     &tool,
-                    &request.params,
+                    &params,
                     id,
                     runtime,
                     entrypoint,
@@ -111,19 +203,61 @@ impl CapabilityRouter {
                 auth,
                 tool_bindings,
             } => {
-                proxy::dispatch(
+                let response = proxy::dispatch(
                     &manifest,
                     &tool,
-                    &request.params,
-                    id,
+                    &params,
+                    id.clone(),
                     base_url,
                     auth,
                     tool_bindings,
                     &credentials,
                 )
-                .await
+                .await;
+                if !proxy::is_unauthorized(&response) {
+                    response
+                } else {
+                    // Rejected credential: force a refresh and try once more
+                    let (provider, account) =
+                        credential_account(&manifest, &tool, &auth.credential_id, &params);
+                    warn!(
+                        "{} got 401, refreshing credential {}:{}",
+                        tool_name, provider, account
+                    );
+                    match self.auth_broker.force_refresh(&provider, &account).await {
+                        Ok(token) => {
+                            credentials.insert(auth.credential_id.clone(), token);
+                            let retried = proxy::dispatch(
+                                &manifest,
+                                &tool,
+                                &params,
+                                id.clone(),
+                                base_url,
+                                auth,
+                                tool_bindings,
+                                &credentials,
+                            )
+                            .await;
+                            if proxy::is_unauthorized(&retried) {
+                                JsonRpcResponse::token_refresh_failed(
+                                    id,
+                                    &provider,
+                                    &account,
+                                    "still unauthorized after refreshing the token",
+                                )
+                            } else {
+                                retried
+                            }
+                        }
+                        Err((_, reason)) => {
+                            JsonRpcResponse::token_refresh_failed(id, &provider, &account, &reason)
+                        }
+                    }
+                }
             }
-        }
+        };
+
+        check_output(&tool, response)
     }
 
     /// Check a single permission
@@ -150,13 +284,14 @@ impl CapabilityRouter {
         manifest: &Manifest,
         tool: &crate::manifest::types::Tool,
         params: &Value,
+        id: &Value,
     ) -> Result<HashMap<String, Value>, JsonRpcResponse> {
         let mut credentials = HashMap::new();
 
         // Manifest-level credentials
         for cred_req in &manifest.requires.credentials {
             if let Some(cred) = self
-                .load_credential(&cred_req.id, &cred_req.provider, params)
+                .load_credential(&cred_req.id, &cred_req.provider, params, id)
                 .await?
             {
                 credentials.insert(cred_req.id.clone(), cred);
@@ -167,7 +302,7 @@ impl CapabilityRouter {
         if let Some(ref tool_reqs) = tool.requires {
             for cred_req in &tool_reqs.credentials {
                 if let Some(cred) = self
-                    .load_credential(&cred_req.id, &cred_req.provider, params)
+                    .load_credential(&cred_req.id, &cred_req.provider, params, id)
                     .await?
                 {
                     credentials.insert(cred_req.id.clone(), cred);
@@ -183,32 +318,97 @@ impl CapabilityRouter {
         credential_id: &str,
         provider_opt: &Option<String>,
         params: &Value,
+        id: &Value,
     ) -> Result<Option<Value>, JsonRpcResponse> {
-        // Parse provider from credential_id or use explicit provider
-        let provider = if let Some(p) = provider_opt {
-            p.as_str()
-        } else {
-            // Try to extract from credential_id (e.g., "google-oauth" → "google")
-            credential_id
-                .strip_suffix("-oauth")
-                .or_else(|| credential_id.strip_suffix("-api"))
-                .unwrap_or(credential_id)
-        };
+        let (provider, account) = provider_account(credential_id, provider_opt, params);
 
-        // Get account from params (default: "me")
-        let account = params
-            .get("account")
-            .and_then(|v| v.as_str())
-            .unwrap_or("me");
+        policy::check_credential(&format!("{}:{}", provider, account))
+            .map_err(|violation| violation.into_response(id.clone()))?;
 
         // Get token from auth broker
-        match self.auth_broker.get_token(provider, account, None).await {
+        match self.auth_broker.get_token(&provider, &account, None).await {
             Ok(token_info) => Ok(Some(token_info)),
             Err((code, msg)) => {
                 // If credential is optional, return None instead of error
                 // (for now, treat all as required)
-                Err(JsonRpcResponse::error(Value::Null, code, msg, None))
+                Err(JsonRpcResponse::error(id.clone(), code, msg, None))
             }
         }
     }
 }
+
+/// Provider and account a credential requirement resolves to
+fn provider_account(credential_id: &str, provider_opt: &Option<String>, params: &Value) -> (String, String) {
+    // Parse provider from credential_id or use explicit provider
+    let provider = if let Some(p) = provider_opt {
+        p.as_str()
+    } else {
+        // Try to extract from credential_id (e.g., "google-oauth" → "google")
+        credential_id
+            .strip_suffix("-oauth")
+            .or_else(|| credential_id.strip_suffix("-api"))
+            .unwrap_or(credential_id)
+    };
+
+    // Get account from params (default: "me")
+    let account = params
+        .get("account")
+        .and_then(|v| v.as_str())
+        .unwrap_or("me");
+
+    (provider.to_string(), account.to_string())
+}
+
+/// Provider and account behind `credential_id`, using the tool's requirement
+/// over the manifest's like [`CapabilityRouter::load_credentials`]
+fn credential_account(manifest: &Manifest, tool: &Tool, credential_id: &str, params: &Value) -> (String, String) {
+    let requirement = tool
+        .requires
+        .iter()
+        .flat_map(|r| r.credentials.iter())
+        .chain(manifest.requires.credentials.iter())
+        .find(|c| c.id == credential_id);
+    let provider = requirement.and_then(|c| c.provider.clone());
+    provider_account(credential_id, &provider, params)
+}
+
+/// `-32602` with every schema failure listed in `data.errors`
+fn invalid_params(id: Value, tool_name: &str, errors: &[schema::SchemaError]) -> JsonRpcResponse {
+    let first = errors.first().map(|e| e.to_string()).unwrap_or_default();
+    let more = match errors.len() {
+        0 | 1 => String::new(),
+        n => format!(" (and {} more)", n - 1),
+    };
+    JsonRpcResponse::error(
+        id,
+        crate::common::ErrorCode::InvalidParams.code(),
+        format!("Invalid params for '{}': {}{}", tool_name, first, more),
+        Some(serde_json::json!({ "errors": errors })),
+    )
+}
+
+/// Check a successful result against the tool's `outputSchema`
+fn check_output(tool: &Tool, response: JsonRpcResponse) -> JsonRpcResponse {
+    let mode = OUTPUT_VALIDATION.read().map(|m| *m).unwrap_or_default();
+    let Some(result) = response.result.as_ref().filter(|_| mode != OutputValidation::Off) else {
+        return response;
+    };
+
+    let errors = schema::validate(&tool.output_schema, result);
+    if errors.is_empty() {
+        return response;
+    }
+
+    let summary = errors.iter().map(|e| e.to_string()).collect::<Vec<_>>().join("; ");
+    warn!("Result of '{}' does not match its outputSchema: {}", tool.name, summary);
+    if mode == OutputValidation::Warn {
+        return response;
+    }
+
+    JsonRpcResponse::error(
+        response.id,
+        crate::common::ErrorCode::InternalError.code(),
+        format!("Result of '{}' does not match its outputSchema", tool.name),
+        Some(serde_json::json!({ "errors": errors })),
+    )
+}
@@ -3,15 +3,19 @@
 //! Makes HTTP calls to external APIs with credential injection via auth headers.
 
 use std::collections::HashMap;
+use reqwest::Method;
 use serde_json::Value;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
+use crate::common::http::HttpClient;
+use crate::common::pagination::{NextPage, Paginator};
+use crate::common::template::{self, Escape};
 use crate::manifest::types::{Manifest, ProxyAuth, ProxyToolBinding, Tool};
 use crate::proxy::protocol::JsonRpcResponse;
 
 /// Dispatch to HTTP API with auth header injection
 pub async fn dispatch(
-    _manifest: &Manifest,
+    manifest: &Manifest,
     tool: &Tool,
     params: &Value,
     id: Value,
@@ -43,142 +47,246 @@ pub async fn dispatch(
         }
     };
 
-    // Build URL with path interpolation
-    let path = crate::common::interpolate_params(&binding.path, params);
-    let mut url = format!("{}{}", base_url, path);
-
-    // Add query parameters
-    if !binding.query.is_empty() {
-        let query_parts: Vec<String> = binding
-            .query
-            .iter()
-            .filter_map(|(key, value_template)| {
-                let value = crate::common::interpolate_params(value_template, params);
-                if !value.is_empty() {
-                    Some(format!(
-                        "{}={}",
-                        urlencoding::encode(key),
-                        urlencoding::encode(&value)
-                    ))
-                } else {
-                    None
-                }
-            })
-            .collect();
-
-        if !query_parts.is_empty() {
-            url.push('?');
-            url.push_str(&query_parts.join("&"));
-        }
+    // Build URL with path interpolation (values are encoded as path segments)
+    let path = template::render(&binding.path, params, Escape::PathSegment);
+    if !path.missing.is_empty() {
+        return JsonRpcResponse::invalid_params(
+            id,
+            format!("Missing value for {} in request path", path.missing.join(", ")),
+        );
     }
+    let url = format!("{}{}", base_url, path.text);
 
-    // Use reqwest for HTTP calls
-    let client = reqwest::Client::new();
+    // Query parameters (omitted when they render empty)
+    let query: Vec<(String, String)> = binding
+        .query
+        .iter()
+        .filter_map(|(key, value_template)| {
+            let value = crate::common::interpolate_params(value_template, params);
+            (!value.is_empty()).then(|| (key.clone(), value))
+        })
+        .collect();
 
-    let mut request_builder = match binding.method.as_str() {
-        "GET" => client.get(&url),
-        "POST" => {
-            let body = binding.body_template.as_ref().unwrap_or(params);
-            client.post(&url).json(body)
-        }
-        "PUT" => {
-            let body = binding.body_template.as_ref().unwrap_or(params);
-            client.put(&url).json(body)
-        }
-        "PATCH" => {
-            let body = binding.body_template.as_ref().unwrap_or(params);
-            client.patch(&url).json(body)
-        }
-        "DELETE" => client.delete(&url),
-        _ => {
-            return JsonRpcResponse::error(
-                id,
-                -32000,
-                format!("Unsupported HTTP method: {}", binding.method),
-                None,
-            );
-        }
+    // Body: the rendered template, or the params as-is without one
+    let body = match &binding.body_template {
+        Some(body_template) => template::render_json(body_template, params)
+            .unwrap_or_else(|| Value::Object(Default::default())),
+        None => params.clone(),
     };
 
-    // Add auth header
-    request_builder = match auth.strategy.as_str() {
-        "oauth2Bearer" => request_builder.bearer_auth(&auth_header),
-        "apiKeyHeader" => {
-            let default_header = "X-API-Key".to_string();
-            let header_name = auth.header_name.as_ref().unwrap_or(&default_header);
-            request_builder.header(header_name, &auth_header)
-        }
-        "apiKeyQuery" => {
-            // Auth already in query params via credential interpolation
-            request_builder
-        }
-        "basic" => {
-            // auth_header is already "Basic base64(...)"
-            request_builder.header("Authorization", &auth_header)
-        }
-        _ => {
-            return JsonRpcResponse::error(
-                id,
-                -32000,
-                format!("Unsupported auth strategy: {}", auth.strategy),
-                None,
-            );
-        }
+    let request = HttpCall {
+        http: HttpClient::for_namespace(&manifest.id),
+        retry_safe: tool
+            .annotations
+            .get("idempotentHint")
+            .and_then(Value::as_bool)
+            .unwrap_or(false),
+        binding,
+        auth,
+        auth_header: &auth_header,
+        params,
+        body: &body,
+        id: &id,
     };
 
-    // Add custom headers from binding
-    for (key, value) in &binding.headers {
-        request_builder = request_builder.header(key, value);
-    }
+    let Some(spec) = &binding.pagination else {
+        let (body, _) = match request.send(&url, &query).await {
+            Ok(page) => page,
+            Err(response) => return response,
+        };
 
-    // Execute request
-    let response = match request_builder.send().await {
-        Ok(r) => r,
-        Err(e) => {
-            error!("HTTP request failed: {}", e);
-            return JsonRpcResponse::error(
-                id,
-                -32000,
-                format!("HTTP request failed: {}", e),
-                None,
-            );
-        }
+        // Extract response path if specified
+        let result = if let Some(ref response_path) = binding.response_path {
+            template::extract(&body, response_path).unwrap_or(body)
+        } else {
+            body
+        };
+        return JsonRpcResponse::success(id, result);
     };
 
-    let status = response.status();
-    if !status.is_success() {
-        error!("HTTP request returned error status: {}", status);
-        let error_body = response.text().await.unwrap_or_default();
-        return JsonRpcResponse::error(
-            id,
-            -32000,
-            format!("HTTP {} error", status),
-            Some(serde_json::json!({ "status": status.as_u16(), "body": error_body })),
-        );
+    // Paginated: walk pages, concatenating items
+    let mut pager = Paginator::new(spec);
+    let mut page_url = url;
+    let mut page_query = with_paging(&query, pager.first_page());
+    loop {
+        let (page, link) = match request.send(&page_url, &page_query).await {
+            Ok(page) => page,
+            Err(response) => return response,
+        };
+        match pager.absorb(&page, link.as_deref()) {
+            None => break,
+            Some(NextPage::Query(paging)) => page_query = with_paging(&query, paging),
+            Some(NextPage::Url(next)) => match same_origin_link(&page_url, &next) {
+                Some(next) => {
+                    page_url = next;
+                    page_query = Vec::new();
+                }
+                None => {
+                    warn!("Not following next-page link to another origin: {}", next);
+                    break;
+                }
+            },
+        }
     }
 
-    // Parse response body
-    let body = match response.json::<Value>().await {
-        Ok(json) => json,
-        Err(e) => {
-            error!("Failed to parse response JSON: {}", e);
-            return JsonRpcResponse::error(
+    let pages = pager.pages();
+    let truncated = pager.truncated();
+    info!("Fetched {} page(s) for {}", pages, tool.name);
+    JsonRpcResponse::success(
+        id,
+        serde_json::json!({
+            "items": pager.into_items(),
+            "pages": pages,
+            "truncated": truncated,
+        }),
+    )
+}
+
+/// Whether a proxied call failed because the API rejected the credential
+pub fn is_unauthorized(response: &JsonRpcResponse) -> bool {
+    response
+        .error
+        .as_ref()
+        .and_then(|e| e.data.as_ref())
+        .and_then(|data| data.get("status"))
+        .and_then(Value::as_u64)
+        == Some(401)
+}
+
+/// Everything needed to issue one request of a proxied call
+struct HttpCall<'a> {
+    http: HttpClient,
+    /// Tool declared `idempotentHint`, so non-idempotent methods may be retried
+    retry_safe: bool,
+    binding: &'a ProxyToolBinding,
+    auth: &'a ProxyAuth,
+    auth_header: &'a str,
+    params: &'a Value,
+    body: &'a Value,
+    id: &'a Value,
+}
+
+impl HttpCall<'_> {
+    /// Send one request; returns the JSON body and any `Link` header
+    async fn send(
+        &self,
+        url: &str,
+        query: &[(String, String)],
+    ) -> Result<(Value, Option<String>), JsonRpcResponse> {
+        let id = self.id.clone();
+        let binding = self.binding;
+        let http = &self.http;
+
+        let request_builder = match binding.method.as_str() {
+            "GET" => http.request(Method::GET, url),
+            "POST" => http.request(Method::POST, url).json(self.body),
+            "PUT" => http.request(Method::PUT, url).json(self.body),
+            "PATCH" => http.request(Method::PATCH, url).json(self.body),
+            "DELETE" => http.request(Method::DELETE, url),
+            _ => {
+                return Err(JsonRpcResponse::error(
+                    id,
+                    -32000,
+                    format!("Unsupported HTTP method: {}", binding.method),
+                    None,
+                ));
+            }
+        };
+        let mut request_builder = request_builder.query(query);
+
+        // Add auth header
+        request_builder = match self.auth.strategy.as_str() {
+            "oauth2Bearer" => request_builder.bearer_auth(self.auth_header),
+            "apiKeyHeader" => {
+                let header_name = self.auth.header_name.as_deref().unwrap_or("X-API-Key");
+                request_builder.header(header_name, self.auth_header)
+            }
+            "apiKeyQuery" => {
+                // Auth already in query params via credential interpolation
+                request_builder
+            }
+            "basic" => {
+                // auth_header is already "Basic base64(...)"
+                request_builder.header("Authorization", self.auth_header)
+            }
+            _ => {
+                return Err(JsonRpcResponse::error(
+                    id,
+                    -32000,
+                    format!("Unsupported auth strategy: {}", self.auth.strategy),
+                    None,
+                ));
+            }
+        };
+
+        // Add custom headers from binding (skipped when they render empty)
+        for (key, value_template) in &binding.headers {
+            let value = crate::common::interpolate_params(value_template, self.params);
+            if !value.is_empty() {
+                request_builder = request_builder.header(key, value);
+            }
+        }
+
+        // Execute request
+        let response = match http.send(request_builder, self.retry_safe).await {
+            Ok(r) => r,
+            Err(e) => {
+                error!("{}", e);
+                return Err(JsonRpcResponse::error(id, -32000, e.to_string(), None));
+            }
+        };
+
+        let status = response.status();
+        if !status.is_success() {
+            error!("HTTP request returned error status: {}", status);
+            let error_body = response.text().await.unwrap_or_default();
+            return Err(JsonRpcResponse::error(
                 id,
                 -32000,
-                format!("Failed to parse response: {}", e),
-                None,
-            );
+                format!("HTTP {} error", status),
+                Some(serde_json::json!({ "status": status.as_u16(), "body": error_body })),
+            ));
         }
-    };
 
-    // Extract response path if specified
-    let result = if let Some(ref response_path) = binding.response_path {
-        extract_json_path(&body, response_path).unwrap_or(body)
-    } else {
-        body
-    };
+        let link = response
+            .headers()
+            .get(reqwest::header::LINK)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+
+        // Parse response body
+        match response.json::<Value>().await {
+            Ok(json) => Ok((json, link)),
+            Err(e) => {
+                error!("Failed to parse response JSON: {}", e);
+                Err(JsonRpcResponse::error(
+                    id,
+                    -32000,
+                    format!("Failed to parse response: {}", e),
+                    None,
+                ))
+            }
+        }
+    }
+}
+
+/// Base query with the paginator's params replacing any of the same name
+fn with_paging(base: &[(String, String)], paging: Vec<(String, String)>) -> Vec<(String, String)> {
+    let mut query: Vec<(String, String)> = base
+        .iter()
+        .filter(|(key, _)| !paging.iter().any(|(k, _)| k == key))
+        .cloned()
+        .collect();
+    query.extend(paging);
+    query
+}
 
-    JsonRpcResponse::success(id, result)
+/// Resolve a `Link` target against the current page, refusing other origins
+/// so credentials are never sent to a different host
+fn same_origin_link(current: &str, next: &str) -> Option<String> {
+    let current = reqwest::Url::parse(current).ok()?;
+    let next = current.join(next).ok()?;
+    (next.origin() == current.origin()).then(|| next.to_string())
 }
 
 fn build_auth_header(auth: &ProxyAuth, credentials: &HashMap<String, Value>) -> Result<String, String> {
@@ -213,14 +321,3 @@ fn build_auth_header(auth: &ProxyAuth, credentials: &HashMap<String, Value>) ->
         _ => Err(format!("Unsupported auth strategy: {}", auth.strategy)),
     }
 }
-
-fn extract_json_path(value: &Value, path: &str) -> Option<Value> {
-    let parts: Vec<&str> = path.split('.').collect();
-    let mut current = value;
-
-    for part in parts {
-        current = current.get(part)?;
-    }
-
-    Some(current.clone())
-}
@@ -2,6 +2,7 @@ import { invoke } from '@tauri-apps/api/core'
 import type {
   AccountInfo,
   ActivityEvent,
+  ApprovalDecision,
   AuthStatus,
   CredentialMetadata,
   CredentialType,
@@ -15,9 +16,11 @@ import type {
   NamespaceStatus,
   NodeConfig,
   OpenClawConfig,
+  PendingApproval,
   Permission,
   PermissionDefinition,
   ProxyStatus,
+  RememberedApproval,
   SocketStatus,
   TokenInfo,
   TokenRecord,
@@ -51,6 +54,8 @@ export const api = {
     customCredentialTypeCreate: (type: string, displayName: string, fields: CredentialType['fields']) =>
       call<void>('auth_credential_types_custom_create', { type, displayName, fields }),
     startGoogleOauth: () => call<{ success: boolean; account: string }>('auth_start_google_oauth'),
+    startOauth: (provider: string, scopes?: string[], account?: string) =>
+      call<{ success: boolean; provider: string; account: string }>('auth_start_oauth', { provider, scopes, account }),
   },
   onePassword: {
     listVaults: () => call<{ vaults: Vault[]; default_vault: string | null }>('op_vaults_list'),
@@ -79,6 +84,12 @@ export const api = {
     installToOpenClaw: () => call<{ success: boolean; message: string; config_path?: string }>('monitor_openclaw_install'),
     manifestSummary: () => call<ManifestSummary>('monitor_manifest_summary_get'),
   },
+  approvals: {
+    pending: () => call<PendingApproval[]>('approvals_pending_list'),
+    decide: (id: number, decision: ApprovalDecision) => call<void>('approvals_single_decide', { id, decision }),
+    remembered: () => call<RememberedApproval[]>('approvals_remembered_list'),
+    revoke: (client: string, tool: string) => call<boolean>('approvals_remembered_revoke', { client, tool }),
+  },
   google: {
     getConfig: () => call<GoogleConfig | null>('config_google_oauth_get'),
     getStatus: () => call<GoogleStatus>('config_google_oauth_status_get'),
@@ -17,6 +17,10 @@ export interface CredentialMetadata {
   label: string
   created_at?: string
   updated_at?: string
+  expires_at?: string
+  rotate_after?: string
+  rotated_at?: string
+  previous_valid_until?: string
 }
 
 export interface Vault {
@@ -27,6 +31,7 @@ export interface Vault {
 export interface AuthStatus {
   initialized: boolean
   master_key_available: boolean
+  master_key_source: string
   account_count: number
   gog_passphrase_set: boolean
 }
@@ -131,10 +136,20 @@ export interface PermissionDefinition {
   system_pref_pane: string
 }
 
+export interface CircuitStatus {
+  baseUrl: string
+  state: 'closed' | 'open' | 'halfOpen'
+  consecutiveFailures: number
+  retryInSecs?: number
+  namespaces: string[]
+}
+
 export interface NamespaceStatus {
   namespace: string
   connected: boolean
   tool_count: number
+  degraded?: boolean
+  circuits?: CircuitStatus[]
 }
 
 export interface SocketStatus {
@@ -221,6 +236,27 @@ export interface ExecApproval {
   timestamp?: string
 }
 
+export interface PendingApproval {
+  id: number
+  client: string
+  tool: string
+  manifest?: string
+  params: unknown
+  reason: 'destructive' | 'policy'
+  requestedAt: string
+  expiresAt: string
+}
+
+export type ApprovalDecision = 'approve' | 'alwaysAllow' | 'deny'
+
+export type ApprovalOutcome = 'approved' | 'denied' | 'timedOut' | 'autoDenied'
+
+export interface RememberedApproval {
+  client: string
+  tool: string
+  grantedAt: string
+}
+
 export interface EnvironmentInfo {
   type: 'gateway' | 'node' | 'unknown'
   files: Array<{ name: string; path: string }>
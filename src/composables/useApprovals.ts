@@ -0,0 +1,60 @@
+import { onBeforeUnmount, onMounted, ref } from 'vue'
+import { listen, type UnlistenFn } from '@tauri-apps/api/event'
+import { api } from '@/api/tairseach'
+import type { ApprovalDecision, ApprovalOutcome, PendingApproval } from '@/api/types'
+import { showToast } from './useToast'
+
+/**
+ * Tool calls held by the proxy's approval gate.
+ *
+ * Seeds from `approvals_pending_list`, then follows the
+ * `tairseach://approval-requested` / `-resolved` events.
+ */
+export function useApprovals() {
+  const pending = ref<PendingApproval[]>([])
+  const unlisteners: UnlistenFn[] = []
+
+  function drop(id: number) {
+    pending.value = pending.value.filter((p) => p.id !== id)
+  }
+
+  async function decide(id: number, decision: ApprovalDecision) {
+    try {
+      await api.approvals.decide(id, decision)
+    } catch (error) {
+      showToast.error(error instanceof Error ? error.message : String(error))
+    }
+    drop(id)
+  }
+
+  onMounted(async () => {
+    try {
+      pending.value = await api.approvals.pending()
+    } catch {
+      pending.value = []
+    }
+
+    try {
+      unlisteners.push(
+        await listen<PendingApproval>('tairseach://approval-requested', ({ payload }) => {
+          drop(payload.id)
+          pending.value.push(payload)
+        }),
+        await listen<{ id: number; outcome: ApprovalOutcome }>('tairseach://approval-resolved', ({ payload }) => {
+          if (payload.outcome === 'timedOut' && pending.value.some((p) => p.id === payload.id)) {
+            showToast.warning('Approval request timed out')
+          }
+          drop(payload.id)
+        }),
+      )
+    } catch {
+      // Not running inside Tauri
+    }
+  })
+
+  onBeforeUnmount(() => {
+    unlisteners.forEach((unlisten) => unlisten())
+  })
+
+  return { pending, decide }
+}
@@ -7,6 +7,7 @@
 
 import { invoke } from '@tauri-apps/api/core'
 import { ref, onMounted, onUnmounted, type Ref } from 'vue'
+import type { CircuitStatus } from '../api/types'
 
 interface ProxyStatus {
   running: boolean
@@ -17,6 +18,8 @@ export interface NamespaceStatus {
   namespace: string
   connected: boolean
   tool_count: number
+  degraded?: boolean
+  circuits?: CircuitStatus[]
 }
 
 interface UseWorkerPollerReturn {